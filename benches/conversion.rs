@@ -0,0 +1,187 @@
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use textr::{
+    builder::{a4, DocumentBuilder},
+    document::Document,
+    font::FontRegistry,
+    pdf::{PdfCanvas, PdfDocument},
+};
+
+/// The index of the font with which the text of the benchmarks is written, a regular face of the CMU family.
+const FONT_INDEX: usize = 15;
+
+/// A line of text which covers most of the printable ASCII characters.
+const LINE: &str =
+    "The quick brown fox jumps over the lazy dog, 0123456789 (ABCDEFGHIJKLMNOPQRSTUVWXYZ)!";
+
+/// The sizes of the documents of the benchmarks, as their names and numbers of pages.
+const DOCUMENT_SIZES: [(&str, usize); 3] = [("small", 1), ("medium", 10), ("large", 100)];
+
+/// The number of lines of text written onto each page of the documents of the benchmarks.
+const LINES_PER_PAGE: usize = 50;
+
+/// Creates a document with the given number of pages, each filled with lines of text.
+fn document_with_pages(page_count: usize) -> Document {
+    let mut builder = DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .page(a4())
+    .text(LINE)
+    .at(20.0, 280.0)
+    .font(FONT_INDEX, 10.0);
+    for page_index in 0..page_count {
+        if page_index > 0 {
+            builder = builder
+                .page(a4())
+                .text(LINE)
+                .at(20.0, 280.0)
+                .font(FONT_INDEX, 10.0);
+        }
+        for line_index in 1..LINES_PER_PAGE {
+            builder = builder
+                .text(LINE)
+                .at(20.0, 280.0 - 5.0 * line_index as f32)
+                .font(FONT_INDEX, 10.0);
+        }
+    }
+
+    builder.build().unwrap()
+}
+
+/// Creates a PDF document with the given fonts, onto which nothing has been drawn yet.
+fn pdf_document_with_fonts(font_registry: &FontRegistry) -> PdfDocument {
+    let mut pdf_document = PdfDocument::new("5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9".to_string());
+    for font_bytes in font_registry.font_bytes() {
+        pdf_document
+            .add_font_from_bytes(font_bytes.clone())
+            .unwrap();
+    }
+
+    pdf_document
+}
+
+/// Creates a PDF document with the given fonts and a single empty page.
+fn page_with_fonts(font_registry: &FontRegistry) -> PdfDocument {
+    let mut pdf_document = pdf_document_with_fonts(font_registry);
+    pdf_document.add_page_with_layer(210.0, 297.0);
+
+    pdf_document
+}
+
+/// Measures how long it takes to parse a font and add it to a PDF document.
+fn font_parsing(criterion: &mut Criterion) {
+    let font_registry = FontRegistry::builtin().unwrap();
+    let font_bytes = &font_registry.font_bytes()[FONT_INDEX];
+
+    let mut group = criterion.benchmark_group("font_parsing");
+    group.throughput(Throughput::Bytes(font_bytes.len() as u64));
+    group.bench_function("add_font_from_bytes", |bencher| {
+        bencher.iter_batched(
+            || {
+                (
+                    PdfDocument::new("5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9".to_string()),
+                    font_bytes.clone(),
+                )
+            },
+            |(mut pdf_document, font_bytes)| pdf_document.add_font_from_bytes(font_bytes).unwrap(),
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+/// Measures how long it takes to write text onto a page, both the first time its characters are mapped to
+/// the glyphs of the font and once the glyphs are known to the font.
+fn text_writing(criterion: &mut Criterion) {
+    let font_registry = FontRegistry::builtin().unwrap();
+    let write_line = |pdf_document: &mut PdfDocument| {
+        pdf_document
+            .write_text_to_layer_in_page(
+                0,
+                0,
+                [0.0, 0.0, 0.0],
+                LINE,
+                FONT_INDEX,
+                10.0,
+                [20.0, 280.0],
+                0.0,
+            )
+            .unwrap()
+    };
+
+    let mut group = criterion.benchmark_group("write_text_to_layer_in_page");
+    group.throughput(Throughput::Elements(LINE.chars().count() as u64));
+    group.bench_function("glyph_mapping", |bencher| {
+        bencher.iter_batched_ref(
+            || page_with_fonts(&font_registry),
+            write_line,
+            BatchSize::LargeInput,
+        )
+    });
+    let mut pdf_document = page_with_fonts(&font_registry);
+    write_line(&mut pdf_document);
+    group.bench_function("known_glyphs", |bencher| {
+        bencher.iter(|| write_line(&mut pdf_document))
+    });
+    group.finish();
+}
+
+/// Measures how long it takes to write and save the PDF documents of different sizes, once their operations
+/// have been drawn, as well as the whole conversion of the same documents.
+fn document_writing(criterion: &mut Criterion) {
+    let font_registry = FontRegistry::builtin().unwrap();
+
+    let mut group = criterion.benchmark_group("write_all");
+    group.sample_size(10);
+    for (size_name, page_count) in DOCUMENT_SIZES {
+        let document = document_with_pages(page_count);
+        group.throughput(Throughput::Elements(page_count as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(size_name),
+            &document,
+            |bencher, document| {
+                bencher.iter_batched(
+                    || {
+                        let mut pdf_document = pdf_document_with_fonts(&font_registry);
+                        document
+                            .draw(&mut PdfCanvas::new(&mut pdf_document))
+                            .unwrap();
+                        pdf_document
+                    },
+                    |mut pdf_document| {
+                        pdf_document
+                            .write_all(document.instance_id.clone())
+                            .unwrap();
+                        pdf_document.save_to_bytes().unwrap()
+                    },
+                    BatchSize::LargeInput,
+                )
+            },
+        );
+    }
+    group.finish();
+
+    let mut group = criterion.benchmark_group("conversion");
+    group.sample_size(10);
+    for (size_name, page_count) in DOCUMENT_SIZES {
+        let document = document_with_pages(page_count);
+        group.throughput(Throughput::Elements(page_count as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(size_name),
+            &document,
+            |bencher, document| {
+                bencher.iter(|| {
+                    document
+                        .to_pdf_document_with_fonts(&font_registry)
+                        .unwrap()
+                        .save_to_bytes()
+                        .unwrap()
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, font_parsing, text_writing, document_writing);
+criterion_main!(benches);
@@ -0,0 +1,122 @@
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use textr::{document::Operation, error::ContextError, font::FontRegistry};
+
+/// The name of the project configuration file, which is looked for in the current working directory
+/// and in all of its ancestors.
+pub const CONFIG_FILE_NAME: &str = "textr.toml";
+
+/// The defaults of a project, read from a `textr.toml` file so that the same flags don't need to be
+/// repeated on every invocation. The relative paths are resolved against the directory of the file.
+///
+/// ```toml
+/// fonts = ["fonts/regular.ttf", "fonts/bold.ttf"]
+/// page-size = [210.0, 297.0]
+/// output-directory = "build"
+/// optimization = "rust"
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Config {
+    /// The fonts loaded into the PDF documents in place of the built-in ones, in the order given by their font index.
+    pub fonts: Option<Vec<PathBuf>>,
+    /// The width and height in millimeters of the page appended to the documents which don't begin with one.
+    pub page_size: Option<[f32; 2]>,
+    /// The directory where the output files are saved when no output path is given.
+    pub output_directory: Option<PathBuf>,
+    /// How the PDF documents are optimized when the `--optimization` flag is not given.
+    pub optimization: Option<Optimization>,
+}
+
+/// The ways in which the PDF documents can be optimized after being saved.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum Optimization {
+    /// Only the optimizations performed in pure Rust, which don't need any external tool.
+    Rust,
+    /// The optimizations performed in pure Rust followed by ghostscript, which produces the smallest files.
+    Ghostscript,
+}
+
+impl Config {
+    /// Looks for the configuration file in the current working directory and then in each of its ancestors,
+    /// returning the configuration of the first one found, or the default configuration if there is none.
+    pub fn discover() -> Result<Self, ContextError> {
+        match Config::discover_path()? {
+            Some(config_path) => Config::from_path(&config_path),
+            None => Ok(Config::default()),
+        }
+    }
+
+    /// Looks for the configuration file in the current working directory and then in each of its ancestors,
+    /// returning the path of the first one found, if any.
+    pub fn discover_path() -> Result<Option<PathBuf>, ContextError> {
+        let current_directory = std::env::current_dir().map_err(|error| {
+            ContextError::with_error("Failed to read the current working directory", &error)
+        })?;
+        Ok(current_directory
+            .ancestors()
+            .map(|directory| directory.join(CONFIG_FILE_NAME))
+            .find(|config_path| config_path.is_file()))
+    }
+
+    /// Reads the configuration file at the given path, resolving its relative paths against its directory.
+    pub fn from_path(config_path: &Path) -> Result<Self, ContextError> {
+        let config_content = std::fs::read_to_string(config_path).map_err(|error| {
+            ContextError::with_error(
+                format!("Failed to read the configuration file {:?}", config_path),
+                &error,
+            )
+        })?;
+        let mut config: Config = toml::from_str(&config_content).map_err(|error| {
+            ContextError::with_error(
+                format!("Failed to parse the configuration file {:?}", config_path),
+                &error,
+            )
+        })?;
+
+        let config_directory = config_path.parent().unwrap_or(Path::new(""));
+        if let Some(font_paths) = &mut config.fonts {
+            for font_path in font_paths {
+                *font_path = config_directory.join(&font_path);
+            }
+        }
+        if let Some(output_directory) = &mut config.output_directory {
+            *output_directory = config_directory.join(&output_directory);
+        }
+
+        Ok(config)
+    }
+
+    /// Reads the fonts of the configuration, or the built-in fonts if none are configured.
+    pub fn font_registry(&self) -> Result<FontRegistry, ContextError> {
+        match &self.fonts {
+            Some(font_paths) => FontRegistry::from_paths(font_paths.clone()),
+            None => FontRegistry::builtin(),
+        }
+    }
+
+    /// The paths of the fonts which are loaded into the PDF documents.
+    pub fn font_paths(&self) -> Result<Vec<PathBuf>, ContextError> {
+        match &self.fonts {
+            Some(font_paths) => Ok(font_paths.clone()),
+            None => textr::document::builtin_font_paths(),
+        }
+    }
+
+    /// Prepends a page of the configured size to the given operations if they don't begin with a page.
+    pub fn apply_page_size(&self, operations: &mut Vec<Operation>) {
+        let Some([page_width, page_height]) = self.page_size else {
+            return;
+        };
+        if !matches!(operations.first(), Some(Operation::AppendNewPage { .. })) {
+            operations.insert(
+                0,
+                Operation::AppendNewPage {
+                    page_width,
+                    page_height,
+                },
+            );
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use std::path::Path;
+use textr::error::{ContextError, ErrorKind};
+
+use crate::config::Config;
+
+/// The maximum number of lines of an operation which are shown in a diagnostic.
+const MAX_SNIPPET_LINES: usize = 6;
+
+/// Renders the operation at fault of the JSON document at the given path, in the style of the diagnostics of the
+/// Rust compiler: the lines of the operation are shown with their line numbers, the beginning of the operation is
+/// underlined and a hint on how to fix the error follows, if there is one. Nothing is returned if the document
+/// can't be read or the operation can't be found in it.
+///
+/// ```text
+///   --> "invoice.json":6:9
+///    |
+///  6 |         { "textString": "Total", "fontIndex": 31, "fontSize": 12.0,
+///    |         ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ operation 1
+///  7 |           "color": [0.0, 0.0, 0.0], "position": [20.0, 250.0] }
+///    = hint: fontIndex 31 but only 31 fonts are loaded (0–30)
+/// ```
+pub fn render_diagnostic(
+    document_path: &Path,
+    operation_index: usize,
+    error: &ContextError,
+) -> Option<String> {
+    let source = std::fs::read_to_string(document_path).ok()?;
+    let (start, end) = operation_span(&source, operation_index)?;
+
+    let first_line_start = source[..start].rfind('\n').map_or(0, |index| index + 1);
+    let first_line_number = source[..start].matches('\n').count() + 1;
+    let column = source[first_line_start..start].chars().count() + 1;
+    let lines = source[first_line_start..end].lines().collect::<Vec<_>>();
+    let gutter_width = (first_line_number + lines.len()).to_string().len();
+    let gutter = " ".repeat(gutter_width);
+
+    let mut diagnostic = format!(
+        "{}--> {:?}:{}:{}\n{} |\n",
+        gutter, document_path, first_line_number, column, gutter
+    );
+    for (line_offset, line) in lines.iter().take(MAX_SNIPPET_LINES).enumerate() {
+        diagnostic.push_str(&format!(
+            "{:>width$} | {}\n",
+            first_line_number + line_offset,
+            line,
+            width = gutter_width
+        ));
+        if line_offset == 0 {
+            // Only the part of the first line which belongs to the operation is underlined
+            let first_line_end = end.min(first_line_start + line.len());
+            let underlined_length = source[start..first_line_end].chars().count();
+            diagnostic.push_str(&format!(
+                "{} | {}{} operation {}\n",
+                gutter,
+                " ".repeat(column - 1),
+                "^".repeat(underlined_length.max(1)),
+                operation_index
+            ));
+        }
+    }
+    if lines.len() > MAX_SNIPPET_LINES {
+        diagnostic.push_str(&format!("{} | ...\n", gutter));
+    }
+    if let Some(hint) = hint(error, &source, operation_index) {
+        diagnostic.push_str(&format!("{} = hint: {}\n", gutter, hint));
+    }
+
+    Some(diagnostic)
+}
+
+/// Suggests how to fix the given error, according to its kind and to the operation at fault.
+fn hint(error: &ContextError, source: &str, operation_index: usize) -> Option<String> {
+    let hint = match error.kind {
+        ErrorKind::FontIndexOutOfRange => {
+            let font_index = serde_json::from_str::<serde_json::Value>(source)
+                .ok()?
+                .pointer(&format!("/operations/{}/fontIndex", operation_index))?
+                .as_u64()?;
+            // The fonts are the same ones with which the document has been converted
+            let font_count = Config::discover().ok()?.font_paths().ok()?.len();
+            format!(
+                "fontIndex {} but only {} fonts are loaded (0–{})",
+                font_index,
+                font_count,
+                font_count.saturating_sub(1)
+            )
+        }
+        ErrorKind::PageIndexOutOfRange => {
+            "append a page with `{ \"pageWidth\": ..., \"pageHeight\": ... }` before writing text".to_string()
+        }
+        ErrorKind::MissingGlyph => {
+            "use a font which contains these characters, or don't set `strict`".to_string()
+        }
+        ErrorKind::InvalidDocument => "an operation is either a text, with `color`, `position`, `textString`, \
+            `fontSize` and `fontIndex`, a page, with `pageWidth` and `pageHeight`, or a container, with `layout`, \
+            `position` and `children`"
+            .to_string(),
+        ErrorKind::InvalidLayout => {
+            "check that the layout of the container fits its children".to_string()
+        }
+        _ => return None,
+    };
+
+    Some(hint)
+}
+
+/// Locates the operation with the given index in the given JSON document, returning the byte offsets of its
+/// beginning and of its end. The document is scanned rather than parsed, so that the operation can be found
+/// even when it is invalid, as long as the JSON syntax is valid up to it.
+fn operation_span(source: &str, operation_index: usize) -> Option<(usize, usize)> {
+    let bytes = source.as_bytes();
+    let mut depth = 0_usize;
+    let mut key = None;
+    // The depth of the values inside the `operations` array, once it has been found
+    let mut operations_depth = None;
+    let mut element_index = 0;
+    let mut element_start = None;
+
+    let mut position = 0;
+    while position < bytes.len() {
+        let byte = bytes[position];
+        if Some(depth) == operations_depth
+            && element_start.is_none()
+            && !matches!(byte, b' ' | b'\t' | b'\n' | b'\r' | b',' | b']')
+        {
+            element_start = Some(position);
+        }
+        match byte {
+            b'"' => {
+                let string_end = string_end(bytes, position)?;
+                if depth == 1 {
+                    key = Some(&source[position + 1..string_end]);
+                }
+                position = string_end;
+            }
+            b'[' if depth == 1 && key == Some("operations") && operations_depth.is_none() => {
+                depth += 1;
+                operations_depth = Some(depth);
+            }
+            b'{' | b'[' => depth += 1,
+            b',' | b']' if Some(depth) == operations_depth => {
+                if let Some(start) = element_start.take() {
+                    if element_index == operation_index {
+                        let end = source[..position].trim_end().len();
+                        return Some((start, end));
+                    }
+                    element_index += 1;
+                }
+                if byte == b']' {
+                    return None;
+                }
+            }
+            b'}' | b']' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        position += 1;
+    }
+
+    None
+}
+
+/// Returns the offset of the quote which closes the JSON string beginning at the given offset.
+fn string_end(bytes: &[u8], string_start: usize) -> Option<usize> {
+    let mut position = string_start + 1;
+    while position < bytes.len() {
+        match bytes[position] {
+            b'\\' => position += 2,
+            b'"' => return Some(position),
+            _ => position += 1,
+        }
+    }
+
+    None
+}
@@ -0,0 +1,139 @@
+use std::path::{Path, PathBuf};
+use textr::font::FontInfo;
+
+use crate::config::{Config, CONFIG_FILE_NAME};
+
+/// The outcome of a single check of the environment.
+enum Check {
+    /// The check passed, with a short description of what has been found.
+    Passed(String),
+    /// An optional part of the environment is missing, with the problem and how to fix it.
+    Warning(String, String),
+    /// A required part of the environment is missing, with the problem and how to fix it.
+    Failed(String, String),
+}
+
+/// Checks that the environment allows documents to be converted: the fonts resolve to readable font files, the
+/// directories of the built-in fonts exist, the optional external tools are installed and the output directory is
+/// writable. Each problem is reported together with how to fix it, and the exit code is non-zero if any required
+/// check fails.
+pub fn doctor(config: &Config) -> i32 {
+    let mut checks = Vec::new();
+
+    // The built-in fonts are looked for relative to the current working directory
+    if config.fonts.is_none() {
+        for fonts_directory in ["fonts/computer-modern", "fonts/lm-math/opentype"] {
+            checks.push(if Path::new(fonts_directory).is_dir() {
+                Check::Passed(format!("The fonts directory {:?} exists", fonts_directory))
+            } else {
+                Check::Failed(
+                    format!("The fonts directory {:?} does not exist", fonts_directory),
+                    format!(
+                        "run the command from the root of the repository, or list the fonts under `fonts` in {}",
+                        CONFIG_FILE_NAME
+                    ),
+                )
+            });
+        }
+    }
+    match config.font_paths() {
+        Ok(font_paths) => checks.extend(font_paths.iter().map(|font_path| check_font(font_path))),
+        Err(error) => checks.push(Check::Failed(
+            format!("The fonts can't be resolved: {}", error),
+            "make sure that the fonts directories are readable".to_string(),
+        )),
+    }
+
+    for (tool, purpose) in [
+        ("gs", "--optimization ghostscript"),
+        ("ps2pdf", "optimize_pdf_file_with_ps2pdf"),
+    ] {
+        checks.push(match find_executable(tool) {
+            Some(tool_path) => Check::Passed(format!("{} is installed at {:?}", tool, tool_path)),
+            None => Check::Warning(
+                format!("{} is not installed, so {} is unavailable", tool, purpose),
+                "install ghostscript, or use `--optimization rust` and the `optimize` subcommand instead"
+                    .to_string(),
+            ),
+        });
+    }
+
+    let output_directory = config
+        .output_directory
+        .clone()
+        .unwrap_or_else(|| PathBuf::from("."));
+    checks.push(check_writable_directory(&output_directory));
+
+    let mut failed_checks_count = 0;
+    for check in &checks {
+        match check {
+            Check::Passed(description) => println!("[ok] {}", description),
+            Check::Warning(problem, fix) => println!("[warning] {}: {}", problem, fix),
+            Check::Failed(problem, fix) => {
+                println!("[error] {}: {}", problem, fix);
+                failed_checks_count += 1;
+            }
+        }
+    }
+    if failed_checks_count > 0 {
+        println!("{} required check(s) failed", failed_checks_count);
+        return 1;
+    }
+
+    0
+}
+
+/// Checks that the font file at the given path can be read and parsed.
+fn check_font(font_path: &Path) -> Check {
+    match FontInfo::from_path(font_path) {
+        Ok(font_info) => Check::Passed(format!(
+            "The font {:?} ({}) is readable",
+            font_path,
+            font_info.family_name.as_deref().unwrap_or("unknown family")
+        )),
+        Err(error) => Check::Failed(
+            format!("The font {:?} can't be loaded: {}", font_path, error),
+            "check that the file exists and is a valid TTF/OTF font".to_string(),
+        ),
+    }
+}
+
+/// Checks that files can be created in the given directory by writing and removing an empty file.
+fn check_writable_directory(directory_path: &Path) -> Check {
+    if !directory_path.is_dir() {
+        // The output directory is created by the `render` subcommand when needed
+        return Check::Warning(
+            format!(
+                "The output directory {:?} does not exist yet",
+                directory_path
+            ),
+            "it will be created on the first conversion, as long as its parent is writable"
+                .to_string(),
+        );
+    }
+    let probe_path = directory_path.join(".textr-doctor");
+    match std::fs::write(&probe_path, []).and_then(|()| std::fs::remove_file(&probe_path)) {
+        Ok(()) => Check::Passed(format!(
+            "The output directory {:?} is writable",
+            directory_path
+        )),
+        Err(error) => Check::Failed(
+            format!(
+                "The output directory {:?} is not writable: {}",
+                directory_path, error
+            ),
+            format!(
+                "fix its permissions, or set `output-directory` in {} to a writable directory",
+                CONFIG_FILE_NAME
+            ),
+        ),
+    }
+}
+
+/// Looks for the executable with the given name in the directories of the `PATH` environment variable.
+fn find_executable(name: &str) -> Option<PathBuf> {
+    let file_name = format!("{}{}", name, std::env::consts::EXE_SUFFIX);
+    std::env::split_paths(&std::env::var_os("PATH")?)
+        .map(|directory| directory.join(&file_name))
+        .find(|executable_path| executable_path.is_file())
+}
@@ -0,0 +1,193 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+use textr::{
+    document::Document,
+    error::{ContextError, ErrorKind},
+    warning::Warning,
+};
+
+use crate::diagnostics::render_diagnostic;
+
+/// The format in which the errors are printed on the standard error.
+#[derive(Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum ErrorFormat {
+    /// One line of text for each error.
+    Human,
+    /// One JSON object for each error, on a single line.
+    Json,
+}
+
+/// The error format requested on the command line, which is set once at startup.
+static ERROR_FORMAT: OnceLock<ErrorFormat> = OnceLock::new();
+
+/// Sets the format in which all the errors are printed from now on.
+pub fn set_error_format(error_format: ErrorFormat) {
+    let _ = ERROR_FORMAT.set(error_format);
+}
+
+/// The format in which the errors are printed, which is human-readable unless requested otherwise.
+pub fn error_format() -> ErrorFormat {
+    ERROR_FORMAT.get().copied().unwrap_or(ErrorFormat::Human)
+}
+
+/// The categories of errors of the command-line toolchain, each with its own exit code which scripts can rely on.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ErrorCode {
+    /// Some of the documents are invalid or have problems, or some of the documents of a directory failed to convert.
+    Failed,
+    /// The arguments are inconsistent, for instance no output path has been given.
+    Usage,
+    /// A file or a directory can't be read or written.
+    Io,
+    /// A JSON document, a configuration file, a PDF file or a font can't be parsed.
+    Parse,
+    /// A document has been parsed but can't be converted.
+    Render,
+}
+
+impl ErrorCode {
+    /// The exit code of the process for the error, see the help of the command-line toolchain.
+    pub fn exit_code(self) -> i32 {
+        match self {
+            ErrorCode::Failed => 1,
+            ErrorCode::Usage => 2,
+            ErrorCode::Io => 3,
+            ErrorCode::Parse => 4,
+            ErrorCode::Render => 5,
+        }
+    }
+
+    /// The name of the error code in the JSON error format.
+    fn name(self) -> &'static str {
+        match self {
+            ErrorCode::Failed => "failed",
+            ErrorCode::Usage => "usage",
+            ErrorCode::Io => "io",
+            ErrorCode::Parse => "parse",
+            ErrorCode::Render => "render",
+        }
+    }
+}
+
+/// An error of the command-line toolchain, which is a `ContextError` together with its category and, if known,
+/// the file in which it occurred.
+#[derive(Debug)]
+pub struct CliError {
+    /// The category of the error, which determines the exit code.
+    pub code: ErrorCode,
+    /// The error itself.
+    pub error: ContextError,
+    /// The file in which the error occurred.
+    pub source_path: Option<PathBuf>,
+}
+
+impl CliError {
+    /// Creates an error of the given category, not yet associated to any file.
+    pub fn new(code: ErrorCode, error: ContextError) -> Self {
+        CliError {
+            code,
+            error,
+            source_path: None,
+        }
+    }
+
+    /// Creates an error about the arguments, see `ErrorCode::Usage`.
+    pub fn usage(error: ContextError) -> Self {
+        Self::new(ErrorCode::Usage, error)
+    }
+
+    /// Creates an error about reading or writing a file, see `ErrorCode::Io`.
+    pub fn io(error: ContextError) -> Self {
+        Self::new(ErrorCode::Io, error)
+    }
+
+    /// Creates an error about parsing a file, see `ErrorCode::Parse`.
+    pub fn parse(error: ContextError) -> Self {
+        Self::new(ErrorCode::Parse, error)
+    }
+
+    /// Creates an error about converting a document, see `ErrorCode::Render`, unless the kind of the error tells
+    /// that a file, such as a font, can't be read or parsed.
+    pub fn render(error: ContextError) -> Self {
+        let code = match error.kind {
+            ErrorKind::Io | ErrorKind::FontNotFound => ErrorCode::Io,
+            ErrorKind::InvalidDocument | ErrorKind::InvalidFont => ErrorCode::Parse,
+            _ => ErrorCode::Render,
+        };
+        Self::new(code, error)
+    }
+
+    /// Associates the error to the file in which it occurred.
+    pub fn with_path(mut self, source_path: &Path) -> Self {
+        self.source_path = Some(source_path.to_path_buf());
+        self
+    }
+
+    /// The exit code of the process for the error.
+    pub fn exit_code(&self) -> i32 {
+        self.code.exit_code()
+    }
+
+    /// The JSON pointer to the operation of the document at fault, such as `/operations/3`, if known.
+    fn pointer(&self) -> Option<String> {
+        self.error
+            .operation_index
+            .map(|operation_index| format!("/operations/{}", operation_index))
+    }
+
+    /// Prints the error on the standard error in the requested format. In the human-readable format, the
+    /// operation at fault is shown as well, if known (see `render_diagnostic`).
+    pub fn report(&self) {
+        match error_format() {
+            ErrorFormat::Human => {
+                eprintln!("{}", self.error);
+                let diagnostic = self
+                    .source_path
+                    .as_deref()
+                    .zip(self.error.operation_index)
+                    .and_then(|(source_path, operation_index)| {
+                        render_diagnostic(source_path, operation_index, &self.error)
+                    });
+                if let Some(diagnostic) = diagnostic {
+                    eprint!("{}", diagnostic);
+                }
+            }
+            ErrorFormat::Json => eprintln!("{}", self.to_json()),
+        }
+    }
+
+    /// The error as a JSON object, as printed in the JSON error format and returned by the `serve` subcommand.
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "code": self.code.name(),
+            "exitCode": self.exit_code(),
+            "kind": self.error.kind.code(),
+            "context": self.error.context,
+            "sourceError": self.error.source_error,
+            "sourcePath": self.source_path,
+            "pointer": self.pointer(),
+        })
+    }
+}
+
+/// Prints the given warning, raised while converting the document at the given path, on the standard error
+/// in the requested format.
+pub fn report_warning(warning: &Warning, document_path: &Path) {
+    match error_format() {
+        ErrorFormat::Human => eprintln!("Warning: {} in {:?}", warning, document_path),
+        ErrorFormat::Json => {
+            let mut warning_value = serde_json::json!(warning);
+            warning_value["sourcePath"] = serde_json::json!(document_path);
+            eprintln!("{}", warning_value);
+        }
+    }
+}
+
+/// Parses the given content of a JSON document, returning an error which points at the operation at fault
+/// (see `Document::from_json_bytes`).
+pub fn parse_document(document_path: &Path, document_content: &[u8]) -> Result<Document, CliError> {
+    Document::from_json_bytes(document_content)
+        .map_err(|error| CliError::parse(error).with_path(document_path))
+}
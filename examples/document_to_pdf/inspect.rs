@@ -0,0 +1,127 @@
+use std::{collections::BTreeSet, path::Path};
+use textr::{
+    document::{Document, Operation},
+    pdf_info::PdfInfo,
+};
+
+use crate::failure::CliError;
+
+/// Prints a summary of the JSON document at the given path: its identifiers, the size of each page
+/// together with the number of operations written onto it, and the fonts that are used. PDF files,
+/// recognized by their extension, are read back instead (see `print_pdf_summary`).
+pub fn inspect(document_path: &Path) -> Result<(), CliError> {
+    if document_path.extension() == Some("pdf".as_ref()) {
+        let pdf_info = PdfInfo::from_path(document_path)
+            .map_err(|error| CliError::parse(error).with_path(document_path))?;
+        print_pdf_summary(&pdf_info);
+        return Ok(());
+    }
+
+    let document = crate::read_document(document_path)?;
+    print_document_summary(&document);
+
+    Ok(())
+}
+
+/// Prints the summary of a PDF file: its identifiers and metadata, the size and the layers of each page,
+/// the embedded fonts with their size and the layers of the document.
+fn print_pdf_summary(pdf_info: &PdfInfo) {
+    println!("IDs: {}", pdf_info.identifiers.join(", "));
+    for (key, value) in &pdf_info.metadata {
+        println!("{}: {}", key, value);
+    }
+
+    println!("Pages: {}", pdf_info.pages.len());
+    for (page_index, page) in pdf_info.pages.iter().enumerate() {
+        println!(
+            "  Page {}: {:.1} x {:.1} mm, layers: {}",
+            page_index + 1,
+            page.width,
+            page.height,
+            page.layers.join(", ")
+        );
+    }
+    println!("Fonts: {}", pdf_info.fonts.len());
+    for font in &pdf_info.fonts {
+        match font.embedded_size {
+            Some(embedded_size) => println!(
+                "  {} ({}), {} bytes embedded",
+                font.name, font.subtype, embedded_size
+            ),
+            None => println!("  {} ({}), not embedded", font.name, font.subtype),
+        }
+    }
+    println!("Layers: {}", pdf_info.layers.join(", "));
+}
+
+/// Prints the summary of the given document, see `inspect`.
+fn print_document_summary(document: &Document) {
+    println!("Document ID: {}", document.document_id);
+    println!("Instance ID: {}", document.instance_id);
+    if let Some([r, g, b]) = document.page_background {
+        println!("Page background: rgb({}, {}, {})", r, g, b);
+    }
+
+    let mut font_indices = BTreeSet::new();
+    let mut pages = Vec::new();
+    let mut operations_before_first_page = 0;
+    for operation in &document.operations {
+        match operation {
+            Operation::AppendNewPage {
+                page_width,
+                page_height,
+            } => pages.push(([*page_width, *page_height], 0)),
+            _ => {
+                collect_font_indices(operation, &mut font_indices);
+                match pages.last_mut() {
+                    Some((_, operations_count)) => *operations_count += 1,
+                    None => operations_before_first_page += 1,
+                }
+            }
+        }
+    }
+
+    println!("Pages: {}", pages.len());
+    if operations_before_first_page > 0 {
+        println!(
+            "  {} operations before the first page",
+            operations_before_first_page
+        );
+    }
+    for (page_index, ([page_width, page_height], operations_count)) in pages.iter().enumerate() {
+        println!(
+            "  Page {}: {} x {} mm, {} operations",
+            page_index + 1,
+            page_width,
+            page_height,
+            operations_count
+        );
+    }
+    println!(
+        "Fonts: {}",
+        font_indices
+            .iter()
+            .map(|font_index| format!("F{}", font_index))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+}
+
+/// Collects the font indices used by the given operation, including the children of containers.
+fn collect_font_indices(operation: &Operation, font_indices: &mut BTreeSet<usize>) {
+    match operation {
+        Operation::WriteUnicodeText { font_index, .. }
+        | Operation::DrawChart { font_index, .. } => {
+            font_indices.insert(*font_index);
+        }
+        Operation::LayoutContainer { children, .. } => {
+            for child in children {
+                collect_font_indices(child, font_indices);
+            }
+        }
+        Operation::AppendNewPage { .. }
+        | Operation::SetPageBackground { .. }
+        | Operation::DrawBarcode { .. }
+        | Operation::Custom { .. } => {}
+    }
+}
@@ -0,0 +1,351 @@
+use clap::Parser as _;
+use failure::{CliError, ErrorFormat};
+use std::path::{Path, PathBuf};
+use textr::{document::Document, error::ContextError, font::FontInfo};
+
+mod config;
+mod diagnostics;
+mod doctor;
+mod failure;
+mod inspect;
+mod minimize;
+mod render;
+mod serve;
+
+/// The exit codes of the command-line toolchain, see `ErrorCode`.
+const EXIT_CODES_HELP: &str = "Exit codes:
+  0  Success
+  1  Some documents are invalid, have problems or failed to convert
+  2  Invalid arguments
+  3  A file can't be read or written
+  4  A document, configuration, PDF file or font can't be parsed
+  5  A document can't be converted";
+
+/// A command-line toolchain for the JSON documents which adhere to the `Document` specification.
+#[derive(clap::Parser)]
+#[command(after_help = EXIT_CODES_HELP)]
+struct CliArguments {
+    /// The task to be performed.
+    #[command(subcommand)]
+    command: Command,
+    /// The format in which the errors are printed on the standard error.
+    #[arg(long = "error-format", global = true, value_enum, default_value_t = ErrorFormat::Human)]
+    error_format: ErrorFormat,
+}
+
+/// The subcommands of the command-line toolchain.
+#[derive(clap::Subcommand)]
+enum Command {
+    /// Convert a JSON document, or a directory of JSON documents, into PDF, SVG, HTML or plain-text files.
+    Render(render::RenderArguments),
+    /// Check that JSON documents can be parsed and converted into PDF documents, without saving them.
+    Validate {
+        /// The paths of the JSON documents.
+        #[arg(required = true, value_name = "document_file")]
+        document_paths: Vec<PathBuf>,
+        /// Treat the characters missing from their font and the glyphs without width as errors, as if the
+        /// documents had `strict` set.
+        #[arg(long)]
+        strict: bool,
+    },
+    /// Print a summary of the pages, operations and fonts of a JSON document, or of the pages, fonts,
+    /// layers and metadata of a PDF file.
+    Inspect {
+        /// The path of the JSON document or of the PDF file.
+        #[arg(value_name = "document_file")]
+        document_path: PathBuf,
+    },
+    /// Report the problems found in JSON documents, such as text outside of its page or missing characters.
+    Lint {
+        /// The paths of the JSON documents.
+        #[arg(required = true, value_name = "document_file")]
+        document_paths: Vec<PathBuf>,
+        /// The format in which the problems are reported.
+        #[arg(long = "format", value_enum, default_value_t = LintFormat::Human)]
+        format: LintFormat,
+    },
+    /// Concatenate the operations of multiple JSON documents into a single JSON document.
+    Merge {
+        /// The paths of the JSON documents, in the order in which they are concatenated.
+        #[arg(required = true, value_name = "document_file")]
+        document_paths: Vec<PathBuf>,
+        /// The path of the merged JSON document.
+        #[arg(short = 'o', long = "output", value_name = "output_file")]
+        output_document_path: PathBuf,
+    },
+    /// Shrink a JSON document which fails to convert into a minimal JSON document which fails in the same way.
+    Minimize {
+        /// The path of the failing JSON document.
+        #[arg(value_name = "document_file")]
+        document_path: PathBuf,
+        /// The path of the minimized JSON document.
+        #[arg(short = 'o', long = "output", value_name = "output_file")]
+        output_document_path: PathBuf,
+        /// Treat the characters missing from their font and the glyphs without width as errors, as if the
+        /// document had `strict` set.
+        #[arg(long)]
+        strict: bool,
+    },
+    /// Reduce the size of a PDF file without relying on ghostscript or ps2pdf.
+    Optimize {
+        /// The path of the PDF file to be optimized.
+        #[arg(value_name = "input_file")]
+        input_path: PathBuf,
+        /// The path of the optimized PDF file, which can be the same as the input one.
+        #[arg(value_name = "output_file")]
+        output_path: PathBuf,
+    },
+    /// Inspect the font files which can be loaded into the documents.
+    Font {
+        /// The font subcommand to be run.
+        #[command(subcommand)]
+        command: FontCommand,
+    },
+    /// Check that the fonts, the external tools and the output directory are set up correctly.
+    Doctor,
+    /// Run an HTTP service which converts the JSON documents posted to `/render` into PDF documents.
+    Serve(serve::ServeArguments),
+}
+
+/// The subcommands of the `font` subcommand.
+#[derive(clap::Subcommand)]
+enum FontCommand {
+    /// Print the names, the glyph count, the Unicode coverage and the embedded metrics of a TTF/OTF font.
+    Info {
+        /// The path of the font file.
+        #[arg(value_name = "font_file")]
+        font_path: PathBuf,
+    },
+}
+
+/// The formats in which the `lint` subcommand reports the problems found in the documents.
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum LintFormat {
+    /// One line for each problem, prefixed by the path of the document.
+    Human,
+    /// A JSON object associating the path of each document to the list of its problems.
+    Json,
+}
+
+fn main() {
+    // Parse the command line arguments
+    let cli_arguments = CliArguments::parse();
+    failure::set_error_format(cli_arguments.error_format);
+    // Read the defaults of the project, if a configuration file is found
+    let config = match config::Config::discover() {
+        Ok(config) => config,
+        Err(error) => {
+            let error = CliError::parse(error);
+            error.report();
+            std::process::exit(error.exit_code());
+        }
+    };
+
+    let exit_code = match &cli_arguments.command {
+        Command::Render(render_arguments) => render::render(render_arguments, &config),
+        Command::Validate {
+            document_paths,
+            strict,
+        } => validate(document_paths, *strict),
+        Command::Lint {
+            document_paths,
+            format,
+        } => lint(document_paths, *format),
+        Command::Inspect { document_path } => report(inspect::inspect(document_path)),
+        Command::Merge {
+            document_paths,
+            output_document_path,
+        } => report(merge(document_paths, output_document_path)),
+        Command::Minimize {
+            document_path,
+            output_document_path,
+            strict,
+        } => report(minimize::minimize(
+            document_path,
+            output_document_path,
+            *strict,
+            &config,
+        )),
+        Command::Optimize {
+            input_path,
+            output_path,
+        } => report(optimize(input_path, output_path)),
+        Command::Font {
+            command: FontCommand::Info { font_path },
+        } => report(
+            FontInfo::from_path(font_path)
+                .map(|font_info| println!("{}", font_info))
+                .map_err(|error| CliError::parse(error).with_path(font_path)),
+        ),
+        Command::Doctor => doctor::doctor(&config),
+        Command::Serve(serve_arguments) => serve::serve(serve_arguments, &config),
+    };
+    std::process::exit(exit_code);
+}
+
+/// Prints the error of a subcommand, if any, and returns the corresponding exit code.
+fn report(result: Result<(), CliError>) -> i32 {
+    match result {
+        Ok(()) => 0,
+        Err(error) => {
+            error.report();
+            error.exit_code()
+        }
+    }
+}
+
+/// Reads the JSON document at the given path and parses it into a `Document`.
+pub fn read_document(document_path: &Path) -> Result<Document, CliError> {
+    let document_content = std::fs::read(document_path).map_err(|error| {
+        CliError::io(ContextError::with_error(
+            format!("Failed to read JSON document {:?}", document_path),
+            &error,
+        ))
+        .with_path(document_path)
+    })?;
+    failure::parse_document(document_path, &document_content)
+}
+
+/// Parses each of the given JSON documents and converts it into a PDF document in memory, reporting
+/// whether it is valid together with its warnings, which are errors in strict mode. The exit code is
+/// the one of the first invalid document, if any.
+fn validate(document_paths: &[PathBuf], strict: bool) -> i32 {
+    let mut exit_code = 0;
+    for document_path in document_paths {
+        let validation_result = read_document(document_path).and_then(|mut document| {
+            document.strict |= strict;
+            document.to_pdf_document().map_err(|error| {
+                CliError::render(ContextError {
+                    context: format!("Unable to convert the document {:?}", document_path),
+                    source_error: Some(error.to_string()),
+                    ..error
+                })
+                .with_path(document_path)
+            })
+        });
+        match validation_result {
+            Ok(pdf_document) => {
+                for warning in pdf_document.warnings() {
+                    failure::report_warning(warning, document_path);
+                }
+                println!("{:?} is valid", document_path)
+            }
+            Err(error) => {
+                error.report();
+                if exit_code == 0 {
+                    exit_code = error.exit_code();
+                }
+            }
+        }
+    }
+
+    exit_code
+}
+
+/// Lints each of the given JSON documents, printing the problems found in them in the given format.
+/// The exit code is the one of the first document which can't be read, if any, or otherwise it is
+/// non-zero if any problem has been found.
+fn lint(document_paths: &[PathBuf], format: LintFormat) -> i32 {
+    let mut exit_code = 0;
+    let mut issues_by_document = serde_json::Map::new();
+    for document_path in document_paths {
+        let lint_result = read_document(document_path).and_then(|document| {
+            document
+                .lint()
+                .map_err(|error| CliError::io(error).with_path(document_path))
+        });
+        let issues = match lint_result {
+            Ok(issues) => issues,
+            Err(error) => {
+                error.report();
+                if exit_code == 0 {
+                    exit_code = error.exit_code();
+                }
+                continue;
+            }
+        };
+        if !issues.is_empty() && exit_code == 0 {
+            exit_code = failure::ErrorCode::Failed.exit_code();
+        }
+
+        match format {
+            LintFormat::Human => {
+                for issue in &issues {
+                    println!("{}: {}", document_path.display(), issue);
+                }
+            }
+            LintFormat::Json => {
+                issues_by_document.insert(
+                    document_path.display().to_string(),
+                    serde_json::to_value(&issues).unwrap(),
+                );
+            }
+        }
+    }
+    if let LintFormat::Json = format {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&issues_by_document).unwrap()
+        );
+    }
+
+    exit_code
+}
+
+/// Concatenates the operations of the given JSON documents and saves the result as a JSON document,
+/// which keeps the document ID, the instance ID and the page background of the first document.
+fn merge(document_paths: &[PathBuf], output_document_path: &Path) -> Result<(), CliError> {
+    let mut documents = document_paths
+        .iter()
+        .map(|document_path| read_document(document_path))
+        .collect::<Result<Vec<_>, CliError>>()?
+        .into_iter();
+    let Some(mut merged_document) = documents.next() else {
+        return Err(CliError::usage(ContextError::with_context(
+            "No documents to be merged",
+        )));
+    };
+    for document in documents {
+        merged_document.operations.extend(document.operations);
+    }
+
+    let merged_document_content =
+        serde_json::to_string_pretty(&merged_document).map_err(|error| {
+            CliError::render(ContextError::with_error(
+                "Failed to serialize the document",
+                &error,
+            ))
+        })?;
+    std::fs::write(output_document_path, merged_document_content).map_err(|error| {
+        CliError::io(ContextError::with_error(
+            format!("Failed to save the document {:?}", output_document_path),
+            &error,
+        ))
+        .with_path(output_document_path)
+    })
+}
+
+/// Optimizes the given PDF file in pure Rust, printing the size of the file before and after the optimization.
+fn optimize(input_path: &Path, output_path: &Path) -> Result<(), CliError> {
+    let file_size = |path: &Path| {
+        std::fs::metadata(path)
+            .map(|metadata| metadata.len())
+            .map_err(|error| {
+                CliError::io(ContextError::with_error(
+                    format!("Failed to read the PDF file {:?}", path),
+                    &error,
+                ))
+                .with_path(path)
+            })
+    };
+    let input_size = file_size(input_path)?;
+    textr::pdf::optimize_pdf_file(input_path, output_path)
+        .map_err(|error| CliError::parse(error).with_path(input_path))?;
+    let output_size = file_size(output_path)?;
+    println!(
+        "Optimized {:?} into {:?}: {} bytes -> {} bytes",
+        input_path, output_path, input_size, output_size
+    );
+
+    Ok(())
+}
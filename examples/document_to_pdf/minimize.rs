@@ -0,0 +1,91 @@
+use std::{
+    panic::{self, AssertUnwindSafe},
+    path::Path,
+};
+use textr::{
+    document::Document, error::ContextError, error::ErrorKind, font::FontRegistry,
+    testing::minimize_document,
+};
+
+use crate::{config::Config, failure::CliError};
+
+/// The way in which the conversion of a document fails.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Failure {
+    /// The conversion returns an error of the given kind.
+    Error(ErrorKind),
+    /// The conversion panics.
+    Panic,
+}
+
+/// Shrinks the JSON document at the given path, which fails to convert, into a minimal JSON document which still
+/// fails in the same way, that is with an error of the same kind or with a panic, and saves it at the output path.
+pub fn minimize(
+    document_path: &Path,
+    output_document_path: &Path,
+    strict: bool,
+    config: &Config,
+) -> Result<(), CliError> {
+    let mut document = crate::read_document(document_path)?;
+    document.strict |= strict;
+    let font_registry = config.font_registry().map_err(CliError::io)?;
+
+    // The panics are expected while minimizing, so they are not printed every time the document is converted
+    let panic_hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let failure = conversion_failure(&document, &font_registry);
+    let minimized_document = failure.map(|failure| {
+        minimize_document(&document, |document| {
+            conversion_failure(document, &font_registry) == Some(failure)
+        })
+    });
+    panic::set_hook(panic_hook);
+
+    let (Some(failure), Some(minimized_document)) = (failure, minimized_document) else {
+        return Err(CliError::usage(ContextError::with_context(format!(
+            "The document {:?} is converted without errors, so there is nothing to minimize",
+            document_path
+        )))
+        .with_path(document_path));
+    };
+
+    let minimized_document_content =
+        serde_json::to_string_pretty(&minimized_document).map_err(|error| {
+            CliError::render(ContextError::with_error(
+                "Failed to serialize the document",
+                &error,
+            ))
+        })?;
+    std::fs::write(output_document_path, minimized_document_content).map_err(|error| {
+        CliError::io(ContextError::with_error(
+            format!("Failed to save the document {:?}", output_document_path),
+            &error,
+        ))
+        .with_path(output_document_path)
+    })?;
+    println!(
+        "Minimized {:?} from {} to {} operations failing with {:?} into {:?}",
+        document_path,
+        document.operations.len(),
+        minimized_document.operations.len(),
+        failure,
+        output_document_path
+    );
+
+    Ok(())
+}
+
+/// Converts the given document and saves it in memory, returning the way in which it fails, if it does.
+fn conversion_failure(document: &Document, font_registry: &FontRegistry) -> Option<Failure> {
+    let conversion_result = panic::catch_unwind(AssertUnwindSafe(|| {
+        document
+            .to_pdf_document_with_fonts(font_registry)
+            .and_then(|mut pdf_document| pdf_document.save_to_bytes())
+    }));
+
+    match conversion_result {
+        Ok(Ok(_)) => None,
+        Ok(Err(error)) => Some(Failure::Error(error.kind)),
+        Err(_) => Some(Failure::Panic),
+    }
+}
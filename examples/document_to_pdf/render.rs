@@ -0,0 +1,744 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant, SystemTime},
+};
+use textr::{
+    document::{Document, Operation},
+    error::{ContextError, ErrorKind},
+    font::FontRegistry,
+    pdf,
+    pdf_info::PdfInfo,
+    timings::{Stage, Timings},
+    warning::Warning,
+};
+
+use crate::{
+    config::{Config, Optimization},
+    failure::{CliError, ErrorCode, ErrorFormat},
+};
+
+/// The arguments of the `render` subcommand are the path of the JSON document and the
+/// path of the output file, feel free to add more depending on the need.
+/// When the document path is a directory, all the JSON documents found in it are
+/// converted and the output path is the directory where the output files are saved.
+/// The flags which are not given fall back to the project configuration (see `Config`).
+#[derive(clap::Args)]
+pub struct RenderArguments {
+    /// The path of the JSON document, or of a directory of JSON documents.
+    #[arg(short = 'd', long = "document", value_name = "document_file")]
+    document_path: PathBuf,
+    /// The path of the output file, or of the output directory. It defaults to the output directory of the
+    /// project configuration, where the output file is named after the document.
+    #[arg(short = 'o', long = "output", value_name = "output_file")]
+    output_path: Option<PathBuf>,
+    /// The format of the output, which is inferred from the extension of the output file if not given.
+    #[arg(short = 'f', long = "format", value_enum)]
+    format: Option<OutputFormat>,
+    /// Keep running and convert the document again whenever it, the project configuration or any of its fonts change.
+    #[arg(short = 'w', long = "watch")]
+    watch: bool,
+    /// The number of documents converted in parallel when converting a directory, which defaults
+    /// to the number of available processors.
+    #[arg(short = 'j', long = "jobs", value_name = "jobs_count")]
+    jobs: Option<usize>,
+    /// Print how long each stage of the conversion takes, such as parsing, drawing and saving.
+    #[arg(short = 't', long = "timings")]
+    timings: bool,
+    /// How the PDF documents are optimized, which defaults to the project configuration or else to ghostscript.
+    #[arg(long = "optimization", value_enum)]
+    optimization: Option<Optimization>,
+    /// Convert the documents in memory without writing anything, reporting the number of pages, the fonts
+    /// which would be embedded and the approximate size of the output.
+    #[arg(short = 'n', long = "dry-run")]
+    dry_run: bool,
+    /// Write the PDF documents page by page rather than holding them in memory as a whole, which suits documents
+    /// with thousands of pages. The documents are then only compressed, and optimized with ghostscript if requested.
+    #[arg(long = "streaming")]
+    streaming: bool,
+}
+
+/// The formats into which a document can be rendered.
+#[derive(Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// A PDF document, optimized according to the `--optimization` flag.
+    Pdf,
+    /// An SVG image with the pages placed one below the other.
+    Svg,
+    /// A standalone HTML page.
+    Html,
+    /// Plain text in reading order.
+    Txt,
+}
+
+impl OutputFormat {
+    /// Infers the output format from the extension of the given path.
+    fn from_extension(path: &Path) -> Result<Self, ContextError> {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("pdf") => Ok(OutputFormat::Pdf),
+            Some("svg") => Ok(OutputFormat::Svg),
+            Some("html") => Ok(OutputFormat::Html),
+            Some("txt") => Ok(OutputFormat::Txt),
+            _ => Err(ContextError::with_context(format!(
+                "Unable to infer the output format from {:?}, use --format with one of pdf, svg, html or txt",
+                path
+            ))),
+        }
+    }
+
+    /// The extension of the files written in the output format.
+    fn extension(self) -> &'static str {
+        match self {
+            OutputFormat::Pdf => "pdf",
+            OutputFormat::Svg => "svg",
+            OutputFormat::Html => "html",
+            OutputFormat::Txt => "txt",
+        }
+    }
+}
+
+/// Renders the document, or the directory of documents, returning the exit code of the process.
+pub fn render(render_arguments: &RenderArguments, config: &Config) -> i32 {
+    let optimization = render_arguments
+        .optimization
+        .or(config.optimization)
+        .unwrap_or(Optimization::Ghostscript);
+    if render_arguments.dry_run {
+        return dry_run(render_arguments, config, optimization);
+    }
+
+    if render_arguments.document_path.is_dir() {
+        if render_arguments.watch {
+            let error = CliError::usage(ContextError::with_context(
+                "The --watch flag is not supported when converting a directory",
+            ));
+            error.report();
+            return error.exit_code();
+        }
+        let Some(output_directory) = render_arguments
+            .output_path
+            .as_ref()
+            .or(config.output_directory.as_ref())
+        else {
+            let error = missing_output_path_error();
+            error.report();
+            return error.exit_code();
+        };
+        // The documents of a directory are converted into PDF files unless requested otherwise
+        let format = render_arguments.format.unwrap_or(OutputFormat::Pdf);
+        let failed_conversions_count = convert_directory(
+            render_arguments,
+            config,
+            output_directory,
+            format,
+            optimization,
+        );
+        return if failed_conversions_count > 0 {
+            ErrorCode::Failed.exit_code()
+        } else {
+            0
+        };
+    }
+
+    let (output_path, format) = match output_file(render_arguments, config) {
+        Ok(output_file) => output_file,
+        Err(error) => {
+            error.report();
+            return error.exit_code();
+        }
+    };
+
+    if !render_arguments.watch {
+        return match convert_document(
+            &render_arguments.document_path,
+            &output_path,
+            format,
+            None,
+            config,
+            optimization,
+            render_arguments.streaming,
+        ) {
+            Ok(timings) => {
+                if render_arguments.timings {
+                    println!("{}", timings);
+                }
+                0
+            }
+            Err(error) => {
+                error.report();
+                error.exit_code()
+            }
+        };
+    }
+
+    // In watch mode the errors are reported without exiting, so that the document can be fixed and saved again.
+    // The configuration is discovered again whenever it is changed, created or removed
+    let mut last_config_path = Config::discover_path().ok().flatten();
+    let mut last_modification_times = Vec::new();
+    let mut reloaded_config = None;
+    loop {
+        let config_path = Config::discover_path().ok().flatten();
+        let modification_times = watched_modification_times(
+            render_arguments,
+            config_path.as_deref(),
+            reloaded_config.as_ref().unwrap_or(config),
+        );
+        if modification_times != last_modification_times {
+            let config_changed = !last_modification_times.is_empty()
+                && (config_path != last_config_path
+                    || modification_times.get(1) != last_modification_times.get(1));
+            last_config_path = config_path;
+            last_modification_times = modification_times;
+            if config_changed {
+                match Config::discover() {
+                    Ok(config) => reloaded_config = Some(config),
+                    Err(error) => CliError::parse(error).report(),
+                }
+            }
+            let config = reloaded_config.as_ref().unwrap_or(config);
+            let optimization = render_arguments
+                .optimization
+                .or(config.optimization)
+                .unwrap_or(Optimization::Ghostscript);
+            // The output file is determined again, since the configured output directory may have changed
+            let conversion_result =
+                output_file(render_arguments, config).and_then(|(output_path, format)| {
+                    convert_document(
+                        &render_arguments.document_path,
+                        &output_path,
+                        format,
+                        None,
+                        config,
+                        optimization,
+                        render_arguments.streaming,
+                    )
+                    .map(|timings| (output_path, timings))
+                });
+            match conversion_result {
+                Ok((output_path, timings)) => {
+                    println!(
+                        "Converted {:?} into {:?}",
+                        render_arguments.document_path, output_path
+                    );
+                    if render_arguments.timings {
+                        println!("{}", timings);
+                    }
+                }
+                Err(error) => error.report(),
+            }
+        }
+        std::thread::sleep(std::time::Duration::from_millis(500));
+    }
+}
+
+/// Converts the document, or each document of the directory, in memory without writing anything, printing the
+/// number of pages, the fonts which would be embedded and the approximate size of each output file. The exit code
+/// is the one of the first document which can't be converted, if any.
+fn dry_run(render_arguments: &RenderArguments, config: &Config, optimization: Optimization) -> i32 {
+    let is_directory = render_arguments.document_path.is_dir();
+    let format = match (render_arguments.format, &render_arguments.output_path) {
+        (Some(format), _) => format,
+        (None, Some(output_path)) if !is_directory => {
+            match OutputFormat::from_extension(output_path) {
+                Ok(format) => format,
+                Err(error) => {
+                    let error = CliError::usage(error);
+                    error.report();
+                    return error.exit_code();
+                }
+            }
+        }
+        (None, _) => OutputFormat::Pdf,
+    };
+    let document_paths = if is_directory {
+        match json_document_paths(&render_arguments.document_path) {
+            Ok(document_paths) => document_paths,
+            Err(error) => {
+                let error = CliError::io(error).with_path(&render_arguments.document_path);
+                error.report();
+                return error.exit_code();
+            }
+        }
+    } else {
+        vec![render_arguments.document_path.clone()]
+    };
+    let font_registry = match format {
+        OutputFormat::Pdf => match config.font_registry() {
+            Ok(font_registry) => Some(font_registry),
+            Err(error) => {
+                let error = CliError::io(error);
+                error.report();
+                return error.exit_code();
+            }
+        },
+        _ => None,
+    };
+
+    let mut exit_code = 0;
+    for document_path in &document_paths {
+        match estimate_document(document_path, format, font_registry.as_ref(), config) {
+            Ok(estimate) => {
+                println!(
+                    "{:?}: {} page(s), about {} bytes as {}{}",
+                    document_path,
+                    estimate.page_count,
+                    estimate.output_size,
+                    format.extension(),
+                    match (format, optimization) {
+                        (OutputFormat::Pdf, Optimization::Ghostscript) =>
+                            " before the optimization with ghostscript",
+                        _ => "",
+                    }
+                );
+                for (font_name, embedded_size) in &estimate.embedded_fonts {
+                    println!("  Embeds {} ({} bytes)", font_name, embedded_size);
+                }
+            }
+            Err(error) => {
+                error.report();
+                if exit_code == 0 {
+                    exit_code = error.exit_code();
+                }
+            }
+        }
+    }
+
+    exit_code
+}
+
+/// The expected outcome of converting a document, see `estimate_document`.
+struct Estimate {
+    /// The number of pages of the output.
+    page_count: usize,
+    /// The size in bytes of the output file.
+    output_size: usize,
+    /// The fonts embedded into a PDF document, each with its file name and its embedded size in bytes.
+    embedded_fonts: Vec<(String, usize)>,
+}
+
+/// Parses the JSON document, lays it out and converts it in memory into the given format, estimating the output
+/// without writing it. PDF documents employ the fonts of the given registry, if any, otherwise the configured fonts
+/// are read, and are read back (see `PdfInfo`) in
+/// order to find out which fonts are embedded.
+fn estimate_document(
+    document_path: &Path,
+    format: OutputFormat,
+    font_registry: Option<&FontRegistry>,
+    config: &Config,
+) -> Result<Estimate, CliError> {
+    let mut document = crate::read_document(document_path)?;
+    config.apply_page_size(&mut document.operations);
+    let render_error = |error| CliError::render(error).with_path(document_path);
+
+    let output_content = match format {
+        OutputFormat::Pdf => {
+            let configured_font_registry;
+            let font_registry = match font_registry {
+                Some(font_registry) => font_registry,
+                None => {
+                    configured_font_registry = config.font_registry().map_err(CliError::io)?;
+                    &configured_font_registry
+                }
+            };
+            let mut pdf_document = document
+                .to_pdf_document_with_fonts(font_registry)
+                .map_err(render_error)?;
+            pdf_document.optimize();
+            let pdf_document_bytes = pdf_document.save_to_bytes().map_err(render_error)?;
+            let pdf_info = PdfInfo::from_bytes(&pdf_document_bytes).map_err(render_error)?;
+            // The fonts are named after their font index, which tells the file they have been read from
+            let embedded_fonts = pdf_info
+                .fonts
+                .iter()
+                .filter_map(|font| {
+                    let font_index = font.name.strip_prefix('F')?.parse::<usize>().ok()?;
+                    let font_path = font_registry.font_paths().get(font_index)?;
+                    let font_file_name = font_path.file_name()?.to_string_lossy();
+                    Some((
+                        format!("{} from {}", font.name, font_file_name),
+                        font.embedded_size?,
+                    ))
+                })
+                .collect();
+
+            return Ok(Estimate {
+                page_count: pdf_info.pages.len(),
+                output_size: pdf_document_bytes.len(),
+                embedded_fonts,
+            });
+        }
+        OutputFormat::Svg => document.to_svg(),
+        OutputFormat::Html => document.to_html(),
+        OutputFormat::Txt => document.to_plain_text(),
+    }
+    .map_err(render_error)?;
+
+    Ok(Estimate {
+        page_count: document
+            .operations
+            .iter()
+            .filter(|operation| matches!(operation, Operation::AppendNewPage { .. }))
+            .count(),
+        output_size: output_content.len(),
+        embedded_fonts: Vec::new(),
+    })
+}
+
+/// Determines the output file of a single document and its format. The output file is either the given one, whose
+/// extension determines the format unless it is given, or a file named after the document inside of the configured
+/// output directory, in which case the format defaults to PDF.
+fn output_file(
+    render_arguments: &RenderArguments,
+    config: &Config,
+) -> Result<(PathBuf, OutputFormat), CliError> {
+    if let Some(output_path) = &render_arguments.output_path {
+        let format = match render_arguments.format {
+            Some(format) => format,
+            None => OutputFormat::from_extension(output_path).map_err(CliError::usage)?,
+        };
+        return Ok((output_path.clone(), format));
+    }
+
+    let output_directory = config
+        .output_directory
+        .as_ref()
+        .ok_or_else(missing_output_path_error)?;
+    std::fs::create_dir_all(output_directory).map_err(|error| {
+        CliError::io(ContextError::with_error(
+            format!(
+                "Failed to create the output directory {:?}",
+                output_directory
+            ),
+            &error,
+        ))
+        .with_path(output_directory)
+    })?;
+    let format = render_arguments.format.unwrap_or(OutputFormat::Pdf);
+    let document_name = render_arguments
+        .document_path
+        .file_name()
+        .unwrap_or_default();
+
+    Ok((
+        output_directory
+            .join(document_name)
+            .with_extension(format.extension()),
+        format,
+    ))
+}
+
+/// The error reported when neither the `--output` flag nor the configured output directory is given.
+fn missing_output_path_error() -> CliError {
+    CliError::usage(ContextError::with_context(format!(
+        "Unable to determine where to save the output, use --output or set output-directory in {}",
+        crate::config::CONFIG_FILE_NAME
+    )))
+}
+
+/// Converts every JSON document found in the given directory and its subdirectories, saving each output file
+/// at the same relative path inside of the output directory. The documents are converted in parallel by the
+/// requested number of jobs, which share the fonts read beforehand. The progress is printed as each document is
+/// converted, while a summary of the conversions, including their timings, is printed at the end and the number
+/// of failed conversions is returned.
+fn convert_directory(
+    render_arguments: &RenderArguments,
+    config: &Config,
+    output_directory: &Path,
+    format: OutputFormat,
+    optimization: Optimization,
+) -> usize {
+    let started_at = Instant::now();
+    let document_paths = match json_document_paths(&render_arguments.document_path) {
+        Ok(document_paths) => document_paths,
+        Err(error) => {
+            CliError::io(error)
+                .with_path(&render_arguments.document_path)
+                .report();
+            return 1;
+        }
+    };
+    // The fonts are only needed in memory when producing PDF documents
+    let font_registry = match format {
+        OutputFormat::Pdf => match config.font_registry() {
+            Ok(font_registry) => Some(font_registry),
+            Err(error) => {
+                CliError::io(error).report();
+                return 1;
+            }
+        },
+        _ => None,
+    };
+    let jobs_count = render_arguments
+        .jobs
+        .unwrap_or_else(|| {
+            std::thread::available_parallelism().map_or(1, |jobs_count| jobs_count.get())
+        })
+        .clamp(1, document_paths.len().max(1));
+
+    // Each job takes the next document which has not been converted yet, until none is left
+    let next_document_index = AtomicUsize::new(0);
+    let converted_documents_count = AtomicUsize::new(0);
+    let conversion_results = Mutex::new(Vec::with_capacity(document_paths.len()));
+    std::thread::scope(|scope| {
+        for _ in 0..jobs_count {
+            scope.spawn(|| loop {
+                let document_index = next_document_index.fetch_add(1, Ordering::Relaxed);
+                let Some(document_path) = document_paths.get(document_index) else {
+                    break;
+                };
+                // Preserve the path of the document relative to the input directory
+                let relative_path = document_path
+                    .strip_prefix(&render_arguments.document_path)
+                    .unwrap_or(document_path);
+                let output_path = output_directory
+                    .join(relative_path)
+                    .with_extension(format.extension());
+
+                let conversion_started_at = Instant::now();
+                let conversion_result = output_path
+                    .parent()
+                    .map_or(Ok(()), std::fs::create_dir_all)
+                    .map_err(|error| {
+                        CliError::io(ContextError::with_error(
+                            format!(
+                                "Failed to create the output directory for {:?}",
+                                output_path
+                            ),
+                            &error,
+                        ))
+                    })
+                    .and_then(|()| {
+                        convert_document(
+                            document_path,
+                            &output_path,
+                            format,
+                            font_registry.as_ref(),
+                            config,
+                            optimization,
+                            render_arguments.streaming,
+                        )
+                    });
+                let conversion_duration = conversion_started_at.elapsed();
+                let progress = format!(
+                    "[{}/{}]",
+                    converted_documents_count.fetch_add(1, Ordering::Relaxed) + 1,
+                    document_paths.len()
+                );
+                match &conversion_result {
+                    Ok(timings) => {
+                        println!(
+                            "{} Converted {:?} into {:?} in {:.2?}",
+                            progress, document_path, output_path, conversion_duration
+                        );
+                        if render_arguments.timings {
+                            println!("{} {}", progress, timings);
+                        }
+                    }
+                    Err(error) => match crate::failure::error_format() {
+                        ErrorFormat::Human => eprintln!(
+                            "{} Failed to convert {:?}: {}",
+                            progress, document_path, error.error
+                        ),
+                        ErrorFormat::Json => error.report(),
+                    },
+                }
+                conversion_results.lock().unwrap().push((
+                    document_index,
+                    conversion_duration,
+                    conversion_result.ok(),
+                ));
+            });
+        }
+    });
+    let mut conversion_results = conversion_results.into_inner().unwrap();
+    conversion_results.sort_by_key(|(document_index, _, _)| *document_index);
+
+    let failed_conversions = conversion_results
+        .iter()
+        .filter(|(_, _, timings)| timings.is_none())
+        .map(|(document_index, _, _)| &document_paths[*document_index])
+        .collect::<Vec<_>>();
+    println!(
+        "{} of {} documents converted successfully, {} failed",
+        document_paths.len() - failed_conversions.len(),
+        document_paths.len(),
+        failed_conversions.len()
+    );
+    for document_path in &failed_conversions {
+        println!("  {:?}", document_path);
+    }
+    if let Some((slowest_document_index, slowest_duration, _)) = conversion_results
+        .iter()
+        .max_by_key(|(_, conversion_duration, _)| *conversion_duration)
+    {
+        let total_duration = conversion_results
+            .iter()
+            .map(|(_, conversion_duration, _)| *conversion_duration)
+            .sum::<Duration>();
+        println!(
+            "Finished in {:.2?} with {} job(s), taking {:.2?} per document on average (slowest {:?} in {:.2?})",
+            started_at.elapsed(),
+            jobs_count,
+            total_duration / conversion_results.len() as u32,
+            document_paths[*slowest_document_index],
+            slowest_duration
+        );
+    }
+    if render_arguments.timings {
+        let mut total_timings = Timings::default();
+        for timings in conversion_results
+            .iter()
+            .flat_map(|(_, _, timings)| timings)
+        {
+            total_timings.merge(timings);
+        }
+        if total_timings != Timings::default() {
+            println!("Total time spent in each stage: {}", total_timings);
+        }
+    }
+
+    failed_conversions.len()
+}
+
+/// Collects the paths of the JSON documents present in the given directory and its subdirectories,
+/// sorted so that the documents are always converted in the same order.
+fn json_document_paths(directory_path: &Path) -> Result<Vec<PathBuf>, ContextError> {
+    let mut document_paths = Vec::new();
+    let directory_entries = std::fs::read_dir(directory_path).map_err(|error| {
+        ContextError::with_error(
+            format!("Failed to read the directory {:?}", directory_path),
+            &error,
+        )
+    })?;
+    for directory_entry in directory_entries {
+        let entry_path = directory_entry
+            .map_err(|error| {
+                ContextError::with_error(
+                    format!("Failed to read the directory {:?}", directory_path),
+                    &error,
+                )
+            })?
+            .path();
+        if entry_path.is_dir() {
+            document_paths.extend(json_document_paths(&entry_path)?);
+        } else if entry_path.extension() == Some("json".as_ref()) {
+            document_paths.push(entry_path);
+        }
+    }
+    document_paths.sort();
+
+    Ok(document_paths)
+}
+
+/// Reads the JSON document, parses it into a `Document` and saves it in the given format, returning how long
+/// each stage of the conversion took. PDF documents employ the fonts of the given registry, if any, otherwise
+/// the configured fonts are read, and are then optimized as requested, unless they are written page by page.
+fn convert_document(
+    document_path: &Path,
+    output_path: &Path,
+    format: OutputFormat,
+    font_registry: Option<&FontRegistry>,
+    config: &Config,
+    optimization: Optimization,
+    streaming: bool,
+) -> Result<Timings, CliError> {
+    let mut timings = Timings::default();
+    let mut document = timings.time(Stage::Parsing, || crate::read_document(document_path))?;
+    config.apply_page_size(&mut document.operations);
+
+    let output_content = match format {
+        OutputFormat::Pdf => {
+            // Save the document as a PDF file, which is optimized in pure Rust, then optimize the result further
+            // with ghostscript if requested
+            let configured_font_registry;
+            let font_registry = match font_registry {
+                Some(font_registry) => font_registry,
+                None => {
+                    configured_font_registry = timings
+                        .time(Stage::FontLoading, || config.font_registry())
+                        .map_err(CliError::io)?;
+                    &configured_font_registry
+                }
+            };
+            let warnings = if streaming {
+                stream_document(&document, output_path, font_registry, &mut timings)
+            } else {
+                document.save_to_pdf_file_with_timings(output_path, font_registry, &mut timings)
+            }
+            .map_err(|error| CliError::render(error).with_path(document_path))?;
+            for warning in &warnings {
+                crate::failure::report_warning(warning, document_path);
+            }
+            if optimization == Optimization::Ghostscript {
+                timings
+                    .time(Stage::Optimizing, || {
+                        pdf::optimize_pdf_file_with_gs(output_path.as_os_str().to_str().unwrap())
+                    })
+                    .map_err(|error| CliError::render(error).with_path(output_path))?;
+            }
+            return Ok(timings);
+        }
+        OutputFormat::Svg => timings.time(Stage::Drawing, || document.to_svg()),
+        OutputFormat::Html => timings.time(Stage::Drawing, || document.to_html()),
+        OutputFormat::Txt => timings.time(Stage::Drawing, || document.to_plain_text()),
+    }
+    .map_err(|error| CliError::render(error).with_path(document_path))?;
+    timings.time(Stage::Saving, || {
+        std::fs::write(output_path, output_content).map_err(|error| {
+            CliError::io(ContextError::with_error(
+                format!("Failed to save the output file {:?}", output_path),
+                &error,
+            ))
+            .with_path(output_path)
+        })
+    })?;
+
+    Ok(timings)
+}
+
+/// Writes the document as a PDF file page by page (see `Document::stream_to_pdf`), returning the warnings
+/// raised during the conversion.
+fn stream_document(
+    document: &Document,
+    output_path: &Path,
+    font_registry: &FontRegistry,
+    timings: &mut Timings,
+) -> Result<Vec<Warning>, ContextError> {
+    let output_file = std::fs::File::create(output_path).map_err(|error| {
+        ContextError::with_error("Failed to create the output file", &error)
+            .with_kind(ErrorKind::Io)
+    })?;
+    // Drawing and writing happen at once, so they are timed as a whole
+    let (_, warnings) = timings.time(Stage::Writing, || {
+        document.stream_to_pdf(font_registry, std::io::BufWriter::new(output_file))
+    })?;
+
+    Ok(warnings)
+}
+
+/// Collects the modification times of the JSON document, of the configuration file and of the configured fonts,
+/// which are the files that affect the output, in this order. Files which can't be read, as well as the missing
+/// configuration file, are reported as having no modification time.
+fn watched_modification_times(
+    render_arguments: &RenderArguments,
+    config_path: Option<&Path>,
+    config: &Config,
+) -> Vec<Option<SystemTime>> {
+    let mut watched_paths = vec![
+        render_arguments.document_path.clone(),
+        config_path.map(Path::to_path_buf).unwrap_or_default(),
+    ];
+    watched_paths.extend(config.font_paths().unwrap_or_default());
+
+    watched_paths
+        .iter()
+        .map(|path| {
+            std::fs::metadata(path)
+                .and_then(|metadata| metadata.modified())
+                .ok()
+        })
+        .collect()
+}
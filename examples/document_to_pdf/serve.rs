@@ -0,0 +1,318 @@
+use std::{
+    io::{self, BufRead, BufReader, Read, Write as _},
+    net::{TcpListener, TcpStream},
+    path::Path,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+use textr::{error::ContextError, font::FontRegistry};
+
+use crate::{
+    config::Config,
+    failure::{CliError, ErrorCode},
+};
+
+/// The largest JSON document which is accepted in the body of a request, in bytes.
+const MAX_BODY_SIZE: usize = 64 * 1024 * 1024;
+
+/// The longest request line or header which is accepted, in bytes.
+const MAX_LINE_LENGTH: usize = 8192;
+
+/// The largest number of headers which is accepted in a request.
+const MAX_HEADER_COUNT: usize = 100;
+
+/// The largest number of connections which are handled at the same time, beyond which the service is unavailable.
+const MAX_CONNECTION_COUNT: usize = 64;
+
+/// How long a connection may wait for the client to send or receive any data before it is closed.
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long a client may take to send its whole request before its connection is closed, however steadily it sends
+/// it, so that a slow client can't hold its connection forever by sending a byte at a time.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// The name given to the documents received in the body of the requests, in place of their path.
+const REQUEST_BODY_NAME: &str = "<request body>";
+
+/// The arguments of the `serve` subcommand, which converts the JSON documents received over HTTP into PDF documents.
+/// The fonts and the page size of the project configuration are read once, when the service starts (see `Config`).
+#[derive(clap::Args)]
+pub struct ServeArguments {
+    /// The address and the port on which the service listens.
+    #[arg(long = "address", default_value = "127.0.0.1:8080")]
+    address: String,
+}
+
+/// A response to an HTTP request, with its status, the type of its content and the content itself.
+struct Response {
+    /// The status code of the response, such as 200.
+    status: u16,
+    /// The media type of the content of the response.
+    content_type: &'static str,
+    /// The content of the response.
+    content: Vec<u8>,
+}
+
+impl Response {
+    /// A response with the given status whose content is the given JSON value.
+    fn json(status: u16, value: serde_json::Value) -> Self {
+        Response {
+            status,
+            content_type: "application/json",
+            content: value.to_string().into_bytes(),
+        }
+    }
+
+    /// A response with the given status and a JSON object describing the given error.
+    fn error(status: u16, error: &CliError) -> Self {
+        Self::json(status, error.to_json())
+    }
+
+    /// Replaces the status of the response with the given one.
+    fn with_status(mut self, status: u16) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// A response about a malformed request, with the given message.
+    fn bad_request(message: &str) -> Self {
+        Self::error(400, &CliError::usage(ContextError::with_context(message)))
+    }
+}
+
+/// Converts the JSON documents received in the body of the `POST /render` requests into PDF documents, which are
+/// returned as the content of the responses, while `GET /health` tells whether the service is running. Each
+/// connection is handled on a thread of its own, up to `MAX_CONNECTION_COUNT` at the same time, and is closed
+/// when the client stays idle for longer than `CONNECTION_TIMEOUT`, or takes longer than `REQUEST_TIMEOUT` to send
+/// its request. The errors are returned as JSON objects, in
+/// the same form as in the JSON error format. The service runs until the process is stopped.
+pub fn serve(serve_arguments: &ServeArguments, config: &Config) -> i32 {
+    let font_registry = match config.font_registry() {
+        Ok(font_registry) => font_registry,
+        Err(error) => {
+            let error = CliError::io(error);
+            error.report();
+            return error.exit_code();
+        }
+    };
+    let listener = match TcpListener::bind(&serve_arguments.address) {
+        Ok(listener) => listener,
+        Err(error) => {
+            let error = CliError::io(ContextError::with_error(
+                format!("Unable to listen on {}", serve_arguments.address),
+                &error,
+            ));
+            error.report();
+            return error.exit_code();
+        }
+    };
+    eprintln!("Listening on http://{}", serve_arguments.address);
+
+    let connection_count = AtomicUsize::new(0);
+    thread::scope(|scope| {
+        for stream in listener.incoming() {
+            let Ok(stream) = stream else {
+                continue;
+            };
+            // A client which never sends or reads anything must not hold its connection forever
+            if stream.set_read_timeout(Some(CONNECTION_TIMEOUT)).is_err()
+                || stream.set_write_timeout(Some(CONNECTION_TIMEOUT)).is_err()
+            {
+                continue;
+            }
+            if connection_count.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTION_COUNT {
+                connection_count.fetch_sub(1, Ordering::SeqCst);
+                let response = Response::bad_request("Too many connections are being handled")
+                    .with_status(503);
+                let _ = write_response(&stream, &response);
+                continue;
+            }
+            let (font_registry, connection_count) = (&font_registry, &connection_count);
+            scope.spawn(move || {
+                handle_connection(stream, font_registry, config);
+                connection_count.fetch_sub(1, Ordering::SeqCst);
+            });
+        }
+    });
+
+    0
+}
+
+/// Reads a single request from the given connection and writes the response to it, before closing it.
+fn handle_connection(stream: TcpStream, font_registry: &FontRegistry, config: &Config) {
+    let deadline_reader = DeadlineReader {
+        stream: &stream,
+        deadline: Instant::now() + REQUEST_TIMEOUT,
+    };
+    let response = match read_request(&mut BufReader::new(deadline_reader)) {
+        Ok((method, path, body)) => match (method.as_str(), path.as_str()) {
+            ("POST", "/render") => render(&body, font_registry, config),
+            ("GET", "/health") => Response::json(200, serde_json::json!({ "status": "ok" })),
+            (_, "/render" | "/health") => {
+                Response::bad_request("The method is not allowed for this path").with_status(405)
+            }
+            _ => Response::bad_request("The path is not found").with_status(404),
+        },
+        Err(response) => response,
+    };
+    // The client may have gone away in the meantime, in which case there is no one to report the error to
+    let _ = write_response(&stream, &response);
+}
+
+/// A reader of the request sent over a connection which fails once the request has taken longer than its deadline
+/// to arrive, since the read timeout of the connection only bounds each read on its own.
+struct DeadlineReader<'a> {
+    /// The connection from which the request is read.
+    stream: &'a TcpStream,
+    /// The instant by which the whole request must have been read.
+    deadline: Instant,
+}
+
+impl Read for DeadlineReader<'_> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        // Each read may only wait for the time which is left before the deadline
+        let remaining_time = self.deadline.saturating_duration_since(Instant::now());
+        if remaining_time.is_zero() {
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "The request took too long to arrive",
+            ));
+        }
+        self.stream
+            .set_read_timeout(Some(remaining_time.min(CONNECTION_TIMEOUT)))?;
+
+        self.stream.read(buffer)
+    }
+}
+
+/// Reads the request line, the headers and the body of an HTTP/1.1 request, returning its method, its path and
+/// its body, or the response to be returned if the request is malformed.
+fn read_request(reader: &mut impl BufRead) -> Result<(String, String, Vec<u8>), Response> {
+    let request_line = read_line(reader).map_err(|response| match response {
+        Some(response) => response.with_status(414),
+        None => Response::bad_request("Unable to read the request line"),
+    })?;
+    let mut request_line_parts = request_line.split_whitespace();
+    let (Some(method), Some(path)) = (request_line_parts.next(), request_line_parts.next()) else {
+        return Err(Response::bad_request("The request line is malformed"));
+    };
+
+    // Only the length of the body is needed out of the headers
+    let mut content_length = 0;
+    for header_index in 0.. {
+        if header_index == MAX_HEADER_COUNT {
+            return Err(Response::bad_request("There are too many headers").with_status(431));
+        }
+        let header = read_line(reader).map_err(|response| match response {
+            Some(response) => response.with_status(431),
+            None => Response::bad_request("Unable to read the headers"),
+        })?;
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value
+                    .trim()
+                    .parse()
+                    .map_err(|_| Response::bad_request("The content length is malformed"))?;
+            }
+        }
+    }
+    if content_length > MAX_BODY_SIZE {
+        return Err(Response::bad_request("The document is too large").with_status(413));
+    }
+    // The body is only allocated as it arrives, so that a client can't reserve memory by lying about its length
+    let mut body = Vec::new();
+    reader
+        .take(content_length as u64)
+        .read_to_end(&mut body)
+        .ok()
+        .filter(|read_length| *read_length == content_length)
+        .ok_or_else(|| Response::bad_request("Unable to read the body"))?;
+
+    Ok((method.to_string(), path.to_string(), body))
+}
+
+/// Reads a line of the request of at most `MAX_LINE_LENGTH` bytes. The error is `None` if the line can't be read,
+/// or the response to be returned if the line is too long, whose status is to be set by the caller.
+fn read_line(reader: &mut impl BufRead) -> Result<String, Option<Response>> {
+    let mut line = String::new();
+    reader
+        .take(MAX_LINE_LENGTH as u64 + 1)
+        .read_line(&mut line)
+        .map_err(|_| None)?;
+    if line.len() > MAX_LINE_LENGTH {
+        return Err(Some(Response::bad_request(
+            "The request line or a header is too long",
+        )));
+    }
+
+    Ok(line)
+}
+
+/// Converts the JSON document in the given body into an optimized PDF document, returning it as the content of
+/// the response, or the error which prevented the conversion. The warnings are printed on the standard error.
+fn render(body: &[u8], font_registry: &FontRegistry, config: &Config) -> Response {
+    let document_path = Path::new(REQUEST_BODY_NAME);
+    let mut document = match crate::failure::parse_document(document_path, body) {
+        Ok(document) => document,
+        Err(error) => return Response::error(400, &error),
+    };
+    config.apply_page_size(&mut document.operations);
+
+    let conversion_result =
+        document
+            .to_pdf_document_with_fonts(font_registry)
+            .and_then(|mut pdf_document| {
+                pdf_document.optimize();
+                for warning in pdf_document.warnings() {
+                    crate::failure::report_warning(warning, document_path);
+                }
+                pdf_document.save_to_bytes()
+            });
+    match conversion_result {
+        Ok(pdf_document_bytes) => Response {
+            status: 200,
+            content_type: "application/pdf",
+            content: pdf_document_bytes,
+        },
+        Err(error) => {
+            let error = CliError::render(error);
+            // The documents which can't be converted are the fault of the client, unlike the fonts which can't be read
+            let status = match error.code {
+                ErrorCode::Io => 500,
+                _ => 422,
+            };
+            Response::error(status, &error)
+        }
+    }
+}
+
+/// Writes the given response to the connection, asking the client to close it afterwards.
+fn write_response(mut stream: &TcpStream, response: &Response) -> io::Result<()> {
+    let reason = match response.status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Payload Too Large",
+        414 => "URI Too Long",
+        422 => "Unprocessable Entity",
+        431 => "Request Header Fields Too Large",
+        503 => "Service Unavailable",
+        _ => "Internal Server Error",
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        reason,
+        response.content_type,
+        response.content.len()
+    )?;
+    stream.write_all(&response.content)?;
+    stream.flush()
+}
@@ -26,10 +26,11 @@ fn main() {
             page_index,
             layer_index_in_page,
             [0.0, 0.0, 0.0],
-            "Hello, world!".into(),
+            "Hello, world!",
             font_index,
             48.0,
             [50.0, 200.0],
+            0.0,
         )
         .unwrap();
 
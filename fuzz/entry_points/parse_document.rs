@@ -0,0 +1,26 @@
+//! Feeds arbitrary bytes into the parsing of a `Document`, converting the documents which can be parsed into
+//! PDF documents with the built-in fonts. Parsing and conversion errors are expected, while panics are bugs.
+
+#![no_main]
+
+use std::sync::OnceLock;
+
+use libfuzzer_sys::fuzz_target;
+use textr::{document::Document, font::FontRegistry};
+
+/// Returns the built-in fonts, which are read once for all the inputs.
+fn font_registry() -> &'static FontRegistry {
+    static FONT_REGISTRY: OnceLock<FontRegistry> = OnceLock::new();
+    FONT_REGISTRY.get_or_init(|| {
+        FontRegistry::builtin().expect("The fuzzer should be run from the root of the crate")
+    })
+}
+
+fuzz_target!(|document_bytes: &[u8]| {
+    let Ok(document) = Document::from_json_bytes(document_bytes) else {
+        return;
+    };
+    if let Ok(mut pdf_document) = document.to_pdf_document_with_fonts(font_registry()) {
+        let _ = pdf_document.save_to_bytes();
+    }
+});
@@ -0,0 +1,181 @@
+//! Feeds arbitrary sequences of operations into a `PdfDocument` through its public methods, then writes and saves
+//! it. The page, layer and font indices are not checked beforehand, so that the methods are the ones to reject
+//! the wrong ones: errors are expected, while panics, such as an overflow in the index arithmetic, are bugs.
+
+#![no_main]
+
+use std::sync::OnceLock;
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use textr::{
+    canvas::{FilledPath, PathSegment},
+    font::FontRegistry,
+    pdf::PdfDocument,
+};
+
+/// The number of built-in fonts loaded into the document, so that the font indices are mostly valid.
+const FONT_COUNT: usize = 2;
+
+/// A call of one of the methods of `PdfDocument`, with its arguments.
+#[derive(Debug, Arbitrary)]
+enum PdfOperation {
+    AddPageWithLayer {
+        page_width: f32,
+        page_height: f32,
+    },
+    ClearPage {
+        page_index: u8,
+    },
+    WriteText {
+        page_index: u8,
+        layer_index: u8,
+        color: [f32; 3],
+        text: String,
+        font_index: u8,
+        font_size: f32,
+        caret_position: [f32; 2],
+        letter_spacing: f32,
+    },
+    PaintPageBackground {
+        page_index: u8,
+        layer_index: u8,
+        color: [f32; 3],
+    },
+    FillPath {
+        page_index: u8,
+        layer_index: u8,
+        segments: Vec<Segment>,
+        color: [f32; 3],
+    },
+    AddImage {
+        page_index: u8,
+        layer_index: u8,
+        image_bytes: Vec<u8>,
+        position: [f32; 2],
+        size: [f32; 2],
+    },
+    Optimize,
+}
+
+/// A segment of a filled path, mirroring `PathSegment`.
+#[derive(Debug, Arbitrary)]
+enum Segment {
+    MoveTo([f32; 2]),
+    LineTo([f32; 2]),
+    CubicTo([f32; 2], [f32; 2], [f32; 2]),
+    Close,
+}
+
+impl From<&Segment> for PathSegment {
+    fn from(segment: &Segment) -> Self {
+        match *segment {
+            Segment::MoveTo(point) => PathSegment::MoveTo(point),
+            Segment::LineTo(point) => PathSegment::LineTo(point),
+            Segment::CubicTo(first_control_point, second_control_point, end_point) => {
+                PathSegment::CubicTo {
+                    first_control_point,
+                    second_control_point,
+                    end_point,
+                }
+            }
+            Segment::Close => PathSegment::Close,
+        }
+    }
+}
+
+/// Returns the built-in fonts, which are read once for all the inputs.
+fn font_registry() -> &'static FontRegistry {
+    static FONT_REGISTRY: OnceLock<FontRegistry> = OnceLock::new();
+    FONT_REGISTRY.get_or_init(|| {
+        FontRegistry::builtin().expect("The fuzzer should be run from the root of the crate")
+    })
+}
+
+fuzz_target!(|operations: Vec<PdfOperation>| {
+    let mut pdf_document = PdfDocument::new("5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9".to_string());
+    for font_bytes in &font_registry().font_bytes()[..FONT_COUNT] {
+        pdf_document
+            .add_font_from_bytes(font_bytes.clone())
+            .unwrap();
+    }
+
+    for operation in &operations {
+        let _ = match operation {
+            PdfOperation::AddPageWithLayer {
+                page_width,
+                page_height,
+            } => {
+                pdf_document.add_page_with_layer(*page_width, *page_height);
+                Ok(())
+            }
+            PdfOperation::ClearPage { page_index } => pdf_document.clear_page(*page_index as usize),
+            PdfOperation::WriteText {
+                page_index,
+                layer_index,
+                color,
+                text,
+                font_index,
+                font_size,
+                caret_position,
+                letter_spacing,
+            } => pdf_document.write_text_to_layer_in_page(
+                *page_index as usize,
+                *layer_index as usize,
+                *color,
+                text,
+                *font_index as usize,
+                *font_size,
+                *caret_position,
+                *letter_spacing,
+            ),
+            PdfOperation::PaintPageBackground {
+                page_index,
+                layer_index,
+                color,
+            } => pdf_document.paint_page_background(
+                *page_index as usize,
+                *layer_index as usize,
+                *color,
+            ),
+            PdfOperation::FillPath {
+                page_index,
+                layer_index,
+                segments,
+                color,
+            } => pdf_document.fill_path_in_layer_in_page(
+                *page_index as usize,
+                *layer_index as usize,
+                &FilledPath {
+                    segments: segments.iter().map(PathSegment::from).collect(),
+                    color: *color,
+                },
+            ),
+            PdfOperation::AddImage {
+                page_index,
+                layer_index,
+                image_bytes,
+                position,
+                size,
+            } => pdf_document.add_image_to_layer_in_page(
+                *page_index as usize,
+                *layer_index as usize,
+                image_bytes,
+                *position,
+                *size,
+            ),
+            PdfOperation::Optimize => {
+                pdf_document.optimize();
+                Ok(())
+            }
+        };
+    }
+
+    let _ = pdf_document.summary();
+    if pdf_document
+        .write_all("hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj".to_string())
+        .is_ok()
+    {
+        let _ = pdf_document.save_to_bytes();
+    }
+});
@@ -0,0 +1,265 @@
+use proptest::{
+    arbitrary::Arbitrary,
+    collection, option,
+    prelude::{prop_oneof, Just, Strategy},
+    strategy::BoxedStrategy,
+};
+
+use crate::{
+    barcode::BarcodeKind,
+    builder::PageSize,
+    chart::ChartKind,
+    container::ContainerLayout,
+    document::{Document, Operation},
+    header::RunningHeader,
+};
+
+/// The number of built-in fonts, from which the fonts of the arbitrary texts are chosen.
+pub const BUILTIN_FONT_COUNT: usize = 30;
+
+/// The maximum number of operations following the first page of an arbitrary document.
+const MAXIMUM_OPERATION_COUNT: usize = 40;
+
+/// Generates an arbitrary RGB color, whose components are in the range from 0 to 1. Colors are plain arrays, so
+/// this strategy takes the place of their implementation of `Arbitrary`.
+pub fn color() -> impl Strategy<Value = [f32; 3]> {
+    [0.0f32..=1.0, 0.0f32..=1.0, 0.0f32..=1.0]
+}
+
+/// Generates an arbitrary position in millimeters, which can also be slightly outside of the page.
+fn position() -> impl Strategy<Value = [f32; 2]> {
+    [-20.0f32..320.0, -20.0f32..320.0]
+}
+
+impl Arbitrary for PageSize {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    /// Generates a page whose width and height are between 10 mm and 1 m.
+    fn arbitrary_with(_parameters: Self::Parameters) -> Self::Strategy {
+        (10.0f32..1000.0, 10.0f32..1000.0)
+            .prop_map(|(width, height)| PageSize::new(width, height))
+            .boxed()
+    }
+}
+
+impl Arbitrary for ContainerLayout {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_parameters: Self::Parameters) -> Self::Strategy {
+        prop_oneof![
+            Just(ContainerLayout::Stack),
+            Just(ContainerLayout::Row),
+            (1usize..6, 5.0f32..100.0, 5.0f32..100.0).prop_map(
+                |(columns, cell_width, cell_height)| ContainerLayout::Grid {
+                    columns,
+                    cell_width,
+                    cell_height,
+                }
+            ),
+        ]
+        .boxed()
+    }
+}
+
+/// Generates an arbitrary `WriteUnicodeText` operation, written with one of the built-in fonts.
+fn text_operation() -> impl Strategy<Value = Operation> {
+    (
+        color(),
+        position(),
+        "\\PC{0,40}",
+        1.0f32..72.0,
+        0..BUILTIN_FONT_COUNT,
+        prop_oneof![Just(0.0f32), -1.0f32..5.0],
+    )
+        .prop_map(
+            |(color, position, text_string, font_size, font_index, letter_spacing)| {
+                Operation::WriteUnicodeText {
+                    color,
+                    position,
+                    text_string,
+                    font_size,
+                    font_index,
+                    letter_spacing,
+                }
+            },
+        )
+}
+
+/// Generates an arbitrary `AppendNewPage` operation.
+fn page_operation() -> impl Strategy<Value = Operation> {
+    PageSize::arbitrary().prop_map(|page_size| Operation::AppendNewPage {
+        page_width: page_size.width,
+        page_height: page_size.height,
+    })
+}
+
+/// Generates an arbitrary `SetPageBackground` operation.
+fn page_background_operation() -> impl Strategy<Value = Operation> {
+    color().prop_map(|color| Operation::SetPageBackground { color })
+}
+
+/// Generates an arbitrary `DrawBarcode` operation, whose data can always be encoded by its symbology.
+fn barcode_operation() -> impl Strategy<Value = Operation> {
+    (
+        prop_oneof![
+            "\\PC{0,40}".prop_map(|data| (BarcodeKind::Qr, data)),
+            "[ -~]{0,20}".prop_map(|data| (BarcodeKind::Code128, data)),
+            "[0-9]{12}".prop_map(|data| (BarcodeKind::Ean13, data)),
+        ],
+        position(),
+        [5.0f32..100.0, 5.0f32..100.0],
+    )
+        .prop_map(|((kind, data), position, size)| Operation::DrawBarcode {
+            kind,
+            data,
+            position,
+            size,
+        })
+}
+
+/// Generates an arbitrary `DrawChart` operation, whose values aren't negative so that they can be drawn by any
+/// type of chart.
+fn chart_operation() -> impl Strategy<Value = Operation> {
+    (
+        prop_oneof![
+            Just(ChartKind::Bar),
+            Just(ChartKind::Line),
+            Just(ChartKind::Pie)
+        ],
+        collection::vec(0.0f32..1000.0, 0..8),
+        collection::vec("[a-zA-Z0-9 ]{0,12}", 0..8),
+        position(),
+        [10.0f32..150.0, 10.0f32..150.0],
+        0..BUILTIN_FONT_COUNT,
+        4.0f32..16.0,
+    )
+        .prop_map(
+            |(kind, values, labels, position, size, font_index, font_size)| Operation::DrawChart {
+                kind,
+                values,
+                labels,
+                position,
+                size,
+                font_index,
+                font_size,
+            },
+        )
+}
+
+/// Generates an arbitrary `LayoutContainer` operation, whose children are texts and other containers.
+fn container_operation() -> impl Strategy<Value = Operation> {
+    let child = text_operation().prop_recursive(3, 16, 4, |child| container_of(child).boxed());
+
+    container_of(child)
+}
+
+/// Generates an arbitrary `LayoutContainer` operation with up to four of the given children.
+fn container_of(child: impl Strategy<Value = Operation>) -> impl Strategy<Value = Operation> {
+    (
+        ContainerLayout::arbitrary(),
+        position(),
+        0.0f32..10.0,
+        0.0f32..10.0,
+        collection::vec(child, 0..4),
+    )
+        .prop_map(
+            |(layout, position, padding, gap, children)| Operation::LayoutContainer {
+                layout,
+                position,
+                padding,
+                gap,
+                children,
+            },
+        )
+}
+
+impl Arbitrary for Operation {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    /// Generates an arbitrary page, text, container, page background, barcode or chart. Custom operations are never generated, since they can
+    /// only be drawn once the corresponding `CustomOperation` has been registered.
+    fn arbitrary_with(_parameters: Self::Parameters) -> Self::Strategy {
+        prop_oneof![
+            1 => page_operation(),
+            6 => text_operation(),
+            1 => container_operation(),
+            1 => page_background_operation(),
+            1 => barcode_operation(),
+            1 => chart_operation(),
+        ]
+        .boxed()
+    }
+}
+
+impl Arbitrary for RunningHeader {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    /// Generates a running header written with one of the built-in fonts.
+    fn arbitrary_with(_parameters: Self::Parameters) -> Self::Strategy {
+        (
+            0..BUILTIN_FONT_COUNT,
+            4.0f32..16.0,
+            option::of("\\PC{0,20}"),
+            0.0f32..30.0,
+        )
+            .prop_map(|(font_index, font_size, date, margin)| RunningHeader {
+                font_index,
+                font_size,
+                date,
+                margin,
+            })
+            .boxed()
+    }
+}
+
+impl Arbitrary for Document {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    /// Generates an arbitrary document which starts with a page, so that all of its content is on a page, and
+    /// which is not strict, so that it can be converted even if some of its characters are missing from the fonts.
+    fn arbitrary_with(_parameters: Self::Parameters) -> Self::Strategy {
+        (
+            "[a-zA-Z0-9]{32}",
+            "[a-zA-Z0-9]{32}",
+            page_operation(),
+            collection::vec(Operation::arbitrary(), 0..MAXIMUM_OPERATION_COUNT),
+            option::of(color()),
+            option::of("\\PC{0,40}"),
+            option::of("\\PC{0,40}"),
+            option::of(RunningHeader::arbitrary()),
+            // Between the years 1970 and 9999, which can be written into a PDF document
+            option::of(0i64..253_402_300_799),
+        )
+            .prop_map(
+                |(
+                    document_id,
+                    instance_id,
+                    first_page,
+                    operations,
+                    page_background,
+                    title,
+                    author,
+                    running_header,
+                    timestamp,
+                )| Document {
+                    document_id,
+                    instance_id,
+                    operations: std::iter::once(first_page).chain(operations).collect(),
+                    page_background,
+                    title,
+                    author,
+                    running_header,
+                    timestamp,
+                    // The arbitrary documents are converted into the same bytes on every run
+                    deterministic: true,
+                    strict: false,
+                },
+            )
+            .boxed()
+    }
+}
@@ -0,0 +1,237 @@
+use qrcodegen::{QrCode, QrCodeEcc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    canvas::FilledPath,
+    error::{ContextError, ErrorKind},
+};
+
+/// The symbology of a barcode drawn by the `DrawBarcode` operation.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum BarcodeKind {
+    /// A QR code, encoding any text with the medium level of error correction.
+    Qr,
+    /// A Code 128 barcode, encoding the printable ASCII characters via its code set B.
+    Code128,
+    /// An EAN-13 barcode, encoding 12 digits followed by their check digit, which is computed if it's left out.
+    Ean13,
+}
+
+/// The widths in modules of the bars and spaces of the Code 128 symbols, alternating between the two and beginning
+/// with a bar, indexed by the value of the symbol. The last one is the stop symbol, which ends with an extra bar.
+const CODE_128_PATTERNS: [&str; 107] = [
+    "212222", "222122", "222221", "121223", "121322", "131222", "122213", "122312", "132212",
+    "221213", "221312", "231212", "112232", "122132", "122231", "113222", "123122", "123221",
+    "223211", "221132", "221231", "213212", "223112", "312131", "311222", "321122", "321221",
+    "312212", "322112", "322211", "212123", "212321", "232121", "111323", "131123", "131321",
+    "112313", "132113", "132311", "211313", "231113", "231311", "112133", "112331", "132131",
+    "113123", "113321", "133121", "313121", "211331", "231131", "213113", "213311", "213131",
+    "311123", "311321", "331121", "312113", "312311", "332111", "314111", "221411", "431111",
+    "111224", "111422", "121124", "121421", "141122", "141221", "112214", "112412", "122114",
+    "122411", "142112", "142211", "241211", "221114", "413111", "241112", "134111", "111242",
+    "121142", "121241", "114212", "124112", "124211", "411212", "421112", "421211", "212141",
+    "214121", "412121", "111143", "111341", "131141", "114113", "114311", "411113", "411311",
+    "113141", "114131", "311141", "411131", "211412", "211214", "211232", "2331112",
+];
+
+/// The value of the Code 128 symbol which begins a barcode in code set B.
+const CODE_128_START_B: usize = 104;
+
+/// The value of the Code 128 stop symbol.
+const CODE_128_STOP: usize = 106;
+
+/// The left-hand odd parity (L) encodings of the EAN-13 digits, with the dark modules set. The even parity (G)
+/// encodings are the reversed right-hand ones, while the right-hand (R) encodings are their complements.
+const EAN_13_L_CODES: [&str; 10] = [
+    "0001101", "0011001", "0010011", "0111101", "0100011", "0110001", "0101111", "0111011",
+    "0110111", "0001011",
+];
+
+/// The parities of the six left-hand digits of an EAN-13 barcode, by the first digit, which is encoded this way.
+const EAN_13_PARITIES: [&str; 10] = [
+    "LLLLLL", "LLGLGG", "LLGGLG", "LLGGGL", "LGLLGG", "LGGLLG", "LGGGLL", "LGLGLG", "LGLGGL",
+    "LGGLGL",
+];
+
+impl BarcodeKind {
+    /// Encodes the given data into the rows of modules of the barcode, from top to bottom, where the dark modules
+    /// are set. The linear barcodes consist of a single row, which is stretched over the whole height of the barcode.
+    /// No quiet zone is added around the modules, so enough space must be left around the barcode for it to be read.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The data to be encoded, which must be supported by the symbology (see `BarcodeKind`).
+    pub fn encode(self, data: &str) -> Result<Vec<Vec<bool>>, ContextError> {
+        match self {
+            BarcodeKind::Qr => encode_qr(data),
+            BarcodeKind::Code128 => encode_code_128(data).map(|modules| vec![modules]),
+            BarcodeKind::Ean13 => encode_ean_13(data).map(|modules| vec![modules]),
+        }
+    }
+}
+
+/// Encodes the given text into the modules of a QR code of the smallest version which fits it.
+fn encode_qr(data: &str) -> Result<Vec<Vec<bool>>, ContextError> {
+    let qr_code = QrCode::encode_text(data, QrCodeEcc::Medium).map_err(|error| {
+        ContextError::with_error("Unable to encode the data into a QR code", &error)
+            .with_kind(ErrorKind::InvalidDocument)
+    })?;
+
+    Ok((0..qr_code.size())
+        .map(|y| {
+            (0..qr_code.size())
+                .map(|x| qr_code.get_module(x, y))
+                .collect()
+        })
+        .collect())
+}
+
+/// Encodes the given printable ASCII text into the modules of a Code 128 barcode via its code set B.
+fn encode_code_128(data: &str) -> Result<Vec<bool>, ContextError> {
+    let mut values = vec![CODE_128_START_B];
+    for character in data.chars() {
+        if !(' '..='~').contains(&character) {
+            return Err(ContextError::with_context(format!(
+                "Unable to encode the character {:?} into a Code 128 barcode, only the printable ASCII characters are supported",
+                character
+            ))
+            .with_kind(ErrorKind::InvalidDocument));
+        }
+        values.push(character as usize - ' ' as usize);
+    }
+    // The check symbol is the weighted sum of the symbols, where the start symbol has the same weight as the first one
+    let checksum = values
+        .iter()
+        .enumerate()
+        .map(|(position, value)| position.max(1) * value)
+        .sum::<usize>()
+        % 103;
+    values.push(checksum);
+    values.push(CODE_128_STOP);
+
+    let mut modules = Vec::new();
+    for value in values {
+        for (element_index, width) in CODE_128_PATTERNS[value].bytes().enumerate() {
+            let is_bar = element_index % 2 == 0;
+            modules.extend(std::iter::repeat_n(is_bar, (width - b'0') as usize));
+        }
+    }
+
+    Ok(modules)
+}
+
+/// Encodes the given 12 or 13 digits into the modules of an EAN-13 barcode, checking or computing the check digit.
+fn encode_ean_13(data: &str) -> Result<Vec<bool>, ContextError> {
+    let invalid_data = |reason: String| {
+        ContextError::with_context(format!(
+            "Unable to encode {:?} into an EAN-13 barcode, {}",
+            data, reason
+        ))
+        .with_kind(ErrorKind::InvalidDocument)
+    };
+    let mut digits = data
+        .chars()
+        .map(|character| character.to_digit(10).map(|digit| digit as usize))
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(|| invalid_data("which must only contain digits".to_string()))?;
+    if !(12..=13).contains(&digits.len()) {
+        return Err(invalid_data(format!(
+            "which must have 12 or 13 digits rather than {}",
+            digits.len()
+        )));
+    }
+    // The digits are weighted alternately by 1 and 3, starting from the first one
+    let weighted_sum = digits[..12]
+        .iter()
+        .enumerate()
+        .map(|(position, digit)| if position % 2 == 0 { *digit } else { 3 * digit })
+        .sum::<usize>();
+    let check_digit = (10 - weighted_sum % 10) % 10;
+    match digits.get(12) {
+        Some(digit) if *digit != check_digit => {
+            return Err(invalid_data(format!(
+                "whose check digit should be {} rather than {}",
+                check_digit, digit
+            )))
+        }
+        Some(_) => {}
+        None => digits.push(check_digit),
+    }
+
+    let mut modules = pattern_modules("101");
+    for (digit, parity) in digits[1..7].iter().zip(EAN_13_PARITIES[digits[0]].chars()) {
+        let digit_modules = pattern_modules(EAN_13_L_CODES[*digit]);
+        match parity {
+            'G' => modules.extend(digit_modules.into_iter().rev().map(|module| !module)),
+            _ => modules.extend(digit_modules),
+        }
+    }
+    modules.extend(pattern_modules("01010"));
+    for digit in &digits[7..] {
+        let digit_modules = pattern_modules(EAN_13_L_CODES[*digit]);
+        modules.extend(digit_modules.into_iter().map(|module| !module));
+    }
+    modules.extend(pattern_modules("101"));
+
+    Ok(modules)
+}
+
+/// Converts a pattern of zeros and ones into modules, where the ones are the dark modules.
+fn pattern_modules(pattern: &str) -> Vec<bool> {
+    pattern.bytes().map(|module| module == b'1').collect()
+}
+
+/// Creates the filled path of the given barcode, made of one rectangle for each run of dark modules in a row, so
+/// that it is drawn as vector graphics by all the output formats.
+///
+/// # Arguments
+///
+/// * `kind` - The symbology of the barcode.
+/// * `data` - The data encoded by the barcode.
+/// * `position` - The position in millimeters of the bottom-left corner of the barcode.
+/// * `size` - The width and height in millimeters over which the modules of the barcode are stretched.
+pub fn barcode_path(
+    kind: BarcodeKind,
+    data: &str,
+    position: [f32; 2],
+    size: [f32; 2],
+) -> Result<FilledPath, ContextError> {
+    let rows = kind.encode(data)?;
+    let column_count = rows.first().map_or(0, Vec::len).max(1);
+    let [module_width, module_height] = [
+        size[0] / column_count as f32,
+        size[1] / rows.len().max(1) as f32,
+    ];
+
+    let mut segments = Vec::new();
+    for (row_index, row) in rows.iter().enumerate() {
+        // The rows are listed from the top, while the position is the bottom-left corner
+        let y = position[1] + size[1] - (row_index + 1) as f32 * module_height;
+        let mut column_index = 0;
+        while column_index < row.len() {
+            if !row[column_index] {
+                column_index += 1;
+                continue;
+            }
+            let run_length = row[column_index..]
+                .iter()
+                .take_while(|module| **module)
+                .count();
+            let x = position[0] + column_index as f32 * module_width;
+            segments.extend(
+                FilledPath::rectangle(
+                    [x, y],
+                    [run_length as f32 * module_width, module_height],
+                    [0.0, 0.0, 0.0],
+                )
+                .segments,
+            );
+            column_index += run_length;
+        }
+    }
+    Ok(FilledPath {
+        segments,
+        color: [0.0, 0.0, 0.0],
+    })
+}
@@ -0,0 +1,67 @@
+use std::{
+    future::Future,
+    panic::{self, AssertUnwindSafe},
+    pin::Pin,
+    sync::{Arc, Mutex, PoisonError},
+    task::{Context, Poll, Waker},
+    thread,
+};
+
+/// A future which resolves to the result of a blocking function, such as reading a document or converting it,
+/// run on the global thread pool of `rayon` so that the asynchronous tasks of the caller keep running meanwhile.
+/// It doesn't depend on any asynchronous runtime, so it can be awaited from any of them without `spawn_blocking`,
+/// and the pool bounds the number of threads however many tasks are spawned, the extra ones waiting for a thread.
+pub struct BlockingTask<T> {
+    /// The state shared with the thread which runs the function.
+    state: Arc<Mutex<BlockingTaskState<T>>>,
+}
+
+/// The state of a `BlockingTask`, shared between the future and the thread which runs its function.
+struct BlockingTaskState<T> {
+    /// The result of the function once it has returned, or the payload of its panic.
+    result: Option<thread::Result<T>>,
+    /// The waker of the task which last polled the future before the function returned.
+    waker: Option<Waker>,
+}
+
+impl<T: Send + 'static> BlockingTask<T> {
+    /// Runs the given function on the global thread pool of `rayon`, returning the future which resolves to its
+    /// result. If the function panics, the panic is resumed when the future is polled.
+    ///
+    /// # Arguments
+    ///
+    /// * `function` - The blocking function to be run.
+    pub fn spawn<F: FnOnce() -> T + Send + 'static>(function: F) -> Self {
+        let state = Arc::new(Mutex::new(BlockingTaskState {
+            result: None,
+            waker: None,
+        }));
+        let thread_state = Arc::clone(&state);
+        rayon::spawn(move || {
+            let result = panic::catch_unwind(AssertUnwindSafe(function));
+            let mut state = thread_state.lock().unwrap_or_else(PoisonError::into_inner);
+            state.result = Some(result);
+            if let Some(waker) = state.waker.take() {
+                waker.wake();
+            }
+        });
+
+        BlockingTask { state }
+    }
+}
+
+impl<T> Future for BlockingTask<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<T> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        match state.result.take() {
+            Some(Ok(result)) => Poll::Ready(result),
+            Some(Err(payload)) => panic::resume_unwind(payload),
+            None => {
+                state.waker = Some(context.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
@@ -0,0 +1,393 @@
+use std::marker::PhantomData;
+
+use crate::{
+    document::{Document, Operation},
+    error::ContextError,
+    font::FontRegistry,
+    header::RunningHeader,
+};
+
+/// The size of a page expressed in millimeters, to be passed to `DocumentBuilder::page`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PageSize {
+    /// The width of the page in millimeters.
+    pub width: f32,
+    /// The height of the page in millimeters.
+    pub height: f32,
+}
+
+impl PageSize {
+    /// Creates a new page size from the given width and height in millimeters.
+    pub fn new(width: f32, height: f32) -> Self {
+        PageSize { width, height }
+    }
+
+    /// Returns the same page size but with the width and height swapped.
+    pub fn landscape(self) -> Self {
+        PageSize {
+            width: self.height,
+            height: self.width,
+        }
+    }
+}
+
+/// The ISO A4 page size (210 by 297 millimeters).
+pub fn a4() -> PageSize {
+    PageSize::new(210.0, 297.0)
+}
+
+/// The ISO A5 page size (148 by 210 millimeters).
+pub fn a5() -> PageSize {
+    PageSize::new(148.0, 210.0)
+}
+
+/// The US letter page size (215.9 by 279.4 millimeters).
+pub fn letter() -> PageSize {
+    PageSize::new(215.9, 279.4)
+}
+
+/// The font of a piece of text, to be passed to `DocumentBuilder::font` either as a font index or as a font name.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FontReference {
+    /// The font index, which refers to the order in which the fonts are loaded by `Document::to_pdf_document`.
+    Index(usize),
+    /// The name of the font, such as `cmu-serif` or `cmu-serif-bold`, which is resolved into its font index when the
+    /// document is built (see `FontRegistry::font_index`).
+    Name(String),
+}
+
+impl From<usize> for FontReference {
+    fn from(font_index: usize) -> Self {
+        FontReference::Index(font_index)
+    }
+}
+
+impl From<&str> for FontReference {
+    fn from(font_name: &str) -> Self {
+        FontReference::Name(font_name.to_string())
+    }
+}
+
+impl From<String> for FontReference {
+    fn from(font_name: String) -> Self {
+        FontReference::Name(font_name)
+    }
+}
+
+/// The state of a `DocumentBuilder` in which no page has been appended yet, so no text can be written.
+#[derive(Debug, Clone, Copy)]
+pub struct NoPage;
+
+/// The state of a `DocumentBuilder` in which a page is available for text to be written onto.
+#[derive(Debug, Clone, Copy)]
+pub struct OnPage;
+
+/// The state of a `DocumentBuilder` right after a piece of text has been added, so that its position,
+/// font and color can still be adjusted.
+#[derive(Debug, Clone, Copy)]
+pub struct OnText;
+
+/// A fluent interface for constructing a `Document` in code without having to assemble the `Operation`
+/// variants by hand. The state of the builder is tracked at compile-time, so that writing text before
+/// a page has been appended is rejected by the compiler instead of failing during the conversion.
+///
+/// # Example
+///
+/// ```
+/// use textr::builder::{a4, DocumentBuilder};
+///
+/// let document = DocumentBuilder::new(
+///     "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+///     "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+/// )
+/// .page(a4())
+/// .text("Hello, world!")
+/// .at(20.0, 40.0)
+/// .font("cmu-serif", 12.0)
+/// .build()?;
+/// assert_eq!(document.operations.len(), 2);
+/// # Ok::<(), textr::error::ContextError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct DocumentBuilder<State = NoPage> {
+    /// The unique ID of the document being built.
+    document_id: String,
+    /// The unique ID of the instance being built.
+    instance_id: String,
+    /// The operations collected so far.
+    operations: Vec<Operation>,
+    /// The color with which every page is painted, if any.
+    page_background: Option<[f32; 3]>,
+    /// The title of the document, if any.
+    title: Option<String>,
+    /// The author of the document, if any.
+    author: Option<String>,
+    /// The header repeated at the top of every page, if any.
+    running_header: Option<RunningHeader>,
+    /// The creation and modification date of the document in seconds since the Unix epoch, if any.
+    timestamp: Option<i64>,
+    /// Whether the document without a timestamp is dated at the Unix epoch.
+    deterministic: bool,
+    /// Whether the document is converted in strict mode.
+    strict: bool,
+    /// The names of the fonts which are resolved when the document is built, each with the index of its operation.
+    font_names: Vec<(usize, String)>,
+    /// The compile-time state of the builder.
+    state: PhantomData<State>,
+}
+
+impl DocumentBuilder<NoPage> {
+    /// Creates a new builder for a document with the given document and instance IDs (see `Document`).
+    pub fn new<S: Into<String>, T: Into<String>>(document_id: S, instance_id: T) -> Self {
+        DocumentBuilder {
+            document_id: document_id.into(),
+            instance_id: instance_id.into(),
+            operations: Vec::new(),
+            page_background: None,
+            title: None,
+            author: None,
+            running_header: None,
+            timestamp: None,
+            deterministic: false,
+            strict: false,
+            font_names: Vec::new(),
+            state: PhantomData,
+        }
+    }
+}
+
+impl<State> DocumentBuilder<State> {
+    /// Appends a new page of the given size to the document, after which text can be written.
+    pub fn page(mut self, page_size: PageSize) -> DocumentBuilder<OnPage> {
+        self.operations.push(Operation::AppendNewPage {
+            page_width: page_size.width,
+            page_height: page_size.height,
+        });
+        self.transition()
+    }
+
+    /// Paints every page of the document with the given RGB color beneath its contents.
+    pub fn page_background(mut self, color: [f32; 3]) -> Self {
+        self.page_background = Some(color);
+        self
+    }
+
+    /// Sets the title of the document, which is written into the metadata of the PDF document.
+    pub fn title<S: Into<String>>(mut self, title: S) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Sets the author of the document, which is written into the metadata of the PDF document.
+    pub fn author<S: Into<String>>(mut self, author: S) -> Self {
+        self.author = Some(author.into());
+        self
+    }
+
+    /// Writes the title of the document at the top of every page with the given running header (see `RunningHeader`).
+    pub fn running_header(mut self, running_header: RunningHeader) -> Self {
+        self.running_header = Some(running_header);
+        self
+    }
+
+    /// Sets the creation and modification date of the document, in seconds since the Unix epoch, in place of the
+    /// time at which the PDF document is written.
+    pub fn timestamp(mut self, unix_timestamp: i64) -> Self {
+        self.timestamp = Some(unix_timestamp);
+        self
+    }
+
+    /// Dates the document at the Unix epoch when it has no timestamp, so that it is converted into the same bytes on
+    /// every run (see `Document`).
+    pub fn deterministic(mut self) -> Self {
+        self.deterministic = true;
+        self
+    }
+
+    /// Makes the conversion of the document fail on any content which would be left out, such as the characters
+    /// missing from their font, instead of reporting it as a warning (see `Document`).
+    pub fn strict(mut self) -> Self {
+        self.strict = true;
+        self
+    }
+
+    /// Resolves the names of the fonts with the given fonts, or with the built-in ones if none are given, and returns
+    /// the constructed `Document`.
+    fn finish(mut self, font_registry: Option<&FontRegistry>) -> Result<Document, ContextError> {
+        if !self.font_names.is_empty() {
+            // The built-in fonts are only read when the text refers to any of them by name
+            let builtin_font_registry;
+            let font_registry = match font_registry {
+                Some(font_registry) => font_registry,
+                None => {
+                    builtin_font_registry = FontRegistry::builtin()?;
+                    &builtin_font_registry
+                }
+            };
+            for (operation_index, font_name) in &self.font_names {
+                let resolved_font_index = font_registry
+                    .font_index(font_name)
+                    .map_err(|error| error.with_operation_index(*operation_index))?;
+                if let Some(Operation::WriteUnicodeText { font_index, .. }) =
+                    self.operations.get_mut(*operation_index)
+                {
+                    *font_index = resolved_font_index;
+                }
+            }
+        }
+
+        Ok(Document {
+            document_id: self.document_id,
+            instance_id: self.instance_id,
+            operations: self.operations,
+            page_background: self.page_background,
+            title: self.title,
+            author: self.author,
+            running_header: self.running_header,
+            timestamp: self.timestamp,
+            deterministic: self.deterministic,
+            strict: self.strict,
+        })
+    }
+
+    /// Moves the builder into another state while keeping the collected operations.
+    fn transition<Next>(self) -> DocumentBuilder<Next> {
+        DocumentBuilder {
+            document_id: self.document_id,
+            instance_id: self.instance_id,
+            operations: self.operations,
+            page_background: self.page_background,
+            title: self.title,
+            author: self.author,
+            running_header: self.running_header,
+            timestamp: self.timestamp,
+            deterministic: self.deterministic,
+            strict: self.strict,
+            font_names: self.font_names,
+            state: PhantomData,
+        }
+    }
+
+    /// Appends a piece of black text with the default position, font and size onto the current page.
+    fn push_text<S: Into<String>>(mut self, text: S) -> DocumentBuilder<OnText> {
+        self.operations.push(Operation::WriteUnicodeText {
+            color: [0.0, 0.0, 0.0],
+            position: [0.0, 0.0],
+            text_string: text.into(),
+            font_size: 12.0,
+            font_index: 0,
+            letter_spacing: 0.0,
+        });
+        self.transition()
+    }
+
+    /// Paints the current page with the given RGB color, in place of the page background of the document.
+    fn push_page_background(mut self, color: [f32; 3]) -> DocumentBuilder<OnPage> {
+        self.operations.push(Operation::SetPageBackground { color });
+        self.transition()
+    }
+}
+
+impl DocumentBuilder<OnPage> {
+    /// Writes the given text onto the current page. By default the text is black, positioned at the origin
+    /// of the page and uses the first font with a size of 12, these can be changed via `at`, `font`, `color` and `letter_spacing`.
+    pub fn text<S: Into<String>>(self, text: S) -> DocumentBuilder<OnText> {
+        self.push_text(text)
+    }
+
+    /// Paints the current page with the given RGB color beneath all of its contents, including the text which
+    /// has already been written, in place of the page background of the document.
+    pub fn background(self, color: [f32; 3]) -> DocumentBuilder<OnPage> {
+        self.push_page_background(color)
+    }
+
+    /// Finalizes the builder and returns the constructed `Document`. The fonts referred to by name are looked for
+    /// among the built-in fonts, failing if any of them is missing.
+    pub fn build(self) -> Result<Document, ContextError> {
+        self.finish(None)
+    }
+
+    /// Finalizes the builder just like `build` does, but looks for the fonts referred to by name among the fonts of
+    /// the given registry, with which the document is then meant to be converted.
+    pub fn build_with_fonts(self, font_registry: &FontRegistry) -> Result<Document, ContextError> {
+        self.finish(Some(font_registry))
+    }
+}
+
+impl DocumentBuilder<OnText> {
+    /// Writes another piece of text onto the current page (see `DocumentBuilder::<OnPage>::text`).
+    pub fn text<S: Into<String>>(self, text: S) -> DocumentBuilder<OnText> {
+        self.push_text(text)
+    }
+
+    /// Paints the current page with the given RGB color (see `DocumentBuilder::<OnPage>::background`).
+    pub fn background(self, color: [f32; 3]) -> DocumentBuilder<OnPage> {
+        self.push_page_background(color)
+    }
+
+    /// Finalizes the builder and returns the constructed `Document` (see `DocumentBuilder::<OnPage>::build`).
+    pub fn build(self) -> Result<Document, ContextError> {
+        self.finish(None)
+    }
+
+    /// Finalizes the builder with the given fonts (see `DocumentBuilder::<OnPage>::build_with_fonts`).
+    pub fn build_with_fonts(self, font_registry: &FontRegistry) -> Result<Document, ContextError> {
+        self.finish(Some(font_registry))
+    }
+
+    /// Sets the position in millimeters of the last piece of text.
+    pub fn at(mut self, x: f32, y: f32) -> Self {
+        if let Some(Operation::WriteUnicodeText { position, .. }) = self.operations.last_mut() {
+            *position = [x, y];
+        }
+        self
+    }
+
+    /// Sets the font and the font size of the last piece of text. The font is given either by its font index, which
+    /// refers to the order in which the fonts are loaded by `Document::to_pdf_document`, or by its name, such as
+    /// `cmu-serif`, which is resolved when the document is built.
+    pub fn font<F: Into<FontReference>>(mut self, font: F, font_size: f32) -> Self {
+        let operation_index = self.operations.len() - 1;
+        self.font_names
+            .retain(|(named_operation_index, _)| *named_operation_index != operation_index);
+        if let Some(Operation::WriteUnicodeText {
+            font_index: last_font_index,
+            font_size: last_font_size,
+            ..
+        }) = self.operations.last_mut()
+        {
+            *last_font_index = match font.into() {
+                FontReference::Index(font_index) => font_index,
+                FontReference::Name(font_name) => {
+                    self.font_names.push((operation_index, font_name));
+                    0
+                }
+            };
+            *last_font_size = font_size;
+        }
+        self
+    }
+
+    /// Sets the additional space in points inserted after each character of the last piece of text.
+    pub fn letter_spacing(mut self, letter_spacing: f32) -> Self {
+        if let Some(Operation::WriteUnicodeText {
+            letter_spacing: last_letter_spacing,
+            ..
+        }) = self.operations.last_mut()
+        {
+            *last_letter_spacing = letter_spacing;
+        }
+        self
+    }
+
+    /// Sets the RGB color of the last piece of text.
+    pub fn color(mut self, color: [f32; 3]) -> Self {
+        if let Some(Operation::WriteUnicodeText {
+            color: last_color, ..
+        }) = self.operations.last_mut()
+        {
+            *last_color = color;
+        }
+        self
+    }
+}
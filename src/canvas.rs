@@ -0,0 +1,496 @@
+use std::{borrow::Cow, collections::BTreeSet, sync::Arc};
+use unicode_normalization::{is_nfc, UnicodeNormalization as _};
+
+use crate::{
+    barcode::barcode_path,
+    chart::Chart,
+    container::TextMeasurer,
+    custom::{CustomOperationContext, CustomOperations},
+    document::{builtin_font_paths, Document, Operation},
+    error::{ContextError, ErrorKind},
+};
+
+/// A run of text to be drawn onto the current page of a `Canvas`.
+#[derive(Debug, Clone, Copy)]
+pub struct TextRun<'a> {
+    /// The text to be drawn, already normalized in the NFC form and with the page numbering tokens resolved.
+    pub text: &'a str,
+    /// The position in millimeters where the baseline of the text begins, from the bottom-left corner of the page.
+    pub position: [f32; 2],
+    /// The index of the font used for drawing the text, in the order in which the fonts are loaded.
+    pub font_index: usize,
+    /// The size of the font in points.
+    pub font_size: f32,
+    /// The RGB color of the text, with components between 0 and 1.
+    pub color: [f32; 3],
+    /// The additional space in points inserted after each character of the text.
+    pub letter_spacing: f32,
+}
+
+/// A segment of a path, with the coordinates expressed in millimeters from the bottom-left corner of the page.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathSegment {
+    /// Begins a new subpath at the given point.
+    MoveTo([f32; 2]),
+    /// Draws a straight line from the current point to the given point.
+    LineTo([f32; 2]),
+    /// Draws a cubic Bézier curve from the current point to the end point through the two control points.
+    CubicTo {
+        /// The first control point of the curve.
+        first_control_point: [f32; 2],
+        /// The second control point of the curve.
+        second_control_point: [f32; 2],
+        /// The point where the curve ends.
+        end_point: [f32; 2],
+    },
+    /// Closes the current subpath with a straight line back to its starting point.
+    Close,
+}
+
+/// A shape described by a path and filled with a color.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilledPath {
+    /// The segments composing the path.
+    pub segments: Vec<PathSegment>,
+    /// The RGB color used for filling the path, with components between 0 and 1.
+    pub color: [f32; 3],
+}
+
+impl FilledPath {
+    /// Creates a filled rectangle with the given bottom-left corner and size, both expressed in millimeters.
+    pub fn rectangle(position: [f32; 2], size: [f32; 2], color: [f32; 3]) -> Self {
+        let ([x, y], [width, height]) = (position, size);
+        FilledPath {
+            segments: vec![
+                PathSegment::MoveTo([x, y]),
+                PathSegment::LineTo([x + width, y]),
+                PathSegment::LineTo([x + width, y + height]),
+                PathSegment::LineTo([x, y + height]),
+                PathSegment::Close,
+            ],
+            color,
+        }
+    }
+}
+
+/// A drawing surface onto which a `Document` can be drawn via `Document::draw`. Every output backend of this crate
+/// implements this trait, so that the operations of a document are interpreted in a single place and each backend
+/// only needs to know how to draw the resulting primitives.
+pub trait Canvas {
+    /// Ends the current page, if any, and begins a new one with the given width and height in millimeters.
+    fn page_break(&mut self, page_width: f32, page_height: f32) -> Result<(), ContextError>;
+
+    /// Draws a run of text onto the current page.
+    fn text_run(&mut self, text_run: &TextRun) -> Result<(), ContextError>;
+
+    /// Draws a filled path onto the current page.
+    fn fill_path(&mut self, path: &FilledPath) -> Result<(), ContextError>;
+
+    /// Adds raw PDF content stream operations to the current page, as drawn by a `CustomOperation`. The canvases
+    /// which don't produce PDF documents leave them out.
+    fn pdf_operations(
+        &mut self,
+        _operations: Vec<lopdf::content::Operation>,
+    ) -> Result<(), ContextError> {
+        Ok(())
+    }
+
+    /// Returns the content of the fonts with which the text runs are drawn, indexed by their font index, so that the
+    /// text laid out by the document, such as the children of the containers, is measured with the same fonts. The
+    /// canvases which don't hold any fonts return `None`, in which case the built-in fonts are used.
+    fn font_bytes(&self) -> Option<Vec<Arc<[u8]>>> {
+        None
+    }
+}
+
+impl<C: Canvas + ?Sized> Canvas for &mut C {
+    fn page_break(&mut self, page_width: f32, page_height: f32) -> Result<(), ContextError> {
+        (**self).page_break(page_width, page_height)
+    }
+
+    fn text_run(&mut self, text_run: &TextRun) -> Result<(), ContextError> {
+        (**self).text_run(text_run)
+    }
+
+    fn fill_path(&mut self, path: &FilledPath) -> Result<(), ContextError> {
+        (**self).fill_path(path)
+    }
+
+    fn pdf_operations(
+        &mut self,
+        operations: Vec<lopdf::content::Operation>,
+    ) -> Result<(), ContextError> {
+        (**self).pdf_operations(operations)
+    }
+
+    fn font_bytes(&self) -> Option<Vec<Arc<[u8]>>> {
+        (**self).font_bytes()
+    }
+}
+
+impl Document {
+    /// Draws the given `Document` onto a canvas by interpreting its operations in order. Pages are begun via
+    /// `Canvas::page_break` and painted with their background, if any, before any text is drawn onto them.
+    /// The text is normalized in the NFC form before being handed to the canvas, and the tokens `{page}` and
+    /// `{pages}` are replaced by the number of the current page (starting from 1) and by the total number of pages,
+    /// so that strings such as "Page {page} of {pages}" can be written anywhere in the document. Layout containers
+    /// are drawn as the text of their children, placed as described in `Document::lay_out_containers`, while the
+    /// running header of the document, if any, is written onto every page before its content.
+    ///
+    /// # Arguments
+    ///
+    /// * `canvas` - The canvas onto which the document is drawn.
+    pub fn draw<C: Canvas + ?Sized>(&self, canvas: &mut C) -> Result<(), ContextError> {
+        self.draw_pages(canvas, None, &CustomOperations::default())
+    }
+
+    /// Draws the given `Document` onto a canvas just like `draw` does, while the custom operations of the document
+    /// are drawn by the ones registered with the same names.
+    ///
+    /// # Arguments
+    ///
+    /// * `canvas` - The canvas onto which the document is drawn.
+    /// * `custom_operations` - The custom operations which can be found in the document.
+    pub fn draw_with_custom_operations<C: Canvas + ?Sized>(
+        &self,
+        canvas: &mut C,
+        custom_operations: &CustomOperations,
+    ) -> Result<(), ContextError> {
+        self.draw_pages(canvas, None, custom_operations)
+    }
+
+    /// Draws the given `Document` onto a canvas just like `draw` does, but only the content of the pages with
+    /// the given indices, if any, is drawn, while the other pages are only begun. The operations of the pages
+    /// which are left out, such as the layout containers, are skipped altogether.
+    ///
+    /// # Arguments
+    ///
+    /// * `canvas` - The canvas onto which the document is drawn.
+    /// * `page_indices` - The indices of the pages whose content is drawn, or `None` for all of them.
+    /// * `custom_operations` - The custom operations which can be found in the document.
+    pub(crate) fn draw_pages<C: Canvas + ?Sized>(
+        &self,
+        canvas: &mut C,
+        page_indices: Option<&BTreeSet<usize>>,
+        custom_operations: &CustomOperations,
+    ) -> Result<(), ContextError> {
+        // The content found before the first page is drawn in any case, so that the canvas reports the error
+        let is_drawn = |page_number: usize| {
+            page_number == 0
+                || page_indices.is_none_or(|page_indices| page_indices.contains(&(page_number - 1)))
+        };
+        let mut current_page_number = 0;
+        let page_count = self
+            .operations
+            .iter()
+            .filter(|operation| matches!(operation, Operation::AppendNewPage { .. }))
+            .count();
+
+        let page_backgrounds = self.page_backgrounds();
+
+        let mut text_measurer = None;
+
+        for (operation_index, operation) in self.operations.iter().enumerate() {
+            let is_page = matches!(operation, Operation::AppendNewPage { .. });
+            if !is_page && !is_drawn(current_page_number) {
+                continue;
+            }
+            match operation {
+                Operation::WriteUnicodeText { .. } => draw_text(
+                    canvas,
+                    operation_index,
+                    operation,
+                    current_page_number,
+                    page_count,
+                )?,
+                Operation::AppendNewPage {
+                    page_width,
+                    page_height,
+                } => {
+                    canvas.page_break(*page_width, *page_height)?;
+                    current_page_number += 1;
+                    // Paint the background of the page, if requested, before anything else is drawn onto it
+                    if let Some(page_background) = page_backgrounds[current_page_number - 1]
+                        .filter(|_| is_drawn(current_page_number))
+                    {
+                        canvas.fill_path(&FilledPath::rectangle(
+                            [0.0, 0.0],
+                            [*page_width, *page_height],
+                            page_background,
+                        ))?;
+                    }
+                    // Write the running header, if requested, before the content of the page
+                    if let (Some(title), Some(running_header), true) = (
+                        &self.title,
+                        &self.running_header,
+                        is_drawn(current_page_number),
+                    ) {
+                        let header_operations = running_header
+                            .lay_out(
+                                title,
+                                self.timestamp,
+                                [*page_width, *page_height],
+                                loaded_text_measurer(&mut text_measurer, canvas)?,
+                            )
+                            .map_err(|error| error.with_operation_index(operation_index))?;
+                        for header_operation in &header_operations {
+                            draw_text(
+                                canvas,
+                                operation_index,
+                                header_operation,
+                                current_page_number,
+                                page_count,
+                            )?;
+                        }
+                    }
+                }
+                Operation::LayoutContainer { .. } => {
+                    let text_measurer = loaded_text_measurer(&mut text_measurer, canvas)?;
+                    let mut laid_out_operations = Vec::new();
+                    operation
+                        .lay_out(text_measurer, &mut laid_out_operations)
+                        .map_err(|error| error.with_operation_index(operation_index))?;
+                    for laid_out_operation in &laid_out_operations {
+                        draw_text(
+                            canvas,
+                            operation_index,
+                            laid_out_operation,
+                            current_page_number,
+                            page_count,
+                        )?;
+                    }
+                }
+                // The background has already been painted when the page has begun, beneath all of its content
+                Operation::SetPageBackground { .. } if current_page_number == 0 => {
+                    return Err(ContextError::with_context(format!(
+                        "Unable to paint the background of operation {} because no page has been appended yet",
+                        operation_index
+                    ))
+                    .with_kind(ErrorKind::PageIndexOutOfRange)
+                    .with_operation_index(operation_index));
+                }
+                Operation::SetPageBackground { .. } => {}
+                Operation::DrawBarcode {
+                    kind,
+                    data,
+                    position,
+                    size,
+                } => {
+                    if current_page_number == 0 {
+                        return Err(ContextError::with_context(format!(
+                            "Unable to draw the barcode of operation {} because no page has been appended yet",
+                            operation_index
+                        ))
+                        .with_kind(ErrorKind::PageIndexOutOfRange)
+                        .with_operation_index(operation_index));
+                    }
+                    barcode_path(*kind, data, *position, *size)
+                        .and_then(|barcode_path| canvas.fill_path(&barcode_path))
+                        .map_err(|error| {
+                            ContextError::with_error(
+                                format!(
+                                    "Unable to draw the barcode of operation {}",
+                                    operation_index
+                                ),
+                                &error,
+                            )
+                            .with_kind(error.kind)
+                            .with_operation_index(operation_index)
+                        })?;
+                }
+                Operation::DrawChart { .. } => {
+                    draw_chart(canvas, operation_index, operation, current_page_number)?
+                }
+                Operation::Custom {
+                    custom_operation,
+                    parameters,
+                } => draw_custom_operation(
+                    &mut *canvas,
+                    operation_index,
+                    custom_operation,
+                    parameters,
+                    custom_operations,
+                    [current_page_number, page_count],
+                )?,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Collects the color with which each page is painted, in the order of the pages: the last `SetPageBackground`
+    /// operation of a page takes the place of the page background of the document, if any.
+    fn page_backgrounds(&self) -> Vec<Option<[f32; 3]>> {
+        let mut page_backgrounds = Vec::new();
+        for operation in &self.operations {
+            match operation {
+                Operation::AppendNewPage { .. } => page_backgrounds.push(self.page_background),
+                Operation::SetPageBackground { color } => {
+                    if let Some(page_background) = page_backgrounds.last_mut() {
+                        *page_background = Some(*color);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        page_backgrounds
+    }
+}
+
+/// Retrieves the given text measurer, creating it if it's the first time that it is needed, so that the fonts are
+/// only loaded for measuring the text once the first container or running header is found. The text is measured
+/// with the fonts of the canvas, if it has any, and with the built-in fonts otherwise (see `Canvas::font_bytes`).
+fn loaded_text_measurer<'a, C: Canvas + ?Sized>(
+    text_measurer: &'a mut Option<TextMeasurer>,
+    canvas: &C,
+) -> Result<&'a mut TextMeasurer, ContextError> {
+    match text_measurer {
+        Some(text_measurer) => Ok(text_measurer),
+        None => Ok(text_measurer.insert(match canvas.font_bytes() {
+            Some(font_bytes) => TextMeasurer::from_font_bytes(font_bytes),
+            None => TextMeasurer::new(builtin_font_paths()?),
+        })),
+    }
+}
+
+/// Draws the custom operation with the given name and parameters onto the current page of the canvas via the
+/// custom operation registered with the same name.
+fn draw_custom_operation<C: Canvas + ?Sized>(
+    mut canvas: &mut C,
+    operation_index: usize,
+    name: &str,
+    parameters: &serde_json::Value,
+    custom_operations: &CustomOperations,
+    [current_page_number, page_count]: [usize; 2],
+) -> Result<(), ContextError> {
+    if current_page_number == 0 {
+        return Err(ContextError::with_context(format!(
+            "Unable to draw the custom operation {} because no page has been appended yet",
+            operation_index
+        ))
+        .with_kind(ErrorKind::PageIndexOutOfRange)
+        .with_operation_index(operation_index));
+    }
+    let custom_operation = custom_operations.get(name).ok_or(
+        ContextError::with_context(format!(
+            "Unable to draw operation {} because no custom operation named {:?} has been registered",
+            operation_index, name
+        ))
+        .with_kind(ErrorKind::InvalidDocument)
+        .with_operation_index(operation_index),
+    )?;
+
+    let mut context = CustomOperationContext::new(&mut canvas, current_page_number, page_count);
+    custom_operation
+        .draw(parameters, &mut context)
+        .map_err(|error| {
+            ContextError::with_error(
+                format!("Unable to draw the custom operation {}", operation_index),
+                &error,
+            )
+            .with_kind(error.kind)
+            .with_operation_index(operation_index)
+        })
+}
+
+/// Draws the chart of the given `DrawChart` operation onto the current page of the canvas.
+fn draw_chart<C: Canvas + ?Sized>(
+    canvas: &mut C,
+    operation_index: usize,
+    operation: &Operation,
+    current_page_number: usize,
+) -> Result<(), ContextError> {
+    let Some(chart) = Chart::from_operation(operation) else {
+        return Ok(());
+    };
+    if current_page_number == 0 {
+        return Err(ContextError::with_context(format!(
+            "Unable to draw the chart of operation {} because no page has been appended yet",
+            operation_index
+        ))
+        .with_kind(ErrorKind::PageIndexOutOfRange)
+        .with_operation_index(operation_index));
+    }
+
+    chart.draw(canvas).map_err(|error| {
+        ContextError::with_error(
+            format!("Unable to draw the chart of operation {}", operation_index),
+            &error,
+        )
+        .with_kind(error.kind)
+        .with_operation_index(operation_index)
+    })
+}
+
+/// Draws the given `WriteUnicodeText` operation onto the current page of the canvas, after having normalized
+/// its text and resolved the page numbering tokens.
+fn draw_text<C: Canvas + ?Sized>(
+    canvas: &mut C,
+    operation_index: usize,
+    operation: &Operation,
+    current_page_number: usize,
+    page_count: usize,
+) -> Result<(), ContextError> {
+    let Operation::WriteUnicodeText {
+        color,
+        position,
+        text_string,
+        font_size,
+        font_index,
+        letter_spacing,
+    } = operation
+    else {
+        return Ok(());
+    };
+    if current_page_number == 0 {
+        return Err(ContextError::with_context(format!(
+            "Unable to write the text of operation {} because no page has been appended yet",
+            operation_index
+        ))
+        .with_kind(ErrorKind::PageIndexOutOfRange)
+        .with_operation_index(operation_index));
+    }
+    // Most pieces of text are already normalized, in which case they are drawn without being copied
+    let normalized_text = if is_nfc(text_string) {
+        Cow::Borrowed(text_string.as_str())
+    } else {
+        Cow::Owned(text_string.nfc().collect())
+    };
+    let text = resolve_page_numbering_tokens(normalized_text, current_page_number, page_count);
+
+    canvas
+        .text_run(&TextRun {
+            text: &text,
+            position: *position,
+            font_index: *font_index,
+            font_size: *font_size,
+            color: *color,
+            letter_spacing: *letter_spacing,
+        })
+        .map_err(|error| {
+            ContextError::with_error(
+                format!("Unable to write the text of operation {}", operation_index),
+                &error,
+            )
+            .with_kind(error.kind)
+            .with_operation_index(operation_index)
+        })
+}
+
+/// Replaces the tokens `{page}` and `{pages}` in the given text by the number of the current page
+/// and by the total number of pages respectively.
+fn resolve_page_numbering_tokens(
+    text: Cow<'_, str>,
+    page_number: usize,
+    page_count: usize,
+) -> Cow<'_, str> {
+    // Most pieces of text contain no tokens at all, so avoid the replacements in that case
+    if !text.contains('{') {
+        return text;
+    }
+    Cow::Owned(
+        text.replace("{pages}", &page_count.to_string())
+            .replace("{page}", &page_number.to_string()),
+    )
+}
@@ -0,0 +1,355 @@
+use serde::{Deserialize, Serialize};
+use unicode_normalization::UnicodeNormalization as _;
+
+use crate::{
+    canvas::{Canvas, FilledPath, PathSegment, TextRun},
+    document::Operation,
+    error::{ContextError, ErrorKind},
+    pdf::points_to_millimeters,
+};
+
+/// The type of a chart drawn by the `DrawChart` operation.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum ChartKind {
+    /// A bar for each value, rising from the zero line, with the label of the value underneath.
+    Bar,
+    /// A line joining the values from left to right, with the label of each value underneath.
+    Line,
+    /// A slice of a disc for each value, proportional to its share of the total, with a legend of the labels on the
+    /// right. The values of a pie chart can't be negative.
+    Pie,
+}
+
+/// The colors with which the bars, the lines and the slices are filled, in turn.
+pub const CHART_PALETTE: [[f32; 3]; 6] = [
+    [0.2, 0.4, 0.7],
+    [0.9, 0.5, 0.1],
+    [0.3, 0.6, 0.3],
+    [0.8, 0.2, 0.2],
+    [0.5, 0.4, 0.7],
+    [0.6, 0.4, 0.3],
+];
+
+/// The color of the zero line of the bar and line charts.
+const AXIS_COLOR: [f32; 3] = [0.3, 0.3, 0.3];
+
+/// The thickness in millimeters of the zero line and of the line of a line chart.
+const LINE_THICKNESS: f32 = 0.4;
+
+/// The side in millimeters of the square marking each value of a line chart.
+const MARKER_SIZE: f32 = 1.2;
+
+/// A chart to be drawn onto the current page of a `Canvas`, as found in a `DrawChart` operation.
+#[derive(Debug, Clone, Copy)]
+pub struct Chart<'a> {
+    /// The type of the chart.
+    pub kind: ChartKind,
+    /// The values represented by the chart.
+    pub values: &'a [f32],
+    /// The labels of the values, in the same order, which are left out where missing.
+    pub labels: &'a [String],
+    /// The position in millimeters of the bottom-left corner of the box of the chart, labels included.
+    pub position: [f32; 2],
+    /// The width and height in millimeters of the box of the chart, labels included.
+    pub size: [f32; 2],
+    /// The index of the font used for the labels.
+    pub font_index: usize,
+    /// The size in points of the font used for the labels.
+    pub font_size: f32,
+}
+
+impl<'a> Chart<'a> {
+    /// Retrieves the chart drawn by the given operation, if it is a `DrawChart` operation.
+    pub fn from_operation(operation: &'a Operation) -> Option<Self> {
+        let Operation::DrawChart {
+            kind,
+            values,
+            labels,
+            position,
+            size,
+            font_index,
+            font_size,
+        } = operation
+        else {
+            return None;
+        };
+
+        Some(Chart {
+            kind: *kind,
+            values,
+            labels,
+            position: *position,
+            size: *size,
+            font_index: *font_index,
+            font_size: *font_size,
+        })
+    }
+
+    /// Checks that the values can be represented by the chart, namely that they are finite and, for a pie chart,
+    /// that none of them is negative.
+    pub fn check(&self) -> Result<(), ContextError> {
+        if let Some(value) = self.values.iter().find(|value| !value.is_finite()) {
+            return Err(ContextError::with_context(format!(
+                "Unable to draw the value {} in a chart, the values must be finite",
+                value
+            ))
+            .with_kind(ErrorKind::InvalidDocument));
+        }
+        if self.kind == ChartKind::Pie {
+            if let Some(value) = self.values.iter().find(|value| **value < 0.0) {
+                return Err(ContextError::with_context(format!(
+                    "Unable to draw the value {} in a pie chart, the values can't be negative",
+                    value
+                ))
+                .with_kind(ErrorKind::InvalidDocument));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Draws the chart onto the current page of the given canvas, with filled paths for its shapes and runs of
+    /// text for its labels, so that it is drawn as vector graphics by all the output formats.
+    ///
+    /// # Arguments
+    ///
+    /// * `canvas` - The canvas onto which the chart is drawn.
+    pub fn draw<C: Canvas + ?Sized>(&self, canvas: &mut C) -> Result<(), ContextError> {
+        self.check()?;
+
+        match self.kind {
+            ChartKind::Bar | ChartKind::Line => self.draw_with_axis(canvas),
+            ChartKind::Pie => self.draw_pie(canvas),
+        }
+    }
+
+    /// The height in millimeters of a line of the labels.
+    fn label_height(&self) -> f32 {
+        points_to_millimeters(self.font_size)
+    }
+
+    /// Draws the label with the given index, if any, with its baseline at the given position.
+    fn draw_label<C: Canvas + ?Sized>(
+        &self,
+        canvas: &mut C,
+        label_index: usize,
+        position: [f32; 2],
+    ) -> Result<(), ContextError> {
+        let Some(label) = self.labels.get(label_index) else {
+            return Ok(());
+        };
+        // The labels are normalized just like the text of the document
+        let label = label.nfc().collect::<String>();
+
+        canvas.text_run(&TextRun {
+            text: &label,
+            position,
+            font_index: self.font_index,
+            font_size: self.font_size,
+            color: [0.0, 0.0, 0.0],
+            letter_spacing: 0.0,
+        })
+    }
+
+    /// Draws a bar or a line chart: the values are placed in evenly wide slots from left to right, above the band
+    /// of the labels, and scaled so that the range from the lowest to the highest of them and zero fills the height.
+    fn draw_with_axis<C: Canvas + ?Sized>(&self, canvas: &mut C) -> Result<(), ContextError> {
+        let ([x, y], [width, height]) = (self.position, self.size);
+        let label_band = if self.labels.is_empty() {
+            0.0
+        } else {
+            1.5 * self.label_height()
+        };
+        let [plot_bottom, plot_height] = [y + label_band, (height - label_band).max(0.0)];
+
+        let minimum = self.values.iter().copied().fold(0.0f32, f32::min);
+        let maximum = self.values.iter().copied().fold(0.0f32, f32::max);
+        let range = if maximum > minimum {
+            maximum - minimum
+        } else {
+            1.0
+        };
+        let value_height = |value: f32| plot_bottom + (value - minimum) / range * plot_height;
+        let zero_height = value_height(0.0);
+        let slot_width = width / self.values.len().max(1) as f32;
+
+        match self.kind {
+            ChartKind::Bar => {
+                for (value_index, value) in self.values.iter().enumerate() {
+                    let bar_x = x + (value_index as f32 + 0.15) * slot_width;
+                    let [bottom, top] = [
+                        zero_height.min(value_height(*value)),
+                        zero_height.max(value_height(*value)),
+                    ];
+                    canvas.fill_path(&FilledPath::rectangle(
+                        [bar_x, bottom],
+                        [0.7 * slot_width, top - bottom],
+                        CHART_PALETTE[0],
+                    ))?;
+                }
+            }
+            _ => {
+                let points = self
+                    .values
+                    .iter()
+                    .enumerate()
+                    .map(|(value_index, value)| {
+                        [
+                            x + (value_index as f32 + 0.5) * slot_width,
+                            value_height(*value),
+                        ]
+                    })
+                    .collect::<Vec<_>>();
+                let mut line_segments = Vec::new();
+                for pair in points.windows(2) {
+                    line_segments.extend(thick_segment(pair[0], pair[1], LINE_THICKNESS));
+                }
+                if !line_segments.is_empty() {
+                    canvas.fill_path(&FilledPath {
+                        segments: line_segments,
+                        color: CHART_PALETTE[0],
+                    })?;
+                }
+                for [point_x, point_y] in points {
+                    canvas.fill_path(&FilledPath::rectangle(
+                        [point_x - MARKER_SIZE / 2.0, point_y - MARKER_SIZE / 2.0],
+                        [MARKER_SIZE, MARKER_SIZE],
+                        CHART_PALETTE[0],
+                    ))?;
+                }
+            }
+        }
+
+        // The zero line is drawn over the bars, so that it stays visible between the positive and negative ones
+        canvas.fill_path(&FilledPath::rectangle(
+            [x, zero_height - LINE_THICKNESS / 2.0],
+            [width, LINE_THICKNESS],
+            AXIS_COLOR,
+        ))?;
+        for value_index in 0..self.values.len() {
+            let label_x = match self.kind {
+                ChartKind::Bar => x + (value_index as f32 + 0.15) * slot_width,
+                _ => x + value_index as f32 * slot_width,
+            };
+            self.draw_label(
+                canvas,
+                value_index,
+                [label_x, y + 0.3 * self.label_height()],
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Draws a pie chart: the disc fills the left part of the box, the slices begin from the top and go clockwise,
+    /// while the legend of the labels is stacked on the right of the disc.
+    fn draw_pie<C: Canvas + ?Sized>(&self, canvas: &mut C) -> Result<(), ContextError> {
+        let ([x, y], [width, height]) = (self.position, self.size);
+        let radius = width.min(height) / 2.0;
+        let center = [x + radius, y + height / 2.0];
+        let total = self.values.iter().sum::<f32>();
+
+        if total > 0.0 {
+            let mut start_angle = std::f32::consts::FRAC_PI_2;
+            for (value_index, value) in self.values.iter().enumerate() {
+                let sweep_angle = value / total * std::f32::consts::TAU;
+                if sweep_angle > 0.0 {
+                    canvas.fill_path(&FilledPath {
+                        segments: slice_segments(center, radius, start_angle, sweep_angle),
+                        color: CHART_PALETTE[value_index % CHART_PALETTE.len()],
+                    })?;
+                }
+                start_angle -= sweep_angle;
+            }
+        }
+
+        let line_height = 1.5 * self.label_height();
+        let legend_x = x + 2.0 * radius + line_height;
+        let legend_top = y + height / 2.0 + line_height * self.labels.len() as f32 / 2.0;
+        for value_index in 0..self.values.len().min(self.labels.len()) {
+            let baseline = legend_top - (value_index + 1) as f32 * line_height;
+            let swatch_size = self.label_height() * 0.7;
+            canvas.fill_path(&FilledPath::rectangle(
+                [legend_x, baseline],
+                [swatch_size, swatch_size],
+                CHART_PALETTE[value_index % CHART_PALETTE.len()],
+            ))?;
+            self.draw_label(
+                canvas,
+                value_index,
+                [legend_x + 2.0 * swatch_size, baseline],
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Creates the segments of a straight line from one point to the other with the given thickness, as a closed
+/// quadrilateral which always winds in the same direction, so that consecutive lines overlap without holes.
+fn thick_segment(from: [f32; 2], to: [f32; 2], thickness: f32) -> Vec<PathSegment> {
+    let [dx, dy] = [to[0] - from[0], to[1] - from[1]];
+    let length = (dx * dx + dy * dy).sqrt();
+    if length == 0.0 {
+        return Vec::new();
+    }
+    let [nx, ny] = [
+        -dy / length * thickness / 2.0,
+        dx / length * thickness / 2.0,
+    ];
+
+    vec![
+        PathSegment::MoveTo([from[0] - nx, from[1] - ny]),
+        PathSegment::LineTo([to[0] - nx, to[1] - ny]),
+        PathSegment::LineTo([to[0] + nx, to[1] + ny]),
+        PathSegment::LineTo([from[0] + nx, from[1] + ny]),
+        PathSegment::Close,
+    ]
+}
+
+/// Creates the segments of a slice of the disc with the given center and radius, starting from the given angle and
+/// going clockwise by the sweep angle, both in radians. The arc is approximated by a cubic Bézier curve for each
+/// quarter of a turn or less.
+fn slice_segments(
+    center: [f32; 2],
+    radius: f32,
+    start_angle: f32,
+    sweep_angle: f32,
+) -> Vec<PathSegment> {
+    let point = |angle: f32| {
+        [
+            center[0] + radius * angle.cos(),
+            center[1] + radius * angle.sin(),
+        ]
+    };
+    let arc_count = (sweep_angle / std::f32::consts::FRAC_PI_2).ceil().max(1.0) as usize;
+    let arc_angle = sweep_angle / arc_count as f32;
+    // The distance of the control points from the ends of each arc, relative to the radius
+    let control_distance = 4.0 / 3.0 * (arc_angle / 4.0).tan() * radius;
+
+    let mut segments = vec![
+        PathSegment::MoveTo(center),
+        PathSegment::LineTo(point(start_angle)),
+    ];
+    for arc_index in 0..arc_count {
+        let from_angle = start_angle - arc_index as f32 * arc_angle;
+        let to_angle = from_angle - arc_angle;
+        let [from, to] = [point(from_angle), point(to_angle)];
+        // The tangent of a clockwise arc points along (sin, -cos) of the angle
+        segments.push(PathSegment::CubicTo {
+            first_control_point: [
+                from[0] + control_distance * from_angle.sin(),
+                from[1] - control_distance * from_angle.cos(),
+            ],
+            second_control_point: [
+                to[0] - control_distance * to_angle.sin(),
+                to[1] + control_distance * to_angle.cos(),
+            ],
+            end_point: to,
+        });
+    }
+    segments.push(PathSegment::Close);
+
+    segments
+}
@@ -0,0 +1,445 @@
+use owned_ttf_parser::{AsFaceRef as _, GlyphId, OwnedFace};
+use serde::{Deserialize, Serialize};
+use std::{path::PathBuf, sync::Arc};
+
+use crate::{
+    document::{builtin_font_paths, Document, Operation},
+    error::{ContextError, ErrorKind},
+    font::FontRegistry,
+    pdf::points_to_millimeters,
+};
+
+/// The way in which a `LayoutContainer` operation positions its children.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum ContainerLayout {
+    /// The children are placed one below the other, aligned to the left of the container.
+    Stack,
+    /// The children are placed one after the other from left to right, aligned to the top of the container.
+    Row,
+    /// The children are placed in cells of a fixed size, filling the rows from left to right and from top to bottom.
+    #[serde(rename_all = "camelCase")]
+    Grid {
+        /// The number of cells in each row.
+        columns: usize,
+        /// The width of each cell in millimeters.
+        cell_width: f32,
+        /// The height of each cell in millimeters.
+        cell_height: f32,
+    },
+}
+
+impl Document {
+    /// Returns a copy of the given `Document` in which every `LayoutContainer` operation has been replaced by the
+    /// text operations of its children, placed at the positions computed by the container. The text is measured
+    /// with the built-in fonts, which are only loaded if the document contains at least one container.
+    pub fn lay_out_containers(&self) -> Result<Document, ContextError> {
+        self.lay_out_containers_with(|| Ok(TextMeasurer::new(builtin_font_paths()?)))
+    }
+
+    /// Lays out the containers of the given `Document` just like `lay_out_containers` does, but measures the text
+    /// with the fonts of the given registry, in the order in which they are loaded into a PDF document (see
+    /// `Document::to_pdf_document_with_fonts`).
+    ///
+    /// # Arguments
+    ///
+    /// * `font_registry` - The fonts with which the text is measured.
+    pub fn lay_out_containers_with_fonts(
+        &self,
+        font_registry: &FontRegistry,
+    ) -> Result<Document, ContextError> {
+        self.lay_out_containers_with(|| {
+            Ok(TextMeasurer::from_font_bytes(
+                font_registry.font_bytes().to_vec(),
+            ))
+        })
+    }
+
+    /// Lays out the containers of the document, measuring the text with the text measurer created by the given
+    /// function, which is only called once the first container is found.
+    fn lay_out_containers_with(
+        &self,
+        new_text_measurer: impl Fn() -> Result<TextMeasurer, ContextError>,
+    ) -> Result<Document, ContextError> {
+        let mut text_measurer = None;
+        let mut operations = Vec::with_capacity(self.operations.len());
+        for operation in &self.operations {
+            match operation {
+                Operation::LayoutContainer { .. } => {
+                    let text_measurer = match &mut text_measurer {
+                        Some(text_measurer) => text_measurer,
+                        None => text_measurer.insert(new_text_measurer()?),
+                    };
+                    operation.lay_out(text_measurer, &mut operations)?;
+                }
+                _ => operations.push(operation.clone()),
+            }
+        }
+
+        Ok(Document {
+            operations,
+            ..self.clone()
+        })
+    }
+}
+
+impl Operation {
+    /// Places the given `LayoutContainer` operation with the top-left corner at its own position, appending the
+    /// positioned text operations of its children to the given vector.
+    pub(crate) fn lay_out(
+        &self,
+        text_measurer: &mut TextMeasurer,
+        operations: &mut Vec<Operation>,
+    ) -> Result<(), ContextError> {
+        match self {
+            Operation::LayoutContainer { position, .. } => {
+                self.place(*position, text_measurer, operations)
+            }
+            _ => Err(ContextError::with_context(
+                "Unable to lay out an operation which is not a container",
+            )
+            .with_kind(ErrorKind::InvalidLayout)),
+        }
+    }
+
+    /// Computes the width and height in millimeters of the box occupied by the operation inside a container.
+    fn measure(&self, text_measurer: &mut TextMeasurer) -> Result<[f32; 2], ContextError> {
+        match self {
+            Operation::WriteUnicodeText {
+                text_string,
+                font_size,
+                font_index,
+                letter_spacing,
+                ..
+            } => {
+                let text_extent =
+                    text_measurer.measure(text_string, *font_index, *font_size, *letter_spacing)?;
+                Ok([text_extent.width, text_extent.ascent - text_extent.descent])
+            }
+            Operation::AppendNewPage { .. } => Err(ContextError::with_context(
+                "Unable to lay out a new page inside of a container",
+            )
+            .with_kind(ErrorKind::InvalidLayout)),
+            Operation::DrawChart { .. } => Err(ContextError::with_context(
+                "Unable to lay out a chart inside of a container",
+            )
+            .with_kind(ErrorKind::InvalidLayout)),
+            Operation::DrawBarcode { .. } => Err(ContextError::with_context(
+                "Unable to lay out a barcode inside of a container",
+            )
+            .with_kind(ErrorKind::InvalidLayout)),
+            Operation::SetPageBackground { .. } => Err(ContextError::with_context(
+                "Unable to lay out a page background inside of a container",
+            )
+            .with_kind(ErrorKind::InvalidLayout)),
+            Operation::Custom { .. } => Err(ContextError::with_context(
+                "Unable to lay out a custom operation inside of a container",
+            )
+            .with_kind(ErrorKind::InvalidLayout)),
+            Operation::LayoutContainer {
+                layout,
+                padding,
+                gap,
+                children,
+                ..
+            } => {
+                let child_sizes = children
+                    .iter()
+                    .map(|child| child.measure(text_measurer))
+                    .collect::<Result<Vec<_>, ContextError>>()?;
+                // The gaps are only left in between the children, not around them
+                let total_gap = |count: usize| gap * count.saturating_sub(1) as f32;
+                let [width, height] = match layout {
+                    ContainerLayout::Stack => [
+                        child_sizes
+                            .iter()
+                            .map(|[width, _]| *width)
+                            .fold(0.0, f32::max),
+                        child_sizes.iter().map(|[_, height]| height).sum::<f32>()
+                            + total_gap(child_sizes.len()),
+                    ],
+                    ContainerLayout::Row => [
+                        child_sizes.iter().map(|[width, _]| width).sum::<f32>()
+                            + total_gap(child_sizes.len()),
+                        child_sizes
+                            .iter()
+                            .map(|[_, height]| *height)
+                            .fold(0.0, f32::max),
+                    ],
+                    ContainerLayout::Grid {
+                        columns,
+                        cell_width,
+                        cell_height,
+                    } => {
+                        let columns = grid_columns(*columns)?;
+                        let rows = children.len().div_ceil(columns);
+                        [
+                            cell_width * columns as f32 + total_gap(columns),
+                            cell_height * rows as f32 + total_gap(rows),
+                        ]
+                    }
+                };
+
+                Ok([width + 2.0 * padding, height + 2.0 * padding])
+            }
+        }
+    }
+
+    /// Places the operation with the top-left corner of its box at the given position in millimeters, appending
+    /// the resulting text operations to the given vector.
+    fn place(
+        &self,
+        top_left_corner: [f32; 2],
+        text_measurer: &mut TextMeasurer,
+        operations: &mut Vec<Operation>,
+    ) -> Result<(), ContextError> {
+        let [x, y] = top_left_corner;
+        match self {
+            Operation::WriteUnicodeText {
+                color,
+                position,
+                text_string,
+                font_size,
+                font_index,
+                letter_spacing,
+            } => {
+                // The position of a text inside a container is an offset from the place assigned to it,
+                // while the baseline lies below the top of its box by the ascent of the font
+                let text_extent =
+                    text_measurer.measure(text_string, *font_index, *font_size, *letter_spacing)?;
+                operations.push(Operation::WriteUnicodeText {
+                    color: *color,
+                    position: [x + position[0], y - text_extent.ascent + position[1]],
+                    text_string: text_string.clone(),
+                    font_size: *font_size,
+                    font_index: *font_index,
+                    letter_spacing: *letter_spacing,
+                });
+            }
+            Operation::AppendNewPage { .. } => {
+                return Err(ContextError::with_context(
+                    "Unable to lay out a new page inside of a container",
+                )
+                .with_kind(ErrorKind::InvalidLayout))
+            }
+            Operation::DrawChart { .. } => {
+                return Err(ContextError::with_context(
+                    "Unable to lay out a chart inside of a container",
+                )
+                .with_kind(ErrorKind::InvalidLayout))
+            }
+            Operation::DrawBarcode { .. } => {
+                return Err(ContextError::with_context(
+                    "Unable to lay out a barcode inside of a container",
+                )
+                .with_kind(ErrorKind::InvalidLayout))
+            }
+            Operation::SetPageBackground { .. } => {
+                return Err(ContextError::with_context(
+                    "Unable to lay out a page background inside of a container",
+                )
+                .with_kind(ErrorKind::InvalidLayout))
+            }
+            Operation::Custom { .. } => {
+                return Err(ContextError::with_context(
+                    "Unable to lay out a custom operation inside of a container",
+                )
+                .with_kind(ErrorKind::InvalidLayout))
+            }
+            Operation::LayoutContainer {
+                layout,
+                padding,
+                gap,
+                children,
+                ..
+            } => {
+                let (x, y) = (x + padding, y - padding);
+                match layout {
+                    ContainerLayout::Stack => {
+                        let mut current_y = y;
+                        for child in children {
+                            let [_, child_height] = child.measure(text_measurer)?;
+                            child.place([x, current_y], text_measurer, operations)?;
+                            current_y -= child_height + gap;
+                        }
+                    }
+                    ContainerLayout::Row => {
+                        let mut current_x = x;
+                        for child in children {
+                            let [child_width, _] = child.measure(text_measurer)?;
+                            child.place([current_x, y], text_measurer, operations)?;
+                            current_x += child_width + gap;
+                        }
+                    }
+                    ContainerLayout::Grid {
+                        columns,
+                        cell_width,
+                        cell_height,
+                    } => {
+                        let columns = grid_columns(*columns)?;
+                        for (child_index, child) in children.iter().enumerate() {
+                            let (row, column) = (child_index / columns, child_index % columns);
+                            child.place(
+                                [
+                                    x + column as f32 * (cell_width + gap),
+                                    y - row as f32 * (cell_height + gap),
+                                ],
+                                text_measurer,
+                                operations,
+                            )?;
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Validates the number of columns of a grid, which needs to be positive.
+fn grid_columns(columns: usize) -> Result<usize, ContextError> {
+    if columns == 0 {
+        return Err(
+            ContextError::with_context("Unable to lay out a grid without any columns")
+                .with_kind(ErrorKind::InvalidLayout),
+        );
+    }
+
+    Ok(columns)
+}
+
+/// The horizontal and vertical extent of a piece of text, expressed in millimeters.
+pub(crate) struct TextExtent {
+    /// The advance width of the whole text.
+    pub(crate) width: f32,
+    /// The distance from the baseline to the top of the font.
+    pub(crate) ascent: f32,
+    /// The distance from the baseline to the bottom of the font, which is negative.
+    pub(crate) descent: f32,
+}
+
+/// Where a `TextMeasurer` loads a font from.
+enum FontSource {
+    /// The font file at the given path, which is read when the font is first needed.
+    Path(PathBuf),
+    /// The content of a font which has already been read, such as the fonts of a `FontRegistry`.
+    Bytes(Arc<[u8]>),
+}
+
+/// Measures pieces of text with the fonts of a document, loading each font the first time it is needed.
+pub(crate) struct TextMeasurer {
+    /// The sources of the fonts in the order in which they are loaded into a PDF document.
+    font_sources: Vec<FontSource>,
+    /// The font faces loaded so far, indexed by their font index.
+    font_faces: Vec<Option<OwnedFace>>,
+}
+
+impl TextMeasurer {
+    /// Creates a text measurer for the fonts at the given paths, without loading them yet.
+    pub(crate) fn new(font_paths: Vec<PathBuf>) -> Self {
+        Self::with_font_sources(font_paths.into_iter().map(FontSource::Path).collect())
+    }
+
+    /// Creates a text measurer for the fonts with the given content, indexed by their font index, such as the
+    /// fonts of the PDF document onto which the text is drawn.
+    pub(crate) fn from_font_bytes(font_bytes: Vec<Arc<[u8]>>) -> Self {
+        Self::with_font_sources(font_bytes.into_iter().map(FontSource::Bytes).collect())
+    }
+
+    /// Creates a text measurer for the fonts from the given sources, without loading them yet.
+    fn with_font_sources(font_sources: Vec<FontSource>) -> Self {
+        let font_faces = font_sources.iter().map(|_| None).collect();
+        TextMeasurer {
+            font_sources,
+            font_faces,
+        }
+    }
+
+    /// Retrieves the font face with the given font index, loading it if it is the first time that it is needed.
+    fn font_face(&mut self, font_index: usize) -> Result<&OwnedFace, ContextError> {
+        match self.font_faces.get_mut(font_index) {
+            Some(Some(font_face)) => Ok(font_face),
+            Some(font_face) => {
+                let font_bytes = match &self.font_sources[font_index] {
+                    FontSource::Path(font_path) => std::fs::read(font_path).map_err(|error| {
+                        ContextError::with_error(
+                            format!("Failed to read the font file {:?}", font_path),
+                            &error,
+                        )
+                        .with_kind(ErrorKind::FontNotFound)
+                    })?,
+                    FontSource::Bytes(font_bytes) => font_bytes.to_vec(),
+                };
+                Ok(
+                    font_face.insert(OwnedFace::from_vec(font_bytes, 0).map_err(|error| {
+                        ContextError::with_error("Failed to parse font", &error)
+                            .with_kind(ErrorKind::InvalidFont)
+                    })?),
+                )
+            }
+            None => Err(ContextError::with_context(format!(
+                "Unable to load the font because the font index {} does not exist",
+                font_index
+            ))
+            .with_kind(ErrorKind::FontIndexOutOfRange)),
+        }
+    }
+
+    /// Returns the number of fonts which can be used for measuring text.
+    pub(crate) fn font_count(&self) -> usize {
+        self.font_sources.len()
+    }
+
+    /// Returns the characters of the given text which are missing from the font with the given index,
+    /// without repetitions and in the order in which they first appear.
+    pub(crate) fn missing_characters(
+        &mut self,
+        text: &str,
+        font_index: usize,
+    ) -> Result<Vec<char>, ContextError> {
+        let face = self.font_face(font_index)?.as_face_ref();
+        let mut missing_characters = Vec::new();
+        for character in text.chars() {
+            if face.glyph_index(character).is_none() && !missing_characters.contains(&character) {
+                missing_characters.push(character);
+            }
+        }
+
+        Ok(missing_characters)
+    }
+
+    /// Measures the given text as it would be written with the given font index, font size and letter spacing,
+    /// the last two being expressed in points.
+    pub(crate) fn measure(
+        &mut self,
+        text: &str,
+        font_index: usize,
+        font_size: f32,
+        letter_spacing: f32,
+    ) -> Result<TextExtent, ContextError> {
+        let face = self.font_face(font_index)?.as_face_ref();
+
+        // Characters missing from the font are measured with the advance of the glyph drawn in their place
+        let advance_width = text
+            .chars()
+            .map(|character| {
+                let glyph_id = face.glyph_index(character).unwrap_or(GlyphId(0));
+                face.glyph_hor_advance(glyph_id).unwrap_or(0) as f32
+            })
+            .sum::<f32>();
+        let font_units_to_millimeters = |font_units: f32| {
+            points_to_millimeters(font_units / face.units_per_em() as f32 * font_size)
+        };
+
+        // The letter spacing is added after each character, the last one included
+        let letter_spacing_width = letter_spacing * text.chars().count() as f32;
+
+        Ok(TextExtent {
+            width: font_units_to_millimeters(advance_width)
+                + points_to_millimeters(letter_spacing_width),
+            ascent: font_units_to_millimeters(face.ascender() as f32),
+            descent: font_units_to_millimeters(face.descender() as f32),
+        })
+    }
+}
@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+
+use crate::{
+    canvas::{Canvas, FilledPath, TextRun},
+    error::ContextError,
+};
+
+/// An operation whose type is defined outside of this crate, such as a stamp or a signature, which is drawn by its
+/// own code. In a JSON document, the operation is given by its name and its parameters, for instance
+/// `{"customOperation": "DrawStamp", "parameters": {"text": "PAID"}}`, and it is drawn by the
+/// `CustomOperation` registered with the same name (see `CustomOperations`).
+pub trait CustomOperation: Send + Sync {
+    /// The name of the operation, by which the documents refer to it.
+    fn name(&self) -> &str;
+
+    /// Draws the operation with the given parameters, as found in the document, onto the current page.
+    ///
+    /// # Arguments
+    ///
+    /// * `parameters` - The parameters of the operation, which are `null` if the document gives none.
+    /// * `context` - The context through which the operation is drawn onto the current page.
+    fn draw(
+        &self,
+        parameters: &serde_json::Value,
+        context: &mut CustomOperationContext<'_>,
+    ) -> Result<(), ContextError>;
+}
+
+/// The context through which a `CustomOperation` is drawn onto the current page of a document, with the runs of
+/// text and the filled paths understood by all the output formats, or with raw PDF operations.
+pub struct CustomOperationContext<'a> {
+    /// The canvas onto which the document is drawn.
+    canvas: &'a mut dyn Canvas,
+    /// The number of the current page, starting from 1.
+    page_number: usize,
+    /// The total number of pages of the document.
+    page_count: usize,
+}
+
+impl<'a> CustomOperationContext<'a> {
+    /// Creates the context for drawing onto the current page of the given canvas.
+    pub(crate) fn new(canvas: &'a mut dyn Canvas, page_number: usize, page_count: usize) -> Self {
+        CustomOperationContext {
+            canvas,
+            page_number,
+            page_count,
+        }
+    }
+
+    /// The number of the current page, starting from 1.
+    pub fn page_number(&self) -> usize {
+        self.page_number
+    }
+
+    /// The total number of pages of the document.
+    pub fn page_count(&self) -> usize {
+        self.page_count
+    }
+
+    /// Draws a run of text onto the current page.
+    pub fn text_run(&mut self, text_run: &TextRun) -> Result<(), ContextError> {
+        self.canvas.text_run(text_run)
+    }
+
+    /// Draws a filled path onto the current page.
+    pub fn fill_path(&mut self, path: &FilledPath) -> Result<(), ContextError> {
+        self.canvas.fill_path(path)
+    }
+
+    /// Adds raw PDF content stream operations to the current page, which are left out of the other output formats
+    /// (see `Canvas::pdf_operations`).
+    pub fn pdf_operations(
+        &mut self,
+        operations: Vec<lopdf::content::Operation>,
+    ) -> Result<(), ContextError> {
+        self.canvas.pdf_operations(operations)
+    }
+}
+
+/// The custom operations which can be drawn, by their names (see `CustomOperation`). The operations of a document
+/// whose name isn't registered make its conversion fail.
+#[derive(Default)]
+pub struct CustomOperations {
+    /// The registered custom operations, by their names.
+    custom_operations: HashMap<String, Box<dyn CustomOperation>>,
+}
+
+impl CustomOperations {
+    /// Creates a registry without any custom operation.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the given custom operation under its name, replacing any operation registered with the same name.
+    ///
+    /// # Arguments
+    ///
+    /// * `custom_operation` - The custom operation to be registered.
+    pub fn register<O: CustomOperation + 'static>(&mut self, custom_operation: O) {
+        self.custom_operations.insert(
+            custom_operation.name().to_string(),
+            Box::new(custom_operation),
+        );
+    }
+
+    /// Retrieves the custom operation registered with the given name, if any.
+    pub fn get(&self, name: &str) -> Option<&dyn CustomOperation> {
+        self.custom_operations
+            .get(name)
+            .map(|custom_operation| custom_operation.as_ref())
+    }
+}
@@ -0,0 +1,321 @@
+use serde::{Deserialize, Serialize};
+
+use crate::document::{Document, Operation};
+
+/// The largest number of cells of the table with which the changed regions of two documents are aligned, which
+/// takes four bytes each, so that the table never grows beyond 64 MiB. Larger regions are compared in place.
+const MAX_ALIGNMENT_CELLS: usize = 1 << 24;
+
+/// A single difference between two documents, as reported by `Document::diff`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "change")]
+pub enum Change {
+    /// The document ID differs between the two documents.
+    #[serde(rename_all = "camelCase")]
+    DocumentId {
+        /// The document ID of the original document.
+        old: String,
+        /// The document ID of the other document.
+        new: String,
+    },
+    /// The instance ID differs between the two documents.
+    #[serde(rename_all = "camelCase")]
+    InstanceId {
+        /// The instance ID of the original document.
+        old: String,
+        /// The instance ID of the other document.
+        new: String,
+    },
+    /// Some properties of the whole document, such as its title or its page background, differ between the two
+    /// documents.
+    #[serde(rename_all = "camelCase")]
+    Properties {
+        /// The properties which differ between the two documents.
+        fields: Vec<FieldChange>,
+    },
+    /// An operation is only present in the other document.
+    #[serde(rename_all = "camelCase")]
+    Added {
+        /// The index of the operation in the other document.
+        index: usize,
+        /// The added operation.
+        operation: Operation,
+    },
+    /// An operation is only present in the original document.
+    #[serde(rename_all = "camelCase")]
+    Removed {
+        /// The index of the operation in the original document.
+        index: usize,
+        /// The removed operation.
+        operation: Operation,
+    },
+    /// An operation of the same kind is present in both documents, but some of its fields differ.
+    #[serde(rename_all = "camelCase")]
+    Modified {
+        /// The index of the operation in the original document.
+        old_index: usize,
+        /// The index of the operation in the other document.
+        new_index: usize,
+        /// The fields which differ between the two operations.
+        fields: Vec<FieldChange>,
+    },
+}
+
+/// A field of an operation, or a property of a document, which differs between two documents, with both of its values
+/// expressed in the same JSON representation used by the document format.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldChange {
+    /// The name of the field as it appears in the JSON document, such as `textString`.
+    pub field: String,
+    /// The value of the field in the original document.
+    pub old: serde_json::Value,
+    /// The value of the field in the other document.
+    pub new: serde_json::Value,
+}
+
+impl Document {
+    /// Computes the semantic differences between this document and the other one. The operations of the two
+    /// documents are aligned on their longest common subsequence, so that inserting an operation is reported
+    /// as a single addition instead of as a modification of all the following operations. Operations of the same
+    /// kind that occupy the same place in the two documents are reported as modified, together with the fields
+    /// that differ between them. When the region which has changed is too large to be aligned, its operations are
+    /// compared in place instead, one by one in their order.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The document to compare this document against.
+    pub fn diff(&self, other: &Document) -> Vec<Change> {
+        let mut changes = Vec::new();
+
+        if self.document_id != other.document_id {
+            changes.push(Change::DocumentId {
+                old: self.document_id.clone(),
+                new: other.document_id.clone(),
+            });
+        }
+        if self.instance_id != other.instance_id {
+            changes.push(Change::InstanceId {
+                old: self.instance_id.clone(),
+                new: other.instance_id.clone(),
+            });
+        }
+
+        let properties = changed_properties(self, other);
+        if !properties.is_empty() {
+            changes.push(Change::Properties { fields: properties });
+        }
+
+        let old_operations = &self.operations;
+        let new_operations = &other.operations;
+
+        // Skip the common prefix and suffix, which are usually the largest part of the documents,
+        // so that the quadratic alignment only runs on the region that has actually changed
+        let prefix_length = old_operations
+            .iter()
+            .zip(new_operations.iter())
+            .take_while(|(old_operation, new_operation)| old_operation == new_operation)
+            .count();
+        let suffix_length = old_operations[prefix_length..]
+            .iter()
+            .rev()
+            .zip(new_operations[prefix_length..].iter().rev())
+            .take_while(|(old_operation, new_operation)| old_operation == new_operation)
+            .count();
+        let old_range = prefix_length..old_operations.len() - suffix_length;
+        let new_range = prefix_length..new_operations.len() - suffix_length;
+
+        // Compute the pairs of indices of the operations which are equal in both documents
+        let anchors = longest_common_subsequence(
+            &old_operations[old_range.clone()],
+            &new_operations[new_range.clone()],
+        );
+
+        // Walk over the anchors and report the gaps in between them as changes
+        let (mut old_index, mut new_index) = (old_range.start, new_range.start);
+        for (old_anchor, new_anchor) in anchors
+            .into_iter()
+            .map(|(old_anchor, new_anchor)| {
+                (old_anchor + old_range.start, new_anchor + new_range.start)
+            })
+            .chain(std::iter::once((old_range.end, new_range.end)))
+        {
+            report_gap(
+                old_operations,
+                new_operations,
+                old_index..old_anchor,
+                new_index..new_anchor,
+                &mut changes,
+            );
+            old_index = old_anchor + 1;
+            new_index = new_anchor + 1;
+        }
+
+        changes
+    }
+}
+
+/// Reports the operations found in between two aligned operations. Operations of the same kind
+/// found at the same offset in the gap are reported as modified, the rest as removed or added.
+fn report_gap(
+    old_operations: &[Operation],
+    new_operations: &[Operation],
+    old_gap: std::ops::Range<usize>,
+    new_gap: std::ops::Range<usize>,
+    changes: &mut Vec<Change>,
+) {
+    let mut old_gap = old_gap.peekable();
+    let mut new_gap = new_gap.peekable();
+
+    loop {
+        match (old_gap.peek().copied(), new_gap.peek().copied()) {
+            (Some(old_index), Some(new_index)) => {
+                let old_operation = &old_operations[old_index];
+                let new_operation = &new_operations[new_index];
+                if std::mem::discriminant(old_operation) == std::mem::discriminant(new_operation) {
+                    changes.push(Change::Modified {
+                        old_index,
+                        new_index,
+                        fields: changed_fields(old_operation, new_operation),
+                    });
+                } else {
+                    changes.push(Change::Removed {
+                        index: old_index,
+                        operation: old_operation.clone(),
+                    });
+                    changes.push(Change::Added {
+                        index: new_index,
+                        operation: new_operation.clone(),
+                    });
+                }
+                old_gap.next();
+                new_gap.next();
+            }
+            (Some(old_index), None) => {
+                changes.push(Change::Removed {
+                    index: old_index,
+                    operation: old_operations[old_index].clone(),
+                });
+                old_gap.next();
+            }
+            (None, Some(new_index)) => {
+                changes.push(Change::Added {
+                    index: new_index,
+                    operation: new_operations[new_index].clone(),
+                });
+                new_gap.next();
+            }
+            (None, None) => break,
+        }
+    }
+}
+
+/// Lists the fields which differ between two operations of the same kind by comparing
+/// their JSON representations, so that the field names match the ones of the document format.
+fn changed_fields(old_operation: &Operation, new_operation: &Operation) -> Vec<FieldChange> {
+    let (Ok(serde_json::Value::Object(old_fields)), Ok(serde_json::Value::Object(new_fields))) = (
+        serde_json::to_value(old_operation),
+        serde_json::to_value(new_operation),
+    ) else {
+        // Operations are always serialized as JSON objects, so this branch is never taken in practice
+        return Vec::new();
+    };
+
+    changed_entries(old_fields, new_fields)
+}
+
+/// Lists the properties of the whole document which differ between the two documents, that is every field of
+/// the document format except for the IDs, which have their own changes, and the operations.
+fn changed_properties(old_document: &Document, new_document: &Document) -> Vec<FieldChange> {
+    let (
+        Ok(serde_json::Value::Object(mut old_fields)),
+        Ok(serde_json::Value::Object(mut new_fields)),
+    ) = (
+        serde_json::to_value(old_document),
+        serde_json::to_value(new_document),
+    )
+    else {
+        // Documents are always serialized as JSON objects, so this branch is never taken in practice
+        return Vec::new();
+    };
+    for field in ["documentId", "instanceId", "operations"] {
+        old_fields.remove(field);
+        new_fields.remove(field);
+    }
+
+    changed_entries(old_fields, new_fields)
+}
+
+/// Lists the entries which differ between two JSON objects, including the ones present in only one of them,
+/// such as the optional fields which are left out when they are not set, whose missing value is `null`.
+fn changed_entries(
+    mut old_fields: serde_json::Map<String, serde_json::Value>,
+    mut new_fields: serde_json::Map<String, serde_json::Value>,
+) -> Vec<FieldChange> {
+    let fields = old_fields
+        .keys()
+        .chain(new_fields.keys())
+        .cloned()
+        .collect::<std::collections::BTreeSet<_>>();
+
+    fields
+        .into_iter()
+        .filter_map(|field| {
+            let old_value = old_fields.remove(&field).unwrap_or(serde_json::Value::Null);
+            let new_value = new_fields.remove(&field).unwrap_or(serde_json::Value::Null);
+            (old_value != new_value).then_some(FieldChange {
+                field,
+                old: old_value,
+                new: new_value,
+            })
+        })
+        .collect()
+}
+
+/// Computes the longest common subsequence between the two slices of operations, returning the
+/// pairs of indices of the operations which are part of it, in increasing order. If the table would have more
+/// than `MAX_ALIGNMENT_CELLS` cells, only the equal operations found at the same index are paired instead.
+fn longest_common_subsequence(
+    old_operations: &[Operation],
+    new_operations: &[Operation],
+) -> Vec<(usize, usize)> {
+    let (old_length, new_length) = (old_operations.len(), new_operations.len());
+    // Past the limit, the operations are only matched against the ones at the same index
+    if (old_length + 1).saturating_mul(new_length + 1) > MAX_ALIGNMENT_CELLS {
+        return (0..old_length.min(new_length))
+            .filter(|&index| old_operations[index] == new_operations[index])
+            .map(|index| (index, index))
+            .collect();
+    }
+    // The table holds at (i, j) the length of the longest common subsequence between
+    // the suffixes of the operations starting at i and j respectively
+    let mut table = vec![0u32; (old_length + 1) * (new_length + 1)];
+    let cell = |i: usize, j: usize| i * (new_length + 1) + j;
+
+    for i in (0..old_length).rev() {
+        for j in (0..new_length).rev() {
+            table[cell(i, j)] = if old_operations[i] == new_operations[j] {
+                table[cell(i + 1, j + 1)] + 1
+            } else {
+                table[cell(i + 1, j)].max(table[cell(i, j + 1)])
+            };
+        }
+    }
+
+    // Walk the table from the beginning in order to recover the matched pairs
+    let mut pairs = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old_length && j < new_length {
+        if old_operations[i] == new_operations[j] {
+            pairs.push((i, j));
+            i += 1;
+            j += 1;
+        } else if table[cell(i + 1, j)] >= table[cell(i, j + 1)] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+
+    pairs
+}
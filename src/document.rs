@@ -1,11 +1,25 @@
 use serde::{Deserialize, Serialize};
 use std::{
-    io::Write as _,
+    io::Write,
     path::{Path, PathBuf},
     str::FromStr as _,
+    time::Instant,
 };
+use time::OffsetDateTime;
 
-use crate::{error::ContextError, pdf::PdfDocument};
+use crate::{
+    barcode::BarcodeKind,
+    blocking::BlockingTask,
+    chart::ChartKind,
+    container::ContainerLayout,
+    custom::CustomOperations,
+    error::{ContextError, ErrorKind},
+    font::FontRegistry,
+    header::RunningHeader,
+    pdf::{PdfCanvas, PdfDocument, PdfStreamWriter},
+    timings::{Stage, Timings},
+    warning::Warning,
+};
 
 /// The document metadata and the operations needed in order to construct it
 /// are saved into this struct. This can be deserialized from a properly-constructed
@@ -14,19 +28,31 @@ use crate::{error::ContextError, pdf::PdfDocument};
 /// # Parameters
 ///
 /// * `document_id` - A string that holds the ID of the document: a unique identifier
-/// which when paired with the instance ID (`instance_id`) uniquely identifies a document.
-/// Both the parameters are needed for creating a correct PDF document.
+///   which when paired with the instance ID (`instance_id`) uniquely identifies a document.
+///   Both the parameters are needed for creating a correct PDF document.
 /// * `instance_id` - A string that holds the ID of the instance (see `document_id`).
 /// * `operations` - A vector of `Operation` structs that holds the operations needed to
-/// construct the document. Such operations can be for instance to include some unicode text
-/// into the document at a specific position and with the given font, font size and color, or
-/// either to append a new page to the document with a given width and height.
+///   construct the document. Such operations can be for instance to include some unicode text
+///   into the document at a specific position and with the given font, font size and color, or
+///   either to append a new page to the document with a given width and height.
+/// * `page_background` - An optional RGB color with which every page is painted before any of its
+///   contents. When it is not specified, the pages are left transparent.
+/// * `title` - The optional title of the document, which is written into the metadata of the PDF document.
+/// * `author` - The optional author of the document, which is written into the metadata of the PDF document.
+/// * `running_header` - The optional header written at the top of every page, which is only written if the
+///   document has a title (see `RunningHeader`).
+/// * `timestamp` - The optional creation and modification date of the document, in seconds since the Unix epoch.
+///   When it is not specified, the time at which the PDF document is written is used (see `PdfDocument::timestamp`).
+/// * `deterministic` - Whether the document without a timestamp is dated at the Unix epoch rather than at the time
+///   it is written, so that it is converted into the same bytes on every run (see `PdfDocument::deterministic`).
+/// * `strict` - Whether the characters missing from their font and the glyphs without width make the
+///   conversion into a PDF document fail, rather than being left out with a warning (see `PdfDocument::strict`).
 ///
 /// # Example
 ///
 /// See the example `document_to_pdf` in the folder `examples` for how to construct a `Document`
 /// from a file in the JSON format which adheres to the `Document` specification.
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Document {
     /// The unique ID of the document (to be paired with the instance ID).
@@ -35,11 +61,33 @@ pub struct Document {
     pub instance_id: String,
     /// The operations needed to construct the document.
     pub operations: Vec<Operation>,
+    /// The color with which every page is painted beneath its contents, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_background: Option<[f32; 3]>,
+    /// The title of the document, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// The author of the document, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// The header repeated at the top of every page, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub running_header: Option<RunningHeader>,
+    /// The creation and modification date of the document in seconds since the Unix epoch, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<i64>,
+    /// Whether the document without a timestamp is dated at the Unix epoch, so that its output is reproducible.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub deterministic: bool,
+    /// Whether the conversion into a PDF document fails on any content which would be left out.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub strict: bool,
 }
 
 /// The `Operation` struct is used to represent the operations needed to construct a document.
-/// It can be any of the following: `UnicodeText`, `AppendNewPage`.
-#[derive(Debug, Deserialize, Serialize, Clone)]
+/// It can be any of the following: `UnicodeText`, `AppendNewPage`, `LayoutContainer`, `SetPageBackground`,
+/// `DrawBarcode`, `DrawChart`, `Custom`.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 #[serde(untagged)]
 pub enum Operation {
     /// Represents a piece of text to be rendered in the PDF document.
@@ -49,7 +97,8 @@ pub enum Operation {
         color: [f32; 3],
         /// The position of the text.
         position: [f32; 2],
-        /// The text to be rendered, save the in an UTF-8-compatible format.
+        /// The text to be rendered, save the in an UTF-8-compatible format. The tokens `{page}` and `{pages}`
+        /// are replaced by the number of the current page and by the total number of pages respectively.
         text_string: String,
         /// The font size of the text.
         font_size: f32,
@@ -57,6 +106,10 @@ pub enum Operation {
         /// This is a low-level information and the proper index for the specific use-case
         /// can be calculated by knowing in which order the fonts have been loaded into the document.
         font_index: usize,
+        /// The additional space in points inserted after each character of the text, which can be negative
+        /// in order to tighten the text. When it is not specified, the text is written with its natural spacing.
+        #[serde(default, skip_serializing_if = "is_zero")]
+        letter_spacing: f32,
     },
     /// Represents a new page with the given width and height to be appended to the PDF document.
     #[serde(rename_all = "camelCase")]
@@ -66,6 +119,80 @@ pub enum Operation {
         /// The height of the new page.
         page_height: f32,
     },
+    /// Represents a container which positions its children automatically according to its layout, so that
+    /// the position of each of them does not need to be computed by hand. The position of a text inside
+    /// a container is an offset from the place assigned to it by the container.
+    #[serde(rename_all = "camelCase")]
+    LayoutContainer {
+        /// The way in which the children are positioned.
+        layout: ContainerLayout,
+        /// The position of the top-left corner of the container in millimeters.
+        position: [f32; 2],
+        /// The space in millimeters left between the border of the container and its children.
+        #[serde(default)]
+        padding: f32,
+        /// The space in millimeters left in between two adjacent children.
+        #[serde(default)]
+        gap: f32,
+        /// The operations positioned by the container, which can be either pieces of text or other containers.
+        children: Vec<Operation>,
+    },
+    /// Represents a color with which the current page is painted edge to edge beneath all of its content, no matter
+    /// where the operation is placed on the page, such as for a colored cover page. It takes the place of the page
+    /// background of the document for the current page only, and the last one wins if the page has several of them.
+    #[serde(rename_all = "camelCase")]
+    SetPageBackground {
+        /// The RGB color of the page, saved as `pageBackground` just like the page background of the document.
+        #[serde(rename = "pageBackground")]
+        color: [f32; 3],
+    },
+    /// Represents a barcode drawn in black onto the current page as vector graphics, so that it stays sharp at any
+    /// resolution without being rendered into an image beforehand (see `BarcodeKind::encode`).
+    #[serde(rename_all = "camelCase")]
+    DrawBarcode {
+        /// The symbology of the barcode, saved as `barcode` so that the operation is told apart from the others.
+        #[serde(rename = "barcode")]
+        kind: BarcodeKind,
+        /// The data encoded by the barcode.
+        data: String,
+        /// The position in millimeters of the bottom-left corner of the barcode.
+        position: [f32; 2],
+        /// The width and height in millimeters over which the modules of the barcode are stretched, which should be
+        /// the same for a QR code.
+        size: [f32; 2],
+    },
+    /// Represents a bar, line or pie chart of the given values drawn onto the current page as vector graphics, whose
+    /// labels are written with one of the fonts of the document (see `Chart::draw`).
+    #[serde(rename_all = "camelCase")]
+    DrawChart {
+        /// The type of the chart, saved as `chart` so that the operation is told apart from the others.
+        #[serde(rename = "chart")]
+        kind: ChartKind,
+        /// The values represented by the chart.
+        values: Vec<f32>,
+        /// The labels of the values, in the same order. When they are not specified, the values are not labeled.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        labels: Vec<String>,
+        /// The position in millimeters of the bottom-left corner of the box of the chart, labels included.
+        position: [f32; 2],
+        /// The width and height in millimeters of the box of the chart, labels included.
+        size: [f32; 2],
+        /// The index of the font with which the labels are written.
+        font_index: usize,
+        /// The size in points of the font with which the labels are written.
+        font_size: f32,
+    },
+    /// Represents an operation whose type is defined outside of this crate, such as a stamp, which is drawn by
+    /// the `CustomOperation` registered with its name (see `Document::draw_with_custom_operations`). Custom
+    /// operations can't be placed inside of containers.
+    #[serde(rename_all = "camelCase")]
+    Custom {
+        /// The name of the custom operation, such as `DrawStamp`.
+        custom_operation: String,
+        /// The parameters of the operation, which are only interpreted by the custom operation.
+        #[serde(default, skip_serializing_if = "serde_json::Value::is_null")]
+        parameters: serde_json::Value,
+    },
 }
 
 impl Document {
@@ -75,22 +202,62 @@ impl Document {
     ///
     /// * `document_path` - The path to the JSON document.
     pub fn from_path(document_path: &PathBuf) -> Result<Self, ContextError> {
+        let _parse_span = tracing::info_span!("parse_document", ?document_path).entered();
         // Read the document content from the given path into a string
         let document_content = std::fs::read_to_string(document_path).map_err(|error| {
             ContextError::with_error(
                 format!("Unable to read the document {:?}", document_path),
                 &error,
             )
+            .with_kind(ErrorKind::Io)
         })?;
         // Deserialize the document content into the `Document` struct
-        let document: Self = serde_json::from_str(&document_content).map_err(|error| {
-            ContextError::with_error(
-                format!("Unable to parse the document {:?}", document_path),
-                &error,
-            )
-        })?;
+        Self::parse_json(
+            document_content.as_bytes(),
+            &format!("the document {:?}", document_path),
+        )
+    }
+
+    /// Creates a new `Document` by deserializing the given JSON document, which may come from any source, such as
+    /// a request or a fuzzer. The errors locate the position and the operation at fault, just like `from_path`.
+    ///
+    /// # Arguments
+    ///
+    /// * `document_bytes` - The content of the JSON document.
+    pub fn from_json_bytes(document_bytes: &[u8]) -> Result<Self, ContextError> {
+        let _parse_span = tracing::info_span!("parse_document").entered();
+        Self::parse_json(document_bytes, "the document")
+    }
 
-        Ok(document)
+    /// Deserializes the given JSON document, describing it as given in the context of the errors.
+    fn parse_json(document_bytes: &[u8], document_description: &str) -> Result<Self, ContextError> {
+        serde_json::from_slice(document_bytes).map_err(|error| {
+            // The position is moved from the message of the error into the context, together with the operation
+            // at fault, since the error of the untagged operations doesn't tell which of them can't be parsed
+            let message = error.to_string();
+            let position = format!(" at line {} column {}", error.line(), error.column());
+            let mut context = format!(
+                "Unable to parse {} at line {}, column {}",
+                document_description,
+                error.line(),
+                error.column()
+            );
+            let operation_index = failing_operation_index(document_bytes);
+            if let Some(operation_index) = operation_index {
+                context.push_str(&format!(" (operation {})", operation_index));
+            }
+            ContextError {
+                context,
+                source_error: Some(
+                    message
+                        .strip_suffix(&position)
+                        .unwrap_or(&message)
+                        .to_string(),
+                ),
+                kind: ErrorKind::InvalidDocument,
+                operation_index,
+            }
+        })
     }
 
     /// Converts the given `Document` into a PDF document (`PdfDocument`). This is done by first loading all the
@@ -98,100 +265,101 @@ impl Document {
     /// then by iterating over the operations present in the document in order to map them to the associated
     /// operation in a PDF document. This is a high-level function that hides the low-level requirements
     /// and procedures needed for constructing a PDF document by calling the functions defined for `PdfDocument`.
+    /// Only the fonts with which some text is written end up embedded into the PDF document.
     pub fn to_pdf_document(&self) -> Result<PdfDocument, ContextError> {
-        // Create a PDF document with the identifier of the document
-        let mut pdf_document = PdfDocument::new(self.document_id.clone());
+        // Load the built-in fonts present in the `fonts` directory of the CMU family, including the math font
+        self.to_pdf_document_with_fonts(&FontRegistry::builtin()?)
+    }
 
-        // Load the built-in fonts present in the `fonts` directory of the CMU family
-        let fonts_directory = std::fs::read_dir("fonts/computer-modern")
-            .map_err(|error| {
-                ContextError::with_error("Failed to read the fonts directory", &error)
-            })?
-            .collect::<Vec<_>>();
-
-        let mut font_paths = fonts_directory
-            .iter()
-            .map(|font_path| {
-                font_path.as_ref().map_err(|error| {
-                    ContextError::with_error(
-                        format!("Failed to read the font file {:?}", font_path),
-                        &error,
-                    )
-                })
-            })
-            .collect::<Result<Vec<_>, ContextError>>()?
-            .into_iter()
-            .filter(|font_path| font_path.path().extension() == Some("ttf".as_ref()))
-            .map(|font_path| font_path.path())
-            .collect::<Vec<_>>(); // Need to collect it because of a borrowing requirements
-                                  // Sort the font paths in order to load them in the correct order
-        font_paths.sort();
-        // Load the math font as well
-        let math_font_path = "fonts/lm-math/opentype/latinmodern-math.otf";
-        font_paths.push(PathBuf::from_str(math_font_path).map_err(|error| {
-            ContextError::with_error(
-                format!("Failed to read the font file {:?}", math_font_path),
-                &error,
-            )
-        })?);
+    /// Converts the given `Document` into a PDF document (`PdfDocument`) just like `to_pdf_document` does, but
+    /// employing the fonts of the given registry instead of reading the font files again. This is useful when
+    /// converting many documents, possibly from multiple threads, since the registry can be shared among them.
+    ///
+    /// # Arguments
+    ///
+    /// * `font_registry` - The fonts to be loaded into the document, in the order given by their font index.
+    pub fn to_pdf_document_with_fonts(
+        &self,
+        font_registry: &FontRegistry,
+    ) -> Result<PdfDocument, ContextError> {
+        self.to_pdf_document_with_timings(font_registry, &mut Timings::default())
+    }
 
-        // Add the fonts to the document one after the other
-        for font_path in font_paths {
-            let _font_index = pdf_document.add_font(&font_path).unwrap();
-        }
+    /// Converts the given `Document` into a PDF document (`PdfDocument`) just like `to_pdf_document_with_fonts`
+    /// does, while recording the duration of the font loading, drawing and writing stages into the given timings.
+    ///
+    /// # Arguments
+    ///
+    /// * `font_registry` - The fonts to be loaded into the document, in the order given by their font index.
+    /// * `timings` - The timings to which the durations of the stages are added.
+    pub fn to_pdf_document_with_timings(
+        &self,
+        font_registry: &FontRegistry,
+        timings: &mut Timings,
+    ) -> Result<PdfDocument, ContextError> {
+        self.convert_to_pdf_document(font_registry, &CustomOperations::default(), timings)
+    }
 
-        // Currently the only states that this PDF-writing function is handling is the current index of the page and of the
-        // layer in the page, which are needed to write the text to the layer in the page
-        // Any user of this library would anyway still need to take care of the indices
-        let mut current_page_index = 0;
-        let mut current_layer_index_in_page = 0;
+    /// Converts the given `Document` into a PDF document (`PdfDocument`) just like `to_pdf_document_with_fonts`
+    /// does, while its custom operations are drawn by the ones registered with the same names (see
+    /// `CustomOperation`).
+    ///
+    /// # Arguments
+    ///
+    /// * `font_registry` - The fonts to be loaded into the document, in the order given by their font index.
+    /// * `custom_operations` - The custom operations which can be found in the document.
+    pub fn to_pdf_document_with_custom_operations(
+        &self,
+        font_registry: &FontRegistry,
+        custom_operations: &CustomOperations,
+    ) -> Result<PdfDocument, ContextError> {
+        self.convert_to_pdf_document(font_registry, custom_operations, &mut Timings::default())
+    }
+
+    /// Converts the given `Document` into a PDF document with the given fonts and custom operations, recording
+    /// the duration of each stage into the given timings.
+    fn convert_to_pdf_document(
+        &self,
+        font_registry: &FontRegistry,
+        custom_operations: &CustomOperations,
+        timings: &mut Timings,
+    ) -> Result<PdfDocument, ContextError> {
+        let _conversion_span =
+            tracing::info_span!("to_pdf_document", document_id = %self.document_id).entered();
+        let mut pdf_document =
+            timings.time(Stage::FontLoading, || self.new_pdf_document(font_registry))?;
 
-        // Iterate over the operations in the document in order to map them to the associated operation
-        // Note that the operations are iterated over in the order they are present in the document,
+        // Draw the operations of the document onto the PDF document in the order they are present in the document,
         // which is important for the correctness of the PDF document
         //
-        // Also, the mapping is one to one because the operations are mapped to the operations in the PDF document
-        // For instance, the `AppendNewPage` operation is mapped to the `add_page_with_layer` function of the `PdfDocument`
-        // struct and the operation `WriteUnicodeText` is mapped to the function `write_text_to_layer_in_page`
-        for operation in self.operations.iter() {
-            match operation {
-                Operation::WriteUnicodeText {
-                    color,
-                    position,
-                    text_string,
-                    font_size,
-                    font_index,
-                } => {
-                    pdf_document
-                        .write_text_to_layer_in_page(
-                            current_page_index,
-                            current_layer_index_in_page,
-                            *color,
-                            text_string.clone(),
-                            *font_index,
-                            *font_size,
-                            *position,
-                        )
-                        .unwrap();
-                }
-                Operation::AppendNewPage {
-                    page_width,
-                    page_height,
-                } => {
-                    let (page_index, layer_index_in_page) =
-                        pdf_document.add_page_with_layer(*page_width, *page_height);
-                    current_page_index = page_index;
-                    current_layer_index_in_page = layer_index_in_page;
-                }
-            }
-        }
+        // The mapping is one to one: the page breaks are mapped to the `add_page_with_layer` function of the `PdfDocument`
+        // struct, while the text runs are mapped to the function `write_text_to_layer_in_page`
+        timings.time(Stage::Drawing, || {
+            self.draw_with_custom_operations(
+                &mut PdfCanvas::new(&mut pdf_document),
+                custom_operations,
+            )
+        })?;
 
         // Write all the PDF document, then return it
-        pdf_document.write_all(self.instance_id.clone())?;
+        timings.time(Stage::Writing, || {
+            pdf_document.write_all(self.instance_id.clone())
+        })?;
 
         Ok(pdf_document)
     }
 
+    /// Creates a new `Document` from the given path just like `from_path` does, but reading and parsing the JSON
+    /// document on the thread pool of `rayon`, so that it can be awaited from any asynchronous runtime (see
+    /// `BlockingTask`).
+    ///
+    /// # Arguments
+    ///
+    /// * `document_path` - The path to the JSON document.
+    pub async fn from_path_async(document_path: PathBuf) -> Result<Self, ContextError> {
+        BlockingTask::spawn(move || Self::from_path(&document_path)).await
+    }
+
     /// This is a commodity function that saves the document as a PDF file. This is done by first converting
     /// the document to the `PdfDocument` format and then by saving the PDF document as bytes, which can be
     /// written to any file. Clearly this function requests the file system to create a file at the given path,
@@ -203,18 +371,197 @@ impl Document {
     ///
     /// Note that all documents tend to be heavy so they need to be processed by `ps2pdf` to be optimized further.
     pub fn save_to_pdf_file(&self, path: &Path) -> Result<(), ContextError> {
-        let mut pdf_document = self.to_pdf_document()?;
+        self.save_to_pdf_file_with_fonts(path, &FontRegistry::builtin()?)
+    }
+
+    /// Saves the document as a PDF file just like `save_to_pdf_file` does, but employing the fonts of the given
+    /// registry (see `to_pdf_document_with_fonts`).
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the output PDF file.
+    /// * `font_registry` - The fonts to be loaded into the document, in the order given by their font index.
+    pub fn save_to_pdf_file_with_fonts(
+        &self,
+        path: &Path,
+        font_registry: &FontRegistry,
+    ) -> Result<(), ContextError> {
+        self.save_to_pdf_file_with_timings(path, font_registry, &mut Timings::default())?;
+
+        Ok(())
+    }
+
+    /// Saves the document as a PDF file just like `save_to_pdf_file_with_fonts` does, but converting and writing it
+    /// on the thread pool of `rayon`, so that it can be awaited from any asynchronous runtime (see `BlockingTask`).
+    /// The document is copied for the thread, while the fonts of the registry are shared with it. The warnings raised
+    /// during the conversion are returned (see `PdfDocument::warnings`).
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the output PDF file.
+    /// * `font_registry` - The fonts to be loaded into the document, in the order given by their font index.
+    pub async fn save_to_pdf_file_async(
+        &self,
+        path: PathBuf,
+        font_registry: &FontRegistry,
+    ) -> Result<Vec<Warning>, ContextError> {
+        let document = self.clone();
+        let font_registry = font_registry.clone();
+        BlockingTask::spawn(move || {
+            document.save_to_pdf_file_with_timings(&path, &font_registry, &mut Timings::default())
+        })
+        .await
+    }
+
+    /// Saves the document as a PDF file just like `save_to_pdf_file_with_fonts` does, while recording the duration
+    /// of each stage of the conversion into the given timings (see `to_pdf_document_with_timings`). The warnings
+    /// raised during the conversion are returned (see `PdfDocument::warnings`).
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the output PDF file.
+    /// * `font_registry` - The fonts to be loaded into the document, in the order given by their font index.
+    /// * `timings` - The timings to which the durations of the stages are added.
+    pub fn save_to_pdf_file_with_timings(
+        &self,
+        path: &Path,
+        font_registry: &FontRegistry,
+        timings: &mut Timings,
+    ) -> Result<Vec<Warning>, ContextError> {
+        let mut pdf_document = self.to_pdf_document_with_timings(font_registry, timings)?;
+        let saving_started_at = Instant::now();
+        let _saving_span = tracing::info_span!("stage", stage = %Stage::Saving, ?path).entered();
         pdf_document.optimize();
         let pdf_document_bytes = pdf_document.save_to_bytes()?;
 
         let mut pdf_file = std::fs::File::create(path).map_err(|error| {
             ContextError::with_error("Failed to create the output file", &error)
+                .with_kind(ErrorKind::Io)
         })?;
-        pdf_file
-            .write_all(&pdf_document_bytes)
-            .map_err(|error| ContextError::with_error("Failed to save the output file", &error))
-            .unwrap();
+        pdf_file.write_all(&pdf_document_bytes).map_err(|error| {
+            ContextError::with_error("Failed to save the output file", &error)
+                .with_kind(ErrorKind::Io)
+        })?;
+        timings.record(Stage::Saving, saving_started_at.elapsed());
 
-        Ok(())
+        Ok(pdf_document.warnings().to_vec())
+    }
+
+    /// Converts the document into a PDF document just like `to_pdf_document_with_fonts` does, but writes it onto
+    /// the given output page by page rather than holding it in memory as a whole (see `PdfStreamWriter`), which
+    /// suits documents with thousands of pages. Only the streams of the PDF document are compressed, while the
+    /// other optimizations are left out (see `PdfStreamWriter`). The output is returned
+    /// together with the warnings raised during the conversion.
+    ///
+    /// # Arguments
+    ///
+    /// * `font_registry` - The fonts to be loaded into the document, in the order given by their font index.
+    /// * `output` - The output onto which the PDF document is written, which should be buffered.
+    pub fn stream_to_pdf<W: Write>(
+        &self,
+        font_registry: &FontRegistry,
+        output: W,
+    ) -> Result<(W, Vec<Warning>), ContextError> {
+        let _conversion_span =
+            tracing::info_span!("stream_to_pdf", document_id = %self.document_id).entered();
+        let mut pdf_stream_writer =
+            PdfStreamWriter::new(self.new_pdf_document(font_registry)?, output)?;
+        self.draw(&mut pdf_stream_writer)?;
+        let (output, pdf_document) = pdf_stream_writer.finish(self.instance_id.clone())?;
+
+        Ok((output, pdf_document.warnings().to_vec()))
     }
+
+    /// Creates an empty PDF document with the identifier of the document, to which the fonts of the given
+    /// registry are added one after the other.
+    pub(crate) fn new_pdf_document(
+        &self,
+        font_registry: &FontRegistry,
+    ) -> Result<PdfDocument, ContextError> {
+        let mut pdf_document = PdfDocument::new(self.document_id.clone());
+        pdf_document.strict = self.strict;
+        pdf_document.title = self.title.clone();
+        pdf_document.author = self.author.clone();
+        pdf_document.deterministic = self.deterministic;
+        pdf_document.timestamp = self
+            .timestamp
+            .map(OffsetDateTime::from_unix_timestamp)
+            .transpose()
+            .map_err(|error| {
+                ContextError::with_error("Invalid timestamp of the document", &error)
+                    .with_kind(ErrorKind::InvalidDocument)
+            })?;
+        for font_bytes in font_registry.font_bytes() {
+            let _font_index = pdf_document.add_font_from_bytes(font_bytes.clone())?;
+        }
+
+        Ok(pdf_document)
+    }
+}
+
+/// Checks whether the given value is zero, so that the optional properties of the operations are only
+/// serialized when they have been set.
+fn is_zero(value: &f32) -> bool {
+    *value == 0.0
+}
+
+/// Checks whether the given flag is not set, so that the optional flags of the document are only serialized
+/// when they have been set.
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+/// Collects the paths of the built-in fonts in the order in which they are loaded into a PDF document, which
+/// determines the font index of each of them: first the fonts of the CMU family present in the `fonts` directory,
+/// sorted by their file name, and then the math font.
+pub fn builtin_font_paths() -> Result<Vec<PathBuf>, ContextError> {
+    let fonts_directory = std::fs::read_dir("fonts/computer-modern")
+        .map_err(|error| {
+            ContextError::with_error("Failed to read the fonts directory", &error)
+                .with_kind(ErrorKind::FontNotFound)
+        })?
+        .collect::<Vec<_>>();
+
+    let mut font_paths = fonts_directory
+        .iter()
+        .map(|font_path| {
+            font_path.as_ref().map_err(|error| {
+                ContextError::with_error(
+                    format!("Failed to read the font file {:?}", font_path),
+                    &error,
+                )
+                .with_kind(ErrorKind::FontNotFound)
+            })
+        })
+        .collect::<Result<Vec<_>, ContextError>>()?
+        .into_iter()
+        .filter(|font_path| font_path.path().extension() == Some("ttf".as_ref()))
+        .map(|font_path| font_path.path())
+        .collect::<Vec<_>>(); // Need to collect it because of a borrowing requirements
+
+    // Sort the font paths in order to load them in the correct order
+    font_paths.sort();
+    // Load the math font as well
+    let math_font_path = "fonts/lm-math/opentype/latinmodern-math.otf";
+    font_paths.push(PathBuf::from_str(math_font_path).map_err(|error| {
+        ContextError::with_error(
+            format!("Failed to read the font file {:?}", math_font_path),
+            &error,
+        )
+        .with_kind(ErrorKind::FontNotFound)
+    })?);
+
+    Ok(font_paths)
+}
+
+/// Looks for the first operation of the given JSON document which can't be parsed, returning its index.
+/// Since the operations are untagged, the error reported for the whole document doesn't tell which of them is at
+/// fault, so each of them is parsed on its own. Nothing is returned if the JSON syntax itself is invalid.
+pub fn failing_operation_index(document_content: &[u8]) -> Option<usize> {
+    let document_value = serde_json::from_slice::<serde_json::Value>(document_content).ok()?;
+    document_value
+        .get("operations")?
+        .as_array()?
+        .iter()
+        .position(|operation| serde_json::from_value::<Operation>(operation.clone()).is_err())
 }
@@ -0,0 +1,317 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    document::{Document, Operation},
+    error::{ContextError, ErrorKind},
+};
+
+impl Document {
+    /// Inserts the given operation at the given index, shifting all the following operations after it.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index at which the operation is inserted, which can be the number of operations in order
+    ///   to append it.
+    /// * `operation` - The operation to be inserted, which is checked beforehand (see `Document::replace_operation`).
+    pub fn insert_operation(
+        &mut self,
+        index: usize,
+        operation: Operation,
+    ) -> Result<(), ContextError> {
+        if index > self.operations.len() {
+            return Err(index_out_of_range(index, self.operations.len()));
+        }
+        check_operation(index, &operation)?;
+
+        self.apply_edit(|operations| operations.insert(index, operation))
+    }
+
+    /// Removes the operation at the given index and returns it, shifting all the following operations before it.
+    /// The first page can't be removed if it would leave some content before any page.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the operation to be removed.
+    pub fn remove_operation(&mut self, index: usize) -> Result<Operation, ContextError> {
+        if index >= self.operations.len() {
+            return Err(index_out_of_range(index, self.operations.len()));
+        }
+
+        let mut removed_operation = None;
+        self.apply_edit(|operations| removed_operation = Some(operations.remove(index)))?;
+
+        Ok(removed_operation.expect("The operation has been removed"))
+    }
+
+    /// Replaces the operation at the given index with the given one and returns the replaced operation. The
+    /// operation is rejected if it appends a page whose width or height is not positive, if it writes text or paints
+    /// the page with a color component outside of the range from 0 to 1, or if it would leave some content before
+    /// any page.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the operation to be replaced.
+    /// * `operation` - The operation which takes its place.
+    pub fn replace_operation(
+        &mut self,
+        index: usize,
+        operation: Operation,
+    ) -> Result<Operation, ContextError> {
+        if index >= self.operations.len() {
+            return Err(index_out_of_range(index, self.operations.len()));
+        }
+        check_operation(index, &operation)?;
+
+        let mut replaced_operation = None;
+        self.apply_edit(|operations| {
+            replaced_operation = Some(std::mem::replace(&mut operations[index], operation))
+        })?;
+
+        Ok(replaced_operation.expect("The operation has been replaced"))
+    }
+
+    /// Moves the operation at the given index so that it ends up at the other index, shifting the operations
+    /// in between. The operation is not moved if it would leave some content before any page.
+    ///
+    /// # Arguments
+    ///
+    /// * `from_index` - The index of the operation to be moved.
+    /// * `to_index` - The index of the operation once it has been moved.
+    pub fn move_operation(
+        &mut self,
+        from_index: usize,
+        to_index: usize,
+    ) -> Result<(), ContextError> {
+        for index in [from_index, to_index] {
+            if index >= self.operations.len() {
+                return Err(index_out_of_range(index, self.operations.len()));
+            }
+        }
+
+        self.apply_edit(|operations| {
+            let operation = operations.remove(from_index);
+            operations.insert(to_index, operation);
+        })
+    }
+
+    /// Applies the given edit to the operations of the document, and reverts it if it leaves some content before
+    /// any page while the document had none. Documents which already had such content can still be edited.
+    fn apply_edit(&mut self, edit: impl FnOnce(&mut Vec<Operation>)) -> Result<(), ContextError> {
+        let had_content_before_first_page = content_before_first_page(&self.operations).is_some();
+        let original_operations = if had_content_before_first_page {
+            None
+        } else {
+            Some(self.operations.clone())
+        };
+
+        edit(&mut self.operations);
+
+        if let Some(original_operations) = original_operations {
+            if let Some(operation_index) = content_before_first_page(&self.operations) {
+                self.operations = original_operations;
+                return Err(ContextError::with_context(
+                    "The edit would leave some content before the first page of the document",
+                )
+                .with_kind(ErrorKind::PageIndexOutOfRange)
+                .with_operation_index(operation_index));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The stable identifier of an operation of a `DocumentEditor`, which keeps referring to the same operation
+/// while the other operations are inserted, removed or moved around it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct OperationId(u64);
+
+impl std::fmt::Display for OperationId {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(formatter, "#{}", self.0)
+    }
+}
+
+/// A `Document` whose operations are edited through their `OperationId`s rather than through their indices,
+/// as needed by graphical editors, whose selections must survive the edits made elsewhere in the document.
+/// The edits are checked just like the ones made via `Document::insert_operation` and the similar methods.
+#[derive(Debug, Clone)]
+pub struct DocumentEditor {
+    /// The document being edited.
+    document: Document,
+    /// The identifiers of the operations of the document, in the same order.
+    operation_ids: Vec<OperationId>,
+    /// The identifier which is assigned to the next inserted operation.
+    next_operation_id: u64,
+}
+
+impl DocumentEditor {
+    /// Starts editing the given document, assigning an identifier to each of its operations.
+    pub fn new(document: Document) -> Self {
+        let operation_count = document.operations.len() as u64;
+
+        DocumentEditor {
+            document,
+            operation_ids: (0..operation_count).map(OperationId).collect(),
+            next_operation_id: operation_count,
+        }
+    }
+
+    /// Returns the document in its current state.
+    pub fn document(&self) -> &Document {
+        &self.document
+    }
+
+    /// Stops editing the document and returns it.
+    pub fn into_document(self) -> Document {
+        self.document
+    }
+
+    /// Returns the identifiers of the operations of the document, in the same order as the operations.
+    pub fn operation_ids(&self) -> &[OperationId] {
+        &self.operation_ids
+    }
+
+    /// Returns the current index of the operation with the given identifier, if it is still in the document.
+    pub fn index_of(&self, operation_id: OperationId) -> Option<usize> {
+        self.operation_ids
+            .iter()
+            .position(|other_operation_id| *other_operation_id == operation_id)
+    }
+
+    /// Returns the operation with the given identifier, if it is still in the document.
+    pub fn operation(&self, operation_id: OperationId) -> Option<&Operation> {
+        self.index_of(operation_id)
+            .map(|index| &self.document.operations[index])
+    }
+
+    /// Inserts the given operation at the given index and returns its newly assigned identifier.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index at which the operation is inserted (see `Document::insert_operation`).
+    /// * `operation` - The operation to be inserted.
+    pub fn insert_operation(
+        &mut self,
+        index: usize,
+        operation: Operation,
+    ) -> Result<OperationId, ContextError> {
+        self.document.insert_operation(index, operation)?;
+        let operation_id = OperationId(self.next_operation_id);
+        self.next_operation_id += 1;
+        self.operation_ids.insert(index, operation_id);
+
+        Ok(operation_id)
+    }
+
+    /// Removes the operation with the given identifier and returns it.
+    pub fn remove_operation(
+        &mut self,
+        operation_id: OperationId,
+    ) -> Result<Operation, ContextError> {
+        let index = self.existing_index_of(operation_id)?;
+        let operation = self.document.remove_operation(index)?;
+        self.operation_ids.remove(index);
+
+        Ok(operation)
+    }
+
+    /// Replaces the operation with the given identifier and returns the replaced operation. The identifier
+    /// keeps referring to the new operation.
+    pub fn replace_operation(
+        &mut self,
+        operation_id: OperationId,
+        operation: Operation,
+    ) -> Result<Operation, ContextError> {
+        let index = self.existing_index_of(operation_id)?;
+
+        self.document.replace_operation(index, operation)
+    }
+
+    /// Moves the operation with the given identifier so that it ends up at the given index.
+    pub fn move_operation(
+        &mut self,
+        operation_id: OperationId,
+        to_index: usize,
+    ) -> Result<(), ContextError> {
+        let from_index = self.existing_index_of(operation_id)?;
+        self.document.move_operation(from_index, to_index)?;
+        let operation_id = self.operation_ids.remove(from_index);
+        self.operation_ids.insert(to_index, operation_id);
+
+        Ok(())
+    }
+
+    /// Returns the current index of the operation with the given identifier, or an error if it has been removed.
+    fn existing_index_of(&self, operation_id: OperationId) -> Result<usize, ContextError> {
+        self.index_of(operation_id).ok_or_else(|| {
+            ContextError::with_context(format!(
+                "The operation {} is not part of the document",
+                operation_id
+            ))
+            .with_kind(ErrorKind::InvalidDocument)
+        })
+    }
+}
+
+/// Creates the error returned when an edit refers to an operation index which doesn't exist.
+fn index_out_of_range(index: usize, operation_count: usize) -> ContextError {
+    ContextError::with_context(format!(
+        "The operation index {} is out of range, the document has {} operations",
+        index, operation_count
+    ))
+    .with_kind(ErrorKind::InvalidDocument)
+}
+
+/// Checks that the given operation can be converted on its own, namely that its pages have a positive size
+/// and that its text and page background have a valid color.
+fn check_operation(index: usize, operation: &Operation) -> Result<(), ContextError> {
+    match operation {
+        Operation::AppendNewPage {
+            page_width,
+            page_height,
+        } if *page_width <= 0.0 || *page_height <= 0.0 => Err(ContextError::with_context(format!(
+            "The page has a width of {} mm and a height of {} mm, which must both be positive",
+            page_width, page_height
+        ))
+        .with_kind(ErrorKind::InvalidDocument)
+        .with_operation_index(index)),
+        Operation::WriteUnicodeText { color, .. }
+            if color
+                .iter()
+                .any(|component| !(0.0..=1.0).contains(component)) =>
+        {
+            Err(ContextError::with_context(format!(
+                "The text has the color {:?}, whose components must be in the range from 0 to 1",
+                color
+            ))
+            .with_kind(ErrorKind::InvalidDocument)
+            .with_operation_index(index))
+        }
+        Operation::SetPageBackground { color }
+            if color
+                .iter()
+                .any(|component| !(0.0..=1.0).contains(component)) =>
+        {
+            Err(ContextError::with_context(format!(
+                "The page background has the color {:?}, whose components must be in the range from 0 to 1",
+                color
+            ))
+            .with_kind(ErrorKind::InvalidDocument)
+            .with_operation_index(index))
+        }
+        Operation::LayoutContainer { children, .. } => children
+            .iter()
+            .try_for_each(|child| check_operation(index, child)),
+        _ => Ok(()),
+    }
+}
+
+/// Returns the index of the first operation if it comes before any page, that is if it is not a page itself.
+fn content_before_first_page(operations: &[Operation]) -> Option<usize> {
+    match operations.first() {
+        Some(Operation::AppendNewPage { .. }) | None => None,
+        Some(_) => Some(0),
+    }
+}
@@ -2,6 +2,59 @@
 
 use serde::{Deserialize, Serialize};
 
+/// The category of a `ContextError`, which allows the callers to tell the errors apart programmatically,
+/// for instance a missing font from a page index out of range, without inspecting the context.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum ErrorKind {
+    /// A font file, or the directory containing it, can't be read.
+    FontNotFound,
+    /// A font file has been read but can't be parsed.
+    InvalidFont,
+    /// A font index doesn't refer to any of the fonts loaded into the document.
+    FontIndexOutOfRange,
+    /// A page or a layer index doesn't exist, for instance because text is written before any page.
+    PageIndexOutOfRange,
+    /// A document can't be parsed, or its operations are inconsistent.
+    InvalidDocument,
+    /// A container can't be laid out.
+    InvalidLayout,
+    /// A file, other than a font, can't be read or written.
+    Io,
+    /// A PDF document can't be encoded or decoded.
+    PdfEncoding,
+    /// An external tool, such as `gs` or `ps2pdf`, can't be run or has failed.
+    ExternalTool,
+    /// A character is missing from its font, or a glyph has no width, while converting a document in strict mode.
+    MissingGlyph,
+    /// An image can't be decoded, or its format isn't supported.
+    InvalidImage,
+    /// Any other error.
+    #[default]
+    Other,
+}
+
+impl ErrorKind {
+    /// Returns the code of the kind of error, which is stable across the versions of this library
+    /// so that it can be relied upon by scripts and other tools.
+    pub fn code(self) -> &'static str {
+        match self {
+            ErrorKind::FontNotFound => "E001",
+            ErrorKind::InvalidFont => "E002",
+            ErrorKind::FontIndexOutOfRange => "E003",
+            ErrorKind::PageIndexOutOfRange => "E004",
+            ErrorKind::InvalidDocument => "E005",
+            ErrorKind::InvalidLayout => "E006",
+            ErrorKind::Io => "E007",
+            ErrorKind::PdfEncoding => "E008",
+            ErrorKind::ExternalTool => "E009",
+            ErrorKind::MissingGlyph => "E010",
+            ErrorKind::InvalidImage => "E011",
+            ErrorKind::Other => "E000",
+        }
+    }
+}
+
 /// A struct that represents an error with a context and possibly the propagated source error.
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ContextError {
@@ -9,6 +62,12 @@ pub struct ContextError {
     pub context: String,
     /// The propagated source error.
     pub source_error: Option<String>,
+    /// The category of the error, which is `ErrorKind::Other` unless it has been set via `with_kind`.
+    #[serde(default)]
+    pub kind: ErrorKind,
+    /// The index of the operation of the `Document` at fault, if the error can be traced back to one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operation_index: Option<usize>,
 }
 
 impl std::fmt::Display for ContextError {
@@ -35,6 +94,8 @@ impl ContextError {
         ContextError {
             context: context.into(),
             source_error: None,
+            kind: ErrorKind::Other,
+            operation_index: None,
         }
     }
 
@@ -43,8 +104,22 @@ impl ContextError {
         ContextError {
             context: context.into(),
             source_error: Some(error.to_string()),
+            kind: ErrorKind::Other,
+            operation_index: None,
         }
     }
+
+    /// Sets the category of the error, for instance `ContextError::with_context(...).with_kind(ErrorKind::Io)`.
+    pub fn with_kind(mut self, kind: ErrorKind) -> ContextError {
+        self.kind = kind;
+        self
+    }
+
+    /// Sets the index of the operation of the `Document` at fault, so that it can be located in the JSON document.
+    pub fn with_operation_index(mut self, operation_index: usize) -> ContextError {
+        self.operation_index = Some(operation_index);
+        self
+    }
 }
 
 /// Minimizes the first letter of a string. It is used for standardizing the error message in the `ContextError` struct.
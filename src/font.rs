@@ -0,0 +1,378 @@
+use owned_ttf_parser::{name_id, AsFaceRef as _, Face, OwnedFace};
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use crate::{
+    blocking::BlockingTask,
+    document::builtin_font_paths,
+    error::{ContextError, ErrorKind},
+};
+
+/// The content of a set of font files, read once so that it can be shared by the conversion of many
+/// documents (see `Document::to_pdf_document_with_fonts`). The font index of each font is its position
+/// in the registry, just like it is for the fonts loaded by `Document::to_pdf_document`.
+#[derive(Debug, Clone)]
+pub struct FontRegistry {
+    /// The paths from which the fonts have been read.
+    font_paths: Vec<PathBuf>,
+    /// The content of each font file, shared with the documents the fonts are added to.
+    font_bytes: Vec<Arc<[u8]>>,
+}
+
+impl FontRegistry {
+    /// Reads the built-in fonts in the same order in which they are loaded by `Document::to_pdf_document`.
+    pub fn builtin() -> Result<Self, ContextError> {
+        Self::from_paths(builtin_font_paths()?)
+    }
+
+    /// Reads the TTF/OTF font files at the given paths, whose order determines the font indices.
+    pub fn from_paths(font_paths: Vec<PathBuf>) -> Result<Self, ContextError> {
+        let font_bytes = font_paths
+            .iter()
+            .map(|font_path| {
+                std::fs::read(font_path).map(Arc::from).map_err(|error| {
+                    ContextError::with_error(
+                        format!("Failed to read the font file {:?}", font_path),
+                        &error,
+                    )
+                    .with_kind(ErrorKind::FontNotFound)
+                })
+            })
+            .collect::<Result<Vec<_>, ContextError>>()?;
+
+        Ok(FontRegistry {
+            font_paths,
+            font_bytes,
+        })
+    }
+
+    /// Reads the built-in fonts just like `builtin` does, but on the thread pool of `rayon`, so that it can be
+    /// awaited from any asynchronous runtime (see `BlockingTask`).
+    pub async fn builtin_async() -> Result<Self, ContextError> {
+        BlockingTask::spawn(Self::builtin).await
+    }
+
+    /// Reads the TTF/OTF font files at the given paths just like `from_paths` does, but on the thread pool of
+    /// `rayon`, so that it can be awaited from any asynchronous runtime (see `BlockingTask`).
+    ///
+    /// # Arguments
+    ///
+    /// * `font_paths` - The paths to the font files, whose order determines the font indices.
+    pub async fn from_paths_async(font_paths: Vec<PathBuf>) -> Result<Self, ContextError> {
+        BlockingTask::spawn(move || Self::from_paths(font_paths)).await
+    }
+
+    /// Returns the paths from which the fonts have been read, in the order given by their font index.
+    pub fn font_paths(&self) -> &[PathBuf] {
+        &self.font_paths
+    }
+
+    /// Returns the content of the font files, in the order given by their font index.
+    pub fn font_bytes(&self) -> &[Arc<[u8]>] {
+        &self.font_bytes
+    }
+
+    /// Looks for the font with the given name, returning its font index. The name is the family name of the font
+    /// followed by its style name, such as `cmu-serif-bold-italic`, or only the family name, such as `cmu-serif`,
+    /// for the regular style of the family. The names are compared in kebab-case, so `CMU Serif` is accepted as well.
+    pub fn font_index(&self, font_name: &str) -> Result<usize, ContextError> {
+        let font_name = kebab_case(font_name);
+        let font_names = self
+            .font_bytes
+            .iter()
+            .map(|font_bytes| {
+                let face = Face::parse(font_bytes, 0).map_err(|error| {
+                    ContextError::with_error("Failed to parse font", &error)
+                        .with_kind(ErrorKind::InvalidFont)
+                })?;
+                Ok((
+                    kebab_case(&face_name(&face, FAMILY_NAME_IDS).unwrap_or_default()),
+                    kebab_case(&face_name(&face, STYLE_NAME_IDS).unwrap_or_default()),
+                ))
+            })
+            .collect::<Result<Vec<_>, ContextError>>()?;
+
+        font_names
+            .iter()
+            .position(|(family_name, style_name)| {
+                format!("{}-{}", family_name, style_name) == font_name
+            })
+            .or_else(|| {
+                // A family name alone refers to the first regular style found among the fonts of the family
+                REGULAR_STYLE_NAMES.iter().find_map(|regular_style_name| {
+                    font_names.iter().position(|(family_name, style_name)| {
+                        *family_name == font_name && style_name == regular_style_name
+                    })
+                })
+            })
+            .ok_or_else(|| {
+                ContextError::with_context(format!(
+                    "The font {:?} is not among the fonts of the registry",
+                    font_name
+                ))
+                .with_kind(ErrorKind::FontNotFound)
+            })
+    }
+}
+
+/// The IDs of the names of a font which hold its family name, with the typographic one preferred since it groups
+/// all the styles of a family under the same name.
+const FAMILY_NAME_IDS: [u16; 2] = [name_id::TYPOGRAPHIC_FAMILY, name_id::FAMILY];
+
+/// The IDs of the names of a font which hold its style name, with the typographic one preferred.
+const STYLE_NAME_IDS: [u16; 2] = [name_id::TYPOGRAPHIC_SUBFAMILY, name_id::SUBFAMILY];
+
+/// The style names, in kebab-case, which are looked for in order when a font is referred to by its family name alone.
+const REGULAR_STYLE_NAMES: &[&str] = &["regular", "roman", "book", "medium"];
+
+/// Reads the first Unicode name of the font with any of the given IDs, in order of preference.
+fn face_name(face: &Face, name_ids: [u16; 2]) -> Option<String> {
+    name_ids.into_iter().find_map(|name_id| {
+        face.names()
+            .into_iter()
+            .filter(|name| name.name_id == name_id && name.is_unicode())
+            .find_map(|name| name.to_string())
+    })
+}
+
+/// Converts the name of a font into kebab-case, so that `CMU Serif` and `BoldItalic` become `cmu-serif` and
+/// `bold-italic`.
+fn kebab_case(name: &str) -> String {
+    let mut kebab_case_name = String::with_capacity(name.len() + 4);
+    let mut previous_character: Option<char> = None;
+    for character in name.chars() {
+        if character.is_whitespace() || character == '_' || character == '-' {
+            if !kebab_case_name.is_empty() && !kebab_case_name.ends_with('-') {
+                kebab_case_name.push('-');
+            }
+        } else {
+            if character.is_uppercase()
+                && previous_character.is_some_and(char::is_lowercase)
+                && !kebab_case_name.ends_with('-')
+            {
+                kebab_case_name.push('-');
+            }
+            kebab_case_name.extend(character.to_lowercase());
+        }
+        previous_character = Some(character);
+    }
+    kebab_case_name.trim_end_matches('-').to_string()
+}
+
+/// The Unicode blocks for which the coverage of a font is reported by `FontInfo`, each with its first and last
+/// codepoint. Only the blocks which are commonly found in text fonts are listed, the characters outside of them
+/// are counted together.
+const UNICODE_BLOCKS: &[(&str, u32, u32)] = &[
+    ("Basic Latin", 0x0000, 0x007F),
+    ("Latin-1 Supplement", 0x0080, 0x00FF),
+    ("Latin Extended-A", 0x0100, 0x017F),
+    ("Latin Extended-B", 0x0180, 0x024F),
+    ("IPA Extensions", 0x0250, 0x02AF),
+    ("Spacing Modifier Letters", 0x02B0, 0x02FF),
+    ("Combining Diacritical Marks", 0x0300, 0x036F),
+    ("Greek and Coptic", 0x0370, 0x03FF),
+    ("Cyrillic", 0x0400, 0x04FF),
+    ("Cyrillic Supplement", 0x0500, 0x052F),
+    ("Armenian", 0x0530, 0x058F),
+    ("Hebrew", 0x0590, 0x05FF),
+    ("Arabic", 0x0600, 0x06FF),
+    ("Devanagari", 0x0900, 0x097F),
+    ("Thai", 0x0E00, 0x0E7F),
+    ("Georgian", 0x10A0, 0x10FF),
+    ("Phonetic Extensions", 0x1D00, 0x1D7F),
+    ("Latin Extended Additional", 0x1E00, 0x1EFF),
+    ("Greek Extended", 0x1F00, 0x1FFF),
+    ("General Punctuation", 0x2000, 0x206F),
+    ("Superscripts and Subscripts", 0x2070, 0x209F),
+    ("Currency Symbols", 0x20A0, 0x20CF),
+    ("Combining Diacritical Marks for Symbols", 0x20D0, 0x20FF),
+    ("Letterlike Symbols", 0x2100, 0x214F),
+    ("Number Forms", 0x2150, 0x218F),
+    ("Arrows", 0x2190, 0x21FF),
+    ("Mathematical Operators", 0x2200, 0x22FF),
+    ("Miscellaneous Technical", 0x2300, 0x23FF),
+    ("Box Drawing", 0x2500, 0x257F),
+    ("Block Elements", 0x2580, 0x259F),
+    ("Geometric Shapes", 0x25A0, 0x25FF),
+    ("Miscellaneous Symbols", 0x2600, 0x26FF),
+    ("Dingbats", 0x2700, 0x27BF),
+    ("Miscellaneous Mathematical Symbols-A", 0x27C0, 0x27EF),
+    ("Supplemental Arrows-A", 0x27F0, 0x27FF),
+    ("Supplemental Arrows-B", 0x2900, 0x297F),
+    ("Miscellaneous Mathematical Symbols-B", 0x2980, 0x29FF),
+    ("Supplemental Mathematical Operators", 0x2A00, 0x2AFF),
+    ("Miscellaneous Symbols and Arrows", 0x2B00, 0x2BFF),
+    ("Latin Extended-C", 0x2C60, 0x2C7F),
+    ("Latin Extended-D", 0xA720, 0xA7FF),
+    ("CJK Unified Ideographs", 0x4E00, 0x9FFF),
+    ("Hangul Syllables", 0xAC00, 0xD7AF),
+    ("Private Use Area", 0xE000, 0xF8FF),
+    ("Alphabetic Presentation Forms", 0xFB00, 0xFB4F),
+    ("Mathematical Alphanumeric Symbols", 0x1D400, 0x1D7FF),
+    ("Emoticons", 0x1F600, 0x1F64F),
+];
+
+/// The number of characters of a Unicode block which are covered by a font, see `FontInfo`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnicodeBlockCoverage {
+    /// The name of the Unicode block, or `Other` for the characters outside of the known blocks.
+    pub name: &'static str,
+    /// The number of characters of the block which are mapped to a glyph of the font.
+    pub covered_characters_count: usize,
+    /// The number of codepoints of the block, unassigned ones included, which is zero for `Other`.
+    pub codepoints_count: usize,
+}
+
+/// A summary of the content of a font file, useful for understanding why some characters are missing from
+/// a document (see `LintIssue::MissingCharacters`) and which metrics are embedded into the PDF documents.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FontInfo {
+    /// The family name of the font, such as `CMU Serif`, if present in the font.
+    pub family_name: Option<String>,
+    /// The style name of the font, such as `Bold Italic`, if present in the font.
+    pub style_name: Option<String>,
+    /// The number of font units in an em, to which all the metrics of the font are relative.
+    pub units_per_em: u16,
+    /// The number of glyphs of the font.
+    pub glyph_count: u16,
+    /// The number of characters mapped to a glyph of the font.
+    pub character_count: usize,
+    /// The coverage of the Unicode blocks in which the font has at least one character.
+    pub unicode_blocks: Vec<UnicodeBlockCoverage>,
+    /// The ascent in font units, which is embedded as both the `Ascent` and the `CapHeight` of the font.
+    pub ascent: i16,
+    /// The descent in font units, which is embedded as the `Descent` of the font.
+    pub descent: i16,
+    /// The number of glyphs without an advance width, which are left out of the widths of the font.
+    pub glyphs_without_width_count: usize,
+}
+
+impl FontInfo {
+    /// Reads the TTF/OTF font file at the given path and summarizes its content.
+    pub fn from_path(font_path: &Path) -> Result<Self, ContextError> {
+        let font_bytes = std::fs::read(font_path).map_err(|error| {
+            ContextError::with_error(
+                format!("Failed to read the font file {:?}", font_path),
+                &error,
+            )
+            .with_kind(ErrorKind::FontNotFound)
+        })?;
+        Self::from_bytes(font_bytes)
+    }
+
+    /// Summarizes the content of the given TTF/OTF font file.
+    pub fn from_bytes(font_bytes: Vec<u8>) -> Result<Self, ContextError> {
+        let owned_face = OwnedFace::from_vec(font_bytes, 0).map_err(|error| {
+            ContextError::with_error("Failed to parse font", &error)
+                .with_kind(ErrorKind::InvalidFont)
+        })?;
+        let face = owned_face.as_face_ref();
+
+        // Collect the characters which are mapped to an actual glyph by any of the Unicode subtables
+        let mut characters = BTreeSet::new();
+        if let Some(cmap) = face.tables().cmap {
+            for subtable in cmap
+                .subtables
+                .into_iter()
+                .filter(|subtable| subtable.is_unicode())
+            {
+                subtable.codepoints(|codepoint| {
+                    if subtable
+                        .glyph_index(codepoint)
+                        .is_some_and(|glyph_id| glyph_id.0 > 0)
+                    {
+                        characters.insert(codepoint);
+                    }
+                });
+            }
+        }
+
+        let mut unicode_blocks = UNICODE_BLOCKS
+            .iter()
+            .map(
+                |(name, first_codepoint, last_codepoint)| UnicodeBlockCoverage {
+                    name,
+                    covered_characters_count: characters
+                        .range(first_codepoint..=last_codepoint)
+                        .count(),
+                    codepoints_count: (last_codepoint - first_codepoint + 1) as usize,
+                },
+            )
+            .filter(|unicode_block| unicode_block.covered_characters_count > 0)
+            .collect::<Vec<_>>();
+        let other_characters_count = characters.len()
+            - unicode_blocks
+                .iter()
+                .map(|unicode_block| unicode_block.covered_characters_count)
+                .sum::<usize>();
+        if other_characters_count > 0 {
+            unicode_blocks.push(UnicodeBlockCoverage {
+                name: "Other",
+                covered_characters_count: other_characters_count,
+                codepoints_count: 0,
+            });
+        }
+
+        Ok(FontInfo {
+            family_name: face_name(face, FAMILY_NAME_IDS),
+            style_name: face_name(face, STYLE_NAME_IDS),
+            units_per_em: face.units_per_em(),
+            glyph_count: face.number_of_glyphs(),
+            character_count: characters.len(),
+            unicode_blocks,
+            ascent: face.ascender(),
+            descent: face.descender(),
+            glyphs_without_width_count: (0..face.number_of_glyphs())
+                .filter(|glyph_id| {
+                    face.glyph_hor_advance(owned_ttf_parser::GlyphId(*glyph_id))
+                        .is_none()
+                })
+                .count(),
+        })
+    }
+}
+
+impl std::fmt::Display for FontInfo {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            formatter,
+            "Family: {}",
+            self.family_name.as_deref().unwrap_or("unknown")
+        )?;
+        writeln!(
+            formatter,
+            "Style: {}",
+            self.style_name.as_deref().unwrap_or("unknown")
+        )?;
+        writeln!(formatter, "Units per em: {}", self.units_per_em)?;
+        writeln!(
+            formatter,
+            "Glyphs: {} ({} without a width)",
+            self.glyph_count, self.glyphs_without_width_count
+        )?;
+        writeln!(formatter, "Characters: {}", self.character_count)?;
+        for unicode_block in &self.unicode_blocks {
+            match unicode_block.codepoints_count {
+                0 => writeln!(
+                    formatter,
+                    "  {}: {}",
+                    unicode_block.name, unicode_block.covered_characters_count
+                )?,
+                codepoints_count => writeln!(
+                    formatter,
+                    "  {}: {}/{}",
+                    unicode_block.name, unicode_block.covered_characters_count, codepoints_count
+                )?,
+            }
+        }
+        // The metrics are reported in the same units in which they are embedded into the PDF documents
+        write!(
+            formatter,
+            "Embedded metrics: ascent {}, descent {}, cap height {}, widths scaled by 1000/{}",
+            self.ascent, self.descent, self.ascent, self.units_per_em
+        )
+    }
+}
@@ -0,0 +1,89 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::{container::TextMeasurer, document::Operation, error::ContextError};
+
+/// The header repeated at the top of every page of a `Document` which has a title, with the title on the left and
+/// the date, if any, on the right, so that it doesn't need to be written onto each page by hand.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RunningHeader {
+    /// The index of the font with which the header is written.
+    pub font_index: usize,
+    /// The size in points of the font with which the header is written.
+    pub font_size: f32,
+    /// The date written on the right of the header, such as "16 October 2026". When it is not specified, the date
+    /// of the timestamp of the document is written, if it has one, otherwise only the title is written.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+    /// The distance in millimeters between the header and the top, left and right edges of the page.
+    #[serde(default = "default_margin")]
+    pub margin: f32,
+}
+
+/// The margin of a running header when it is not specified, in millimeters.
+fn default_margin() -> f32 {
+    10.0
+}
+
+impl RunningHeader {
+    /// Creates a running header written with the given font index and font size, without a date and with the
+    /// default margin of 10 mm.
+    pub fn new(font_index: usize, font_size: f32) -> Self {
+        RunningHeader {
+            font_index,
+            font_size,
+            date: None,
+            margin: default_margin(),
+        }
+    }
+
+    /// Lays out the header of a page of the given size into the `WriteUnicodeText` operations of the title and of
+    /// the date, whose fonts touch the top margin, while the date is aligned to the right margin.
+    ///
+    /// # Arguments
+    ///
+    /// * `title` - The title of the document.
+    /// * `timestamp` - The timestamp of the document in seconds since the Unix epoch, whose date is written when
+    ///   the header has no date of its own.
+    /// * `page_size` - The width and height of the page in millimeters.
+    /// * `text_measurer` - The text measurer with which the date is aligned to the right.
+    pub(crate) fn lay_out(
+        &self,
+        title: &str,
+        timestamp: Option<i64>,
+        [page_width, page_height]: [f32; 2],
+        text_measurer: &mut TextMeasurer,
+    ) -> Result<Vec<Operation>, ContextError> {
+        let title_extent = text_measurer.measure(title, self.font_index, self.font_size, 0.0)?;
+        let baseline = page_height - self.margin - title_extent.ascent;
+        let text = |text_string: &str, x: f32| Operation::WriteUnicodeText {
+            color: [0.0, 0.0, 0.0],
+            position: [x, baseline],
+            text_string: text_string.to_string(),
+            font_size: self.font_size,
+            font_index: self.font_index,
+            letter_spacing: 0.0,
+        };
+
+        let mut operations = vec![text(title, self.margin)];
+        let date = self
+            .date
+            .clone()
+            .or_else(|| timestamp.and_then(timestamp_date));
+        if let Some(date) = &date {
+            let date_extent = text_measurer.measure(date, self.font_index, self.font_size, 0.0)?;
+            operations.push(text(date, page_width - self.margin - date_extent.width));
+        }
+
+        Ok(operations)
+    }
+}
+
+/// Formats the date of the given timestamp in seconds since the Unix epoch, in UTC, such as "16 October 2026", or
+/// returns `None` if the timestamp is out of the range of the dates.
+fn timestamp_date(timestamp: i64) -> Option<String> {
+    let date = OffsetDateTime::from_unix_timestamp(timestamp).ok()?.date();
+
+    Some(format!("{} {} {}", date.day(), date.month(), date.year()))
+}
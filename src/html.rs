@@ -0,0 +1,176 @@
+use std::path::PathBuf;
+
+use crate::{
+    canvas::{Canvas, FilledPath, PathSegment, TextRun},
+    document::{builtin_font_paths, Document},
+    error::{ContextError, ErrorKind},
+};
+
+impl Document {
+    /// Converts the given `Document` into a standalone HTML page that mirrors the layout of the PDF document.
+    /// Each page is represented by a block of the same size in millimeters and each piece of text by an absolutely
+    /// positioned span. The built-in fonts are referenced through `@font-face` rules pointing at the same font files
+    /// loaded by `to_pdf_document`, with the paths relative to the current working directory, so that the font
+    /// indices of the document resolve to the same faces in the browser.
+    ///
+    /// Note that browsers position text by its line box rather than by its baseline, so the output is a close
+    /// approximation of the PDF layout meant for quick previews, not a pixel-perfect reproduction.
+    pub fn to_html(&self) -> Result<String, ContextError> {
+        let mut html_canvas = HtmlCanvas::new(&self.document_id, &builtin_font_paths()?);
+        self.draw(&mut html_canvas)?;
+
+        Ok(html_canvas.finish())
+    }
+}
+
+/// A `Canvas` which produces a standalone HTML page, see `Document::to_html`.
+pub struct HtmlCanvas {
+    /// The HTML page constructed so far.
+    html: String,
+    /// The width and height in millimeters of the current page, if a page has been begun.
+    current_page_size: Option<[f32; 2]>,
+}
+
+impl HtmlCanvas {
+    /// Creates a canvas for an HTML page with the given title, declaring one font family for each of the given
+    /// font paths, named after the identifier of the font in the PDF document (`F0`, `F1`, ...).
+    pub fn new(title: &str, font_paths: &[PathBuf]) -> Self {
+        let mut html = String::new();
+        html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+        html.push_str(&format!("<title>{}</title>\n", escape_html(title)));
+        html.push_str("<style>\n");
+        for (font_index, font_path) in font_paths.iter().enumerate() {
+            html.push_str(&format!(
+                "@font-face {{ font-family: \"F{font_index}\"; src: url(\"{}\"); }}\n",
+                escape_html(&font_path.to_string_lossy().replace('\\', "/")),
+            ));
+        }
+        html.push_str(concat!(
+            ".page { position: relative; overflow: hidden; margin: 10mm auto; }\n",
+            ".page span { position: absolute; white-space: pre; line-height: 1; }\n",
+            ".page svg { position: absolute; left: 0; top: 0; width: 100%; height: 100%; }\n",
+        ));
+        html.push_str("</style>\n</head>\n<body>\n");
+
+        HtmlCanvas {
+            html,
+            current_page_size: None,
+        }
+    }
+
+    /// Closes the last page and the document, returning the HTML page.
+    pub fn finish(mut self) -> String {
+        if self.current_page_size.is_some() {
+            self.html.push_str("</div>\n");
+        }
+        self.html.push_str("</body>\n</html>\n");
+
+        self.html
+    }
+
+    /// Retrieve the width and height of the current page.
+    fn current_page_size(&self) -> Result<[f32; 2], ContextError> {
+        self.current_page_size.ok_or(
+            ContextError::with_context(
+                "Unable to draw onto the HTML page because no page has been begun yet",
+            )
+            .with_kind(ErrorKind::PageIndexOutOfRange),
+        )
+    }
+}
+
+impl Canvas for HtmlCanvas {
+    fn page_break(&mut self, page_width: f32, page_height: f32) -> Result<(), ContextError> {
+        if self.current_page_size.is_some() {
+            self.html.push_str("</div>\n");
+        }
+        // Pages are left transparent, just like in the PDF document, unless a background is drawn onto them
+        self.html.push_str(&format!(
+            "<div class=\"page\" style=\"width: {page_width}mm; height: {page_height}mm;\">\n"
+        ));
+        self.current_page_size = Some([page_width, page_height]);
+
+        Ok(())
+    }
+
+    fn text_run(&mut self, text_run: &TextRun) -> Result<(), ContextError> {
+        self.current_page_size()?;
+        let [x, y] = text_run.position;
+        let (font_index, font_size) = (text_run.font_index, text_run.font_size);
+        let color = css_color(text_run.color);
+        // The letter spacing is expressed in points, just like the font size
+        let letter_spacing = if text_run.letter_spacing != 0.0 {
+            format!(" letter-spacing: {}pt;", text_run.letter_spacing)
+        } else {
+            String::new()
+        };
+        // The position is expressed in millimeters from the bottom-left corner of the page,
+        // just like in the PDF document, while the font size is expressed in points
+        self.html.push_str(&format!(
+            "<span style=\"left: {x}mm; bottom: {y}mm; font-family: F{font_index}; font-size: {font_size}pt; color: {color};{letter_spacing}\">{}</span>\n",
+            escape_html(text_run.text),
+        ));
+
+        Ok(())
+    }
+
+    fn fill_path(&mut self, path: &FilledPath) -> Result<(), ContextError> {
+        let [page_width, page_height] = self.current_page_size()?;
+        let path_data = svg_path_data(path, page_height);
+        // The SVG covers the whole page and uses millimeters as its user units
+        self.html.push_str(&format!(
+            "<svg viewBox=\"0 0 {page_width} {page_height}\"><path d=\"{path_data}\" fill=\"{}\"/></svg>\n",
+            css_color(path.color),
+        ));
+
+        Ok(())
+    }
+}
+
+/// Converts a path into the data of an SVG `path` element, whose user units are millimeters. The vertical axis
+/// of SVG points downwards, so the vertical coordinates are flipped with respect to the given page height.
+pub(crate) fn svg_path_data(path: &FilledPath, page_height: f32) -> String {
+    let point = |[x, y]: [f32; 2]| format!("{x} {}", page_height - y);
+    path.segments
+        .iter()
+        .map(|segment| match *segment {
+            PathSegment::MoveTo(end_point) => format!("M {}", point(end_point)),
+            PathSegment::LineTo(end_point) => format!("L {}", point(end_point)),
+            PathSegment::CubicTo {
+                first_control_point,
+                second_control_point,
+                end_point,
+            } => format!(
+                "C {} {} {}",
+                point(first_control_point),
+                point(second_control_point),
+                point(end_point)
+            ),
+            PathSegment::Close => "Z".to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Converts an RGB color with components between 0 and 1 into its CSS representation.
+pub(crate) fn css_color(color: [f32; 3]) -> String {
+    let [r, g, b] = color.map(|component| (component.clamp(0.0, 1.0) * 255.0).round());
+    format!("rgb({r}, {g}, {b})")
+}
+
+/// Escapes the characters which have a special meaning in HTML, so that the text is displayed as-is.
+pub(crate) fn escape_html(text: &str) -> String {
+    let mut escaped_text = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '&' => escaped_text.push_str("&amp;"),
+            '<' => escaped_text.push_str("&lt;"),
+            '>' => escaped_text.push_str("&gt;"),
+            '"' => escaped_text.push_str("&quot;"),
+            '\'' => escaped_text.push_str("&#39;"),
+            _ => escaped_text.push(character),
+        }
+    }
+
+    escaped_text
+}
@@ -0,0 +1,287 @@
+use std::{collections::BTreeSet, sync::Arc};
+
+use crate::{
+    canvas::{Canvas, FilledPath, TextRun},
+    custom::CustomOperations,
+    diff::Change,
+    document::{Document, Operation},
+    error::{ContextError, ErrorKind},
+    font::FontRegistry,
+    pdf::{PdfCanvas, PdfDocument},
+};
+
+/// Converts the successive versions of a document into PDF documents, drawing again only the pages affected by
+/// the changes between one version and the next, as in the watch and preview workflows where the document is
+/// converted again after each edit. The pages are kept drawn in a `PdfDocument` which is never written, so that
+/// each conversion only writes a copy of it (see `IncrementalConverter::to_pdf_document`).
+pub struct IncrementalConverter {
+    /// The latest version of the document.
+    document: Document,
+    /// The fonts with which the document is converted.
+    font_registry: FontRegistry,
+    /// The custom operations which can be found in the document.
+    custom_operations: CustomOperations,
+    /// The PDF document onto which the latest version of the document has been drawn, but not yet written.
+    pdf_document: PdfDocument,
+}
+
+impl IncrementalConverter {
+    /// Draws the first version of the given document with the fonts of the given registry.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - The first version of the document.
+    /// * `font_registry` - The fonts to be loaded into the document, in the order given by their font index.
+    pub fn new(document: Document, font_registry: &FontRegistry) -> Result<Self, ContextError> {
+        Self::with_custom_operations(document, font_registry, CustomOperations::default())
+    }
+
+    /// Draws the first version of the given document just like `new` does, with the given custom operations drawing
+    /// the `Custom` operations of this version and of the next ones (see `Document::draw_with_custom_operations`).
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - The first version of the document.
+    /// * `font_registry` - The fonts to be loaded into the document, in the order given by their font index.
+    /// * `custom_operations` - The custom operations which can be found in the document.
+    pub fn with_custom_operations(
+        document: Document,
+        font_registry: &FontRegistry,
+        custom_operations: CustomOperations,
+    ) -> Result<Self, ContextError> {
+        let pdf_document = draw_document(&document, font_registry, &custom_operations)?;
+
+        Ok(IncrementalConverter {
+            document,
+            font_registry: font_registry.clone(),
+            custom_operations,
+            pdf_document,
+        })
+    }
+
+    /// The latest version of the document, against which the changes of the next version are computed.
+    pub fn document(&self) -> &Document {
+        &self.document
+    }
+
+    /// Replaces the document with its next version, drawing again only the pages which contain the operations
+    /// added, removed or modified by the given changes, as computed by `Document::diff` between the latest
+    /// version and the next one. The whole document is drawn again when the pages themselves change, such as
+    /// when a page is added or resized. Returns the indices of the pages which have been drawn again. If an
+    /// error is returned, the latest version of the document is kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - The next version of the document.
+    /// * `changes` - The changes between the latest version of the document and the next one.
+    pub fn update(
+        &mut self,
+        document: Document,
+        changes: &[Change],
+    ) -> Result<BTreeSet<usize>, ContextError> {
+        let _update_span =
+            tracing::info_span!("update", document_id = %document.document_id).entered();
+        let (pdf_document, page_indices) = match affected_pages(&self.document, &document, changes)
+        {
+            Some(page_indices) => {
+                // The pages are drawn onto a copy, so that nothing is left half drawn if an error occurs
+                let mut pdf_document = self.pdf_document.clone();
+                document.draw_pages(
+                    &mut RedrawCanvas::new(&mut pdf_document, &page_indices),
+                    Some(&page_indices),
+                    &self.custom_operations,
+                )?;
+                (pdf_document, page_indices)
+            }
+            None => {
+                let pdf_document =
+                    draw_document(&document, &self.font_registry, &self.custom_operations)?;
+                let page_indices = (0..pdf_document.stats().page_count).collect();
+                (pdf_document, page_indices)
+            }
+        };
+        tracing::debug!(?page_indices, "Drawn the pages again");
+        self.pdf_document = pdf_document;
+        self.document = document;
+
+        Ok(page_indices)
+    }
+
+    /// Converts the latest version of the document into a PDF document, which is ready to be saved.
+    pub fn to_pdf_document(&self) -> Result<PdfDocument, ContextError> {
+        let mut pdf_document = self.pdf_document.clone();
+        pdf_document.write_all(self.document.instance_id.clone())?;
+
+        Ok(pdf_document)
+    }
+}
+
+/// Draws the whole given document onto a new PDF document with the fonts of the given registry and the given custom
+/// operations.
+fn draw_document(
+    document: &Document,
+    font_registry: &FontRegistry,
+    custom_operations: &CustomOperations,
+) -> Result<PdfDocument, ContextError> {
+    let mut pdf_document = document.new_pdf_document(font_registry)?;
+    document
+        .draw_with_custom_operations(&mut PdfCanvas::new(&mut pdf_document), custom_operations)?;
+
+    Ok(pdf_document)
+}
+
+/// Collects the indices of the pages which contain the operations affected by the given changes between the
+/// two versions of a document, or `None` if the whole document needs to be drawn again, because the pages
+/// themselves or the properties of the whole document have changed.
+fn affected_pages(
+    old_document: &Document,
+    new_document: &Document,
+    changes: &[Change],
+) -> Option<BTreeSet<usize>> {
+    if page_sizes(old_document) != page_sizes(new_document)
+        || old_document.page_background != new_document.page_background
+        || old_document.title != new_document.title
+        || old_document.author != new_document.author
+        || old_document.running_header != new_document.running_header
+        || old_document.timestamp != new_document.timestamp
+        || old_document.deterministic != new_document.deterministic
+        || old_document.strict != new_document.strict
+    {
+        return None;
+    }
+
+    let mut page_indices = BTreeSet::new();
+    for change in changes {
+        // The operations affected by the change, in the original document and in the other one
+        let (old_index, new_index) = match change {
+            Change::DocumentId { .. } | Change::Properties { .. } => return None,
+            // The instance ID is only written once the PDF document is written
+            Change::InstanceId { .. } => continue,
+            Change::Added { index, .. } => (None, Some(*index)),
+            Change::Removed { index, .. } => (Some(*index), None),
+            Change::Modified {
+                old_index,
+                new_index,
+                ..
+            } => (Some(*old_index), Some(*new_index)),
+        };
+        for (document, operation_index) in [(old_document, old_index), (new_document, new_index)] {
+            let Some(operation_index) = operation_index else {
+                continue;
+            };
+            // Moving a page break moves the operations around it onto another page
+            if let Some(Operation::AppendNewPage { .. }) | None =
+                document.operations.get(operation_index)
+            {
+                return None;
+            }
+            page_indices.insert(page_index(document, operation_index)?);
+        }
+    }
+
+    Some(page_indices)
+}
+
+/// The sizes of the pages of the given document, in their order.
+fn page_sizes(document: &Document) -> Vec<(f32, f32)> {
+    document
+        .operations
+        .iter()
+        .filter_map(|operation| match operation {
+            Operation::AppendNewPage {
+                page_width,
+                page_height,
+            } => Some((*page_width, *page_height)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The index of the page onto which the operation with the given index is drawn, or `None` if the operation
+/// precedes the first page.
+fn page_index(document: &Document, operation_index: usize) -> Option<usize> {
+    document.operations[..=operation_index]
+        .iter()
+        .filter(|operation| matches!(operation, Operation::AppendNewPage { .. }))
+        .count()
+        .checked_sub(1)
+}
+
+/// A `Canvas` which draws again onto the pages of a `PdfDocument` which has already been drawn. Each page break
+/// moves onto the following page, which is cleared if its content is drawn again, rather than appending a page.
+struct RedrawCanvas<'a> {
+    /// The PDF document being drawn onto.
+    pdf_document: &'a mut PdfDocument,
+    /// The indices of the pages whose content is drawn again.
+    page_indices: &'a BTreeSet<usize>,
+    /// The index of the current page, if a page break has occurred.
+    current_page_index: Option<usize>,
+}
+
+impl<'a> RedrawCanvas<'a> {
+    /// Creates a canvas which draws again the content of the given pages of the given PDF document.
+    fn new(pdf_document: &'a mut PdfDocument, page_indices: &'a BTreeSet<usize>) -> Self {
+        RedrawCanvas {
+            pdf_document,
+            page_indices,
+            current_page_index: None,
+        }
+    }
+
+    /// Retrieve the index of the current page.
+    fn current_page_index(&self) -> Result<usize, ContextError> {
+        self.current_page_index.ok_or(
+            ContextError::with_context(
+                "Unable to draw onto the PDF document because no page has been added yet",
+            )
+            .with_kind(ErrorKind::PageIndexOutOfRange),
+        )
+    }
+}
+
+impl Canvas for RedrawCanvas<'_> {
+    fn page_break(&mut self, _page_width: f32, _page_height: f32) -> Result<(), ContextError> {
+        let page_index = self
+            .current_page_index
+            .map_or(0, |page_index| page_index + 1);
+        if self.page_indices.contains(&page_index) {
+            self.pdf_document.clear_page(page_index)?;
+        }
+        self.current_page_index = Some(page_index);
+
+        Ok(())
+    }
+
+    fn text_run(&mut self, text_run: &TextRun) -> Result<(), ContextError> {
+        let page_index = self.current_page_index()?;
+        self.pdf_document.write_text_to_layer_in_page(
+            page_index,
+            0,
+            text_run.color,
+            text_run.text,
+            text_run.font_index,
+            text_run.font_size,
+            text_run.position,
+            text_run.letter_spacing,
+        )
+    }
+
+    fn fill_path(&mut self, path: &FilledPath) -> Result<(), ContextError> {
+        let page_index = self.current_page_index()?;
+        self.pdf_document
+            .fill_path_in_layer_in_page(page_index, 0, path)
+    }
+
+    fn pdf_operations(
+        &mut self,
+        operations: Vec<lopdf::content::Operation>,
+    ) -> Result<(), ContextError> {
+        let page_index = self.current_page_index()?;
+        self.pdf_document
+            .add_raw_operations_to_layer_in_page(page_index, 0, operations)
+    }
+
+    fn font_bytes(&self) -> Option<Vec<Arc<[u8]>>> {
+        Some(self.pdf_document.indexed_font_bytes())
+    }
+}
@@ -26,10 +26,185 @@
 /// if it is successfully able to convert the document into a PDF document representation, which can then be saved.
 pub mod document;
 
+/// The module were the `DocumentBuilder` interface for constructing a `Document` in code is presented.
+///
+/// Instead of hand-assembling the variants of the `Operation` enum, the end user can chain the calls of the
+/// builder, for instance by appending a page via `page(a4())` and then writing some text onto it via `text`, `at`
+/// and `font`, before calling `build` in order to obtain the `Document`. The builder keeps track of its state at
+/// compile-time, so that text can only be written once a page has been appended to the document.
+pub mod builder;
+
+/// The module were the `FontRegistry` for sharing the fonts among the conversion of many documents is presented.
+///
+/// Converting a document via `Document::to_pdf_document` reads all the built-in fonts from the file system, which
+/// dominates the time needed for converting small documents. A `FontRegistry` reads them once, so that it can then be
+/// passed to `Document::to_pdf_document_with_fonts` for every document, possibly from multiple threads.
+pub mod font;
+
+/// The module were the layout containers of a `Document` are presented.
+///
+/// The `LayoutContainer` operation positions its children automatically as a vertical stack, a horizontal row or a
+/// grid of fixed cells, with an optional padding and gap, so that table-like layouts such as invoices do not need the
+/// absolute coordinates of every cell. Containers are resolved while drawing a document, or ahead of time via
+/// `Document::lay_out_containers`, by measuring the text with the built-in fonts.
+pub mod container;
+
+/// The module were the semantic comparison between two `Document`s is presented.
+///
+/// The entry point of this module is the `Document::diff` method, which reports the operations that have been
+/// added, removed or modified between two documents, together with their indices and the fields that changed.
+/// The list of `Change`s can be serialized, so that CI pipelines can review how a generated document changes
+/// over time without having to diff the raw JSON files.
+pub mod diff;
+
+/// The module were the editing of the operations of a `Document` in place is presented.
+///
+/// The `Document::insert_operation`, `Document::remove_operation`, `Document::replace_operation` and
+/// `Document::move_operation` methods edit a document by the indices of its operations, rejecting the edits which
+/// would make it impossible to convert. The `DocumentEditor` makes the same edits by the `OperationId`s it assigns,
+/// which don't change as the other operations are edited, so that graphical editors built on this crate can keep
+/// track of the selected operations without serializing the document to JSON and back.
+pub mod edit;
+
+/// The module were the `CustomOperation`s, whose types are defined outside of this crate, are presented.
+///
+/// The `Custom` operations of a document, such as `{"customOperation": "DrawStamp", "parameters": {...}}`, are
+/// drawn by the `CustomOperation` registered with the same name into `CustomOperations`, which emits text, filled
+/// paths or raw PDF operations through a `CustomOperationContext` (see `Document::draw_with_custom_operations` and
+/// `Document::to_pdf_document_with_custom_operations`), so that downstream crates don't need to fork this one.
+pub mod custom;
+
+/// The module were the incremental conversion of the successive versions of a `Document` is presented.
+///
+/// The `IncrementalConverter` keeps the pages of the latest version of a document drawn, and given the next
+/// version together with its `Change`s, as reported by `Document::diff`, it draws again only the pages which
+/// contain the changed operations, so that watch and preview workflows don't convert the whole document again
+/// after each edit.
+pub mod incremental;
+
+/// The module were the sanity checks of a `Document` are presented.
+///
+/// The `Document::lint` method reports the `LintIssue`s found in a document, such as text written before any page,
+/// pages without a size, text placed outside of its page, font indices out of range and characters missing from the
+/// selected fonts, so that mistakes in generated documents are caught before they end up in a PDF document. The
+/// `Document::validate` method performs the subset of these checks which doesn't need the fonts, together with the
+/// colors and the empty text, and never fails, so that all the problems of a document are reported at once.
+pub mod lint;
+
+/// The module were the `Canvas` trait, shared by all the output backends of this crate, is presented.
+///
+/// A `Document` is drawn onto a canvas via `Document::draw`, which interprets its operations in a single place and
+/// hands the resulting primitives (page breaks, text runs and filled paths) to the canvas. The PDF writer
+/// (`PdfCanvas`), the HTML export (`HtmlCanvas`) and the plain-text export (`PlainTextCanvas`) all implement this
+/// trait, and so can any other backend written by the end user.
+pub mod canvas;
+
+/// The module were the barcodes drawn by the `DrawBarcode` operation of a `Document` are presented.
+///
+/// The QR codes, the Code 128 and the EAN-13 barcodes are encoded into their modules by `BarcodeKind::encode`, and
+/// drawn as filled rectangles by `barcode_path`, so that invoices and tickets don't need pre-rendered images.
+pub mod barcode;
+
+/// The module were the charts drawn by the `DrawChart` operation of a `Document` are presented.
+///
+/// A `Chart` draws the bars, the lines or the slices of its values as filled paths and its labels as runs of text
+/// with the fonts of the document, so that the charts of generated reports stay vector in every output format.
+pub mod chart;
+
+/// The module were the `RunningHeader` repeated at the top of the pages of a `Document` is presented.
+///
+/// When a document has a title and a running header, the title is written at the top left of every page and the
+/// date of the header, if any, at the top right, without any header operation being written by hand.
+pub mod header;
+
+/// The module were the HTML export of a `Document` is presented.
+///
+/// The `Document::to_html` method converts a document into a standalone HTML page which mirrors the layout of the
+/// PDF document: every page becomes a block of the same size and every piece of text an absolutely positioned span
+/// that uses the same built-in fonts, so that a "view in browser" link can be offered next to the generated PDFs.
+pub mod html;
+
+/// The module were the SVG export of a `Document` is presented.
+///
+/// The `Document::to_svg` method converts a document into a standalone SVG image in which the pages are placed one
+/// below the other, every piece of text being a `text` element that uses the same built-in fonts as the PDF document.
+pub mod svg;
+
+/// The module were the embedding of SVG images into a `PdfDocument` is presented.
+///
+/// The `PdfDocument::add_svg_to_layer_in_page` method converts the paths and the text of an SVG image, such as a
+/// logo, into the equivalent PDF paths, so that the image stays vector, while the images which use features
+/// without an equivalent in plain PDF paths, such as gradients, are rasterized at a configurable resolution instead.
+pub mod svg_asset;
+
+/// The module were the plain-text export of a `Document` is presented.
+///
+/// The `Document::to_plain_text` method flattens the text operations of a document in reading order, that is from the
+/// top to the bottom and from left to right on each page, which is useful for indexing and searching generated documents.
+pub mod plain_text;
+
+/// The module were the `Timings` of the stages of a conversion are presented.
+///
+/// The `*_with_timings` methods of `Document`, such as `Document::save_to_pdf_file_with_timings`, record how long each
+/// `Stage` of the conversion takes, so that the slow parts of converting large documents or large batches of documents
+/// can be found without an external profiler. Each stage is also a `tracing` span, as are the parsing of a document,
+/// the writing of each page and the saving of a PDF document, so that services embedding this crate can collect the
+/// same telemetry through their own `tracing` subscriber. Without a subscriber, the events are forwarded to `log`.
+pub mod timings;
+
+/// The module were the `BlockingTask` future is presented.
+///
+/// The asynchronous methods, such as `Document::from_path_async`, `Document::save_to_pdf_file_async` and
+/// `FontRegistry::from_paths_async`, run the blocking reading, conversion and writing on the thread pool of `rayon`,
+/// so that web services can await them from any asynchronous runtime without wrapping each of them in a
+/// `spawn_blocking` call.
+pub mod blocking;
+
+/// The module were the `ConversionStats` of the output of a conversion are presented.
+///
+/// The statistics, such as the number of pages, the glyphs written or dropped and the size of the embedded fonts, are
+/// collected while a `PdfDocument` is built and are available via `PdfDocument::stats`, so that batch pipelines can
+/// monitor the characteristics of their output over time.
+pub mod stats;
+
+/// The module were the helpers for testing this crate, and the crates built on it, are presented.
+///
+/// The `DocumentGenerator` generates random documents, such as the fuzz targets of this crate, from an explicit seed
+/// and a `GeneratorConfiguration`, so that a corpus of documents can be reproduced on any machine. The
+/// `normalize_pdf` and `compare_pdfs` functions strip the dates and the numbering of the objects from PDF documents,
+/// so that the regression tests can compare the output of a conversion with a reference without any external tool,
+/// while `diff_pdfs` reports which objects, and which of their entries, differ between two PDF documents. The
+/// `assert_document_snapshot!` macro compares a document, or any other serializable value, with a committed snapshot,
+/// and `minimize_document` shrinks a failing document, such as a fuzz target, to a minimal reproducer.
+pub mod testing;
+
+/// The module were the arbitrary documents for property-based tests are presented, which is only available
+/// with the `proptest` feature.
+///
+/// `Document`, `Operation`, `PageSize` and `ContainerLayout` implement `proptest::arbitrary::Arbitrary`, while the
+/// colors are generated by the `color` strategy. The arbitrary documents always start with a page and only use the
+/// built-in fonts, so that properties such as "the conversion never fails" can be checked by the crates built
+/// on this one too.
+#[cfg(feature = "proptest")]
+pub mod arbitrary;
+
+/// The module were the conversions between the documents of this crate and the ones of printpdf are presented, which
+/// is only available with the `interop-printpdf` feature.
+///
+/// A `Document` is drawn onto a new printpdf document via `Document::to_printpdf_document`, or via `TryFrom` with the
+/// built-in fonts, while a `PrintpdfCanvas` draws it onto the pages of an existing one, whose code can then keep
+/// writing with the same fonts through `PrintpdfCanvas::font`. A printpdf document is converted back into a
+/// `Document` via `TryFrom`, by saving it and recovering its pages and text, so that the projects built on printpdf
+/// can migrate to this crate one document at a time, or mix both crates in the same PDF document.
+#[cfg(feature = "interop-printpdf")]
+pub mod printpdf_interop;
+
 /// This module contains the `ContextError` type which is the error type used throughout this library.
 ///
 /// The reason why this type has been implemented is to uniform the error reporting without delving to deep
-/// into specific error codes which for such library would be too many and definitely out of scope.
+/// into specific error codes which for such library would be too many and definitely out of scope. Instead, each error
+/// carries a coarse `ErrorKind`, such as `ErrorKind::FontNotFound` or `ErrorKind::PageIndexOutOfRange`, whose code
+/// (`E001`, `E004`, ...) is stable, so that callers can branch on the category of an error without parsing its message.
 ///
 /// The `ContextError` type is always returns from a `Result` type, which means that the end user can expect to obtain an explanation
 /// whenever a function returns an error. If an error happened in a function which was called inside a function of this library,
@@ -39,6 +214,13 @@ pub mod document;
 /// a public type, which means that it can be reused in different libraries by implementing functions or external traits on top of it.
 pub mod error;
 
+/// The module were the `Warning`s raised while building a `PdfDocument` are presented.
+///
+/// Unlike errors, warnings don't prevent a document from being converted, but they tell that part of its content,
+/// such as characters missing from a font, won't be displayed as expected. They are collected by the `PdfDocument`
+/// and are available via `PdfDocument::warnings`, so that they can be surfaced to the end users.
+pub mod warning;
+
 /// The module were the `PdfDocument` interface for working with PDF documents is presented.
 ///
 /// # Disclaimer
@@ -63,6 +245,22 @@ pub mod error;
 /// # Introduction
 ///
 /// The main component of this module is the struct `PdfDocument`. For it, I have implemented different convenience functions
-/// such as `add_page_with_layer`, `add_font`, `write_text_to_layer_in_page`, `write_all` and `save_to_bytes` which allow the end user to interact
+/// such as `add_page_with_layer`, `add_font`, `write_text_to_layer_in_page`, `add_image_to_layer_in_page`, `write_all` and `save_to_bytes` which allow the end user to interact
 /// with a PDF document in a meaningful way, while keeping all the complexity hidden below a curtain of private methods.
+/// Documents which are too large to be held in memory can instead be written page by page via `PdfStreamWriter`, while
+/// `PdfDocument::summary` shows the pages, the layers and the fonts of a document before it is saved.
 pub mod pdf;
+
+/// The module were the `PdfInfo` summary of an existing PDF file is presented.
+///
+/// The `PdfInfo::from_path` method reads a PDF file back and reports its identifiers, its metadata, the size and
+/// the layers of each page, and the fonts with the size of their embedded files, so that the end user can verify
+/// what this crate, or the optimization via `gs` or `ps2pdf`, has actually produced.
+pub mod pdf_info;
+
+/// The module were a `Document` is recovered from an existing PDF file.
+///
+/// The `Document::from_pdf_bytes` method reads back the page sizes and the runs of text of a PDF file on a best-effort
+/// basis, so that round-trip tests can verify that what was written can be recovered, and so that documents can be
+/// bootstrapped from existing simple PDF files.
+pub mod pdf_import;
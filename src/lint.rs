@@ -0,0 +1,528 @@
+use serde::Serialize;
+use unicode_normalization::UnicodeNormalization as _;
+
+use crate::{
+    chart::Chart,
+    container::TextMeasurer,
+    document::{builtin_font_paths, Document, Operation},
+    error::ContextError,
+};
+
+/// A problem found in a `Document` by `Document::lint`. Each issue refers to the index of the operation
+/// in which it has been found, so that it can be located in the JSON document.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(tag = "issue", rename_all = "camelCase")]
+pub enum LintIssue {
+    /// A piece of text, or any other content, is written before any page has been appended, so it can't be converted.
+    #[serde(rename_all = "camelCase")]
+    TextBeforeFirstPage {
+        /// The index of the text operation.
+        operation_index: usize,
+    },
+    /// A page has a width or a height which is not positive.
+    #[serde(rename_all = "camelCase")]
+    ZeroSizePage {
+        /// The index of the page operation.
+        operation_index: usize,
+        /// The width of the page in millimeters.
+        page_width: f32,
+        /// The height of the page in millimeters.
+        page_height: f32,
+    },
+    /// A piece of text begins outside of the page onto which it is written.
+    #[serde(rename_all = "camelCase")]
+    OffPageText {
+        /// The index of the text operation, or of the container which positioned the text.
+        operation_index: usize,
+        /// The position of the text in millimeters.
+        position: [f32; 2],
+        /// The width and height of the page in millimeters.
+        page_size: [f32; 2],
+    },
+    /// A piece of text refers to a font which is not loaded into the document.
+    #[serde(rename_all = "camelCase")]
+    FontIndexOutOfRange {
+        /// The index of the text operation, or of the container which contains the text.
+        operation_index: usize,
+        /// The font index of the text.
+        font_index: usize,
+        /// The number of fonts loaded into the document.
+        font_count: usize,
+    },
+    /// A piece of text contains characters which are missing from its font, so they won't be displayed.
+    #[serde(rename_all = "camelCase")]
+    MissingCharacters {
+        /// The index of the text operation, or of the container which positioned the text.
+        operation_index: usize,
+        /// The font index of the text.
+        font_index: usize,
+        /// The missing characters, without repetitions.
+        characters: String,
+    },
+    /// A piece of text or a page background has a color component outside of the range from 0 to 1.
+    #[serde(rename_all = "camelCase")]
+    InvalidColor {
+        /// The index of the text or page background operation, or of the container which contains the text.
+        operation_index: usize,
+        /// The RGB color of the text or of the page.
+        color: [f32; 3],
+    },
+    /// A piece of text is empty, so nothing is written.
+    #[serde(rename_all = "camelCase")]
+    EmptyText {
+        /// The index of the text operation, or of the container which contains the text.
+        operation_index: usize,
+    },
+    /// A container can't be laid out, for instance because it contains a page.
+    #[serde(rename_all = "camelCase")]
+    InvalidContainer {
+        /// The index of the container operation.
+        operation_index: usize,
+        /// The reason why the container can't be laid out.
+        reason: String,
+    },
+    /// A barcode can't be drawn because its data can't be encoded, for instance because it contains characters
+    /// which aren't supported by its symbology.
+    #[serde(rename_all = "camelCase")]
+    InvalidBarcode {
+        /// The index of the barcode operation.
+        operation_index: usize,
+        /// The reason why the data can't be encoded.
+        reason: String,
+    },
+    /// A chart can't be drawn because of its values, for instance because a pie chart has a negative value.
+    #[serde(rename_all = "camelCase")]
+    InvalidChart {
+        /// The index of the chart operation.
+        operation_index: usize,
+        /// The reason why the values can't be drawn.
+        reason: String,
+    },
+}
+
+impl std::fmt::Display for LintIssue {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LintIssue::TextBeforeFirstPage { operation_index } => write!(
+                formatter,
+                "operation {}: text is written before any page has been appended",
+                operation_index
+            ),
+            LintIssue::ZeroSizePage {
+                operation_index,
+                page_width,
+                page_height,
+            } => write!(
+                formatter,
+                "operation {}: the page has a size of {} x {} mm",
+                operation_index, page_width, page_height
+            ),
+            LintIssue::OffPageText {
+                operation_index,
+                position: [x, y],
+                page_size: [page_width, page_height],
+            } => write!(
+                formatter,
+                "operation {}: text at ({}, {}) mm begins outside of the page of {} x {} mm",
+                operation_index, x, y, page_width, page_height
+            ),
+            LintIssue::FontIndexOutOfRange {
+                operation_index,
+                font_index,
+                font_count,
+            } => write!(
+                formatter,
+                "operation {}: font index {} is out of range, only {} fonts are available",
+                operation_index, font_index, font_count
+            ),
+            LintIssue::MissingCharacters {
+                operation_index,
+                font_index,
+                characters,
+            } => write!(
+                formatter,
+                "operation {}: the characters {:?} are missing from the font F{}",
+                operation_index, characters, font_index
+            ),
+            LintIssue::InvalidColor {
+                operation_index,
+                color: [r, g, b],
+            } => write!(
+                formatter,
+                "operation {}: the color ({}, {}, {}) has components outside of the range from 0 to 1",
+                operation_index, r, g, b
+            ),
+            LintIssue::EmptyText { operation_index } => write!(
+                formatter,
+                "operation {}: the text is empty",
+                operation_index
+            ),
+            LintIssue::InvalidContainer {
+                operation_index,
+                reason,
+            } => write!(
+                formatter,
+                "operation {}: the container can't be laid out: {}",
+                operation_index, reason
+            ),
+            LintIssue::InvalidBarcode {
+                operation_index,
+                reason,
+            } => write!(
+                formatter,
+                "operation {}: the barcode can't be encoded: {}",
+                operation_index, reason
+            ),
+            LintIssue::InvalidChart {
+                operation_index,
+                reason,
+            } => write!(
+                formatter,
+                "operation {}: the chart can't be drawn: {}",
+                operation_index, reason
+            ),
+        }
+    }
+}
+
+impl Document {
+    /// Checks every operation of the given `Document` for the problems which can be found without the fonts, namely
+    /// text written before any page, pages without a size, text placed outside of its page, colors outside of
+    /// the range from 0 to 1, empty text, barcodes whose data can't be encoded and charts whose values can't be
+    /// drawn. Unlike the conversion, which fails on the first problem, all of them are returned, in the order of
+    /// the operations. The text of the containers is checked without laying them out, so its placement is only
+    /// checked by `Document::lint`.
+    pub fn validate(&self) -> Vec<LintIssue> {
+        let mut issues = Vec::new();
+        let mut current_page_size = None;
+
+        for (operation_index, operation) in self.operations.iter().enumerate() {
+            match operation {
+                Operation::AppendNewPage {
+                    page_width,
+                    page_height,
+                } => {
+                    check_page_size(operation_index, *page_width, *page_height, &mut issues);
+                    current_page_size = Some([*page_width, *page_height]);
+                }
+                Operation::WriteUnicodeText { .. } => {
+                    check_text_placement(
+                        operation_index,
+                        operation,
+                        current_page_size,
+                        &mut issues,
+                    );
+                    check_text_content(operation_index, operation, &mut issues);
+                }
+                Operation::LayoutContainer { .. } => {
+                    if current_page_size.is_none() {
+                        issues.push(LintIssue::TextBeforeFirstPage { operation_index });
+                    }
+                    check_container_content(operation_index, operation, &mut issues);
+                }
+                Operation::SetPageBackground { color } => {
+                    if current_page_size.is_none() {
+                        issues.push(LintIssue::TextBeforeFirstPage { operation_index });
+                    }
+                    check_color(operation_index, *color, &mut issues);
+                }
+                Operation::DrawBarcode { .. } => {
+                    if current_page_size.is_none() {
+                        issues.push(LintIssue::TextBeforeFirstPage { operation_index });
+                    }
+                    check_barcode(operation_index, operation, &mut issues);
+                }
+                Operation::DrawChart { .. } => {
+                    if current_page_size.is_none() {
+                        issues.push(LintIssue::TextBeforeFirstPage { operation_index });
+                    }
+                    check_chart(operation_index, operation, &mut issues);
+                }
+                Operation::Custom { .. } => {
+                    if current_page_size.is_none() {
+                        issues.push(LintIssue::TextBeforeFirstPage { operation_index });
+                    }
+                }
+            }
+        }
+
+        issues
+    }
+
+    /// Checks the given `Document` for problems which would make the conversion fail or produce an unexpected
+    /// output, namely the ones found by `Document::validate` together with font indices out of range and characters
+    /// missing from the selected fonts, while the text of the containers is checked once they are laid out. The issues
+    /// are returned in the order of the operations, while an error is only returned if the built-in fonts can't
+    /// be loaded.
+    pub fn lint(&self) -> Result<Vec<LintIssue>, ContextError> {
+        let mut text_measurer = TextMeasurer::new(builtin_font_paths()?);
+        let mut issues = Vec::new();
+        let mut current_page_size = None;
+
+        for (operation_index, operation) in self.operations.iter().enumerate() {
+            match operation {
+                Operation::AppendNewPage {
+                    page_width,
+                    page_height,
+                } => {
+                    check_page_size(operation_index, *page_width, *page_height, &mut issues);
+                    current_page_size = Some([*page_width, *page_height]);
+                }
+                Operation::WriteUnicodeText { .. } => lint_text(
+                    operation_index,
+                    operation,
+                    current_page_size,
+                    &mut text_measurer,
+                    &mut issues,
+                )?,
+                Operation::LayoutContainer { .. } => {
+                    // The font indices are checked beforehand, since containers can't be laid out without the fonts
+                    let font_count = text_measurer.font_count();
+                    let font_index_issues_count = issues.len();
+                    collect_font_index_issues(operation_index, operation, font_count, &mut issues);
+                    if issues.len() > font_index_issues_count {
+                        continue;
+                    }
+
+                    let mut laid_out_operations = Vec::new();
+                    if let Err(error) =
+                        operation.lay_out(&mut text_measurer, &mut laid_out_operations)
+                    {
+                        issues.push(LintIssue::InvalidContainer {
+                            operation_index,
+                            reason: error.to_string(),
+                        });
+                        continue;
+                    }
+                    for laid_out_operation in &laid_out_operations {
+                        lint_text(
+                            operation_index,
+                            laid_out_operation,
+                            current_page_size,
+                            &mut text_measurer,
+                            &mut issues,
+                        )?;
+                    }
+                }
+                Operation::SetPageBackground { color } => {
+                    if current_page_size.is_none() {
+                        issues.push(LintIssue::TextBeforeFirstPage { operation_index });
+                    }
+                    check_color(operation_index, *color, &mut issues);
+                }
+                Operation::DrawBarcode { .. } => {
+                    if current_page_size.is_none() {
+                        issues.push(LintIssue::TextBeforeFirstPage { operation_index });
+                    }
+                    check_barcode(operation_index, operation, &mut issues);
+                }
+                Operation::DrawChart {
+                    labels, font_index, ..
+                } => {
+                    if current_page_size.is_none() {
+                        issues.push(LintIssue::TextBeforeFirstPage { operation_index });
+                    }
+                    check_chart(operation_index, operation, &mut issues);
+                    let font_count = text_measurer.font_count();
+                    if !labels.is_empty() && *font_index >= font_count {
+                        issues.push(LintIssue::FontIndexOutOfRange {
+                            operation_index,
+                            font_index: *font_index,
+                            font_count,
+                        });
+                    }
+                }
+                Operation::Custom { .. } => {
+                    if current_page_size.is_none() {
+                        issues.push(LintIssue::TextBeforeFirstPage { operation_index });
+                    }
+                }
+            }
+        }
+
+        Ok(issues)
+    }
+}
+
+/// Checks the given `WriteUnicodeText` operation against the current page and its font.
+fn lint_text(
+    operation_index: usize,
+    operation: &Operation,
+    current_page_size: Option<[f32; 2]>,
+    text_measurer: &mut TextMeasurer,
+    issues: &mut Vec<LintIssue>,
+) -> Result<(), ContextError> {
+    let Operation::WriteUnicodeText {
+        text_string,
+        font_index,
+        ..
+    } = operation
+    else {
+        return Ok(());
+    };
+    check_text_placement(operation_index, operation, current_page_size, issues);
+    check_text_content(operation_index, operation, issues);
+
+    let font_count = text_measurer.font_count();
+    if *font_index >= font_count {
+        issues.push(LintIssue::FontIndexOutOfRange {
+            operation_index,
+            font_index: *font_index,
+            font_count,
+        });
+        return Ok(());
+    }
+    // The text is normalized just like it is done when it is written into the document
+    let text = text_string.nfc().collect::<String>();
+    let missing_characters = text_measurer.missing_characters(&text, *font_index)?;
+    if !missing_characters.is_empty() {
+        issues.push(LintIssue::MissingCharacters {
+            operation_index,
+            font_index: *font_index,
+            characters: missing_characters.into_iter().collect(),
+        });
+    }
+
+    Ok(())
+}
+
+/// Checks that the page appended by the given operation has a positive size.
+fn check_page_size(
+    operation_index: usize,
+    page_width: f32,
+    page_height: f32,
+    issues: &mut Vec<LintIssue>,
+) {
+    if page_width <= 0.0 || page_height <= 0.0 {
+        issues.push(LintIssue::ZeroSizePage {
+            operation_index,
+            page_width,
+            page_height,
+        });
+    }
+}
+
+/// Checks that the given `WriteUnicodeText` operation is written onto a page and begins inside of it.
+fn check_text_placement(
+    operation_index: usize,
+    operation: &Operation,
+    current_page_size: Option<[f32; 2]>,
+    issues: &mut Vec<LintIssue>,
+) {
+    let Operation::WriteUnicodeText { position, .. } = operation else {
+        return;
+    };
+
+    match current_page_size {
+        None => issues.push(LintIssue::TextBeforeFirstPage { operation_index }),
+        Some(page_size @ [page_width, page_height]) => {
+            let [x, y] = *position;
+            if !(0.0..=page_width).contains(&x) || !(0.0..=page_height).contains(&y) {
+                issues.push(LintIssue::OffPageText {
+                    operation_index,
+                    position: *position,
+                    page_size,
+                });
+            }
+        }
+    }
+}
+
+/// Checks that the given `WriteUnicodeText` operation has a valid color and some text to be written.
+fn check_text_content(operation_index: usize, operation: &Operation, issues: &mut Vec<LintIssue>) {
+    let Operation::WriteUnicodeText {
+        color, text_string, ..
+    } = operation
+    else {
+        return;
+    };
+
+    check_color(operation_index, *color, issues);
+    if text_string.is_empty() {
+        issues.push(LintIssue::EmptyText { operation_index });
+    }
+}
+
+/// Checks that the given color of a text or of a page background has its components in the range from 0 to 1.
+fn check_color(operation_index: usize, color: [f32; 3], issues: &mut Vec<LintIssue>) {
+    if color
+        .iter()
+        .any(|component| !(0.0..=1.0).contains(component))
+    {
+        issues.push(LintIssue::InvalidColor {
+            operation_index,
+            color,
+        });
+    }
+}
+
+/// Checks that the data of the given `DrawBarcode` operation can be encoded by its symbology.
+fn check_barcode(operation_index: usize, operation: &Operation, issues: &mut Vec<LintIssue>) {
+    let Operation::DrawBarcode { kind, data, .. } = operation else {
+        return;
+    };
+
+    if let Err(error) = kind.encode(data) {
+        issues.push(LintIssue::InvalidBarcode {
+            operation_index,
+            reason: error.to_string(),
+        });
+    }
+}
+
+/// Checks that the values of the given `DrawChart` operation can be drawn by its type of chart.
+fn check_chart(operation_index: usize, operation: &Operation, issues: &mut Vec<LintIssue>) {
+    let Some(chart) = Chart::from_operation(operation) else {
+        return;
+    };
+
+    if let Err(error) = chart.check() {
+        issues.push(LintIssue::InvalidChart {
+            operation_index,
+            reason: error.to_string(),
+        });
+    }
+}
+
+/// Checks the content of the text among the children of the given container, see `check_text_content`.
+fn check_container_content(
+    operation_index: usize,
+    operation: &Operation,
+    issues: &mut Vec<LintIssue>,
+) {
+    match operation {
+        Operation::WriteUnicodeText { .. } => {
+            check_text_content(operation_index, operation, issues)
+        }
+        Operation::LayoutContainer { children, .. } => {
+            for child in children {
+                check_container_content(operation_index, child, issues);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Collects the font indices which are out of range among the children of the given container.
+fn collect_font_index_issues(
+    operation_index: usize,
+    operation: &Operation,
+    font_count: usize,
+    issues: &mut Vec<LintIssue>,
+) {
+    match operation {
+        Operation::WriteUnicodeText { font_index, .. } if *font_index >= font_count => {
+            issues.push(LintIssue::FontIndexOutOfRange {
+                operation_index,
+                font_index: *font_index,
+                font_count,
+            })
+        }
+        Operation::LayoutContainer { children, .. } => {
+            for child in children {
+                collect_font_index_issues(operation_index, child, font_count, issues);
+            }
+        }
+        _ => {}
+    }
+}
@@ -1,15 +1,24 @@
 use lopdf::{Object, StringFormat};
-use owned_ttf_parser::{AsFaceRef as _, Face, OwnedFace};
+use owned_ttf_parser::Face;
+use rayon::iter::{IntoParallelRefIterator as _, ParallelIterator as _};
+use serde::Serialize;
 use std::{
-    collections::{BTreeMap, HashMap},
-    io::BufWriter,
+    collections::{hash_map::DefaultHasher, BTreeMap, BTreeSet, HashMap},
+    hash::{Hash, Hasher},
+    io::Write,
     mem,
     path::Path,
+    sync::Arc,
 };
 use time::OffsetDateTime;
 use unicode_normalization::UnicodeNormalization as _;
 
-use crate::error::ContextError;
+use crate::{
+    canvas::{Canvas, FilledPath, PathSegment, TextRun},
+    error::{ContextError, ErrorKind},
+    stats::ConversionStats,
+    warning::Warning,
+};
 
 /// The relevant vertical metrics of a font.
 #[derive(Clone, Copy, Debug, Default)]
@@ -31,16 +40,15 @@ struct GlyphMetrics {
     height: u32,
 }
 
-/// A font face loaded from a TTF font, together with its measure of units per em.
-#[derive(Clone, Debug)]
-struct TtfFontFace {
+/// A font face parsed from the data of a TTF font, which it borrows, together with its measure of units per em.
+struct TtfFontFace<'a> {
     /// The underlying font face which is represented through the `ttf_parser` crate.
-    inner: std::sync::Arc<owned_ttf_parser::OwnedFace>,
+    inner: Face<'a>,
     /// The number of units per em of the font face.
     units_per_em: u16,
 }
 
-impl TtfFontFace {
+impl<'a> TtfFontFace<'a> {
     /// Retrieve the font metrics from the associated font face.
     fn font_metrics(&self) -> FontMetrics {
         FontMetrics {
@@ -59,7 +67,7 @@ impl TtfFontFace {
 
     /// Retrieve the mapping between the glyph IDs and the characters (codepoints), that specifically
     /// contains exactly the number of unicode glyphs present in the font.
-    fn glyph_ids(&self) -> HashMap<u16, char> {
+    fn glyph_ids(&self) -> BTreeMap<u16, char> {
         // Retrieve all the unicode subtables of the font face
         let font_subtables = self.face().tables().cmap.map(|cmap| {
             cmap.subtables
@@ -69,13 +77,13 @@ impl TtfFontFace {
         // If no suitable subtables have been found, then return an empty association between
         // glyph IDs and characters
         let Some(font_subtables) = font_subtables else {
-            return HashMap::new();
+            return BTreeMap::new();
         };
 
         // Once the subtables have been fetched, creates an association between the glyph IDs
-        // and the characters (codepoints) that contains the number of glyphs of the font face
-        let mut gid_to_codepoint_map =
-            HashMap::with_capacity(self.face().number_of_glyphs().into());
+        // and the characters (codepoints) that contains the number of glyphs of the font face, ordered by
+        // glyph ID so that the order in which they are iterated doesn't depend on the hashing
+        let mut gid_to_codepoint_map = BTreeMap::new();
         for font_subtable in font_subtables {
             font_subtable.codepoints(|codepoint| {
                 use std::convert::TryFrom as _;
@@ -130,53 +138,77 @@ impl TtfFontFace {
         }
     }
 
-    /// Constructs a font face from the underlying raw data extracted from the TTF font file.
-    pub fn from_bytes(data: &[u8]) -> Result<Self, ContextError> {
-        let face = OwnedFace::from_vec(data.to_vec(), 0)
-            .map_err(|error| ContextError::with_error("Failed to parse font", &error))?;
-        let units_per_em = face.as_face_ref().units_per_em();
+    /// Constructs a font face from the underlying raw data extracted from the TTF font file, without copying it.
+    pub fn from_bytes(data: &'a [u8]) -> Result<Self, ContextError> {
+        let face = Face::parse(data, 0).map_err(|error| {
+            ContextError::with_error("Failed to parse font", &error)
+                .with_kind(ErrorKind::InvalidFont)
+        })?;
+        let units_per_em = face.units_per_em();
 
         Ok(Self {
-            inner: std::sync::Arc::new(face),
+            inner: face,
             units_per_em,
         })
     }
 
     /// Retrieve the underlying font face as a reference.
-    fn face(&self) -> &Face<'_> {
-        self.inner.as_face_ref()
+    fn face(&self) -> &Face<'a> {
+        &self.inner
     }
 }
 
-/// A font loaded from a TTF font, together with its measure of units per em, the byte data
-/// data was loaded from and an identifier for the font face.
+/// A font loaded from a TTF font, together with the byte data it was loaded from and an identifier for the font
+/// face. The byte data is shared with the other documents the same font has been added to, and it is only copied
+/// when the font is embedded into the PDF document.
 #[derive(Debug, Clone)]
 struct Font {
-    /// The byte data the font was loaded from.
-    bytes: Vec<u8>,
-    /// The actual font face, together with its measure of units per em.
-    ttf_face: TtfFontFace,
+    /// The byte data the font was loaded from, which has already been parsed successfully.
+    bytes: Arc<[u8]>,
     /// The identifier of the font face.
     face_identifier: String,
+    /// The glyph IDs of the characters looked up so far, or `None` for the characters missing from the font,
+    /// so that the subtables of the font are only walked once for each character.
+    glyph_id_cache: HashMap<char, Option<u16>>,
 }
 
 impl Font {
-    /// Takes a well-formed font and inserts it into the PDF document, returning the associated PDF dictionary.
-    fn insert_into_document(&self, inner_document: &mut lopdf::Document) -> lopdf::Dictionary {
+    /// Parse the font face from the byte data of the font, which is cheap since the tables of the font are only
+    /// read when they are accessed.
+    fn ttf_face(&self) -> TtfFontFace<'_> {
+        TtfFontFace::from_bytes(&self.bytes)
+            .expect("The font data has already been parsed when the font was added")
+    }
+
+    /// Retrieve the glyph ID of the given character, looking it up in the font only the first time.
+    fn glyph_id(&mut self, character: char) -> Option<u16> {
+        if let Some(glyph_id) = self.glyph_id_cache.get(&character) {
+            return *glyph_id;
+        }
+        let glyph_id = self.ttf_face().glyph_id(character);
+        self.glyph_id_cache.insert(character, glyph_id);
+
+        glyph_id
+    }
+
+    /// Takes a well-formed font and builds the PDF objects which represent it, without inserting them into
+    /// the PDF document yet, so that the objects of many fonts can be built in parallel (see `FontObjects`).
+    fn build_objects(&self) -> FontObjects {
         use lopdf::Object::*;
         // Retrieve the font metrics of the underlying font face
-        let face_metrics = self.ttf_face.font_metrics();
+        let ttf_face = self.ttf_face();
+        let face_metrics = ttf_face.font_metrics();
 
         // Construct the PDF stream which sets the length in bytes of the font data, this is requested by
         // the PDF specification because the PDF format with mixed text and byte data
         let font_stream = lopdf::Stream::new(
             lopdf::Dictionary::from_iter(vec![("Length1", Integer(self.bytes.len() as i64))]),
-            self.bytes.clone(),
+            self.bytes.to_vec(),
         )
         .with_compression(false); // Do not compress it
 
         // Begin setting the required font attributes
-        let mut font_vector: Vec<(::std::string::String, lopdf::Object)> = vec![
+        let font_vector: Vec<(::std::string::String, lopdf::Object)> = vec![
             ("Type".into(), Name("Font".into())),
             ("Subtype".into(), Name("Type0".into())),
             (
@@ -189,7 +221,7 @@ impl Font {
         ];
 
         // Specify the font properties which will be used by PDF renderers to position the glyphs
-        let mut font_descriptor_vector: Vec<(::std::string::String, lopdf::Object)> = vec![
+        let font_descriptor_vector: Vec<(::std::string::String, lopdf::Object)> = vec![
             ("Type".into(), Name("FontDescriptor".into())),
             (
                 "FontName".into(),
@@ -219,9 +251,9 @@ impl Font {
         gid_to_glyph_properties_map.insert(0, (0, 1000, 1000));
 
         // For each pair ofglyph ID and associated character present in the font face...
-        for (glyph_id, character) in self.ttf_face.glyph_ids() {
+        for (glyph_id, character) in ttf_face.glyph_ids() {
             // Retrieve the glyph metrics for that glyph ID
-            if let Some(glyph_metrics) = self.ttf_face.glyph_metrics(glyph_id) {
+            if let Some(glyph_metrics) = ttf_face.glyph_metrics(glyph_id) {
                 if glyph_metrics.height > maximum_character_height {
                     // Save the maximum character heights registered so far into a variable to be later used
                     maximum_character_height = glyph_metrics.height;
@@ -256,10 +288,20 @@ impl Font {
         // Widths (or heights, depends on `self.vertical_writing`) of the individual characters, indexed by glyph ID
         let mut character_widths = Vec::<(u32, u32)>::new();
 
+        // Only the glyphs which are actually written by the text of the document need to be mapped back to their
+        // characters, which keeps the mapping small for the fonts with thousands of glyphs, such as the CJK ones
+        let used_glyph_ids = self
+            .glyph_id_cache
+            .values()
+            .flatten()
+            .map(|glyph_id| u32::from(*glyph_id))
+            .collect::<BTreeSet<_>>();
+
         let mut current_gid_to_character_block = Vec::new();
-        // For each previously collected glyph ID, extract the associated character and width of the corresponding glyph...
-        for (glyph_id, (character, glyph_width, _glyph_height)) in
-            gid_to_glyph_properties_map.iter()
+        // For each previously collected glyph ID which is used, extract the associated character and width of the corresponding glyph...
+        for (glyph_id, (character, glyph_width, _glyph_height)) in gid_to_glyph_properties_map
+            .iter()
+            .filter(|(glyph_id, _)| **glyph_id == 0 || used_glyph_ids.contains(glyph_id))
         {
             // Remap the glyph ID into the accepted range for the PDF specification and make sure that
             // we haven't reached the first bit of the current bucket, or either that we haven't exceeded the maximum bucket length of 100 elements
@@ -288,56 +330,33 @@ impl Font {
             lopdf::Dictionary::new(),
             cid_to_unicode_map.as_bytes().to_vec(),
         );
-        let cid_to_unicode_map_stream_id = inner_document.add_object(cid_to_unicode_map_stream);
 
         // NOTE(ghovax): The following is a comments from the original author.
 
         // Encode widths and heights so that they fit into what PDF expects
         // See page 439 in the PDF 1.7 reference
-        // Basically `widths_objects` will contain objects like this: 20 [21, 99, 34, 25],
-        // which means that the character with the GID 20 has a width of 21 units and the character with the GID 21 has a width of 99 units
-        let mut width_objects = Vec::<Object>::new();
-        let mut current_lesser_glyph_id = 0;
-        let mut current_upper_gid = 0;
-        let mut current_widths_vector = Vec::<Object>::new();
+        let mut glyph_widths = Vec::<(u16, i64)>::new();
+        let mut glyph_ids_without_width = Vec::<u16>::new();
 
         // Scale the font width so that it sort-of fits into an 1000 unit square
         // TODO(ghovax): Why does he exactly need to do that?
         let percentage_font_scaling = 1000.0 / (face_metrics.units_per_em as f32);
 
         // For each glyph ID present in the font face...
-        for glyph_id in 0..self.ttf_face.glyph_count() {
-            // If it has an available width extracted from the font itself...
-            if let Some(GlyphMetrics { width, .. }) = self.ttf_face.glyph_metrics(glyph_id) {
-                if glyph_id == current_upper_gid {
-                    // Register its width (corrected by the font scaling) as a PDF object if its glyph ID
-                    // is the same as the current upper bound of the glyph ID range
-                    current_widths_vector
-                        .push(Integer((width as f32 * percentage_font_scaling) as i64));
-                    current_upper_gid += 1;
-                } else {
-                    // Otherwise, drain the current width vector to the width objects and update the current lesser and upper glyph IDs
-                    width_objects.push(Integer(current_lesser_glyph_id as i64));
-                    width_objects.push(Array(std::mem::take(&mut current_widths_vector)));
-
-                    current_widths_vector
-                        .push(Integer((width as f32 * percentage_font_scaling) as i64));
-                    current_lesser_glyph_id = glyph_id;
-                    current_upper_gid = glyph_id + 1;
-                }
+        for glyph_id in 0..ttf_face.glyph_count() {
+            // If it has an available width extracted from the font itself, register it (corrected by the font scaling)
+            if let Some(GlyphMetrics { width, .. }) = ttf_face.glyph_metrics(glyph_id) {
+                glyph_widths.push((glyph_id, (width as f32 * percentage_font_scaling) as i64));
             } else {
                 // If the width is not available, then we just skip the character and log it
-                log::warn!("Glyph ID {} for the font {:?} has no width, skipping it when adding it to the document from the font", glyph_id, self.face_identifier);
-                continue;
+                tracing::warn!(glyph_id, font_name = %self.face_identifier, "Glyph ID {} for the font {:?} has no width, skipping it when adding it to the document from the font", glyph_id, self.face_identifier);
+                glyph_ids_without_width.push(glyph_id);
             }
         }
-
-        // Push the last widths in any case because the loop is delayed by one iteration
-        width_objects.push(Integer(current_lesser_glyph_id as i64));
-        width_objects.push(Array(std::mem::take(&mut current_widths_vector)));
+        let width_objects = encode_glyph_widths(&glyph_widths);
 
         // Configure the descriptors of the font for it to adhere to the PDF specification
-        let mut font_descriptors = lopdf::Dictionary::from_iter(vec![
+        let font_descriptors = lopdf::Dictionary::from_iter(vec![
             ("Type", Name("Font".into())),
             ("Subtype", Name("CIDFontType2".into())),
             ("BaseFont", Name(self.face_identifier.clone().into())),
@@ -363,6 +382,69 @@ impl Font {
             Integer(total_width as i64),
             Integer(maximum_character_height as i64),
         ];
+        FontObjects {
+            face_identifier: self.face_identifier.clone(),
+            font_vector,
+            font_descriptor_vector,
+            font_descriptors,
+            font_bounding_box,
+            font_stream,
+            cid_to_unicode_map_stream,
+            glyph_ids_without_width,
+        }
+    }
+}
+
+/// The PDF objects which represent a font, as built by `Font::build_objects`. Building them is by far the most
+/// expensive part of writing a PDF document, while inserting them into it is cheap but needs exclusive access to
+/// the document, so that the two steps are separated.
+struct FontObjects {
+    /// The identifier of the font face.
+    face_identifier: String,
+    /// The entries of the font dictionary, except for `DescendantFonts` and `ToUnicode`.
+    font_vector: Vec<(String, Object)>,
+    /// The entries of the font descriptor, except for `FontFile2` and `FontBBox`.
+    font_descriptor_vector: Vec<(String, Object)>,
+    /// The descendant font, except for its `FontDescriptor`.
+    font_descriptors: lopdf::Dictionary,
+    /// The bounding box of the glyphs of the font.
+    font_bounding_box: Vec<Object>,
+    /// The stream of the font file.
+    font_stream: lopdf::Stream,
+    /// The stream of the mapping between the glyph IDs and the characters.
+    cid_to_unicode_map_stream: lopdf::Stream,
+    /// The IDs of the glyphs which have no width.
+    glyph_ids_without_width: Vec<u16>,
+}
+
+impl FontObjects {
+    /// Inserts the objects of the font into the PDF document, returning the associated PDF dictionary.
+    /// The glyphs which have no width are reported into the given warnings.
+    fn insert_into_document(
+        self,
+        inner_document: &mut lopdf::Document,
+        warnings: &mut Vec<Warning>,
+    ) -> lopdf::Dictionary {
+        use lopdf::Object::*;
+        let FontObjects {
+            face_identifier,
+            mut font_vector,
+            mut font_descriptor_vector,
+            mut font_descriptors,
+            font_bounding_box,
+            font_stream,
+            cid_to_unicode_map_stream,
+            glyph_ids_without_width,
+        } = self;
+
+        if !glyph_ids_without_width.is_empty() {
+            warnings.push(Warning::GlyphsWithoutWidth {
+                font_name: face_identifier,
+                glyph_ids: glyph_ids_without_width,
+            });
+        }
+
+        let cid_to_unicode_map_stream_id = inner_document.add_object(cid_to_unicode_map_stream);
         font_descriptor_vector.push((
             "FontFile2".into(),
             Reference(inner_document.add_object(font_stream)),
@@ -391,7 +473,7 @@ impl Font {
     }
 }
 
-/// One layer of PDF data. It can be converted into a `lopdf::Stream` by calling `Into<lopdf::Stream>::into`.
+/// One layer of PDF data. Its operations are encoded into the content stream of its page by `PdfLayer::encode_into`.
 #[derive(Debug, Clone)]
 struct PdfLayer {
     /// Name of the layer. Must be present for the optional content group.
@@ -400,25 +482,45 @@ struct PdfLayer {
     operations: Vec<lopdf::content::Operation>,
 }
 
-impl From<PdfLayer> for lopdf::Stream {
-    fn from(value: PdfLayer) -> Self {
-        use lopdf::{Dictionary, Stream};
-        // Construct the stream content from the actual underlying operations of the layer
-        let stream_content = lopdf::content::Content {
-            operations: value.operations,
-        };
+impl PdfLayer {
+    /// Encodes the operations of the layer at the end of the given buffer, enclosed in a marked-content sequence
+    /// which associates them to the given optional content group. The operations are borrowed rather than copied,
+    /// so that the layer is left untouched.
+    ///
+    /// # Arguments
+    ///
+    /// * `ocg_name` - The name under which the optional content group of the layer is found in the resources of the page.
+    /// * `buffer` - The content stream of the page, onto which the encoded operations are appended.
+    fn encode_into(&self, ocg_name: &str, buffer: &mut Vec<u8>) -> Result<(), ContextError> {
+        use lopdf::content::{Content, Operation};
+        use lopdf::Object::*;
 
-        // Encode the uncompressed stream content into the stream
-        Stream::new(
-            Dictionary::new(),
-            stream_content
-                .encode()
-                .map_err(|error| {
-                    ContextError::with_error("Failed to encode PDF layer content", &error)
-                })
-                .unwrap(),
-        )
-        .with_compression(false) // Page contents should not be compressed
+        // In the PDF specification the BDC operator represents the beginning of a marked-content sequence, while the
+        // q/Q operator creates an isolated graphics state block, in our case with no state
+        let prologue = [
+            // In the PDF specification the OC operand relates to optional content
+            Operation::new("BDC", vec![Name("OC".into()), Name(ocg_name.into())]),
+            Operation::new("q", vec![]),
+        ];
+        let epilogue = [Operation::new("Q", vec![]), Operation::new("EMC", vec![])];
+
+        // Consecutive operations are separated by a new line, just like when encoding them all at once
+        for (index, operations) in [&prologue[..], &self.operations, &epilogue]
+            .into_iter()
+            .filter(|operations| !operations.is_empty())
+            .enumerate()
+        {
+            if index > 0 {
+                buffer.push(b'\n');
+            }
+            let encoded_operations = Content { operations }.encode().map_err(|error| {
+                ContextError::with_error("Failed to encode PDF layer content", &error)
+                    .with_kind(ErrorKind::PdfEncoding)
+            })?;
+            buffer.extend_from_slice(&encoded_operations);
+        }
+
+        Ok(())
     }
 }
 
@@ -428,27 +530,221 @@ use nalgebra_glm as glm;
 #[allow(dead_code)]
 #[derive(Debug, Clone)]
 struct ImageXObject {
-    /// Width of the image (original width, not scaled width).
-    width: f32,
-    /// Height of the image (original height, not scaled height).
-    height: f32,
+    /// Width of the image in pixels (original width, not scaled width).
+    width: u32,
+    /// Height of the image in pixels (original height, not scaled height).
+    height: u32,
+    /// The color space of the pixels, such as `DeviceRGB`.
+    color_space: &'static str,
     /// Bits per color component (1, 2, 4, 8, 16) - 1 for black/white, 8 Greyscale / RGB, etc.
-    /// If using a JPXDecode filter (for JPEG images), this can be inferred from the image data.
+    /// If using a DCTDecode filter (for JPEG images), this can be inferred from the image data.
     bits_per_component: u16,
     /// Should the image be interpolated when scaled?
     interpolate: bool,
     /// The actual data from the image.
     image_data: Vec<u8>,
+    /// The filter with which the data is already encoded, such as `DCTDecode` for the JPEG images which are
+    /// embedded as they are. Otherwise, the data is made of raw pixels and it is compressed when it is written.
+    filter: Option<&'static str>,
     // SoftMask for transparency, if `None` assumes no transparency. See page 444 of the adope pdf 1.4 reference.
     soft_mask: Option<lopdf::ObjectId>,
     /// The bounding box of the image.
     clipping_bounding_box: Option<glm::Mat4>,
 }
 
+impl ImageXObject {
+    /// Reads the image from the given bytes of a JPEG or a PNG file, returning it together with its soft mask
+    /// if the image has an alpha channel.
+    fn from_bytes(image_bytes: &[u8]) -> Result<(Self, Option<Self>), ContextError> {
+        if image_bytes.starts_with(&[0xFF, 0xD8]) {
+            Ok((Self::from_jpeg_bytes(image_bytes)?, None))
+        } else if image_bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+            Self::from_png_bytes(image_bytes)
+        } else {
+            Err(ContextError::with_context(
+                "Unable to embed the image, only JPEG and PNG images are supported",
+            )
+            .with_kind(ErrorKind::InvalidImage))
+        }
+    }
+
+    /// Reads the size and the color space of a JPEG image from its header, so that its data can be embedded
+    /// as it is, without being decoded and compressed again.
+    fn from_jpeg_bytes(image_bytes: &[u8]) -> Result<Self, ContextError> {
+        let frame_header = jpeg_frame_header(image_bytes).ok_or(
+            ContextError::with_context("Unable to find the size of the JPEG image in its header")
+                .with_kind(ErrorKind::InvalidImage),
+        )?;
+        let color_space = match frame_header.component_count {
+            1 => "DeviceGray",
+            3 => "DeviceRGB",
+            4 => "DeviceCMYK",
+            component_count => {
+                return Err(ContextError::with_context(format!(
+                    "Unable to embed a JPEG image with {} color components",
+                    component_count
+                ))
+                .with_kind(ErrorKind::InvalidImage))
+            }
+        };
+
+        Ok(ImageXObject {
+            width: frame_header.width,
+            height: frame_header.height,
+            color_space,
+            bits_per_component: frame_header.bits_per_component,
+            interpolate: true,
+            image_data: image_bytes.to_vec(),
+            filter: Some("DCTDecode"),
+            soft_mask: None,
+            clipping_bounding_box: None,
+        })
+    }
+
+    /// Decodes a PNG image into its raw pixels, separating the alpha channel, if any, into a soft mask.
+    fn from_png_bytes(image_bytes: &[u8]) -> Result<(Self, Option<Self>), ContextError> {
+        let image = image::load_from_memory_with_format(image_bytes, image::ImageFormat::Png)
+            .map_err(|error| {
+                ContextError::with_error("Failed to decode the PNG image", &error)
+                    .with_kind(ErrorKind::InvalidImage)
+            })?;
+        let (width, height) = (image.width(), image.height());
+        let has_color = image.color().has_color();
+        let alpha_channel = image.color().has_alpha().then(|| {
+            image
+                .to_luma_alpha8()
+                .pixels()
+                .map(|pixel| pixel.0[1])
+                .collect::<Vec<u8>>()
+        });
+
+        let (color_space, image_data) = if has_color {
+            ("DeviceRGB", image.to_rgb8().into_raw())
+        } else {
+            ("DeviceGray", image.to_luma8().into_raw())
+        };
+        let image_xobject = |color_space, image_data| ImageXObject {
+            width,
+            height,
+            color_space,
+            bits_per_component: 8,
+            interpolate: true,
+            image_data,
+            filter: None,
+            soft_mask: None,
+            clipping_bounding_box: None,
+        };
+
+        Ok((
+            image_xobject(color_space, image_data),
+            alpha_channel.map(|alpha_channel| image_xobject("DeviceGray", alpha_channel)),
+        ))
+    }
+}
+
+/// The properties of a JPEG image which are found in the header of its frame.
+struct JpegFrameHeader {
+    /// The width of the image in pixels.
+    width: u32,
+    /// The height of the image in pixels.
+    height: u32,
+    /// The number of bits of each color component.
+    bits_per_component: u16,
+    /// The number of color components, such as 3 for the RGB images.
+    component_count: u8,
+}
+
+/// Iterates over the markers and the contents of the segments of the given JPEG image, which are skipped over
+/// by their length, up to the start of the compressed data.
+fn jpeg_segments(image_bytes: &[u8]) -> impl Iterator<Item = (u8, &[u8])> {
+    // Skip the marker of the start of the image
+    let mut offset = 2;
+    std::iter::from_fn(move || {
+        let [0xFF, marker, length_high, length_low] = *image_bytes.get(offset..offset + 4)? else {
+            return None;
+        };
+        // The marker of the start of the scan is followed by the compressed data rather than by other segments
+        if marker == 0xDA {
+            return None;
+        }
+        let segment = image_bytes
+            .get(offset + 4..offset + 2 + u16::from_be_bytes([length_high, length_low]) as usize)?;
+        offset += 2 + segment.len() + 2;
+
+        Some((marker, segment))
+    })
+}
+
+/// Finds the header of the frame among the segments of the given JPEG image.
+fn jpeg_frame_header(image_bytes: &[u8]) -> Option<JpegFrameHeader> {
+    // The start of frame markers, except for the ones of the Huffman tables, of the arithmetic coding and of the JPEG extensions
+    let (_, segment) = jpeg_segments(image_bytes).find(|(marker, _)| {
+        matches!(marker, 0xC0..=0xCF) && !matches!(marker, 0xC4 | 0xC8 | 0xCC)
+    })?;
+    let [bits_per_component, height_high, height_low, width_high, width_low, component_count, ..] =
+        *segment
+    else {
+        return None;
+    };
+
+    Some(JpegFrameHeader {
+        width: u16::from_be_bytes([width_high, width_low]) as u32,
+        height: u16::from_be_bytes([height_high, height_low]) as u32,
+        bits_per_component: bits_per_component as u16,
+        component_count,
+    })
+}
+
+/// Finds the orientation of the given JPEG image among its EXIF metadata, as a number from 1 to 8 which tells
+/// how the stored pixels are rotated or flipped when the image is displayed.
+fn jpeg_exif_orientation(image_bytes: &[u8]) -> Option<u16> {
+    let tiff_bytes = jpeg_segments(image_bytes).find_map(|(marker, segment)| {
+        segment.strip_prefix(b"Exif\0\0").filter(|_| marker == 0xE1)
+    })?;
+    // The numbers of the TIFF structure, which holds the metadata, can be either little-endian or big-endian
+    let read_u16 = |offset: usize| {
+        let bytes = [*tiff_bytes.get(offset)?, *tiff_bytes.get(offset + 1)?];
+        Some(match &tiff_bytes[..2] {
+            b"II" => u16::from_le_bytes(bytes),
+            _ => u16::from_be_bytes(bytes),
+        })
+    };
+    let first_directory_offset = {
+        let bytes: [u8; 4] = tiff_bytes.get(4..8)?.try_into().ok()?;
+        match &tiff_bytes[..2] {
+            b"II" => u32::from_le_bytes(bytes),
+            b"MM" => u32::from_be_bytes(bytes),
+            _ => return None,
+        }
+    } as usize;
+
+    // Each entry of the first directory is made of its tag, its type, its count and its value, in 12 bytes
+    let entry_count = read_u16(first_directory_offset)? as usize;
+    (0..entry_count)
+        .map(|entry_index| first_directory_offset + 2 + 12 * entry_index)
+        .find(|entry_offset| read_u16(*entry_offset) == Some(0x0112))
+        .and_then(|entry_offset| read_u16(entry_offset + 8))
+        .filter(|orientation| (1..=8).contains(orientation))
+}
+
+/// Returns the transformation matrix which maps the unit square, onto which the stored pixels of an image are
+/// drawn, to the same square, but with the pixels rotated or flipped according to the given EXIF orientation.
+fn orientation_matrix(orientation: u16) -> [f32; 6] {
+    match orientation {
+        2 => [-1.0, 0.0, 0.0, 1.0, 1.0, 0.0],  // Flipped horizontally
+        3 => [-1.0, 0.0, 0.0, -1.0, 1.0, 1.0], // Rotated by 180 degrees
+        4 => [1.0, 0.0, 0.0, -1.0, 0.0, 1.0],  // Flipped vertically
+        5 => [0.0, -1.0, -1.0, 0.0, 1.0, 1.0], // Flipped along the diagonal from the top-left corner
+        6 => [0.0, -1.0, 1.0, 0.0, 0.0, 1.0],  // Rotated clockwise by 90 degrees
+        7 => [0.0, 1.0, 1.0, 0.0, 0.0, 0.0], // Flipped along the diagonal from the top-right corner
+        8 => [0.0, 1.0, -1.0, 0.0, 1.0, 0.0], // Rotated counterclockwise by 90 degrees
+        _ => [1.0, 0.0, 0.0, 1.0, 0.0, 0.0],
+    }
+}
+
 /// `XObject`s are parts of the PDF specification. They allow for complex behavior to be
 /// inserted into the PDF document: this comprises bookmarks, annotations and even images.
 /// My implementation is only partial as it allows only for images.
-#[allow(dead_code)]
 #[derive(Debug, Clone)]
 enum XObject {
     /// The `XObject` interface for an image. It can be converted into a `lopdf::Object`.
@@ -458,9 +754,28 @@ enum XObject {
 impl From<XObject> for lopdf::Object {
     fn from(value: XObject) -> Self {
         match value {
-            // TODO(ghovax): The conversion from an `XObject` to a PDF object is not yet implemented.
-            XObject::Image(_) => {
-                unimplemented!()
+            XObject::Image(image) => {
+                let mut dictionary = lopdf::Dictionary::new();
+                dictionary.set("Type", Object::Name(b"XObject".to_vec()));
+                dictionary.set("Subtype", Object::Name(b"Image".to_vec()));
+                dictionary.set("Width", image.width as i64);
+                dictionary.set("Height", image.height as i64);
+                dictionary.set("ColorSpace", Object::Name(image.color_space.into()));
+                dictionary.set("BitsPerComponent", image.bits_per_component as i64);
+                dictionary.set("Interpolate", image.interpolate);
+                if let Some(filter) = image.filter {
+                    dictionary.set("Filter", Object::Name(filter.into()));
+                }
+                if let Some(soft_mask) = image.soft_mask {
+                    dictionary.set("SMask", Object::Reference(soft_mask));
+                }
+
+                let mut stream = lopdf::Stream::new(dictionary, image.image_data);
+                // The data which already has a filter is left as it is, while the raw pixels are compressed,
+                // or left uncompressed in the unlikely case that the compression fails, which is still valid
+                let _ = stream.compress();
+
+                Object::Stream(stream)
             }
         }
     }
@@ -472,7 +787,6 @@ struct XObjectReference(String);
 
 impl XObjectReference {
     /// Creates a new reference for an `XObject` from a number.
-    #[allow(dead_code)]
     fn new(index: usize) -> Self {
         Self(format!("X{index}"))
     }
@@ -480,7 +794,7 @@ impl XObjectReference {
 
 /// The association between the `XObject`s properties and the actual `XObject`s themselves.
 #[derive(Default, Debug, Clone)]
-struct XObjectMap(HashMap<String, XObject>);
+struct XObjectMap(BTreeMap<String, XObject>);
 
 impl XObjectMap {
     /// Inserts the `XObject`s into the document, simultaneously constructing a PDF dictionary of them.
@@ -544,6 +858,8 @@ struct PdfResources {
     xobjects: XObjectMap,
     /// Layers / optional content ("Properties") in the resource dictionary.
     ocg_layers: OcgLayersMap,
+    /// The resources registered via `PdfDocument::register_page_resource`, by their category such as `ExtGState`.
+    raw_resources: BTreeMap<String, lopdf::Dictionary>,
 }
 
 impl PdfResources {
@@ -588,6 +904,18 @@ impl PdfResources {
             dictionary.set("XObject", lopdf::Object::Dictionary(xobjects_dictionary));
         }
 
+        // Add the registered resources to the categories, merging them with the `XObject`s if needed
+        for (category, resources) in &self.raw_resources {
+            match dictionary.get_mut(category.as_bytes()) {
+                Ok(lopdf::Object::Dictionary(category_dictionary)) => {
+                    for (name, resource) in resources.iter() {
+                        category_dictionary.set(name.clone(), resource.clone());
+                    }
+                }
+                _ => dictionary.set(category.clone(), resources.clone()),
+            }
+        }
+
         // Finally, return the constructed dictionary and the OCG references for later usage
         (dictionary, ocg_references)
     }
@@ -599,14 +927,19 @@ impl PdfResources {
 struct PdfPage {
     /// The index of the page in the document.
     number: usize,
-    /// Page width in millimeters.
+    /// Page width in points.
     width: f32,
-    /// Page height in millimeters.
+    /// Page height in points.
     height: f32,
     /// Page layers.
     layers: Vec<PdfLayer>,
     /// Resources used in this page.
     resources: PdfResources,
+    /// The statistics of the text written onto the page.
+    stats: ConversionStats,
+    /// The identifiers of the fonts with which some text has been written onto the page, since only the fonts
+    /// used by some page are embedded.
+    used_fonts: BTreeSet<String>,
     /// Extend the page with custom ad-hoc attributes, as an escape hatch to the low level lopdf library.
     /// Can be used to add annotations to a page.
     /// If your dictionary is wrong it will produce a broken PDF without warning or useful messages.
@@ -614,20 +947,21 @@ struct PdfPage {
 }
 
 impl PdfPage {
-    /// Iterates over all the layers in order to construct the dictionary for the PDF resources
-    /// and the PDF streams contained into the page so that they can be inserted in to the document.
-    /// Returns the dictionary of the resources and the vector containing all the streams associated
-    /// to the layers.
+    /// Iterates over all the layers in order to construct the dictionary for the PDF resources and the content
+    /// stream of the page, so that they can be inserted in to the document. Returns the dictionary of the
+    /// resources, while the operations of all the layers are encoded one after the other into the given buffer.
     ///
     /// # Arguments
     ///
     /// * `inner_document` - The underlying PDF document.
     /// * `layers` - The layers to be iterated over.
-    fn collect_resources_and_streams(
-        &mut self,
+    /// * `content` - The buffer onto which the content stream of the page is written.
+    fn collect_resources_and_content(
+        &self,
         inner_document: &mut lopdf::Document,
         layers: &[(usize, lopdf::Object)],
-    ) -> Result<(lopdf::Dictionary, Vec<lopdf::Stream>), ContextError> {
+        content: &mut Vec<u8>,
+    ) -> Result<lopdf::Dictionary, ContextError> {
         // Collects all the objects present in the given layers
         let current_layers = layers.iter().map(|layer| layer.1.clone()).collect();
         // Collect the resources dictionary and the references to the OCG from the resources of the page,
@@ -636,61 +970,53 @@ impl PdfPage {
             .resources
             .with_document_and_layers(inner_document, current_layers);
 
-        let mut layer_streams = Vec::<lopdf::Stream>::new();
-        use lopdf::content::Operation;
-        use lopdf::Object::*;
-
-        for (index, layer) in self.layers.iter_mut().enumerate() {
-            // Push OCG and q to the beginning of the layer
-            // In the PDF specification the q/Q operator is an operator which creates an isolated graphics state block
-            // In our case we are creating one with no state
-            layer.operations.insert(0, Operation::new("q", vec![]));
-            // In the PDF specification the BDC operator represents the beginning of a marked-content sequence
-            layer.operations.insert(
-                0,
-                Operation::new(
-                    "BDC",
-                    vec![
-                        // In the PDF specification the OC operand relates to optional content
-                        Name("OC".into()),
-                        Name(
-                            ocg_references
-                                .get(index)
-                                .ok_or(ContextError::with_context(
-                                    "Unable to find the index in the OCG references",
-                                ))?
-                                .0
-                                .clone()
-                                .into(),
-                        ),
-                    ],
-                ),
-            );
-
-            // Push OCG END and Q to the end of the layer stream
-            layer.operations.push(Operation::new("Q", vec![]));
-            layer.operations.push(Operation::new("EMC", vec![]));
-
-            let layer_stream = layer.clone().into();
-            layer_streams.push(layer_stream);
+        // Reserve room for the operations of all the layers at once, assuming a few tens of bytes for each of them
+        let operation_count = self
+            .layers
+            .iter()
+            .map(|layer| layer.operations.len() + 4)
+            .sum::<usize>();
+        content.reserve(operation_count * ENCODED_OPERATION_SIZE_HINT);
+
+        for (index, layer) in self.layers.iter().enumerate() {
+            let ocg_reference = ocg_references.get(index).ok_or(
+                ContextError::with_context("Unable to find the index in the OCG references")
+                    .with_kind(ErrorKind::PageIndexOutOfRange),
+            )?;
+            layer.encode_into(&ocg_reference.0, content)?;
         }
 
-        Ok((resource_dictionary, layer_streams))
+        Ok(resource_dictionary)
     }
 }
 
+/// The approximate size in bytes of an encoded object of the PDF document, apart from the content of the streams,
+/// employed in order to allocate the saved document at once.
+const ENCODED_OBJECT_SIZE_HINT: usize = 128;
+
+/// The approximate size in bytes of an encoded content stream operation, such as `/F15 12 Tf`, employed in order to
+/// allocate the content streams of the pages at once.
+const ENCODED_OPERATION_SIZE_HINT: usize = 24;
+
 /// Converts millimeters to points. This function is used in order to present the data
 /// in the format required by the PDF specification, while the end user might want to work in
 /// millimeters which are easier to reason about.
-fn millimeters_to_points(millimeters: f32) -> f32 {
+pub(crate) fn millimeters_to_points(millimeters: f32) -> f32 {
     millimeters * 2.834646
 }
 
+/// Converts points to millimeters, which is the inverse of `millimeters_to_points`. This function is used
+/// when the font size, which is expressed in points, needs to be compared to positions in millimeters.
+pub(crate) fn points_to_millimeters(points: f32) -> f32 {
+    points / 2.834646
+}
+
 /// This struct represents the actual PDF document on a high-level. It is an interface to the actual underlying
 /// `lopdf::document` with the addition of the PDF pages, the document ID and the fonts used in the document.
 ///
 /// Various convenience functions are exposed for this struct, such as `add_page_with_layer`, `add_font`,
 /// `write_text_to_layer_in_page`, `save_to_bytes`, which make the creation of a PDF document very much simplified.
+#[derive(Clone)]
 pub struct PdfDocument {
     /// The association between the fonts ID, the object it is represented by and its face data.
     fonts: BTreeMap<String, (lopdf::ObjectId, Font)>,
@@ -701,6 +1027,91 @@ pub struct PdfDocument {
     pub identifier: String,
     /// The pages of the PDF document.
     pages: Vec<PdfPage>,
+    /// The warnings raised while building the PDF document.
+    warnings: Vec<Warning>,
+    /// The statistics about the embedded fonts, collected while writing the PDF document, while the ones about
+    /// the text are collected by each page.
+    stats: ConversionStats,
+    /// Whether the problems which are otherwise reported as warnings, such as the characters missing from their font
+    /// or the glyphs without width, are errors instead. This is meant for the documents, such as legal or financial
+    /// ones, in which no content may be silently left out.
+    pub strict: bool,
+    /// The title written into the metadata of the PDF document, which is `Unknown` when it is not set.
+    pub title: Option<String>,
+    /// The author written into the metadata of the PDF document, which is `Unknown` when it is not set.
+    pub author: Option<String>,
+    /// The creation and modification date written into the metadata of the PDF document. When it is not set, the
+    /// time at which the document is written is used, unless the document is `deterministic`.
+    pub timestamp: Option<OffsetDateTime>,
+    /// Whether the Unix epoch is used as the date of the document when it has no timestamp, rather than the time at
+    /// which it is written, so that the same document is always written into the same bytes, as the regression
+    /// tests expect.
+    pub deterministic: bool,
+    /// The database of the fonts of the document with which the text of the SVG images is converted into paths,
+    /// which is built the first time an SVG image is added and reused until another font is added.
+    pub(crate) svg_font_database: Option<Arc<resvg::usvg::fontdb::Database>>,
+}
+
+/// A summary of the structure of a `PdfDocument` while it is built, before it is written and saved, as returned by
+/// `PdfDocument::summary`. Unlike `PdfInfo`, which reads back a saved PDF file, it shows what the document holds in
+/// memory, and it can be serialized for debugging the services which lay out the documents.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PdfDocumentSummary {
+    /// The identifier of the document.
+    pub identifier: String,
+    /// Whether the problems which are otherwise reported as warnings are errors instead.
+    pub strict: bool,
+    /// The pages of the document, in their order.
+    pub pages: Vec<PdfPageSummary>,
+    /// The fonts added to the document, in the order given by their font index.
+    pub fonts: Vec<PdfFontSummary>,
+    /// The warnings raised so far.
+    pub warnings: Vec<Warning>,
+    /// The statistics of the document built so far.
+    pub stats: ConversionStats,
+}
+
+/// A page of a `PdfDocument`, see `PdfDocumentSummary`.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PdfPageSummary {
+    /// The width of the page in millimeters.
+    pub width: f32,
+    /// The height of the page in millimeters.
+    pub height: f32,
+    /// The layers of the page, in their order.
+    pub layers: Vec<PdfLayerSummary>,
+    /// The identifiers of the fonts with which some text has been written onto the page, such as `F0`.
+    pub used_fonts: Vec<String>,
+    /// The statistics of the text written onto the page.
+    pub stats: ConversionStats,
+}
+
+/// A layer of a page of a `PdfDocument`, see `PdfDocumentSummary`.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PdfLayerSummary {
+    /// The name of the layer.
+    pub name: String,
+    /// The number of content stream operations of the layer.
+    pub operation_count: usize,
+    /// The number of content stream operations of the layer for each of their operators, such as `Tj` or `re`.
+    pub operator_counts: BTreeMap<String, usize>,
+}
+
+/// A font of a `PdfDocument`, see `PdfDocumentSummary`.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PdfFontSummary {
+    /// The identifier of the font face, such as `F0`, by which the pages refer to the font.
+    pub identifier: String,
+    /// The number of glyphs of the font.
+    pub glyph_count: u16,
+    /// The size in bytes of the font file.
+    pub data_size: usize,
+    /// Whether some text has been written with the font, in which case it is embedded into the document.
+    pub is_used: bool,
 }
 
 impl PdfDocument {
@@ -716,6 +1127,14 @@ impl PdfDocument {
             inner_document: lopdf::Document::with_version("1.5"),
             identifier: pdf_document_identifier,
             pages: Vec::new(),
+            warnings: Vec::new(),
+            stats: ConversionStats::default(),
+            strict: false,
+            title: None,
+            author: None,
+            timestamp: None,
+            deterministic: false,
+            svg_font_database: None,
         }
     }
 
@@ -736,6 +1155,8 @@ impl PdfDocument {
             height: millimeters_to_points(page_height),
             layers: Vec::new(), // The layer will be later added
             resources: PdfResources::default(),
+            stats: ConversionStats::default(),
+            used_fonts: BTreeSet::new(),
             extend_with: None, // NOTE(ghovax): This could be actually further on inserted, but it's not clear how even from the original author's work.
         };
 
@@ -753,6 +1174,27 @@ impl PdfDocument {
         (page_index, layer_index_in_page)
     }
 
+    /// Removes everything which has been written onto the given page, keeping its size and its layers, so that
+    /// the page can be drawn again from scratch. The warnings raised while writing onto the page and its
+    /// contribution to the statistics are removed as well.
+    ///
+    /// # Arguments
+    ///
+    /// * `page_index` - The index of the page to be cleared (should be previously obtained).
+    pub fn clear_page(&mut self, page_index: usize) -> Result<(), ContextError> {
+        let page = self.get_mut_page(page_index)?;
+        for layer in &mut page.layers {
+            layer.operations.clear();
+        }
+        page.stats = ConversionStats::default();
+        page.used_fonts.clear();
+        self.warnings.retain(|warning| {
+            !matches!(warning, Warning::MissingCharacters { page_index: warning_page_index, .. } if *warning_page_index == page_index)
+        });
+
+        Ok(())
+    }
+
     /// Add a font from the given path to the document. This function expects the font to be TTF, or either way
     /// an OTF font which is just a wrapper around a TTF font. If successful, the function returns
     /// the index of the font which is then to be used in order to write text via the `write_text_to_layer_in_page` function.
@@ -764,20 +1206,40 @@ impl PdfDocument {
         // Load the bytes associated to the font from the given path
         let font_bytes = std::fs::read(font_path).map_err(|error| {
             ContextError::with_error("Failed to read font, probably the path is wrong", &error)
+                .with_kind(ErrorKind::FontNotFound)
         })?;
 
-        // Parse the font face from the given data and then construct the font
-        let ttf_font_face = TtfFontFace::from_bytes(&font_bytes)
-            .map_err(|error| ContextError::with_error("Failed to parse font", &error))?;
+        self.add_font_from_bytes(font_bytes)
+    }
+
+    /// Add a font from the given bytes of a TTF/OTF font file to the document, just like `add_font` does
+    /// for a font file. This is useful when the same fonts are added to many documents, so that the font
+    /// files are only read once (see `FontRegistry`): the bytes given as an `Arc<[u8]>` are shared with the
+    /// other documents rather than copied, until the font is embedded.
+    ///
+    /// # Arguments
+    ///
+    /// * `font_bytes` - The content of the TTF/OTF font file.
+    pub fn add_font_from_bytes(
+        &mut self,
+        font_bytes: impl Into<Arc<[u8]>>,
+    ) -> Result<usize, ContextError> {
+        // Check that the font face can be parsed from the given data and then construct the font
+        let font_bytes = font_bytes.into();
+        TtfFontFace::from_bytes(&font_bytes).map_err(|error| {
+            ContextError::with_error("Failed to parse font", &error)
+                .with_kind(ErrorKind::InvalidFont)
+        })?;
         let font = Font {
             bytes: font_bytes,
-            ttf_face: ttf_font_face,
             face_identifier: format!("F{}", self.fonts.len()),
+            glyph_id_cache: HashMap::new(),
         };
         // Inserts the object into the fonts of the PDF document, to be later processed
         let font_object_id = self.inner_document.new_object_id();
         self.fonts
             .insert(font.face_identifier.clone(), (font_object_id, font.clone()));
+        self.svg_font_database = None;
 
         let font_index = self.fonts.len() - 1;
         // Return the font index
@@ -797,6 +1259,7 @@ impl PdfDocument {
     /// * `font_index` - The index of the font to be used when writing the text (should be previously obtained).
     /// * `font_size` - The size of the font.
     /// * `caret_position` - The position in millimeters where the text should begin to be drawn.
+    /// * `letter_spacing` - The additional space in points inserted after each character, usually zero.
     ///
     /// This function might appear to have too many arguments, but this is on purpose in order to keep the
     /// API or this library quite on the simpler side. Any external algorithm for layouting text should
@@ -807,13 +1270,53 @@ impl PdfDocument {
         page_index: usize,
         layer_index: usize,
         color: [f32; 3],
-        text: String,
+        text: &str,
         font_index: usize,
         font_size: f32,
         caret_position: [f32; 2],
+        letter_spacing: f32,
     ) -> Result<(), ContextError> {
-        // Retrieve the font at the given font index
-        let font = self.get_font(font_index)?.1.clone(); // TODO: I shouldn't have to clone the font data
+        // Retrieve the font at the given font index, then the glyph ID of each character of the text from it
+        let font = &mut self.get_mut_font(font_index)?.1;
+        let face_identifier = font.face_identifier.clone();
+        let mut glyph_id_list = Vec::<u16>::new();
+        let mut missing_characters = String::new();
+        let mut dropped_glyph_count = 0;
+        // Normalize the text in the NFC form before processing
+        for character in text.nfc() {
+            if let Some(glyph_id) = font.glyph_id(character) {
+                glyph_id_list.push(glyph_id);
+            } else {
+                // Otherwise, if the character is not present in the font, log the event and collect it for the warning
+                tracing::warn!(
+                    page_index,
+                    font_index,
+                    "Unable to find the character {:?} in the font",
+                    character
+                );
+                if !missing_characters.contains(character) {
+                    missing_characters.push(character);
+                }
+                dropped_glyph_count += 1;
+            }
+        }
+        let page = self.get_mut_page(page_index)?;
+        page.stats.dropped_glyph_count += dropped_glyph_count;
+        page.used_fonts.insert(face_identifier.clone());
+        if !missing_characters.is_empty() {
+            if self.strict {
+                return Err(ContextError::with_context(format!(
+                    "The characters {:?} are missing from the font {}",
+                    missing_characters, font_index
+                ))
+                .with_kind(ErrorKind::MissingGlyph));
+            }
+            self.warnings.push(Warning::MissingCharacters {
+                page_index,
+                font_index,
+                characters: missing_characters,
+            });
+        }
 
         // Insert the required operations for writing text to the layer
         self.add_operations_to_layer_in_page(
@@ -823,7 +1326,7 @@ impl PdfDocument {
                 lopdf::content::Operation::new("BT", vec![]), // Begin text section
                 lopdf::content::Operation::new(
                     "Tf",
-                    vec![font.face_identifier.clone().into(), (font_size).into()],
+                    vec![face_identifier.into(), (font_size).into()],
                 ), // Set the font and the font size
                 lopdf::content::Operation::new("Td", {
                     let [x, y] = caret_position;
@@ -839,19 +1342,23 @@ impl PdfDocument {
                 // Set the filling color of the text
             ],
         )?;
-
-        let mut glyph_id_list = Vec::<u16>::new();
-        // Normalize the text in the NFC form before processing
-        for character in text.nfc() {
-            // Retrieve the glyph ID of each character from the font
-            if let Some(glyph_id) = font.ttf_face.glyph_id(character) {
-                glyph_id_list.push(glyph_id);
-            } else {
-                // Otherwise, if the character is not present in the font, log the event
-                log::warn!("Unable to find the character {:?} in the font", character)
-            }
+        // The character spacing is part of the text state, which persists across text sections,
+        // so it is only set when needed and reset once the text has been written
+        if letter_spacing != 0.0 {
+            self.add_operations_to_layer_in_page(
+                layer_index,
+                page_index,
+                vec![lopdf::content::Operation::new(
+                    "Tc",
+                    vec![letter_spacing.into()],
+                )],
+            )?;
         }
 
+        let page = self.get_mut_page(page_index)?;
+        page.stats.text_run_count += 1;
+        page.stats.emitted_glyph_count += glyph_id_list.len();
+
         // Convert each glyph ID into the required byte format which is accepted by the PDF specification
         let glyph_id_bytes = glyph_id_list
             .iter()
@@ -870,6 +1377,14 @@ impl PdfDocument {
             )],
         )?;
 
+        if letter_spacing != 0.0 {
+            self.add_operations_to_layer_in_page(
+                layer_index,
+                page_index,
+                vec![lopdf::content::Operation::new("Tc", vec![0.into()])],
+            )?;
+        }
+
         // Finalize the writing operation by including the text ending section
         self.add_operations_to_layer_in_page(
             layer_index,
@@ -881,6 +1396,253 @@ impl PdfDocument {
         Ok(())
     }
 
+    /// Paints the whole page with the given color by drawing a filled rectangle at the beginning of the given layer,
+    /// so that it lies beneath any content that has been or will be written onto the layer. Pages which are not painted
+    /// are left transparent, which most PDF viewers display as white.
+    ///
+    /// # Arguments
+    ///
+    /// * `page_index` - The index of the page to paint (should be previously obtained).
+    /// * `layer_index` - The index of the layer to paint the background onto (should be previously obtained).
+    /// * `color` - The RGB color employed for filling the page.
+    pub fn paint_page_background(
+        &mut self,
+        page_index: usize,
+        layer_index: usize,
+        color: [f32; 3],
+    ) -> Result<(), ContextError> {
+        // Retrieve the dimensions of the page, which are already expressed in points
+        let (page_width, page_height) = self
+            .pages
+            .get(page_index)
+            .map(|page| (page.width, page.height))
+            .ok_or(
+                ContextError::with_context(format!(
+                    "Failed to find the page with index {}",
+                    page_index
+                ))
+                .with_kind(ErrorKind::PageIndexOutOfRange),
+            )?;
+
+        let [r, g, b] = color;
+        let background_operations = vec![
+            // Isolate the graphics state so that the filling color doesn't leak onto the following contents
+            lopdf::content::Operation::new("q", vec![]),
+            lopdf::content::Operation::new(
+                "rg",
+                vec![r, g, b].into_iter().map(lopdf::Object::Real).collect(),
+            ),
+            // Draw a rectangle covering the whole page and fill it
+            lopdf::content::Operation::new(
+                "re",
+                vec![0.into(), 0.into(), page_width.into(), page_height.into()],
+            ),
+            lopdf::content::Operation::new("f", vec![]),
+            lopdf::content::Operation::new("Q", vec![]),
+        ];
+
+        // Insert the operations at the beginning of the layer, beneath everything else
+        let pdf_layer_reference = self.get_mut_layer_in_page(layer_index, page_index)?;
+        pdf_layer_reference
+            .operations
+            .splice(0..0, background_operations);
+
+        Ok(())
+    }
+
+    /// Fills the given path with its color on the given layer of the specified page. The path is drawn on top of
+    /// the content which has already been written onto the layer.
+    ///
+    /// # Arguments
+    ///
+    /// * `page_index` - The index of the page to draw the path onto (should be previously obtained).
+    /// * `layer_index` - The index of the layer to draw the path onto (should be previously obtained).
+    /// * `path` - The path to be filled, with its coordinates expressed in millimeters.
+    pub fn fill_path_in_layer_in_page(
+        &mut self,
+        page_index: usize,
+        layer_index: usize,
+        path: &FilledPath,
+    ) -> Result<(), ContextError> {
+        use lopdf::content::Operation;
+
+        let point_to_operands = |[x, y]: [f32; 2]| {
+            vec![
+                millimeters_to_points(x).into(),
+                millimeters_to_points(y).into(),
+            ]
+        };
+
+        let [r, g, b] = path.color;
+        // Isolate the graphics state so that the filling color doesn't leak onto the following contents
+        let mut path_operations = vec![
+            Operation::new("q", vec![]),
+            Operation::new(
+                "rg",
+                vec![r, g, b].into_iter().map(lopdf::Object::Real).collect(),
+            ),
+        ];
+        // Construct the path segment by segment, then fill it with the nonzero winding number rule
+        for segment in path.segments.iter() {
+            path_operations.push(match *segment {
+                PathSegment::MoveTo(point) => Operation::new("m", point_to_operands(point)),
+                PathSegment::LineTo(point) => Operation::new("l", point_to_operands(point)),
+                PathSegment::CubicTo {
+                    first_control_point,
+                    second_control_point,
+                    end_point,
+                } => Operation::new(
+                    "c",
+                    [first_control_point, second_control_point, end_point]
+                        .into_iter()
+                        .flat_map(point_to_operands)
+                        .collect(),
+                ),
+                PathSegment::Close => Operation::new("h", vec![]),
+            });
+        }
+        path_operations.push(Operation::new("f", vec![]));
+        path_operations.push(Operation::new("Q", vec![]));
+
+        self.add_operations_to_layer_in_page(layer_index, page_index, path_operations)
+    }
+
+    /// Adds the given operations at the end of the given layer of the specified page, as they are. Together with
+    /// `add_raw_object` and `register_page_resource`, this allows drawing what this crate doesn't support yet,
+    /// such as transparency or patterns, without reaching into `inner_document`. The operations
+    /// are not checked, so wrong operations produce a broken PDF document.
+    ///
+    /// # Arguments
+    ///
+    /// * `page_index` - The index of the page to add the operations to (should be previously obtained).
+    /// * `layer_index` - The index of the layer to add the operations to (should be previously obtained).
+    /// * `operations` - The content stream operations, such as `/GS0 gs`.
+    pub fn add_raw_operations_to_layer_in_page(
+        &mut self,
+        page_index: usize,
+        layer_index: usize,
+        operations: Vec<lopdf::content::Operation>,
+    ) -> Result<(), ContextError> {
+        self.add_operations_to_layer_in_page(layer_index, page_index, operations)
+    }
+
+    /// Adds the given object to the PDF document as it is, returning its ID so that it can be referred to by the
+    /// other objects or registered as a resource of a page (see `register_page_resource`). The object is saved
+    /// together with the ones of the document, unless nothing refers to it and the document is optimized.
+    ///
+    /// # Arguments
+    ///
+    /// * `object` - The object to be added, such as a dictionary or a stream.
+    pub fn add_raw_object(&mut self, object: impl Into<lopdf::Object>) -> lopdf::ObjectId {
+        self.inner_document.add_object(object)
+    }
+
+    /// Registers the object with the given ID as a resource of the specified page, under the given category and
+    /// name, so that the operations of the page can refer to it by its name (see
+    /// `add_raw_operations_to_layer_in_page`). The resource is added to the resource dictionary of the page when
+    /// the page is written, replacing any resource of the same category with the same name. The fonts and the layers of the page are managed by the
+    /// document, so their categories can't be registered.
+    ///
+    /// # Arguments
+    ///
+    /// * `page_index` - The index of the page the resource belongs to (should be previously obtained).
+    /// * `category` - The category of the resource in the resource dictionary, such as `ExtGState` or `XObject`.
+    /// * `name` - The name by which the operations of the page refer to the resource, such as `GS0`.
+    /// * `object_id` - The ID of the object, usually obtained via `add_raw_object`.
+    pub fn register_page_resource(
+        &mut self,
+        page_index: usize,
+        category: &str,
+        name: &str,
+        object_id: lopdf::ObjectId,
+    ) -> Result<(), ContextError> {
+        if matches!(category, "Font" | "Properties") {
+            return Err(ContextError::with_context(format!(
+                "Unable to register a resource of the category {:?}, which is managed by the document",
+                category
+            ))
+            .with_kind(ErrorKind::InvalidDocument));
+        }
+        self.get_mut_page(page_index)?
+            .resources
+            .raw_resources
+            .entry(category.to_string())
+            .or_default()
+            .set(name, lopdf::Object::Reference(object_id));
+
+        Ok(())
+    }
+
+    /// Draws the image of the given JPEG or PNG file onto the specified layer of the page, stretched to fill the
+    /// given rectangle. The JPEG images are embedded as they are, without being decoded and compressed again, and
+    /// are rotated or flipped according to their EXIF orientation, while the alpha channel of the PNG images is
+    /// embedded as a soft mask, so that their transparent parts let the content beneath them show through.
+    ///
+    /// # Arguments
+    ///
+    /// * `page_index` - The index of the page onto which the image is drawn (should be previously obtained).
+    /// * `layer_index` - The index of the layer of the page onto which the image is drawn.
+    /// * `image_bytes` - The content of the JPEG or PNG file.
+    /// * `position` - The position of the bottom-left corner of the image in millimeters.
+    /// * `size` - The width and the height of the image on the page in millimeters, once it has been oriented.
+    pub fn add_image_to_layer_in_page(
+        &mut self,
+        page_index: usize,
+        layer_index: usize,
+        image_bytes: &[u8],
+        position: [f32; 2],
+        size: [f32; 2],
+    ) -> Result<(), ContextError> {
+        let (mut image, soft_mask) = ImageXObject::from_bytes(image_bytes)?;
+        // The layer is looked up beforehand, so that no soft mask is added for an image which is not drawn
+        self.get_mut_layer_in_page(layer_index, page_index)?;
+        if let Some(soft_mask) = soft_mask {
+            image.soft_mask = Some(
+                self.inner_document
+                    .add_object(lopdf::Object::from(XObject::Image(soft_mask))),
+            );
+        }
+
+        let xobjects = &mut self.get_mut_page(page_index)?.resources.xobjects;
+        let xobject_reference = XObjectReference::new(xobjects.0.len());
+        xobjects
+            .0
+            .insert(xobject_reference.0.clone(), XObject::Image(image));
+
+        // The photos are often stored sideways, together with the orientation in which they are to be displayed
+        let orientation = image_bytes
+            .starts_with(&[0xFF, 0xD8])
+            .then(|| jpeg_exif_orientation(image_bytes))
+            .flatten()
+            .unwrap_or(1);
+        let [a, b, c, d, e, f] = orientation_matrix(orientation);
+        let [x, y] = position.map(millimeters_to_points);
+        let [width, height] = size.map(millimeters_to_points);
+        self.add_operations_to_layer_in_page(
+            layer_index,
+            page_index,
+            vec![
+                lopdf::content::Operation::new("q", vec![]), // Save the graphics state
+                lopdf::content::Operation::new(
+                    "cm",
+                    vec![
+                        (a * width).into(),
+                        (b * height).into(),
+                        (c * width).into(),
+                        (d * height).into(),
+                        (e * width + x).into(),
+                        (f * height + y).into(),
+                    ],
+                ), // Orient the unit square, onto which images are drawn, and scale it to the rectangle of the image
+                lopdf::content::Operation::new(
+                    "Do",
+                    vec![lopdf::Object::Name(xobject_reference.0.into_bytes())],
+                ), // Draw the image
+                lopdf::content::Operation::new("Q", vec![]), // Restore the graphics state
+            ],
+        )
+    }
+
     /// Write the operations so far specified to the PDF file and finalize it.
     ///
     /// # Disclaimer
@@ -889,37 +1651,144 @@ impl PdfDocument {
     /// 32 characters-long string. Also, saving the PDF to an actual document is a complicated process, so I recommend
     /// end-users of this library to even tinker with this function and adapt it to their needs.
     /// The output of this function is not optimized and should be fed into either ghostscript or `ps2pdf`.
+    ///
+    /// The output is deterministic: the same operations with the same fonts, instance ID and timestamp are written
+    /// into the same bytes on every run and on every platform, since every collection whose order ends up in the file,
+    /// such as the glyphs of the CMaps, is ordered (see `PdfDocument::deterministic` for the documents without a
+    /// timestamp).
     pub fn write_all(&mut self, instance_id: String) -> Result<(), ContextError> {
         use lopdf::Object::*;
-        use lopdf::StringFormat::*;
 
         // Construct all the general info that the PDF document needs in order to be parsed correctly
         // and insert it into the PDF document itself
-        // TODO(ghovax): The user might want to choose all these parameters.
-        let document_info = lopdf::Dictionary::from_iter(vec![
-            ("Trapped", "False".into()),
-            (
-                "CreationDate",
-                String(
-                    to_pdf_timestamp_format(&OffsetDateTime::UNIX_EPOCH).into_bytes(),
-                    Literal,
-                ),
+        let document_info_id = self
+            .inner_document
+            .add_object(Dictionary(self.document_info()));
+
+        // Construct the catalog, required by the PDF specification
+        let pages_id = self.inner_document.new_object_id();
+
+        // Begin constructing the pages dictionary
+        let mut pages = lopdf::Dictionary::from_iter(vec![
+            ("Type", "Pages".into()),
+            ("Count", Integer(self.pages.len() as i64)),
+        ]);
+
+        let (intent_array_id, usage_ocg_dictionary_id) = self.insert_ocg_intent_and_usage();
+
+        // For each page in our PDF document, retrieve the number of the page and insert the OCG dictionaries
+        // of the layers composing it in order to construct the OCG list
+        let ocg_association: Vec<(usize, Vec<(usize, lopdf::Object)>)> = (0..self.pages.len())
+            .map(|page_index| {
+                (
+                    self.pages[page_index].number,
+                    self.insert_ocg_dictionaries(
+                        page_index,
+                        intent_array_id,
+                        usage_ocg_dictionary_id,
+                    ),
+                )
+            })
+            .collect();
+
+        // For each layer present in the OCG association just constructed, retrieve each object
+        let ocg_dictionary_references: Vec<lopdf::Object> = ocg_association
+            .iter()
+            .flat_map(|(_, layers)| {
+                layers
+                    .iter()
+                    .map(|(_, dictionary_reference)| dictionary_reference.clone())
+            })
+            .collect();
+
+        // Save the catalog, updated with the OCGs just inserted into the document, into the PDF document
+        let catalog_id = self
+            .inner_document
+            .add_object(catalog(pages_id, ocg_dictionary_references));
+
+        self.inner_document
+            .trailer
+            .set("Root", Reference(catalog_id));
+        self.inner_document
+            .trailer
+            .set("Info", Reference(document_info_id));
+        self.inner_document
+            .trailer
+            .set("ID", self.identifiers(&instance_id));
+
+        // Load the set fonts and insert them into the PDF document
+        let fonts_dictionary = self.insert_fonts_into_document()?;
+        let fonts_dictionary_id = self.inner_document.add_object(fonts_dictionary);
+
+        let mut page_ids = Vec::<lopdf::Object>::new();
+
+        // For each page present in the document...
+        for index in 0..self.pages.len() {
+            // Collect the layers of the OCG associated to the current document page
+            // The pages are numbered from one, so the number is compared without subtracting from it, which would
+            // overflow on a page numbered zero
+            let unmerged_layer = ocg_association.iter().find(|ocg| ocg.0 == index + 1).ok_or_else(|| {
+                // If this operation fails, return an error with context
+                let page_numbers = ocg_association.iter().map(|ocg| ocg.0).collect::<Vec<_>>();
+                ContextError::with_context(
+                    format!("Unable to collect the resources needed for rendering the page: can't find the page number {:?} in {:?}", index + 1, page_numbers),
+                ).with_kind(ErrorKind::PageIndexOutOfRange)
+            })?;
+
+            // Inserts the page into the document and save the associated reference
+            let page_id = self.insert_page_into_document(
+                index,
+                &unmerged_layer.1,
+                pages_id,
+                fonts_dictionary_id,
+            )?;
+            page_ids.push(Reference(page_id))
+        }
+
+        // Use all the collected page references in order to set the "Kids" field of the PDF document
+        // and then insert the pages dictionary into the document itself as a last operation
+        pages.set::<_, lopdf::Object>("Kids".to_string(), page_ids.into());
+        self.inner_document
+            .objects
+            .insert(pages_id, Dictionary(pages));
+
+        Ok(())
+    }
+
+    /// Constructs the document information dictionary, which holds the metadata of the PDF document.
+    fn document_info(&self) -> lopdf::Dictionary {
+        use lopdf::Object::*;
+        use lopdf::StringFormat::*;
+
+        let timestamp = self.timestamp.unwrap_or_else(|| {
+            if self.deterministic {
+                OffsetDateTime::UNIX_EPOCH
+            } else {
+                OffsetDateTime::now_utc()
+            }
+        });
+        // TODO(ghovax): The user might want to choose all these parameters.
+        lopdf::Dictionary::from_iter(vec![
+            ("Trapped", "False".into()),
+            (
+                "CreationDate",
+                String(to_pdf_timestamp_format(&timestamp).into_bytes(), Literal),
             ),
             (
                 "ModDate",
-                String(
-                    to_pdf_timestamp_format(&OffsetDateTime::UNIX_EPOCH).into_bytes(),
-                    Literal,
-                ),
+                String(to_pdf_timestamp_format(&timestamp).into_bytes(), Literal),
             ),
             (
                 "GTS_PDFX_Version",
                 String("PDF/A-3:2012".to_string().into_bytes(), Literal),
             ),
-            ("Title", String("Unknown".to_string().into_bytes(), Literal)),
+            (
+                "Title",
+                text_string(self.title.as_deref().unwrap_or("Unknown")),
+            ),
             (
                 "Author",
-                String("Unknown".to_string().into_bytes(), Literal),
+                text_string(self.author.as_deref().unwrap_or("Unknown")),
             ),
             (
                 "Creator",
@@ -938,23 +1807,25 @@ impl PdfDocument {
                 String(self.identifier.clone().into_bytes(), Literal),
             ),
             ("Keywords", String("".to_string().into_bytes(), Literal)),
-        ]);
-        let document_info_id = self.inner_document.add_object(Dictionary(document_info));
+        ])
+    }
 
-        // Construct the catalog, required by the PDF specification
-        let pages_id = self.inner_document.new_object_id();
-        let mut catalog = lopdf::Dictionary::from_iter(vec![
-            ("Type", "Catalog".into()),
-            ("PageLayout", "OneColumn".into()),
-            ("PageMode", "UseNone".into()),
-            ("Pages", Reference(pages_id)),
-        ]);
+    /// Constructs the `ID` entry of the trailer from the identifier of the document and the given instance ID.
+    fn identifiers(&self, instance_id: &str) -> lopdf::Object {
+        use lopdf::Object::*;
+        use lopdf::StringFormat::*;
 
-        // Begin constructing the pages dictionary
-        let mut pages = lopdf::Dictionary::from_iter(vec![
-            ("Type", "Pages".into()),
-            ("Count", Integer(self.pages.len() as i64)),
-        ]);
+        Array(vec![
+            String(self.identifier.clone().into_bytes(), Literal),
+            String(instance_id.as_bytes().to_vec(), Literal),
+        ])
+    }
+
+    /// Inserts into the PDF document the intents and the usage shared by the OCGs of all the layers,
+    /// returning their object IDs.
+    fn insert_ocg_intent_and_usage(&mut self) -> (lopdf::ObjectId, lopdf::ObjectId) {
+        use lopdf::Object::*;
+        use lopdf::StringFormat::*;
 
         // Construct the dictionary for clarifying the OCG usage and insert it into the PDF document
         let ocg_usage_dictionary = lopdf::Dictionary::from_iter(vec![
@@ -973,206 +1844,294 @@ impl PdfDocument {
         let intent_array = Array(vec![Name("View".into()), Name("Design".into())]);
         let intent_array_id = self.inner_document.add_object(intent_array);
 
-        let page_layer_numbers_and_names: Vec<(usize, Vec<::std::string::String>)> = self
-            .pages
-            .iter()
-            .map(|page| {
-                // For each page in our PDF document, retrieve the number of the page and the
-                // names of the layers composing it in order to construct the OCG list
-                (
-                    page.number,
-                    page.layers.iter().map(|layer| layer.name.clone()).collect(),
-                )
-            })
-            .collect();
+        (intent_array_id, usage_ocg_dictionary_id)
+    }
 
-        // For each page number and layer name in each page...
-        let ocg_association: Vec<(usize, Vec<(usize, lopdf::Object)>)> =
-            page_layer_numbers_and_names
-                .into_iter()
-                .map(|(page_index, layer_names)| {
-                    // Collect the layer index and the reference to OCG dictionary just inserted into the document
-                    let layer_indices_and_dictionary_references = layer_names
-                        .into_iter()
-                        .enumerate()
-                        .map(|(layer_index, layer_name)| {
-                            // Insert the OCG dictionary with the intents, layer name and usage into the PDF document
-                            let ocg_dictionary = lopdf::Dictionary::from_iter(vec![
-                                ("Type", Name("OCG".into())),
-                                ("Name", String(layer_name.into(), Literal)),
-                                ("Intent", Reference(intent_array_id)),
-                                ("Usage", Reference(usage_ocg_dictionary_id)),
-                            ]);
-                            let ocg_dictionary_id =
-                                self.inner_document.add_object(Dictionary(ocg_dictionary));
-
-                            (layer_index, Reference(ocg_dictionary_id))
-                        })
-                        .collect();
-
-                    // For each page index, collect the layer indices and the reference to OCG dictionaries inserted into the PDF document
-                    (page_index, layer_indices_and_dictionary_references)
-                })
-                .collect();
+    /// Inserts into the PDF document the OCG dictionary of each layer of the given page, returning the
+    /// index of each layer together with the reference to its OCG dictionary.
+    ///
+    /// # Arguments
+    ///
+    /// * `page_index` - The index of the page whose layers are inserted.
+    /// * `intent_array_id` - The object ID of the intents of the OCGs (see `insert_ocg_intent_and_usage`).
+    /// * `usage_ocg_dictionary_id` - The object ID of the usage of the OCGs (see `insert_ocg_intent_and_usage`).
+    fn insert_ocg_dictionaries(
+        &mut self,
+        page_index: usize,
+        intent_array_id: lopdf::ObjectId,
+        usage_ocg_dictionary_id: lopdf::ObjectId,
+    ) -> Vec<(usize, lopdf::Object)> {
+        use lopdf::Object::*;
+        use lopdf::StringFormat::*;
 
-        // For each layer present in the OCG association just constructed, retrieve each object
-        let ocg_dictionary_references: Vec<lopdf::Object> = ocg_association
+        self.pages[page_index]
+            .layers
             .iter()
-            .flat_map(|(_, layers)| {
-                layers
-                    .iter()
-                    .map(|(_, dictionary_reference)| dictionary_reference.clone())
+            .enumerate()
+            .map(|(layer_index, layer)| {
+                // Insert the OCG dictionary with the intents, layer name and usage into the PDF document
+                let ocg_dictionary = lopdf::Dictionary::from_iter(vec![
+                    ("Type", Name("OCG".into())),
+                    ("Name", String(layer.name.clone().into(), Literal)),
+                    ("Intent", Reference(intent_array_id)),
+                    ("Usage", Reference(usage_ocg_dictionary_id)),
+                ]);
+                let ocg_dictionary_id = self.inner_document.add_object(Dictionary(ocg_dictionary));
+
+                (layer_index, Reference(ocg_dictionary_id))
             })
-            .collect();
-
-        // Update the PDF catalog with the OCGs just inserted into the document
-        catalog.set(
-            "OCProperties",
-            Dictionary(lopdf::Dictionary::from_iter(vec![
-                ("OCGs", Array(ocg_dictionary_references.clone())),
-                (
-                    "D",
-                    Dictionary(lopdf::Dictionary::from_iter(vec![
-                        ("Order", Array(ocg_dictionary_references.clone())),
-                        ("RBGroups", Array(vec![])),
-                        ("ON", Array(ocg_dictionary_references)),
-                    ])),
-                ),
-            ])),
-        );
-
-        // Save the catalog after inserting it into the PDF document
-        let catalog_id = self.inner_document.add_object(catalog);
-
-        self.inner_document
-            .trailer
-            .set("Root", Reference(catalog_id));
-        self.inner_document
-            .trailer
-            .set("Info", Reference(document_info_id));
-        self.inner_document.trailer.set(
-            "ID",
-            Array(vec![
-                String(self.identifier.clone().into_bytes(), Literal),
-                String(instance_id.as_bytes().to_vec(), Literal),
-            ]),
-        );
+            .collect()
+    }
 
-        // Load the set fonts and insert them into the PDF document
-        let fonts_dictionary = self.insert_fonts_into_document();
-        let fonts_dictionary_id = self.inner_document.add_object(fonts_dictionary);
+    /// Inserts the given page into the PDF document, together with its resources and its content stream,
+    /// returning the object ID of the page.
+    ///
+    /// # Arguments
+    ///
+    /// * `page_index` - The index of the page to be inserted.
+    /// * `layers` - The indices of the layers of the page together with the references to their OCG dictionaries.
+    /// * `pages_id` - The object ID of the pages dictionary, which is the parent of the page.
+    /// * `fonts_dictionary_id` - The object ID of the dictionary of the embedded fonts.
+    fn insert_page_into_document(
+        &mut self,
+        page_index: usize,
+        layers: &[(usize, lopdf::Object)],
+        pages_id: lopdf::ObjectId,
+        fonts_dictionary_id: lopdf::ObjectId,
+    ) -> Result<lopdf::ObjectId, ContextError> {
+        use lopdf::Object::*;
 
-        let mut page_ids = Vec::<lopdf::Object>::new();
+        let _page_span = tracing::debug_span!("write_page", page_index).entered();
+        let page = &self.pages[page_index];
+        // Construct the dictionary which specifies all the page information
+        let mut page_dictionary = lopdf::Dictionary::from_iter(vec![
+            ("Type", "Page".into()),
+            ("Rotate", Integer(0)),
+            (
+                "MediaBox",
+                vec![0.into(), 0.into(), page.width.into(), page.height.into()].into(),
+            ),
+            (
+                "TrimBox",
+                vec![0.into(), 0.into(), page.width.into(), page.height.into()].into(),
+            ),
+            (
+                "CropBox",
+                vec![0.into(), 0.into(), page.width.into(), page.height.into()].into(),
+            ),
+            ("Annots", vec![].into()),
+            ("Parent", Reference(pages_id)),
+        ]);
 
-        // For each page present in the document...
-        for (index, page) in self.pages.iter_mut().enumerate() {
-            // Construct the dictionary which specifies all the page information
-            let mut page_dictionary = lopdf::Dictionary::from_iter(vec![
-                ("Type", "Page".into()),
-                ("Rotate", Integer(0)),
-                (
-                    "MediaBox",
-                    vec![0.into(), 0.into(), page.width.into(), page.height.into()].into(),
-                ),
-                (
-                    "TrimBox",
-                    vec![0.into(), 0.into(), page.width.into(), page.height.into()].into(),
-                ),
-                (
-                    "CropBox",
-                    vec![0.into(), 0.into(), page.width.into(), page.height.into()].into(),
-                ),
-                ("Annots", vec![].into()),
-                ("Parent", Reference(pages_id)),
-            ]);
-
-            // If present, extend the page dictionary with further settings
-            if let Some(extension) = &page.extend_with {
-                for (key, value) in extension.iter() {
-                    page_dictionary.set(key.to_vec(), value.clone())
-                }
+        // If present, extend the page dictionary with further settings
+        if let Some(extension) = &page.extend_with {
+            for (key, value) in extension.iter() {
+                page_dictionary.set(key.to_vec(), value.clone())
             }
+        }
 
-            // Collect the layers of the OCG associated to the current document page
-            let unmerged_layer = ocg_association.iter().find(|ocg| ocg.0 - 1 == index).ok_or({
-                // If this operation fails, return an error with context
-                let comparisons = ocg_association.iter().map(|ocg| ocg.0 - 1).collect::<Vec<_>>();
-                ContextError::with_context(
-                    format!("Unable to collect the resources needed for rendering the page: can't find {:?} in {:?}", index, comparisons),
-                )
-            })?;
+        // Collect the resources associated to the current layer, while merging the streams of the individual
+        // layers into one unified stream
+        let mut content = Vec::new();
+        let mut resource_dictionary =
+            page.collect_resources_and_content(&mut self.inner_document, layers, &mut content)?;
+
+        // Set the fonts for the resource associated to the current layer, insert it into the PDF document
+        // and then inserts the resource dictionary into the one for the pages
+        resource_dictionary.set("Font", Reference(fonts_dictionary_id));
+        let resources_page_id = self
+            .inner_document
+            .add_object(Dictionary(resource_dictionary));
+        page_dictionary.set("Resources", Reference(resources_page_id));
+
+        // Insert the unified stream into the PDF document as a whole by setting the "Contents" field
+        let page_content_id = self
+            .inner_document
+            .add_object(lopdf::Stream::new(lopdf::Dictionary::new(), content));
+        page_dictionary.set("Contents", Reference(page_content_id));
+
+        // Inserts the page dictionary into the document
+        Ok(self.inner_document.add_object(page_dictionary))
+    }
 
-            // Collect the streams and the resources associated to the current layer
-            let (mut resource_dictionary, layer_streams) =
-                page.collect_resources_and_streams(&mut self.inner_document, &unmerged_layer.1)?;
-
-            // Set the fonts for the resource associated to the current layer, insert it into the PDF document
-            // and then inserts the resource dictionary into the one for the pages
-            resource_dictionary.set("Font", Reference(fonts_dictionary_id));
-            let resources_page_id = self
-                .inner_document
-                .add_object(Dictionary(resource_dictionary));
-            page_dictionary.set("Resources", Reference(resources_page_id));
-
-            // Merge all streams of the individual layers into one unified stream, then
-            // it into the PDF document as a whole by setting the "Contents" field
-            let mut merged_layer_streams = Vec::<u8>::new();
-            for mut stream in layer_streams {
-                merged_layer_streams.append(&mut stream.content);
-            }
-            let merged_layer_stream =
-                lopdf::Stream::new(lopdf::Dictionary::new(), merged_layer_streams);
-            let page_content_id = self.inner_document.add_object(merged_layer_stream);
-            page_dictionary.set("Contents", Reference(page_content_id));
+    /// Optimize the PDF document without relying on external tools: the unused objects and the empty streams are
+    /// removed, the identical objects, streams included, are merged into one, and every stream which is not
+    /// compressed yet, including the embedded fonts, is compressed (see `optimize_pdf_file` for optimizing an existing PDF file).
+    pub fn optimize(&mut self) {
+        optimize_inner_document(&mut self.inner_document);
+    }
 
-            // Inserts the page dictionary into the document and save the associated reference
-            let page_id = self.inner_document.add_object(page_dictionary);
-            page_ids.push(Reference(page_id))
-        }
+    /// The warnings raised while building the PDF document so far, such as the characters which are missing from
+    /// their font when writing text, in the order in which they have been raised. The warnings about the embedded
+    /// fonts are only raised by `write_all`.
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
 
-        // Use all the collected page references in order to set the "Kids" field of the PDF document
-        // and then insert the pages dictionary into the document itself as a last operation
-        pages.set::<_, lopdf::Object>("Kids".to_string(), page_ids.into());
-        self.inner_document
-            .objects
-            .insert(pages_id, Dictionary(pages));
+    /// The statistics of the PDF document built so far, such as the number of pages and of glyphs written. The
+    /// embedded fonts are only counted by `write_all`.
+    pub fn stats(&self) -> ConversionStats {
+        let mut stats = ConversionStats {
+            page_count: self.pages.len(),
+            ..self.stats
+        };
+        for page in &self.pages {
+            stats.merge(&page.stats);
+        }
 
-        Ok(())
+        stats
     }
 
-    /// Optimize the PDF document (only superficially).
-    pub fn optimize(&mut self) {
-        self.inner_document.prune_objects();
-        self.inner_document.delete_zero_length_streams();
-        self.inner_document.renumber_objects();
-        self.inner_document.compress();
+    /// Summarizes the structure of the PDF document built so far, namely its pages with their layers and the fonts,
+    /// so that it can be inspected before it is written and saved (see `PdfDocumentSummary`). The pages which have
+    /// already been written by a `PdfStreamWriter` are reported without their layers.
+    pub fn summary(&self) -> PdfDocumentSummary {
+        let used_fonts = self
+            .pages
+            .iter()
+            .flat_map(|page| page.used_fonts.iter())
+            .collect::<BTreeSet<_>>();
+        let pages = self
+            .pages
+            .iter()
+            .map(|page| PdfPageSummary {
+                width: points_to_millimeters(page.width),
+                height: points_to_millimeters(page.height),
+                layers: page
+                    .layers
+                    .iter()
+                    .map(|layer| {
+                        let mut operator_counts = BTreeMap::new();
+                        for operation in &layer.operations {
+                            *operator_counts
+                                .entry(operation.operator.clone())
+                                .or_default() += 1;
+                        }
+                        PdfLayerSummary {
+                            name: layer.name.clone(),
+                            operation_count: layer.operations.len(),
+                            operator_counts,
+                        }
+                    })
+                    .collect(),
+                used_fonts: page.used_fonts.iter().cloned().collect(),
+                stats: ConversionStats {
+                    page_count: 1,
+                    ..page.stats
+                },
+            })
+            .collect();
+        // The fonts are sorted by their identifiers, in which the font index is not padded
+        let fonts = (0..self.fonts.len())
+            .filter_map(|font_index| self.fonts.get(&format!("F{font_index}")))
+            .map(|(_, font)| PdfFontSummary {
+                identifier: font.face_identifier.clone(),
+                glyph_count: font.ttf_face().glyph_count(),
+                data_size: font.bytes.len(),
+                is_used: used_fonts.contains(&font.face_identifier),
+            })
+            .collect();
+
+        PdfDocumentSummary {
+            identifier: self.identifier.clone(),
+            strict: self.strict,
+            pages,
+            fonts,
+            warnings: self.warnings.clone(),
+            stats: self.stats(),
+        }
     }
 
     /// Save the `PdfDocument` to bytes in order for it to be written to a file or further processed.
     pub fn save_to_bytes(&mut self) -> Result<Vec<u8>, ContextError> {
         let mut pdf_document_bytes = Vec::new();
-        let mut writer = BufWriter::new(&mut pdf_document_bytes);
-        self.inner_document.save_to(&mut writer).map_err(|error| {
-            ContextError::with_error("Error while saving the PDF document to bytes", &error)
-        })?;
-        mem::drop(writer);
+        self.save_to_buffer(&mut pdf_document_bytes)?;
 
         Ok(pdf_document_bytes)
     }
 
-    /// Converts the fonts into a dictionary and inserts them into the document.
-    fn insert_fonts_into_document(&mut self) -> lopdf::Dictionary {
+    /// Save the `PdfDocument` to bytes just like `save_to_bytes` does, but into the given buffer, whose previous
+    /// content is discarded. When many documents are saved one after the other, passing the same buffer each
+    /// time spares allocating the memory for the whole document again.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer` - The buffer into which the PDF document is written.
+    pub fn save_to_buffer(&mut self, buffer: &mut Vec<u8>) -> Result<(), ContextError> {
+        let _save_span = tracing::info_span!("save_to_bytes").entered();
+        buffer.clear();
+        buffer.reserve(self.estimated_size());
+        self.inner_document.save_to(buffer).map_err(|error| {
+            ContextError::with_error("Error while saving the PDF document to bytes", &error)
+                .with_kind(ErrorKind::PdfEncoding)
+        })
+    }
+
+    /// Estimates the size in bytes of the saved PDF document from the content of its streams, which make up
+    /// most of it, and from the number of its objects.
+    fn estimated_size(&self) -> usize {
+        self.inner_document
+            .objects
+            .values()
+            .map(|object| match object {
+                Object::Stream(stream) => stream.content.len() + ENCODED_OBJECT_SIZE_HINT,
+                _ => ENCODED_OBJECT_SIZE_HINT,
+            })
+            .sum()
+    }
+
+    /// Converts the fonts with which some text has been written into a dictionary and inserts them into the document,
+    /// while the other fonts are left out. In strict mode, an error is returned if some glyphs of the fonts have no width. The objects of the fonts are built in parallel, then inserted one after
+    /// the other in the order of the fonts, so that the document is the same regardless of the number of threads.
+    fn insert_fonts_into_document(&mut self) -> Result<lopdf::Dictionary, ContextError> {
         let mut font_dictionary = lopdf::Dictionary::new();
 
-        for (font_id, font) in self.fonts.iter_mut() {
-            let collected_font_dictionary = font.1.insert_into_document(&mut self.inner_document);
+        let used_font_ids = self
+            .pages
+            .iter()
+            .flat_map(|page| &page.used_fonts)
+            .collect::<BTreeSet<_>>();
+        let used_fonts = self
+            .fonts
+            .iter()
+            .filter(|(font_id, _)| used_font_ids.contains(font_id))
+            .collect::<Vec<_>>();
+        let fonts_objects = used_fonts
+            .par_iter()
+            .map(|(_, (_, font))| font.build_objects())
+            .collect::<Vec<_>>();
+        for ((font_id, font), font_objects) in used_fonts.into_iter().zip(fonts_objects) {
+            let collected_font_dictionary =
+                font_objects.insert_into_document(&mut self.inner_document, &mut self.warnings);
 
             self.inner_document
                 .objects
                 .insert(font.0, lopdf::Object::Dictionary(collected_font_dictionary));
             font_dictionary.set(font_id.clone(), lopdf::Object::Reference(font.0));
+
+            self.stats.embedded_font_count += 1;
+            self.stats.font_data_size += font.1.bytes.len();
+        }
+
+        // In strict mode, the glyphs which would be displayed with the default width are an error
+        if self.strict {
+            if let Some(Warning::GlyphsWithoutWidth {
+                font_name,
+                glyph_ids,
+            }) = self
+                .warnings
+                .iter()
+                .find(|warning| matches!(warning, Warning::GlyphsWithoutWidth { .. }))
+            {
+                return Err(ContextError::with_context(format!(
+                    "The glyphs {:?} of the font {} have no width",
+                    glyph_ids, font_name
+                ))
+                .with_kind(ErrorKind::MissingGlyph));
+            }
         }
-        font_dictionary
+
+        Ok(font_dictionary)
     }
 
     /// This function is responsible for adding the given operations to the specified layer and page.
@@ -1189,13 +2148,43 @@ impl PdfDocument {
     }
 
     // Retrieve the font at the given font index.
-    fn get_font(&mut self, font_index: usize) -> Result<&((u32, u16), Font), ContextError> {
-        self.fonts
-            .get(&format!("F{font_index}"))
-            .ok_or(ContextError::with_context(format!(
+    fn get_mut_font(&mut self, font_index: usize) -> Result<&mut ((u32, u16), Font), ContextError> {
+        self.fonts.get_mut(&format!("F{font_index}")).ok_or(
+            ContextError::with_context(format!(
                 "Failed to find font {} into the fonts map",
                 font_index
-            )))
+            ))
+            .with_kind(ErrorKind::FontIndexOutOfRange),
+        )
+    }
+
+    /// Retrieve the bytes of the fonts added to the document, indexed by their font index.
+    pub(crate) fn indexed_font_bytes(&self) -> Vec<Arc<[u8]>> {
+        (0..self.fonts.len())
+            .filter_map(|font_index| self.fonts.get(&format!("F{font_index}")))
+            .map(|(_, font)| font.bytes.clone())
+            .collect()
+    }
+
+    // Ensure that the given layer of the specified page exists.
+    pub(crate) fn check_layer_in_page(
+        &mut self,
+        layer_index: usize,
+        page_index: usize,
+    ) -> Result<(), ContextError> {
+        self.get_mut_layer_in_page(layer_index, page_index)
+            .map(|_| ())
+    }
+
+    // Retrieve the specified page via its index.
+    fn get_mut_page(&mut self, page_index: usize) -> Result<&mut PdfPage, ContextError> {
+        self.pages.get_mut(page_index).ok_or(
+            ContextError::with_context(format!(
+                "Failed to find the page with index {}",
+                page_index
+            ))
+            .with_kind(ErrorKind::PageIndexOutOfRange),
+        )
     }
 
     // Retrieve the specified layer in the given page via the respective indices.
@@ -1204,29 +2193,384 @@ impl PdfDocument {
         layer_index: usize,
         page_index: usize,
     ) -> Result<&mut PdfLayer, ContextError> {
-        let pdf_page = self
-            .pages
-            .get_mut(page_index)
-            .ok_or(ContextError::with_context(format!(
-                "Failed to find the page with index {}",
-                page_index
-            )))?;
-        let pdf_layer = pdf_page
-            .layers
-            .get_mut(layer_index)
-            .ok_or(ContextError::with_context(format!(
+        let pdf_page = self.get_mut_page(page_index)?;
+        let pdf_layer = pdf_page.layers.get_mut(layer_index).ok_or(
+            ContextError::with_context(format!(
                 "Failed to find the layer with index {}",
                 layer_index
-            )))?;
+            ))
+            .with_kind(ErrorKind::PageIndexOutOfRange),
+        )?;
 
         Ok(pdf_layer)
     }
 }
 
+/// A `Canvas` which draws onto a `PdfDocument`. Each page break appends a new page with a single layer
+/// onto which the following text runs and paths are written.
+pub struct PdfCanvas<'a> {
+    /// The PDF document being drawn onto.
+    pdf_document: &'a mut PdfDocument,
+    /// The indices of the current page and of the layer in it, if a page has been added.
+    current_page_and_layer: Option<(usize, usize)>,
+}
+
+impl<'a> PdfCanvas<'a> {
+    /// Creates a canvas which draws onto the given PDF document. The fonts referenced by the text runs
+    /// need to be added to the PDF document beforehand.
+    pub fn new(pdf_document: &'a mut PdfDocument) -> Self {
+        PdfCanvas {
+            pdf_document,
+            current_page_and_layer: None,
+        }
+    }
+
+    /// Retrieve the indices of the current page and of the layer in it.
+    fn current_page_and_layer(&self) -> Result<(usize, usize), ContextError> {
+        self.current_page_and_layer.ok_or(
+            ContextError::with_context(
+                "Unable to draw onto the PDF document because no page has been added yet",
+            )
+            .with_kind(ErrorKind::PageIndexOutOfRange),
+        )
+    }
+}
+
+impl Canvas for PdfCanvas<'_> {
+    fn page_break(&mut self, page_width: f32, page_height: f32) -> Result<(), ContextError> {
+        self.current_page_and_layer = Some(
+            self.pdf_document
+                .add_page_with_layer(page_width, page_height),
+        );
+
+        Ok(())
+    }
+
+    fn text_run(&mut self, text_run: &TextRun) -> Result<(), ContextError> {
+        let (page_index, layer_index) = self.current_page_and_layer()?;
+        self.pdf_document.write_text_to_layer_in_page(
+            page_index,
+            layer_index,
+            text_run.color,
+            text_run.text,
+            text_run.font_index,
+            text_run.font_size,
+            text_run.position,
+            text_run.letter_spacing,
+        )
+    }
+
+    fn fill_path(&mut self, path: &FilledPath) -> Result<(), ContextError> {
+        let (page_index, layer_index) = self.current_page_and_layer()?;
+        self.pdf_document
+            .fill_path_in_layer_in_page(page_index, layer_index, path)
+    }
+
+    fn pdf_operations(
+        &mut self,
+        operations: Vec<lopdf::content::Operation>,
+    ) -> Result<(), ContextError> {
+        let (page_index, layer_index) = self.current_page_and_layer()?;
+        self.pdf_document
+            .add_raw_operations_to_layer_in_page(page_index, layer_index, operations)
+    }
+
+    fn font_bytes(&self) -> Option<Vec<Arc<[u8]>>> {
+        Some(self.pdf_document.indexed_font_bytes())
+    }
+}
+
+/// Writes a PDF document onto an output page by page, for documents which are too large to be held in memory as a
+/// whole: as soon as a page is complete, its objects are written onto the output and its content is dropped, so that
+/// only the fonts, the page being drawn and the offsets of the objects written so far are kept in memory. The fonts
+/// are written last, once it is known which ones are used. Just like a `PdfCanvas`, it is a `Canvas` onto which a
+/// `Document` can be drawn, where each page break completes the previous page (see `Document::stream_to_pdf`).
+/// The streams are compressed as they are written, while the other optimizations of `PdfDocument::optimize`,
+/// which need the whole document, can be performed afterwards via `optimize_pdf_file`.
+pub struct PdfStreamWriter<W: Write> {
+    /// The PDF document being drawn onto, whose underlying document only holds the objects not written yet.
+    pdf_document: PdfDocument,
+    /// The output onto which the PDF document is written.
+    output: W,
+    /// The number of bytes written onto the output so far.
+    written_size: usize,
+    /// The offsets in the output of the objects written so far, indexed by their object number, from which
+    /// the cross-reference table is written at the end.
+    object_offsets: BTreeMap<u32, usize>,
+    /// The object ID of the pages dictionary, which is written last.
+    pages_id: lopdf::ObjectId,
+    /// The object ID of the dictionary of the embedded fonts, which is written last.
+    fonts_dictionary_id: lopdf::ObjectId,
+    /// The object IDs of the intents and of the usage shared by the OCGs of all the layers.
+    ocg_intent_and_usage_ids: (lopdf::ObjectId, lopdf::ObjectId),
+    /// The references to the pages written so far, in their order.
+    page_references: Vec<Object>,
+    /// The references to the OCG dictionaries of the layers of the pages written so far, in their order.
+    ocg_dictionary_references: Vec<Object>,
+    /// The indices of the current page and of the layer in it, if a page has been added.
+    current_page_and_layer: Option<(usize, usize)>,
+}
+
+impl<W: Write> PdfStreamWriter<W> {
+    /// Begins writing the given PDF document onto the given output. The fonts need to be added to the PDF document
+    /// beforehand, while its pages are added by drawing onto the writer.
+    ///
+    /// # Arguments
+    ///
+    /// * `pdf_document` - The PDF document to be written, with its fonts but without pages.
+    /// * `output` - The output onto which the PDF document is written, which should be buffered.
+    pub fn new(mut pdf_document: PdfDocument, output: W) -> Result<Self, ContextError> {
+        let pages_id = pdf_document.inner_document.new_object_id();
+        let fonts_dictionary_id = pdf_document.inner_document.new_object_id();
+        let ocg_intent_and_usage_ids = pdf_document.insert_ocg_intent_and_usage();
+        let header = format!("%PDF-{}\n", pdf_document.inner_document.version);
+
+        let mut pdf_stream_writer = PdfStreamWriter {
+            pdf_document,
+            output,
+            written_size: 0,
+            object_offsets: BTreeMap::new(),
+            pages_id,
+            fonts_dictionary_id,
+            ocg_intent_and_usage_ids,
+            page_references: Vec::new(),
+            ocg_dictionary_references: Vec::new(),
+            current_page_and_layer: None,
+        };
+        pdf_stream_writer.write_bytes(header.as_bytes())?;
+
+        Ok(pdf_stream_writer)
+    }
+
+    /// The PDF document being written, whose warnings and statistics describe the pages drawn so far.
+    pub fn pdf_document(&self) -> &PdfDocument {
+        &self.pdf_document
+    }
+
+    /// Writes onto the output the pages which haven't been written yet, the fonts, the catalog and the
+    /// cross-reference table, completing the PDF document. Returns the output together with the PDF document,
+    /// whose warnings and statistics describe the whole conversion.
+    ///
+    /// # Arguments
+    ///
+    /// * `instance_id` - The instance ID of the PDF document, see `PdfDocument::write_all`.
+    pub fn finish(mut self, instance_id: String) -> Result<(W, PdfDocument), ContextError> {
+        use lopdf::Object::*;
+
+        self.write_pages()?;
+
+        // The fonts are only known once all the text has been written
+        let fonts_dictionary = self.pdf_document.insert_fonts_into_document()?;
+        let pages = lopdf::Dictionary::from_iter(vec![
+            ("Type", "Pages".into()),
+            ("Count", Integer(self.page_references.len() as i64)),
+            ("Kids", Array(mem::take(&mut self.page_references))),
+        ]);
+        let document_info = self.pdf_document.document_info();
+        let inner_document = &mut self.pdf_document.inner_document;
+        inner_document
+            .objects
+            .insert(self.fonts_dictionary_id, Dictionary(fonts_dictionary));
+        inner_document
+            .objects
+            .insert(self.pages_id, Dictionary(pages));
+        let document_info_id = inner_document.add_object(Dictionary(document_info));
+        let catalog_id = inner_document.add_object(catalog(
+            self.pages_id,
+            mem::take(&mut self.ocg_dictionary_references),
+        ));
+        self.write_pending_objects()?;
+
+        // Write the cross-reference table, in which the objects which have never been written are free
+        let cross_reference_offset = self.written_size;
+        let object_count = self.pdf_document.inner_document.max_id + 1;
+        let mut cross_reference_table = format!("xref\n0 {}\n0000000000 65535 f \n", object_count);
+        for object_number in 1..object_count {
+            match self.object_offsets.get(&object_number) {
+                Some(offset) => {
+                    cross_reference_table.push_str(&format!("{:010} 00000 n \n", offset))
+                }
+                None => cross_reference_table.push_str("0000000000 65535 f \n"),
+            }
+        }
+        self.write_bytes(cross_reference_table.as_bytes())?;
+
+        let trailer = Dictionary(lopdf::Dictionary::from_iter(vec![
+            ("Size", Integer(object_count as i64)),
+            ("Root", Reference(catalog_id)),
+            ("Info", Reference(document_info_id)),
+            ("ID", self.pdf_document.identifiers(&instance_id)),
+        ]));
+        self.write_bytes(b"trailer\n")?;
+        self.write_bytes(&encode_object(trailer)?)?;
+        self.write_bytes(format!("\nstartxref\n{}\n%%EOF", cross_reference_offset).as_bytes())?;
+        self.output.flush().map_err(|error| {
+            ContextError::with_error("Failed to write the PDF document", &error)
+                .with_kind(ErrorKind::Io)
+        })?;
+
+        Ok((self.output, self.pdf_document))
+    }
+
+    /// Writes onto the output all the pages which haven't been written yet, dropping their content,
+    /// so that no more text can be written onto them.
+    fn write_pages(&mut self) -> Result<(), ContextError> {
+        let (intent_array_id, usage_ocg_dictionary_id) = self.ocg_intent_and_usage_ids;
+        for page_index in self.page_references.len()..self.pdf_document.pages.len() {
+            let layers = self.pdf_document.insert_ocg_dictionaries(
+                page_index,
+                intent_array_id,
+                usage_ocg_dictionary_id,
+            );
+            self.ocg_dictionary_references
+                .extend(layers.iter().map(|(_, reference)| reference.clone()));
+            let page_id = self.pdf_document.insert_page_into_document(
+                page_index,
+                &layers,
+                self.pages_id,
+                self.fonts_dictionary_id,
+            )?;
+            self.page_references.push(Object::Reference(page_id));
+
+            // Only the size of the page is kept
+            let page = &mut self.pdf_document.pages[page_index];
+            page.layers = Vec::new();
+            page.resources = PdfResources::default();
+            self.write_pending_objects()?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes onto the output all the objects which have been inserted into the underlying document so far,
+    /// removing them from it. The streams are compressed beforehand, just like `PdfDocument::optimize` does.
+    fn write_pending_objects(&mut self) -> Result<(), ContextError> {
+        for (object_id, mut object) in mem::take(&mut self.pdf_document.inner_document.objects) {
+            if let Object::Stream(stream) = &mut object {
+                stream.compress().map_err(|error| {
+                    ContextError::with_error("Failed to compress a PDF stream", &error)
+                        .with_kind(ErrorKind::PdfEncoding)
+                })?;
+            }
+            self.object_offsets.insert(object_id.0, self.written_size);
+            self.write_bytes(format!("{} {} obj\n", object_id.0, object_id.1).as_bytes())?;
+            self.write_bytes(&encode_object(object)?)?;
+            self.write_bytes(b"\nendobj\n")?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the given bytes onto the output, keeping track of the offset of the following ones.
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), ContextError> {
+        self.output.write_all(bytes).map_err(|error| {
+            ContextError::with_error("Failed to write the PDF document", &error)
+                .with_kind(ErrorKind::Io)
+        })?;
+        self.written_size += bytes.len();
+
+        Ok(())
+    }
+
+    /// Draws onto the current page via a `PdfCanvas`.
+    fn canvas(&mut self) -> PdfCanvas<'_> {
+        PdfCanvas {
+            pdf_document: &mut self.pdf_document,
+            current_page_and_layer: self.current_page_and_layer,
+        }
+    }
+}
+
+impl<W: Write> Canvas for PdfStreamWriter<W> {
+    fn page_break(&mut self, page_width: f32, page_height: f32) -> Result<(), ContextError> {
+        // The current page is complete, so it can be written
+        self.write_pages()?;
+        self.current_page_and_layer = Some(
+            self.pdf_document
+                .add_page_with_layer(page_width, page_height),
+        );
+
+        Ok(())
+    }
+
+    fn text_run(&mut self, text_run: &TextRun) -> Result<(), ContextError> {
+        self.canvas().text_run(text_run)
+    }
+
+    fn fill_path(&mut self, path: &FilledPath) -> Result<(), ContextError> {
+        self.canvas().fill_path(path)
+    }
+
+    fn pdf_operations(
+        &mut self,
+        operations: Vec<lopdf::content::Operation>,
+    ) -> Result<(), ContextError> {
+        self.canvas().pdf_operations(operations)
+    }
+
+    fn font_bytes(&self) -> Option<Vec<Arc<[u8]>>> {
+        Some(self.pdf_document.indexed_font_bytes())
+    }
+}
+
+/// Encodes the given object as it appears in a PDF file. The writer of `lopdf` is not public, but it encodes the
+/// operands of the operations of a content stream in the same way, so an operation without operator is encoded
+/// instead, and the space separating the operand from the operator is removed.
+fn encode_object(object: Object) -> Result<Vec<u8>, ContextError> {
+    use lopdf::content::{Content, Operation};
+
+    let mut encoded_object = Content {
+        operations: vec![Operation::new("", vec![object])],
+    }
+    .encode()
+    .map_err(|error| {
+        ContextError::with_error("Failed to encode a PDF object", &error)
+            .with_kind(ErrorKind::PdfEncoding)
+    })?;
+    encoded_object.pop();
+
+    Ok(encoded_object)
+}
+
 type GlyphId = u32;
 type UnicodeCodePoint = u32;
 type CmapBlock = Vec<(GlyphId, UnicodeCodePoint)>;
 
+/// Constructs the catalog of a PDF document, required by the PDF specification, whose optional content
+/// properties list the OCGs of all the layers.
+///
+/// # Arguments
+///
+/// * `pages_id` - The object ID of the pages dictionary.
+/// * `ocg_dictionary_references` - The references to the OCG dictionaries of all the layers, in their order.
+fn catalog(
+    pages_id: lopdf::ObjectId,
+    ocg_dictionary_references: Vec<lopdf::Object>,
+) -> lopdf::Dictionary {
+    use lopdf::Object::*;
+
+    lopdf::Dictionary::from_iter(vec![
+        ("Type", "Catalog".into()),
+        ("PageLayout", "OneColumn".into()),
+        ("PageMode", "UseNone".into()),
+        ("Pages", Reference(pages_id)),
+        (
+            "OCProperties",
+            Dictionary(lopdf::Dictionary::from_iter(vec![
+                ("OCGs", Array(ocg_dictionary_references.clone())),
+                (
+                    "D",
+                    Dictionary(lopdf::Dictionary::from_iter(vec![
+                        ("Order", Array(ocg_dictionary_references.clone())),
+                        ("RBGroups", Array(vec![])),
+                        ("ON", Array(ocg_dictionary_references)),
+                    ])),
+                ),
+            ])),
+        ),
+    ])
+}
+
 /// Generates a CMAP (character map) from valid cmap blocks by iterating over them. This function adheres to
 /// the PDF specification by employing a predefined beginning and end section which is inserted at compile time.
 fn generate_cid_to_unicode_map(face_name: String, all_cmap_blocks: Vec<CmapBlock>) -> String {
@@ -1255,6 +2599,71 @@ fn generate_cid_to_unicode_map(face_name: String, all_cmap_blocks: Vec<CmapBlock
     cid_to_unicode_map
 }
 
+/// The minimum number of consecutive glyphs with the same width which are encoded as a range by
+/// `encode_glyph_widths`: shorter runs take less space when listed one by one.
+const MIN_WIDTH_RANGE_LENGTH: usize = 4;
+
+/// Encodes the given widths, sorted by glyph ID, into the `W` array of a CID font (see page 439 in the PDF 1.7
+/// reference). Runs of consecutive glyphs with the same width, which are common in monospace and CJK fonts, are
+/// encoded as `first_glyph_id last_glyph_id width`, while the other glyphs are listed as
+/// `first_glyph_id [width width ...]`, so that for instance `20 [21 99] 22 40 1000` means that the glyph with
+/// ID 20 has a width of 21 units, the one with ID 21 has a width of 99 units and the ones from 22 to 40 have a
+/// width of 1000 units.
+fn encode_glyph_widths(glyph_widths: &[(u16, i64)]) -> Vec<Object> {
+    let mut width_objects = Vec::<Object>::new();
+    // The glyphs which have been encountered but not yet encoded, because they are listed one by one
+    let mut pending_glyph_widths = Vec::<(u16, i64)>::new();
+
+    let flush_pending = |width_objects: &mut Vec<Object>,
+                         pending_glyph_widths: &mut Vec<(u16, i64)>| {
+        if let Some(&(first_glyph_id, _)) = pending_glyph_widths.first() {
+            width_objects.push(Object::Integer(first_glyph_id as i64));
+            width_objects.push(Object::Array(
+                pending_glyph_widths
+                    .drain(..)
+                    .map(|(_, width)| Object::Integer(width))
+                    .collect(),
+            ));
+        }
+    };
+
+    let mut index = 0;
+    while index < glyph_widths.len() {
+        let (first_glyph_id, width) = glyph_widths[index];
+        // Find how many of the following glyphs are consecutive and have the same width
+        let run_length = glyph_widths[index..]
+            .iter()
+            .enumerate()
+            .take_while(|(offset, (glyph_id, glyph_width))| {
+                *glyph_id as usize == first_glyph_id as usize + offset && *glyph_width == width
+            })
+            .count();
+
+        // A gap in the glyph IDs breaks the glyphs listed one by one
+        if let Some(&(last_glyph_id, _)) = pending_glyph_widths.last() {
+            if last_glyph_id as usize + 1 != first_glyph_id as usize {
+                flush_pending(&mut width_objects, &mut pending_glyph_widths);
+            }
+        }
+
+        if run_length >= MIN_WIDTH_RANGE_LENGTH {
+            flush_pending(&mut width_objects, &mut pending_glyph_widths);
+            width_objects.push(Object::Integer(first_glyph_id as i64));
+            width_objects.push(Object::Integer(
+                first_glyph_id as i64 + run_length as i64 - 1,
+            ));
+            width_objects.push(Object::Integer(width));
+            index += run_length;
+        } else {
+            pending_glyph_widths.push((first_glyph_id, width));
+            index += 1;
+        }
+    }
+    flush_pending(&mut width_objects, &mut pending_glyph_widths);
+
+    width_objects
+}
+
 /// Formats the given time so that it matches what the PDF specification expects.
 /// An example of it is the following: D:20170505150224+02'00'.
 fn to_pdf_timestamp_format(date: &OffsetDateTime) -> String {
@@ -1273,6 +2682,147 @@ fn to_pdf_timestamp_format(date: &OffsetDateTime) -> String {
     )
 }
 
+/// Optimizes the given low-level PDF document, see `PdfDocument::optimize`.
+fn optimize_inner_document(inner_document: &mut lopdf::Document) {
+    inner_document.prune_objects();
+    inner_document.delete_zero_length_streams();
+    deduplicate_objects(inner_document);
+    inner_document.renumber_objects();
+    // Streams can opt out of the compression, which is lifted for all of them since it is lossless
+    for object in inner_document.objects.values_mut() {
+        if let Object::Stream(stream) = object {
+            stream.allows_compression = true;
+        }
+    }
+    inner_document.compress();
+}
+
+/// Merges the objects which are identical into a single one, updating all the references to them. Streams are
+/// merged when both their dictionaries and their contents are identical, such as the content streams of repeated
+/// headers or the same CMap used by different fonts, while the nodes of the page tree and the layers (optional
+/// content groups) are not, since each page needs to appear only once in the page tree and each layer is listed
+/// on its own by the optional content properties of the catalog. Merging some objects can make the objects
+/// referring to them identical, so the process is repeated until nothing changes.
+fn deduplicate_objects(inner_document: &mut lopdf::Document) {
+    loop {
+        // Group the candidate objects by the hash of their content, then compare them exactly
+        let mut candidate_ids = HashMap::<u64, Vec<lopdf::ObjectId>>::new();
+        let mut replacements = HashMap::<lopdf::ObjectId, lopdf::ObjectId>::new();
+        for (object_id, object) in &inner_document.objects {
+            if matches!(object.type_name(), Ok("Page") | Ok("Pages") | Ok("OCG")) {
+                continue;
+            }
+            let identical_ids = candidate_ids.entry(object_hash(object)).or_default();
+            match identical_ids.iter().find(|identical_id| {
+                are_objects_identical(&inner_document.objects[identical_id], object)
+            }) {
+                Some(identical_id) => {
+                    replacements.insert(*object_id, *identical_id);
+                }
+                None => identical_ids.push(*object_id),
+            }
+        }
+        if replacements.is_empty() {
+            break;
+        }
+
+        for object_id in replacements.keys() {
+            inner_document.objects.remove(object_id);
+        }
+        for object in inner_document.objects.values_mut() {
+            replace_references(object, &replacements);
+        }
+        for (_, object) in inner_document.trailer.iter_mut() {
+            replace_references(object, &replacements);
+        }
+    }
+}
+
+/// Hashes the given object for finding the candidates to be merged with it. The content of a stream is hashed as
+/// raw bytes rather than through its textual representation, which would be several times larger.
+fn object_hash(object: &Object) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    match object {
+        Object::Stream(stream) => {
+            format!("{:?}", stream.dict).hash(&mut hasher);
+            stream.content.hash(&mut hasher);
+        }
+        _ => format!("{:?}", object).hash(&mut hasher),
+    }
+
+    hasher.finish()
+}
+
+/// Whether the two given objects are identical. Two streams are identical when their dictionaries and their
+/// contents are, regardless of where they were found in the PDF file they have been loaded from.
+fn are_objects_identical(object: &Object, other_object: &Object) -> bool {
+    match (object, other_object) {
+        (Object::Stream(stream), Object::Stream(other_stream)) => {
+            stream.dict == other_stream.dict && stream.content == other_stream.content
+        }
+        _ => object == other_object,
+    }
+}
+
+/// Replaces the references found in the given object, at any depth, according to the given replacements.
+fn replace_references(
+    object: &mut Object,
+    replacements: &HashMap<lopdf::ObjectId, lopdf::ObjectId>,
+) {
+    match object {
+        Object::Reference(object_id) => {
+            if let Some(replacement_id) = replacements.get(object_id) {
+                *object_id = *replacement_id;
+            }
+        }
+        Object::Array(array) => {
+            for object in array {
+                replace_references(object, replacements);
+            }
+        }
+        Object::Dictionary(dictionary) => {
+            for (_, object) in dictionary.iter_mut() {
+                replace_references(object, replacements);
+            }
+        }
+        Object::Stream(stream) => {
+            for (_, object) in stream.dict.iter_mut() {
+                replace_references(object, replacements);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Optimizes the PDF file at the given input path and saves the result at the given output path, which can be the
+/// same. This is a pure-Rust alternative to `optimize_pdf_file_with_gs` and `optimize_pdf_file_with_ps2pdf` which
+/// works on any platform, although the files it produces are usually larger than the ones produced by ghostscript
+/// (see `PdfDocument::optimize` for the optimizations which are performed).
+///
+/// # Arguments
+///
+/// * `input_path` - The path to the PDF file to be optimized.
+/// * `output_path` - The path where the optimized PDF file is saved.
+pub fn optimize_pdf_file(input_path: &Path, output_path: &Path) -> Result<(), ContextError> {
+    let mut inner_document = lopdf::Document::load(input_path).map_err(|error| {
+        ContextError::with_error(
+            format!("Unable to load the PDF file {:?}", input_path),
+            &error,
+        )
+        .with_kind(ErrorKind::PdfEncoding)
+    })?;
+    optimize_inner_document(&mut inner_document);
+    inner_document.save(output_path).map_err(|error| {
+        ContextError::with_error(
+            format!("Unable to save the PDF file {:?}", output_path),
+            &error,
+        )
+        .with_kind(ErrorKind::Io)
+    })?;
+
+    Ok(())
+}
+
 /// This function is used to optimize the PDF file by running ghostscript on it. The command which is run
 /// is the following:
 ///
@@ -1303,22 +2853,25 @@ pub fn optimize_pdf_file_with_gs(pdf_path: &str) -> Result<(), ContextError> {
         Ok(mut child) => {
             let status = child.wait().map_err(|error| {
                 ContextError::with_error("Unable to wait for the gs command execution", &error)
+                    .with_kind(ErrorKind::ExternalTool)
             })?;
             if !status.success() {
                 return Err(ContextError::with_context(format!(
                     "gs failed with status {:?}",
                     status
-                )));
+                ))
+                .with_kind(ErrorKind::ExternalTool));
             }
             std::fs::rename(format!("{}.swp", pdf_path), pdf_path).map_err(|error| {
                 ContextError::with_error("Unable to rename the optimized PDF file", &error)
+                    .with_kind(ErrorKind::Io)
             })?;
         }
         Err(error) => {
-            return Err(ContextError::with_error(
-                "Unable to run the gs command",
-                &error,
-            ));
+            return Err(
+                ContextError::with_error("Unable to run the gs command", &error)
+                    .with_kind(ErrorKind::ExternalTool),
+            );
         }
     }
 
@@ -1344,24 +2897,42 @@ pub fn optimize_pdf_file_with_ps2pdf(pdf_path: &str) -> Result<(), ContextError>
         Ok(mut child) => {
             let status = child.wait().map_err(|error| {
                 ContextError::with_error("Unable to wait for the ps2pdf command execution", &error)
+                    .with_kind(ErrorKind::ExternalTool)
             })?;
             if !status.success() {
                 return Err(ContextError::with_context(format!(
                     "ps2pdf failed with status {:?}",
                     status
-                )));
+                ))
+                .with_kind(ErrorKind::ExternalTool));
             }
             std::fs::rename(format!("{}.swp", pdf_path), pdf_path).map_err(|error| {
                 ContextError::with_error("Unable to rename the optimized PDF file", &error)
+                    .with_kind(ErrorKind::Io)
             })?;
         }
         Err(error) => {
-            return Err(ContextError::with_error(
-                "Unable to run the ps2pdf command",
-                &error,
-            ));
+            return Err(
+                ContextError::with_error("Unable to run the ps2pdf command", &error)
+                    .with_kind(ErrorKind::ExternalTool),
+            );
         }
     }
 
     Ok(())
 }
+
+/// Encodes the given text as a PDF text string, which is kept as it is if it's made of ASCII characters only, and
+/// otherwise encoded in UTF-16 with a byte order mark, as PDF readers expect.
+fn text_string(text: &str) -> lopdf::Object {
+    if text.is_ascii() {
+        return lopdf::Object::String(text.as_bytes().to_vec(), lopdf::StringFormat::Literal);
+    }
+    let utf16_bytes = [0xFEFF]
+        .into_iter()
+        .chain(text.encode_utf16())
+        .flat_map(u16::to_be_bytes)
+        .collect();
+
+    lopdf::Object::String(utf16_bytes, lopdf::StringFormat::Hexadecimal)
+}
@@ -0,0 +1,480 @@
+use lopdf::{content::Content, Dictionary, Object};
+use std::collections::BTreeMap;
+
+use crate::{
+    document::{Document, Operation},
+    error::{ContextError, ErrorKind},
+    pdf::points_to_millimeters,
+    pdf_info::{decode_text_string, inherited_entry},
+};
+
+/// The identity matrix, as the six numbers `[a b c d e f]` with which the PDF documents express their matrices.
+const IDENTITY_MATRIX: [f32; 6] = [1.0, 0.0, 0.0, 1.0, 0.0, 0.0];
+
+/// The adjustment of a `TJ` array, in thousandths of the font size, beyond which it is read as a space between
+/// two words rather than as kerning.
+const WORD_SPACE_ADJUSTMENT: f32 = -200.0;
+
+impl Document {
+    /// Recovers a `Document` from the given PDF file on a best-effort basis, so that round-trip tests can verify
+    /// that what was written can be read back, and so that documents can be bootstrapped from existing simple PDF
+    /// files. Only the page sizes and the runs of text are recovered, with their position, font size, color and
+    /// letter spacing, while the images, the paths and the page backgrounds are left out.
+    ///
+    /// The characters are recovered via the `ToUnicode` map of the fonts, which is embedded by this crate. The font
+    /// index of a run is the number at the end of the name of its font, such as `F15` for the fonts embedded by this
+    /// crate, or else 0. The document ID and the instance ID are read from the `ID` entry of the trailer, while the
+    /// title and the author are read from the document information dictionary.
+    ///
+    /// # Arguments
+    ///
+    /// * `pdf_bytes` - The content of the PDF file, for instance as returned by `PdfDocument::save_to_bytes`.
+    pub fn from_pdf_bytes(pdf_bytes: &[u8]) -> Result<Self, ContextError> {
+        let inner_document = lopdf::Document::load_mem(pdf_bytes).map_err(|error| {
+            ContextError::with_error("Unable to load the PDF file", &error)
+                .with_kind(ErrorKind::PdfEncoding)
+        })?;
+
+        let mut identifiers = inner_document
+            .trailer
+            .get(b"ID")
+            .and_then(Object::as_array)
+            .map(|identifiers| {
+                identifiers
+                    .iter()
+                    .filter_map(|identifier| identifier.as_str().ok())
+                    .map(decode_text_string)
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default()
+            .into_iter();
+        // The title and the author written as `Unknown` are the ones which were not set when writing the document
+        let info_entry = |key: &[u8]| {
+            inner_document
+                .trailer
+                .get(b"Info")
+                .and_then(|info| inner_document.dereference(info))
+                .and_then(|(_, info)| info.as_dict())
+                .and_then(|info| info.get(key))
+                .and_then(|value| inner_document.dereference(value))
+                .and_then(|(_, value)| value.as_str())
+                .map(decode_text_string)
+                .ok()
+                .filter(|value| value != "Unknown")
+        };
+
+        let mut operations = Vec::new();
+        for (page_index, page_id) in inner_document.get_pages().into_values().enumerate() {
+            let page = inner_document.get_dictionary(page_id).map_err(|error| {
+                ContextError::with_error(
+                    format!("Unable to read the page with index {}", page_index),
+                    &error,
+                )
+                .with_kind(ErrorKind::PdfEncoding)
+            })?;
+            let media_box = inherited_entry(&inner_document, page, b"MediaBox")
+                .and_then(|media_box| media_box.as_array().ok())
+                .map(|media_box| {
+                    media_box
+                        .iter()
+                        .map(|coordinate| coordinate.as_float().unwrap_or(0.0))
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+            let (page_width, page_height) = match media_box[..] {
+                [x_min, y_min, x_max, y_max] => (x_max - x_min, y_max - y_min),
+                _ => (0.0, 0.0),
+            };
+            operations.push(Operation::AppendNewPage {
+                page_width: points_to_millimeters(page_width),
+                page_height: points_to_millimeters(page_height),
+            });
+
+            let fonts = inner_document
+                .get_page_fonts(page_id)
+                .into_iter()
+                .map(|(font_name, font)| {
+                    let imported_font = ImportedFont::new(&inner_document, &font_name, font);
+                    (font_name, imported_font)
+                })
+                .collect::<BTreeMap<_, _>>();
+            let content = inner_document
+                .get_page_content(page_id)
+                .ok()
+                .and_then(|content| Content::decode(&content).ok())
+                .map(|content| content.operations)
+                .unwrap_or_default();
+            operations.extend(TextRunCollector::new(&fonts).collect(&content));
+        }
+
+        Ok(Document {
+            document_id: identifiers.next().unwrap_or_default(),
+            instance_id: identifiers.next().unwrap_or_default(),
+            operations,
+            page_background: None,
+            title: info_entry(b"Title"),
+            author: info_entry(b"Author"),
+            running_header: None,
+            timestamp: None,
+            deterministic: false,
+            strict: false,
+        })
+    }
+}
+
+/// A font of a page, as needed to recover the characters of its text.
+struct ImportedFont {
+    /// The font index of the runs of text written with the font.
+    font_index: usize,
+    /// Whether the characters are encoded with two bytes, as in the composite fonts, rather than with one.
+    has_two_byte_codes: bool,
+    /// The characters of each code, read from the `ToUnicode` map of the font.
+    characters_by_code: BTreeMap<u32, String>,
+}
+
+impl ImportedFont {
+    /// Reads the given font, named as in the resources of the page.
+    fn new(inner_document: &lopdf::Document, font_name: &[u8], font: &Dictionary) -> Self {
+        let font_index = String::from_utf8_lossy(font_name)
+            .trim_start_matches(|character: char| !character.is_ascii_digit())
+            .parse()
+            .unwrap_or(0);
+        let has_two_byte_codes = matches!(
+            font.get(b"Subtype").and_then(Object::as_name_str),
+            Ok("Type0")
+        );
+        let characters_by_code = font
+            .get(b"ToUnicode")
+            .ok()
+            .and_then(|to_unicode| inner_document.dereference(to_unicode).ok())
+            .and_then(|(_, to_unicode)| to_unicode.as_stream().ok())
+            .map(|to_unicode| {
+                let content = to_unicode
+                    .decompressed_content()
+                    .unwrap_or_else(|_| to_unicode.content.clone());
+                parse_to_unicode_map(&content)
+            })
+            .unwrap_or_default();
+
+        ImportedFont {
+            font_index,
+            has_two_byte_codes,
+            characters_by_code,
+        }
+    }
+
+    /// Decodes the given string of a text showing operator into its characters, leaving out the codes which can't
+    /// be mapped. The codes of the simple fonts without a `ToUnicode` map are read as Latin-1 characters.
+    fn decode(&self, bytes: &[u8]) -> String {
+        let codes: Vec<u32> = if self.has_two_byte_codes {
+            bytes
+                .chunks_exact(2)
+                .map(|pair| u16::from_be_bytes([pair[0], pair[1]]) as u32)
+                .collect()
+        } else {
+            bytes.iter().map(|byte| *byte as u32).collect()
+        };
+
+        codes
+            .into_iter()
+            .filter_map(|code| match self.characters_by_code.get(&code) {
+                Some(characters) => Some(characters.clone()),
+                None if !self.has_two_byte_codes => char::from_u32(code).map(String::from),
+                None => None,
+            })
+            .collect()
+    }
+}
+
+/// Collects the runs of text of the content stream of a page by following the graphics and the text state.
+struct TextRunCollector<'a> {
+    /// The fonts of the page, by their names in the resources of the page.
+    fonts: &'a BTreeMap<Vec<u8>, ImportedFont>,
+    /// The current transformation matrix.
+    transformation_matrix: [f32; 6],
+    /// The current filling color, with which the text is painted.
+    color: [f32; 3],
+    /// The transformation matrices and the colors saved by the `q` operator.
+    saved_states: Vec<([f32; 6], [f32; 3])>,
+    /// The name of the current font and its size in points.
+    font: Option<(Vec<u8>, f32)>,
+    /// The current character spacing in points.
+    letter_spacing: f32,
+    /// The current leading in points, by which the `T*` operator moves to the next line.
+    leading: f32,
+    /// The current text matrix.
+    text_matrix: [f32; 6],
+    /// The text matrix at the beginning of the current line.
+    line_matrix: [f32; 6],
+    /// Whether the next text continues the last run, since neither the position nor the state have changed.
+    continues_run: bool,
+    /// The runs of text collected so far.
+    text_runs: Vec<Operation>,
+}
+
+impl<'a> TextRunCollector<'a> {
+    /// Creates a collector for a page with the given fonts.
+    fn new(fonts: &'a BTreeMap<Vec<u8>, ImportedFont>) -> Self {
+        TextRunCollector {
+            fonts,
+            transformation_matrix: IDENTITY_MATRIX,
+            color: [0.0, 0.0, 0.0],
+            saved_states: Vec::new(),
+            font: None,
+            letter_spacing: 0.0,
+            leading: 0.0,
+            text_matrix: IDENTITY_MATRIX,
+            line_matrix: IDENTITY_MATRIX,
+            continues_run: false,
+            text_runs: Vec::new(),
+        }
+    }
+
+    /// Follows the given operations of the content stream and returns the runs of text they write.
+    fn collect(mut self, operations: &[lopdf::content::Operation]) -> Vec<Operation> {
+        for operation in operations {
+            let operands = operation
+                .operands
+                .iter()
+                .filter_map(|operand| operand.as_float().ok())
+                .collect::<Vec<_>>();
+            match (operation.operator.as_str(), &operands[..]) {
+                ("q", _) => self
+                    .saved_states
+                    .push((self.transformation_matrix, self.color)),
+                ("Q", _) => {
+                    if let Some((transformation_matrix, color)) = self.saved_states.pop() {
+                        self.transformation_matrix = transformation_matrix;
+                        self.color = color;
+                    }
+                    self.continues_run = false;
+                }
+                ("cm", &[a, b, c, d, e, f]) => {
+                    self.transformation_matrix =
+                        multiply([a, b, c, d, e, f], self.transformation_matrix);
+                    self.continues_run = false;
+                }
+                ("rg" | "sc" | "scn", &[r, g, b]) => self.set_color([r, g, b]),
+                ("g" | "sc" | "scn", &[gray]) => self.set_color([gray, gray, gray]),
+                ("k", &[c, m, y, k]) => {
+                    self.set_color([c, m, y].map(|component| (1.0 - component) * (1.0 - k)))
+                }
+                ("BT", _) => {
+                    self.text_matrix = IDENTITY_MATRIX;
+                    self.line_matrix = IDENTITY_MATRIX;
+                    self.continues_run = false;
+                }
+                ("Tf", &[font_size]) => {
+                    self.font = operation
+                        .operands
+                        .first()
+                        .and_then(|font_name| font_name.as_name().ok())
+                        .map(|font_name| (font_name.to_vec(), font_size));
+                    self.continues_run = false;
+                }
+                ("Tc", &[letter_spacing]) => {
+                    self.letter_spacing = letter_spacing;
+                    self.continues_run = false;
+                }
+                ("TL", &[leading]) => self.leading = leading,
+                ("Td", &[x, y]) => self.move_to_next_line(x, y),
+                ("TD", &[x, y]) => {
+                    self.leading = -y;
+                    self.move_to_next_line(x, y);
+                }
+                ("Tm", &[a, b, c, d, e, f]) => {
+                    self.text_matrix = [a, b, c, d, e, f];
+                    self.line_matrix = self.text_matrix;
+                    self.continues_run = false;
+                }
+                ("T*", _) => self.move_to_next_line(0.0, -self.leading),
+                ("Tj" | "'" | "\"", _) => {
+                    if operation.operator != "Tj" {
+                        self.move_to_next_line(0.0, -self.leading);
+                    }
+                    if let Some(Object::String(bytes, _)) = operation.operands.last() {
+                        self.show_text(&[(bytes, false)]);
+                    }
+                }
+                ("TJ", _) => {
+                    if let Some(Ok(elements)) = operation.operands.first().map(Object::as_array) {
+                        let strings = elements
+                            .iter()
+                            .map(|element| match element {
+                                Object::String(bytes, _) => (bytes.as_slice(), false),
+                                element => (
+                                    &[][..],
+                                    element.as_float().unwrap_or(0.0) < WORD_SPACE_ADJUSTMENT,
+                                ),
+                            })
+                            .collect::<Vec<_>>();
+                        self.show_text(&strings);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        self.text_runs
+    }
+
+    /// Sets the filling color, with which the following text is painted.
+    fn set_color(&mut self, color: [f32; 3]) {
+        self.color = color;
+        self.continues_run = false;
+    }
+
+    /// Moves the text matrix to the beginning of the next line, offset by the given amount from the current one.
+    fn move_to_next_line(&mut self, x: f32, y: f32) {
+        self.line_matrix = multiply([1.0, 0.0, 0.0, 1.0, x, y], self.line_matrix);
+        self.text_matrix = self.line_matrix;
+        self.continues_run = false;
+    }
+
+    /// Writes the given strings, with whether each of them is preceded by a space, as a new run of text or at the
+    /// end of the last run if it continues it.
+    fn show_text(&mut self, strings: &[(&[u8], bool)]) {
+        let Some((font_name, font_size)) = &self.font else {
+            return;
+        };
+        let Some(font) = self.fonts.get(font_name) else {
+            return;
+        };
+        let mut text = String::new();
+        for (bytes, is_preceded_by_space) in strings {
+            if *is_preceded_by_space {
+                text.push(' ');
+            }
+            text.push_str(&font.decode(bytes));
+        }
+
+        if self.continues_run {
+            if let Some(Operation::WriteUnicodeText { text_string, .. }) = self.text_runs.last_mut()
+            {
+                text_string.push_str(&text);
+                return;
+            }
+        }
+
+        // The position and the size of the text are the ones on the page, once both matrices are applied
+        let [_, _, c, d, x, y] = multiply(self.text_matrix, self.transformation_matrix);
+        self.text_runs.push(Operation::WriteUnicodeText {
+            color: self.color,
+            position: [points_to_millimeters(x), points_to_millimeters(y)],
+            text_string: text,
+            font_size: font_size * c.hypot(d),
+            font_index: font.font_index,
+            letter_spacing: self.letter_spacing,
+        });
+        self.continues_run = true;
+    }
+}
+
+/// Multiplies the two given matrices, so that the resulting matrix applies the first one and then the second one.
+fn multiply(first: [f32; 6], second: [f32; 6]) -> [f32; 6] {
+    let [a1, b1, c1, d1, e1, f1] = first;
+    let [a2, b2, c2, d2, e2, f2] = second;
+
+    [
+        a1 * a2 + b1 * c2,
+        a1 * b2 + b1 * d2,
+        c1 * a2 + d1 * c2,
+        c1 * b2 + d1 * d2,
+        e1 * a2 + f1 * c2 + e2,
+        e1 * b2 + f1 * d2 + f2,
+    ]
+}
+
+/// Parses the `bfchar` and `bfrange` sections of the given `ToUnicode` map into the characters of each code.
+fn parse_to_unicode_map(content: &[u8]) -> BTreeMap<u32, String> {
+    let content = String::from_utf8_lossy(content);
+    // The arrays of the ranges are split into tokens of their own, so that the tokens are separated by whitespace
+    let content = content
+        .replace('[', " [ ")
+        .replace(']', " ] ")
+        .replace("><", "> <");
+    let mut tokens = content.split_whitespace().peekable();
+    let is_hexadecimal_string = |token: &&str| token.starts_with('<');
+
+    let mut characters_by_code = BTreeMap::new();
+    while let Some(token) = tokens.next() {
+        match token {
+            "beginbfchar" => {
+                while let Some(code) = tokens.next_if(is_hexadecimal_string) {
+                    let characters = tokens.next_if(is_hexadecimal_string);
+                    if let (Some(code), Some(characters)) =
+                        (parse_code(code), characters.and_then(parse_characters))
+                    {
+                        characters_by_code.insert(code, characters);
+                    }
+                }
+            }
+            "beginbfrange" => {
+                while let Some(first_code) = tokens.next_if(is_hexadecimal_string) {
+                    let last_code = tokens.next_if(is_hexadecimal_string);
+                    let (Some(first_code), Some(last_code)) =
+                        (parse_code(first_code), last_code.and_then(parse_code))
+                    else {
+                        continue;
+                    };
+                    // The number of codes is bounded so that a malformed range can't take forever
+                    let codes = first_code..=last_code.min(first_code.saturating_add(0xFFFF));
+                    if tokens.next_if_eq(&"[").is_some() {
+                        // Each code of the range is mapped to its own characters
+                        let mut codes = codes;
+                        while let Some(characters) = tokens.next_if(is_hexadecimal_string) {
+                            if let (Some(code), Some(characters)) =
+                                (codes.next(), parse_characters(characters))
+                            {
+                                characters_by_code.insert(code, characters);
+                            }
+                        }
+                        tokens.next_if_eq(&"]");
+                    } else if let Some(characters) = tokens.next_if(is_hexadecimal_string) {
+                        // The codes of the range are mapped to consecutive characters
+                        let Some(first_character) = parse_characters(characters)
+                            .and_then(|characters| characters.chars().next())
+                        else {
+                            continue;
+                        };
+                        for (offset, code) in codes.enumerate() {
+                            if let Some(character) =
+                                char::from_u32(first_character as u32 + offset as u32)
+                            {
+                                characters_by_code.insert(code, character.to_string());
+                            }
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    characters_by_code
+}
+
+/// Parses a code of a `ToUnicode` map, written as a hexadecimal string such as `<0041>`.
+fn parse_code(token: &str) -> Option<u32> {
+    u32::from_str_radix(token.strip_prefix('<')?.strip_suffix('>')?, 16).ok()
+}
+
+/// Parses the characters a code is mapped to, written as a hexadecimal string of UTF-16BE code units. A string
+/// which isn't made of whole code units, such as `<1d400>`, is read as a single code point instead.
+fn parse_characters(token: &str) -> Option<String> {
+    let hexadecimal = token.strip_prefix('<')?.strip_suffix('>')?;
+    if !hexadecimal.is_ascii() {
+        return None;
+    }
+    if hexadecimal.len() % 4 == 0 {
+        let code_units = (0..hexadecimal.len())
+            .step_by(4)
+            .map(|index| u16::from_str_radix(&hexadecimal[index..index + 4], 16).ok())
+            .collect::<Option<Vec<_>>>()?;
+        if let Ok(characters) = String::from_utf16(&code_units) {
+            return Some(characters);
+        }
+    }
+
+    char::from_u32(u32::from_str_radix(hexadecimal, 16).ok()?).map(String::from)
+}
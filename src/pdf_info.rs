@@ -0,0 +1,263 @@
+use lopdf::{Dictionary, Object};
+use std::path::Path;
+
+use crate::{
+    error::{ContextError, ErrorKind},
+    pdf::points_to_millimeters,
+};
+
+/// A summary of the content of a PDF file, read back from its objects, so that the documents produced by this crate,
+/// or by any other tool, can be verified without a PDF viewer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PdfInfo {
+    /// The two identifiers in the `ID` entry of the trailer, which for the documents produced by this crate are the
+    /// document ID and the instance ID.
+    pub identifiers: Vec<String>,
+    /// The entries of the document information dictionary, such as the title and the producer, in their order.
+    pub metadata: Vec<(String, String)>,
+    /// The pages of the document, in their order.
+    pub pages: Vec<PdfPageInfo>,
+    /// The fonts of the document, in the order of their objects.
+    pub fonts: Vec<PdfFontInfo>,
+    /// The names of the layers (optional content groups) of the document.
+    pub layers: Vec<String>,
+}
+
+/// A page of a PDF file, see `PdfInfo`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PdfPageInfo {
+    /// The width of the page in millimeters.
+    pub width: f32,
+    /// The height of the page in millimeters.
+    pub height: f32,
+    /// The names of the layers which are used by the page.
+    pub layers: Vec<String>,
+}
+
+/// A font of a PDF file, see `PdfInfo`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PdfFontInfo {
+    /// The name of the font, such as `F0` for the fonts embedded by this crate.
+    pub name: String,
+    /// The type of the font, such as `Type0` or `TrueType`.
+    pub subtype: String,
+    /// The size in bytes of the font file embedded into the document, as it is stored (thus possibly compressed),
+    /// or `None` if the font is not embedded.
+    pub embedded_size: Option<usize>,
+}
+
+impl PdfInfo {
+    /// Reads the PDF file at the given path and summarizes its content.
+    pub fn from_path(pdf_path: &Path) -> Result<Self, ContextError> {
+        let inner_document = lopdf::Document::load(pdf_path).map_err(|error| {
+            ContextError::with_error(
+                format!("Unable to load the PDF file {:?}", pdf_path),
+                &error,
+            )
+            .with_kind(ErrorKind::PdfEncoding)
+        })?;
+        Ok(Self::from_inner_document(&inner_document))
+    }
+
+    /// Summarizes the content of the given PDF file, for instance as returned by `PdfDocument::save_to_bytes`.
+    pub fn from_bytes(pdf_bytes: &[u8]) -> Result<Self, ContextError> {
+        let inner_document = lopdf::Document::load_mem(pdf_bytes).map_err(|error| {
+            ContextError::with_error("Unable to load the PDF file", &error)
+                .with_kind(ErrorKind::PdfEncoding)
+        })?;
+        Ok(Self::from_inner_document(&inner_document))
+    }
+
+    /// Summarizes the content of the given low-level PDF document. Entries which are missing or malformed
+    /// are skipped rather than reported as errors, since the purpose is to show what the file contains.
+    fn from_inner_document(inner_document: &lopdf::Document) -> Self {
+        let identifiers = inner_document
+            .trailer
+            .get(b"ID")
+            .and_then(Object::as_array)
+            .ok()
+            .map(|identifiers| {
+                identifiers
+                    .iter()
+                    .filter_map(|identifier| identifier.as_str().ok())
+                    .map(decode_text_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let metadata = inner_document
+            .trailer
+            .get(b"Info")
+            .ok()
+            .and_then(|info| dereference_dictionary(inner_document, info))
+            .map(|info| {
+                info.iter()
+                    .map(|(key, value)| {
+                        let value = match inner_document.dereference(value) {
+                            Ok((_, Object::String(bytes, _))) => decode_text_string(bytes),
+                            Ok((_, Object::Name(name))) => String::from_utf8_lossy(name).into(),
+                            Ok((_, value)) => format!("{:?}", value),
+                            Err(_) => String::new(),
+                        };
+                        (String::from_utf8_lossy(key).into(), value)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let pages = inner_document
+            .get_pages()
+            .into_values()
+            .filter_map(|page_id| inner_document.get_dictionary(page_id).ok())
+            .map(|page| {
+                // The media box is possibly inherited from the ancestors of the page in the page tree
+                let media_box = inherited_entry(inner_document, page, b"MediaBox")
+                    .and_then(|media_box| media_box.as_array().ok())
+                    .map(|media_box| {
+                        media_box
+                            .iter()
+                            .map(|coordinate| coordinate.as_float().unwrap_or(0.0))
+                            .collect::<Vec<_>>()
+                    })
+                    .unwrap_or_default();
+                let (width, height) = match media_box[..] {
+                    [x_min, y_min, x_max, y_max] => (x_max - x_min, y_max - y_min),
+                    _ => (0.0, 0.0),
+                };
+                let layers = inherited_entry(inner_document, page, b"Resources")
+                    .and_then(|resources| dereference_dictionary(inner_document, resources))
+                    .and_then(|resources| resources.get(b"Properties").ok())
+                    .and_then(|properties| dereference_dictionary(inner_document, properties))
+                    .map(|properties| {
+                        properties
+                            .iter()
+                            .filter_map(|(_, ocg)| layer_name(inner_document, ocg))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+
+                PdfPageInfo {
+                    width: points_to_millimeters(width),
+                    height: points_to_millimeters(height),
+                    layers,
+                }
+            })
+            .collect();
+
+        let fonts = inner_document
+            .objects
+            .values()
+            .filter_map(|object| object.as_dict().ok())
+            .filter(|dictionary| dictionary.type_is(b"Font"))
+            .filter_map(|font| {
+                let subtype = font.get(b"Subtype").and_then(Object::as_name_str).ok()?;
+                // The descendants of the composite fonts are reported through their parent
+                if matches!(subtype, "CIDFontType0" | "CIDFontType2") {
+                    return None;
+                }
+                let name = font
+                    .get(b"BaseFont")
+                    .and_then(Object::as_name_str)
+                    .unwrap_or("unknown");
+
+                Some(PdfFontInfo {
+                    name: name.to_string(),
+                    subtype: subtype.to_string(),
+                    embedded_size: embedded_font_size(inner_document, font),
+                })
+            })
+            .collect();
+
+        let layers = inner_document
+            .catalog()
+            .ok()
+            .and_then(|catalog| catalog.get(b"OCProperties").ok())
+            .and_then(|properties| dereference_dictionary(inner_document, properties))
+            .and_then(|properties| properties.get(b"OCGs").and_then(Object::as_array).ok())
+            .map(|ocgs| {
+                ocgs.iter()
+                    .filter_map(|ocg| layer_name(inner_document, ocg))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        PdfInfo {
+            identifiers,
+            metadata,
+            pages,
+            fonts,
+            layers,
+        }
+    }
+}
+
+/// Resolves the given object into a dictionary, following it if it is a reference.
+fn dereference_dictionary<'a>(
+    inner_document: &'a lopdf::Document,
+    object: &'a Object,
+) -> Option<&'a Dictionary> {
+    inner_document
+        .dereference(object)
+        .ok()
+        .and_then(|(_, object)| object.as_dict().ok())
+}
+
+/// Retrieves an entry of the given page, looking for it in the ancestors of the page if it is not set on the page.
+pub(crate) fn inherited_entry<'a>(
+    inner_document: &'a lopdf::Document,
+    page: &'a Dictionary,
+    key: &[u8],
+) -> Option<&'a Object> {
+    let mut node = page;
+    // The depth is bounded so that a malformed page tree containing a cycle can't loop forever
+    for _ in 0..32 {
+        if let Ok(entry) = node.get(key) {
+            return Some(entry);
+        }
+        node = dereference_dictionary(inner_document, node.get(b"Parent").ok()?)?;
+    }
+
+    None
+}
+
+/// Retrieves the name of the layer (optional content group) referred to by the given object.
+fn layer_name(inner_document: &lopdf::Document, ocg: &Object) -> Option<String> {
+    dereference_dictionary(inner_document, ocg)?
+        .get(b"Name")
+        .and_then(Object::as_str)
+        .ok()
+        .map(decode_text_string)
+}
+
+/// Retrieves the stored size of the font file embedded for the given font, looking into the descendant font
+/// in the case of a composite font.
+fn embedded_font_size(inner_document: &lopdf::Document, font: &Dictionary) -> Option<usize> {
+    let font = match font.get(b"DescendantFonts").and_then(Object::as_array) {
+        Ok(descendant_fonts) => dereference_dictionary(inner_document, descendant_fonts.first()?)?,
+        Err(_) => font,
+    };
+    let font_descriptor =
+        dereference_dictionary(inner_document, font.get(b"FontDescriptor").ok()?)?;
+
+    [b"FontFile".as_slice(), b"FontFile2", b"FontFile3"]
+        .into_iter()
+        .filter_map(|key| font_descriptor.get(key).ok())
+        .find_map(|font_file| match inner_document.dereference(font_file) {
+            Ok((_, Object::Stream(stream))) => Some(stream.content.len()),
+            _ => None,
+        })
+}
+
+/// Decodes a PDF text string, which is either encoded in UTF-16BE with a byte order mark or else, for simplicity,
+/// treated as UTF-8, which matches PDFDocEncoding for ASCII text.
+pub(crate) fn decode_text_string(bytes: &[u8]) -> String {
+    match bytes {
+        [0xFE, 0xFF, utf16_bytes @ ..] => String::from_utf16_lossy(
+            &utf16_bytes
+                .chunks_exact(2)
+                .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+                .collect::<Vec<_>>(),
+        ),
+        _ => String::from_utf8_lossy(bytes).into(),
+    }
+}
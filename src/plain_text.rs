@@ -0,0 +1,115 @@
+use crate::{
+    canvas::{Canvas, FilledPath, TextRun},
+    document::Document,
+    error::{ContextError, ErrorKind},
+    pdf::points_to_millimeters,
+};
+
+/// A piece of text placed on a page, as collected while flattening the document.
+struct PlacedText {
+    /// The horizontal position of the text in millimeters.
+    x: f32,
+    /// The vertical position of the baseline of the text in millimeters.
+    y: f32,
+    /// The font size of the text expressed in millimeters.
+    font_size: f32,
+    /// The normalized text itself.
+    text: String,
+}
+
+impl Document {
+    /// Converts the given `Document` into plain text by flattening its operations in reading order, which is useful
+    /// for indexing and searching the generated documents. The pieces of text of every page are ordered from the top
+    /// to the bottom of the page and from left to right. Pieces of text whose baselines are closer than half of their
+    /// font size are considered to be on the same line and are joined by a space, while each page is terminated by a
+    /// form feed character, just like it is done by most PDF-to-text converters.
+    pub fn to_plain_text(&self) -> Result<String, ContextError> {
+        let mut plain_text_canvas = PlainTextCanvas::default();
+        self.draw(&mut plain_text_canvas)?;
+
+        Ok(plain_text_canvas.finish())
+    }
+}
+
+/// A `Canvas` which collects the text runs of every page and flattens them into plain text,
+/// see `Document::to_plain_text`. Paths are ignored.
+#[derive(Default)]
+pub struct PlainTextCanvas {
+    /// The pieces of text collected for each page.
+    pages: Vec<Vec<PlacedText>>,
+}
+
+impl PlainTextCanvas {
+    /// Flattens the collected pieces of text in reading order, returning the plain text.
+    pub fn finish(self) -> String {
+        let mut plain_text = String::new();
+        for mut page in self.pages {
+            // The origin of the page is in the bottom-left corner, so the reading order
+            // corresponds to decreasing vertical positions and then increasing horizontal ones
+            page.sort_by(|first_text, second_text| {
+                second_text
+                    .y
+                    .total_cmp(&first_text.y)
+                    .then(first_text.x.total_cmp(&second_text.x))
+            });
+
+            // Group the pieces of text into lines by comparing their baseline to the one of the line
+            let mut lines: Vec<(f32, Vec<PlacedText>)> = Vec::new();
+            for placed_text in page {
+                match lines.last_mut() {
+                    Some((line_y, line))
+                        if (*line_y - placed_text.y).abs() <= placed_text.font_size / 2.0 =>
+                    {
+                        line.push(placed_text)
+                    }
+                    _ => lines.push((placed_text.y, vec![placed_text])),
+                }
+            }
+
+            for (_, mut line) in lines {
+                line.sort_by(|first_text, second_text| first_text.x.total_cmp(&second_text.x));
+                let line = line
+                    .into_iter()
+                    .map(|placed_text| placed_text.text)
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                plain_text.push_str(&line);
+                plain_text.push('\n');
+            }
+            // Terminate the page with a form feed
+            plain_text.push('\u{c}');
+        }
+
+        plain_text
+    }
+}
+
+impl Canvas for PlainTextCanvas {
+    fn page_break(&mut self, _page_width: f32, _page_height: f32) -> Result<(), ContextError> {
+        self.pages.push(Vec::new());
+
+        Ok(())
+    }
+
+    fn text_run(&mut self, text_run: &TextRun) -> Result<(), ContextError> {
+        let current_page = self.pages.last_mut().ok_or(
+            ContextError::with_context(
+                "Unable to collect the text because no page has been begun yet",
+            )
+            .with_kind(ErrorKind::PageIndexOutOfRange),
+        )?;
+        let [x, y] = text_run.position;
+        current_page.push(PlacedText {
+            x,
+            y,
+            font_size: points_to_millimeters(text_run.font_size),
+            text: text_run.text.to_string(),
+        });
+
+        Ok(())
+    }
+
+    fn fill_path(&mut self, _path: &FilledPath) -> Result<(), ContextError> {
+        Ok(())
+    }
+}
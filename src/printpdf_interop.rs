@@ -0,0 +1,242 @@
+use std::{collections::HashMap, sync::Arc};
+
+use printpdf::{
+    Color, IndirectFontRef, Mm, PdfDocumentReference, PdfLayerIndex, PdfLayerReference,
+    PdfPageIndex, Point, Polygon, Rgb,
+};
+use time::OffsetDateTime;
+
+use crate::{
+    canvas::{Canvas, FilledPath, PathSegment, TextRun},
+    document::Document,
+    error::{ContextError, ErrorKind},
+    font::FontRegistry,
+};
+
+/// The name of the layer of each page begun by a `PrintpdfCanvas`, which is the same as the one of the pages of the
+/// PDF documents of this crate.
+const LAYER_NAME: &str = "Layer0";
+
+impl Document {
+    /// Draws the given `Document` onto a new printpdf document with the fonts of the given registry, so that the
+    /// projects built on printpdf can keep drawing onto it with their own code. The title, the author, the document
+    /// ID and the date of the document are carried over, while the raw PDF operations added by the custom operations
+    /// are left out, since they refer to the resources of the PDF documents of this crate (see `PrintpdfCanvas`).
+    ///
+    /// # Arguments
+    ///
+    /// * `font_registry` - The fonts with which the text is drawn, in the order given by their font index.
+    pub fn to_printpdf_document(
+        &self,
+        font_registry: &FontRegistry,
+    ) -> Result<PdfDocumentReference, ContextError> {
+        let timestamp = match self.timestamp {
+            Some(timestamp) => OffsetDateTime::from_unix_timestamp(timestamp).map_err(|error| {
+                ContextError::with_error("Invalid timestamp of the document", &error)
+                    .with_kind(ErrorKind::InvalidDocument)
+            })?,
+            None if self.deterministic => OffsetDateTime::UNIX_EPOCH,
+            None => OffsetDateTime::now_utc(),
+        };
+        let printpdf_document =
+            printpdf::PdfDocument::empty(self.title.as_deref().unwrap_or("Unknown"))
+                .with_author(self.author.as_deref().unwrap_or("Unknown"))
+                .with_document_id(self.document_id.clone())
+                .with_creation_date(timestamp)
+                .with_mod_date(timestamp)
+                .with_metadata_date(timestamp);
+
+        self.draw(&mut PrintpdfCanvas::new(&printpdf_document, font_registry))?;
+
+        Ok(printpdf_document)
+    }
+}
+
+impl TryFrom<&Document> for PdfDocumentReference {
+    type Error = ContextError;
+
+    /// Draws the document onto a new printpdf document with the built-in fonts (see `Document::to_printpdf_document`).
+    fn try_from(document: &Document) -> Result<Self, ContextError> {
+        document.to_printpdf_document(&FontRegistry::builtin()?)
+    }
+}
+
+impl TryFrom<PdfDocumentReference> for Document {
+    type Error = ContextError;
+
+    /// Saves the printpdf document and recovers a `Document` from it, on the same best-effort basis as
+    /// `Document::from_pdf_bytes`, so that the documents generated with printpdf can be migrated to this crate.
+    fn try_from(printpdf_document: PdfDocumentReference) -> Result<Self, ContextError> {
+        let pdf_bytes = printpdf_document.save_to_bytes().map_err(|error| {
+            ContextError::with_error("Unable to save the printpdf document", &error)
+                .with_kind(ErrorKind::PdfEncoding)
+        })?;
+
+        Document::from_pdf_bytes(&pdf_bytes)
+    }
+}
+
+/// A `Canvas` which draws onto a printpdf document, so that the documents of this crate can be drawn onto the same
+/// PDF document as the content drawn with printpdf, see `Document::to_printpdf_document`. Each page break appends a
+/// page with a single layer to the printpdf document, and the fonts are added to it the first time they are used.
+pub struct PrintpdfCanvas<'a> {
+    /// The printpdf document onto which the pages are appended.
+    printpdf_document: &'a PdfDocumentReference,
+    /// The content of the fonts, in the order given by their font index.
+    font_bytes: Vec<Arc<[u8]>>,
+    /// The fonts which have already been added to the printpdf document, by their font index.
+    fonts: HashMap<usize, IndirectFontRef>,
+    /// The pages begun so far, each with the index of its only layer.
+    pages: Vec<(PdfPageIndex, PdfLayerIndex)>,
+}
+
+impl<'a> PrintpdfCanvas<'a> {
+    /// Creates a canvas which appends its pages to the given printpdf document, drawing the text with the fonts of
+    /// the given registry.
+    pub fn new(printpdf_document: &'a PdfDocumentReference, font_registry: &FontRegistry) -> Self {
+        PrintpdfCanvas {
+            printpdf_document,
+            font_bytes: font_registry.font_bytes().to_vec(),
+            fonts: HashMap::new(),
+            pages: Vec::new(),
+        }
+    }
+
+    /// The indices of the pages begun so far in the printpdf document, each with the index of its only layer.
+    pub fn pages(&self) -> &[(PdfPageIndex, PdfLayerIndex)] {
+        &self.pages
+    }
+
+    /// Retrieves the font with the given index as a font of the printpdf document, adding it to the document if it
+    /// hasn't been used yet, so that the code built on printpdf can write with the same fonts.
+    pub fn font(&mut self, font_index: usize) -> Result<IndirectFontRef, ContextError> {
+        if let Some(font) = self.fonts.get(&font_index) {
+            return Ok(font.clone());
+        }
+        let font_bytes = self.font_bytes.get(font_index).ok_or(
+            ContextError::with_context(format!(
+                "Failed to find the font with index {}",
+                font_index
+            ))
+            .with_kind(ErrorKind::FontIndexOutOfRange),
+        )?;
+        let font = self
+            .printpdf_document
+            .add_external_font(font_bytes.as_ref())
+            .map_err(|error| {
+                ContextError::with_error("Failed to parse font", &error)
+                    .with_kind(ErrorKind::InvalidFont)
+            })?;
+        self.fonts.insert(font_index, font.clone());
+
+        Ok(font)
+    }
+
+    /// Retrieve the only layer of the current page.
+    fn current_layer(&self) -> Result<PdfLayerReference, ContextError> {
+        let (page_index, layer_index) = self.pages.last().ok_or(
+            ContextError::with_context(
+                "Unable to draw onto the printpdf document because no page has been begun yet",
+            )
+            .with_kind(ErrorKind::PageIndexOutOfRange),
+        )?;
+
+        Ok(self
+            .printpdf_document
+            .get_page(*page_index)
+            .get_layer(*layer_index))
+    }
+}
+
+impl Canvas for PrintpdfCanvas<'_> {
+    fn page_break(&mut self, page_width: f32, page_height: f32) -> Result<(), ContextError> {
+        self.pages.push(self.printpdf_document.add_page(
+            Mm(page_width),
+            Mm(page_height),
+            LAYER_NAME,
+        ));
+
+        Ok(())
+    }
+
+    fn text_run(&mut self, text_run: &TextRun) -> Result<(), ContextError> {
+        let font = self.font(text_run.font_index)?;
+        let layer = self.current_layer()?;
+        let [x, y] = text_run.position;
+        layer.set_fill_color(rgb_color(text_run.color));
+        layer.set_character_spacing(text_run.letter_spacing);
+        layer.use_text(text_run.text, text_run.font_size, Mm(x), Mm(y), &font);
+
+        Ok(())
+    }
+
+    fn fill_path(&mut self, path: &FilledPath) -> Result<(), ContextError> {
+        let layer = self.current_layer()?;
+        layer.set_fill_color(rgb_color(path.color));
+        layer.add_polygon(polygon(path));
+
+        Ok(())
+    }
+
+    fn font_bytes(&self) -> Option<Vec<Arc<[u8]>>> {
+        Some(self.font_bytes.clone())
+    }
+}
+
+/// Converts the given RGB color, with components between 0 and 1, into a printpdf color.
+fn rgb_color([red, green, blue]: [f32; 3]) -> Color {
+    Color::Rgb(Rgb::new(red, green, blue, None))
+}
+
+/// Converts the given filled path into a printpdf polygon, with one ring for each of its subpaths. In the rings of
+/// printpdf, a cubic Bézier curve is given by its starting point and its first control point being both marked,
+/// followed by its second control point and its end point.
+fn polygon(path: &FilledPath) -> Polygon {
+    let point = |[x, y]: [f32; 2]| Point::new(Mm(x), Mm(y));
+    let mut rings: Vec<Vec<(Point, bool)>> = Vec::new();
+    // The first point of the current subpath, from which the next one begins after it is closed
+    let mut subpath_start = None;
+    let mut is_closed = false;
+
+    for segment in &path.segments {
+        if let (PathSegment::LineTo(_) | PathSegment::CubicTo { .. }, true, Some(start)) =
+            (segment, is_closed, subpath_start)
+        {
+            rings.push(vec![(point(start), false)]);
+            is_closed = false;
+        }
+        match *segment {
+            PathSegment::MoveTo(position) => {
+                rings.push(vec![(point(position), false)]);
+                subpath_start = Some(position);
+                is_closed = false;
+            }
+            PathSegment::LineTo(position) => {
+                if let Some(ring) = rings.last_mut() {
+                    ring.push((point(position), false));
+                }
+            }
+            PathSegment::CubicTo {
+                first_control_point,
+                second_control_point,
+                end_point,
+            } => {
+                if let Some(ring) = rings.last_mut() {
+                    if let Some((_, is_curve_start)) = ring.last_mut() {
+                        *is_curve_start = true;
+                    }
+                    ring.push((point(first_control_point), true));
+                    ring.push((point(second_control_point), false));
+                    ring.push((point(end_point), false));
+                }
+            }
+            // The rings are closed when they are filled
+            PathSegment::Close => is_closed = true,
+        }
+    }
+
+    Polygon {
+        rings,
+        ..Default::default()
+    }
+}
@@ -0,0 +1,49 @@
+use serde::Serialize;
+
+/// The characteristics of the output of one or more conversions into PDF documents, such as the number of pages and
+/// the size of the embedded fonts, which are collected by the `PdfDocument` while it is built (see
+/// `PdfDocument::stats`). Statistics of many conversions, for instance of a batch of documents, can be accumulated
+/// via `ConversionStats::merge`.
+#[derive(Debug, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversionStats {
+    /// The number of pages.
+    pub page_count: usize,
+    /// The number of pieces of text which have been written.
+    pub text_run_count: usize,
+    /// The number of glyphs which have been written.
+    pub emitted_glyph_count: usize,
+    /// The number of characters which have been left out because they are missing from their font.
+    pub dropped_glyph_count: usize,
+    /// The number of fonts embedded into the document, which is only known once the document has been written.
+    pub embedded_font_count: usize,
+    /// The size in bytes of the font files embedded into the document, before any compression.
+    pub font_data_size: usize,
+}
+
+impl ConversionStats {
+    /// Adds the statistics of another conversion to these ones.
+    pub fn merge(&mut self, stats: &ConversionStats) {
+        self.page_count += stats.page_count;
+        self.text_run_count += stats.text_run_count;
+        self.emitted_glyph_count += stats.emitted_glyph_count;
+        self.dropped_glyph_count += stats.dropped_glyph_count;
+        self.embedded_font_count += stats.embedded_font_count;
+        self.font_data_size += stats.font_data_size;
+    }
+}
+
+impl std::fmt::Display for ConversionStats {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            formatter,
+            "{} page(s), {} text run(s), {} glyph(s) written and {} dropped, {} font(s) embedded ({} bytes)",
+            self.page_count,
+            self.text_run_count,
+            self.emitted_glyph_count,
+            self.dropped_glyph_count,
+            self.embedded_font_count,
+            self.font_data_size
+        )
+    }
+}
@@ -0,0 +1,146 @@
+use std::path::PathBuf;
+
+use crate::{
+    canvas::{Canvas, FilledPath, TextRun},
+    document::{builtin_font_paths, Document},
+    error::{ContextError, ErrorKind},
+    html::{css_color, escape_html, svg_path_data},
+    pdf::points_to_millimeters,
+};
+
+/// The vertical space in millimeters left in between two pages of the SVG image.
+const PAGE_SPACING: f32 = 10.0;
+
+impl Document {
+    /// Converts the given `Document` into a standalone SVG image that mirrors the layout of the PDF document.
+    /// The pages are placed one below the other, separated by a small gap, and millimeters are used as the user
+    /// units of the image. Each piece of text becomes a `text` element which uses the built-in fonts through
+    /// `@font-face` rules, with the paths relative to the current working directory, just like `to_html` does.
+    pub fn to_svg(&self) -> Result<String, ContextError> {
+        let mut svg_canvas = SvgCanvas::new(&builtin_font_paths()?);
+        self.draw(&mut svg_canvas)?;
+
+        Ok(svg_canvas.finish())
+    }
+}
+
+/// A `Canvas` which produces a standalone SVG image, see `Document::to_svg`.
+pub struct SvgCanvas {
+    /// The `@font-face` rules declaring the fonts.
+    font_faces: String,
+    /// The pages drawn so far, each with its width, height and the SVG elements drawn onto it.
+    pages: Vec<([f32; 2], String)>,
+}
+
+impl SvgCanvas {
+    /// Creates a canvas for an SVG image, declaring one font family for each of the given font paths,
+    /// named after the identifier of the font in the PDF document (`F0`, `F1`, ...).
+    pub fn new(font_paths: &[PathBuf]) -> Self {
+        let mut font_faces = String::new();
+        for (font_index, font_path) in font_paths.iter().enumerate() {
+            font_faces.push_str(&format!(
+                "@font-face {{ font-family: \"F{font_index}\"; src: url(\"{}\"); }}\n",
+                escape_html(&font_path.to_string_lossy().replace('\\', "/")),
+            ));
+        }
+
+        SvgCanvas {
+            font_faces,
+            pages: Vec::new(),
+        }
+    }
+
+    /// Places the pages one below the other, returning the SVG image.
+    pub fn finish(self) -> String {
+        let width = self
+            .pages
+            .iter()
+            .map(|([page_width, _], _)| *page_width)
+            .fold(0.0, f32::max);
+        let height = self
+            .pages
+            .iter()
+            .map(|([_, page_height], _)| page_height)
+            .sum::<f32>()
+            + PAGE_SPACING * self.pages.len().saturating_sub(1) as f32;
+
+        let mut svg = String::new();
+        svg.push_str(&format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}mm\" height=\"{height}mm\" viewBox=\"0 0 {width} {height}\">\n"
+        ));
+        svg.push_str("<style>\n");
+        svg.push_str(&self.font_faces);
+        svg.push_str("text { white-space: pre; }\n");
+        svg.push_str("</style>\n");
+        let mut page_offset = 0.0;
+        for ([page_width, page_height], page_elements) in &self.pages {
+            // Pages are left transparent, just like in the PDF document, unless a background is drawn onto them
+            svg.push_str(&format!(
+                "<svg x=\"0\" y=\"{page_offset}\" width=\"{page_width}\" height=\"{page_height}\" overflow=\"hidden\">\n"
+            ));
+            svg.push_str(page_elements);
+            svg.push_str("</svg>\n");
+            page_offset += page_height + PAGE_SPACING;
+        }
+        svg.push_str("</svg>\n");
+
+        svg
+    }
+
+    /// Retrieve the height and the elements of the current page.
+    fn current_page(&mut self) -> Result<(f32, &mut String), ContextError> {
+        self.pages
+            .last_mut()
+            .map(|([_, page_height], page_elements)| (*page_height, page_elements))
+            .ok_or(
+                ContextError::with_context(
+                    "Unable to draw onto the SVG image because no page has been begun yet",
+                )
+                .with_kind(ErrorKind::PageIndexOutOfRange),
+            )
+    }
+}
+
+impl Canvas for SvgCanvas {
+    fn page_break(&mut self, page_width: f32, page_height: f32) -> Result<(), ContextError> {
+        self.pages.push(([page_width, page_height], String::new()));
+
+        Ok(())
+    }
+
+    fn text_run(&mut self, text_run: &TextRun) -> Result<(), ContextError> {
+        let (page_height, page_elements) = self.current_page()?;
+        let [x, y] = text_run.position;
+        // The vertical axis of SVG points downwards, so the vertical coordinate is flipped, while the
+        // font size and the letter spacing are converted from points into millimeters
+        let font_size = points_to_millimeters(text_run.font_size);
+        let letter_spacing = if text_run.letter_spacing != 0.0 {
+            format!(
+                " letter-spacing=\"{}\"",
+                points_to_millimeters(text_run.letter_spacing)
+            )
+        } else {
+            String::new()
+        };
+        page_elements.push_str(&format!(
+            "<text x=\"{x}\" y=\"{}\" font-family=\"F{}\" font-size=\"{font_size}\" fill=\"{}\"{letter_spacing}>{}</text>\n",
+            page_height - y,
+            text_run.font_index,
+            css_color(text_run.color),
+            escape_html(text_run.text),
+        ));
+
+        Ok(())
+    }
+
+    fn fill_path(&mut self, path: &FilledPath) -> Result<(), ContextError> {
+        let (page_height, page_elements) = self.current_page()?;
+        let path_data = svg_path_data(path, page_height);
+        page_elements.push_str(&format!(
+            "<path d=\"{path_data}\" fill=\"{}\"/>\n",
+            css_color(path.color),
+        ));
+
+        Ok(())
+    }
+}
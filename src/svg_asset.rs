@@ -0,0 +1,349 @@
+use std::sync::Arc;
+
+use resvg::{
+    tiny_skia,
+    usvg::{self, tiny_skia_path},
+};
+
+use crate::{
+    error::{ContextError, ErrorKind},
+    pdf::{millimeters_to_points, PdfDocument},
+};
+
+impl PdfDocument {
+    /// Retrieve the database of the fonts of the document, in the order given by their font index, with which the
+    /// text of the SVG images is converted into paths. It is only built for the first SVG image, and it shares the
+    /// content of the fonts with the document rather than copying it.
+    fn svg_font_database(&mut self) -> Arc<usvg::fontdb::Database> {
+        if self.svg_font_database.is_none() {
+            let mut font_database = usvg::fontdb::Database::new();
+            for font_bytes in self.indexed_font_bytes() {
+                font_database.load_font_source(usvg::fontdb::Source::Binary(Arc::new(font_bytes)));
+            }
+            self.svg_font_database = Some(Arc::new(font_database));
+        }
+
+        self.svg_font_database.clone().unwrap_or_default()
+    }
+
+    /// Draws the image of the given SVG file onto the specified layer of the page, stretched to fill the given
+    /// rectangle. The paths filled or stroked with a plain color are converted into the equivalent PDF paths, and
+    /// so is the text, once it has been converted into paths with the fonts added to the document, so that logos
+    /// stay sharp at any zoom level. The images which use anything else, such as gradients, transparency, clipping
+    /// paths or embedded images, are rasterized at the given resolution and drawn as a PNG image instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `page_index` - The index of the page onto which the image is drawn (should be previously obtained).
+    /// * `layer_index` - The index of the layer of the page onto which the image is drawn.
+    /// * `svg_bytes` - The content of the SVG file.
+    /// * `position` - The position of the bottom-left corner of the image in millimeters.
+    /// * `size` - The width and the height of the image on the page in millimeters.
+    /// * `raster_dpi` - The number of pixels per inch of the image, if it needs to be rasterized.
+    pub fn add_svg_to_layer_in_page(
+        &mut self,
+        page_index: usize,
+        layer_index: usize,
+        svg_bytes: &[u8],
+        position: [f32; 2],
+        size: [f32; 2],
+        raster_dpi: f32,
+    ) -> Result<(), ContextError> {
+        self.check_layer_in_page(layer_index, page_index)?;
+
+        // The text is converted into paths with the fonts of the document, the first of them being the default one
+        let mut options = usvg::Options {
+            fontdb: self.svg_font_database(),
+            ..Default::default()
+        };
+        if let Some(family) = options
+            .fontdb
+            .faces()
+            .next()
+            .and_then(|face| face.families.first())
+        {
+            options.font_family = family.0.clone();
+        }
+        let tree = usvg::Tree::from_data(svg_bytes, &options).map_err(|error| {
+            ContextError::with_error("Failed to parse the SVG image", &error)
+                .with_kind(ErrorKind::InvalidImage)
+        })?;
+
+        let mut path_operations = Vec::new();
+        if append_group_operations(tree.root(), &mut path_operations).is_some() {
+            // Map the coordinates of the image, whose vertical axis points downwards, onto the given rectangle
+            let [x, y] = position.map(millimeters_to_points);
+            let [width, height] = size.map(millimeters_to_points);
+            let mut operations = vec![
+                lopdf::content::Operation::new("q", vec![]),
+                lopdf::content::Operation::new(
+                    "cm",
+                    vec![
+                        (width / tree.size().width()).into(),
+                        0.into(),
+                        0.into(),
+                        (-height / tree.size().height()).into(),
+                        x.into(),
+                        (y + height).into(),
+                    ],
+                ),
+            ];
+            operations.extend(path_operations);
+            operations.push(lopdf::content::Operation::new("Q", vec![]));
+
+            return self.add_raw_operations_to_layer_in_page(page_index, layer_index, operations);
+        }
+
+        tracing::debug!(
+            page_index,
+            "Rasterizing an SVG image which can't be converted into paths"
+        );
+        let png_bytes = rasterize(&tree, size, raster_dpi)?;
+        self.add_image_to_layer_in_page(page_index, layer_index, &png_bytes, position, size)
+    }
+}
+
+/// Appends the operations which draw the paths of the given group, or returns `None` if the group contains
+/// anything which can't be converted into plain PDF paths.
+fn append_group_operations(
+    group: &usvg::Group,
+    operations: &mut Vec<lopdf::content::Operation>,
+) -> Option<()> {
+    if group.opacity().get() != 1.0
+        || group.clip_path().is_some()
+        || group.mask().is_some()
+        || !group.filters().is_empty()
+    {
+        return None;
+    }
+
+    for node in group.children() {
+        match node {
+            usvg::Node::Group(group) => append_group_operations(group, operations)?,
+            usvg::Node::Path(path) => append_path_operations(path, operations)?,
+            usvg::Node::Text(text) => append_group_operations(text.flattened(), operations)?,
+            usvg::Node::Image(_) => return None,
+        }
+    }
+
+    Some(())
+}
+
+/// Appends the operations which fill and stroke the given path, or returns `None` if it is painted with anything
+/// other than a plain opaque color.
+fn append_path_operations(
+    path: &usvg::Path,
+    operations: &mut Vec<lopdf::content::Operation>,
+) -> Option<()> {
+    use lopdf::content::Operation;
+
+    if !path.is_visible() {
+        return Some(());
+    }
+    let plain_color = |paint: &usvg::Paint, opacity: usvg::Opacity| match paint {
+        usvg::Paint::Color(color) if opacity.get() == 1.0 => Some(
+            [color.red, color.green, color.blue]
+                .map(|component| lopdf::Object::Real(component as f32 / 255.0))
+                .to_vec(),
+        ),
+        _ => None,
+    };
+    let fill = match path.fill() {
+        Some(fill) => Some((plain_color(fill.paint(), fill.opacity())?, fill.rule())),
+        None => None,
+    };
+    let stroke = match path.stroke() {
+        Some(stroke) => Some((plain_color(stroke.paint(), stroke.opacity())?, stroke)),
+        None => None,
+    };
+
+    // Isolate the graphics state, since the transformation and the colors only apply to this path
+    let transform = path.abs_transform();
+    operations.push(Operation::new("q", vec![]));
+    operations.push(Operation::new(
+        "cm",
+        [
+            transform.sx,
+            transform.ky,
+            transform.kx,
+            transform.sy,
+            transform.tx,
+            transform.ty,
+        ]
+        .map(lopdf::Object::Real)
+        .to_vec(),
+    ));
+    if let Some((color, _)) = &fill {
+        operations.push(Operation::new("rg", color.clone()));
+    }
+    if let Some((color, stroke)) = &stroke {
+        operations.push(Operation::new("RG", color.clone()));
+        operations.push(Operation::new("w", vec![stroke.width().get().into()]));
+        operations.push(Operation::new(
+            "J",
+            vec![match stroke.linecap() {
+                usvg::LineCap::Butt => 0.into(),
+                usvg::LineCap::Round => 1.into(),
+                usvg::LineCap::Square => 2.into(),
+            }],
+        ));
+        operations.push(Operation::new(
+            "j",
+            vec![match stroke.linejoin() {
+                usvg::LineJoin::Miter | usvg::LineJoin::MiterClip => 0.into(),
+                usvg::LineJoin::Round => 1.into(),
+                usvg::LineJoin::Bevel => 2.into(),
+            }],
+        ));
+        operations.push(Operation::new("M", vec![stroke.miterlimit().get().into()]));
+        if let Some(dasharray) = stroke.dasharray() {
+            operations.push(Operation::new(
+                "d",
+                vec![
+                    dasharray
+                        .iter()
+                        .map(|length| lopdf::Object::Real(*length))
+                        .collect::<Vec<_>>()
+                        .into(),
+                    stroke.dashoffset().into(),
+                ],
+            ));
+        }
+    }
+
+    // Filling the path after stroking it requires the path to be constructed twice
+    let painting_operators = match (&fill, &stroke, path.paint_order()) {
+        (Some((_, rule)), Some(_), usvg::PaintOrder::StrokeAndFill) => {
+            vec!["S", fill_operator(*rule, false)]
+        }
+        (Some((_, rule)), Some(_), usvg::PaintOrder::FillAndStroke) => {
+            vec![fill_operator(*rule, true)]
+        }
+        (Some((_, rule)), None, _) => vec![fill_operator(*rule, false)],
+        (None, Some(_), _) => vec!["S"],
+        (None, None, _) => vec!["n"],
+    };
+    for painting_operator in painting_operators {
+        append_segment_operations(path.data(), operations);
+        operations.push(Operation::new(painting_operator, vec![]));
+    }
+    operations.push(Operation::new("Q", vec![]));
+
+    Some(())
+}
+
+/// Returns the operator which fills a path with the given rule, and which also strokes it if requested.
+fn fill_operator(rule: usvg::FillRule, also_stroke: bool) -> &'static str {
+    match (rule, also_stroke) {
+        (usvg::FillRule::NonZero, false) => "f",
+        (usvg::FillRule::EvenOdd, false) => "f*",
+        (usvg::FillRule::NonZero, true) => "B",
+        (usvg::FillRule::EvenOdd, true) => "B*",
+    }
+}
+
+/// Appends the operations which construct the given path segment by segment.
+fn append_segment_operations(
+    path: &tiny_skia_path::Path,
+    operations: &mut Vec<lopdf::content::Operation>,
+) {
+    use lopdf::content::Operation;
+
+    let point_to_operands = |point: tiny_skia_path::Point| {
+        vec![lopdf::Object::Real(point.x), lopdf::Object::Real(point.y)]
+    };
+    // The point two thirds of the way from the first point to the second one
+    let two_thirds_towards = |from: tiny_skia_path::Point, to: tiny_skia_path::Point| {
+        tiny_skia_path::Point::from_xy(
+            from.x + (to.x - from.x) * 2.0 / 3.0,
+            from.y + (to.y - from.y) * 2.0 / 3.0,
+        )
+    };
+    let mut current_point = tiny_skia_path::Point::zero();
+    for segment in path.segments() {
+        match segment {
+            tiny_skia_path::PathSegment::MoveTo(point) => {
+                operations.push(Operation::new("m", point_to_operands(point)));
+                current_point = point;
+            }
+            tiny_skia_path::PathSegment::LineTo(point) => {
+                operations.push(Operation::new("l", point_to_operands(point)));
+                current_point = point;
+            }
+            tiny_skia_path::PathSegment::QuadTo(control_point, end_point) => {
+                // PDF paths only have cubic curves, into which the quadratic ones are exactly converted
+                let first_control_point = two_thirds_towards(current_point, control_point);
+                let second_control_point = two_thirds_towards(end_point, control_point);
+                operations.push(Operation::new(
+                    "c",
+                    [first_control_point, second_control_point, end_point]
+                        .into_iter()
+                        .flat_map(point_to_operands)
+                        .collect(),
+                ));
+                current_point = end_point;
+            }
+            tiny_skia_path::PathSegment::CubicTo(
+                first_control_point,
+                second_control_point,
+                end_point,
+            ) => {
+                operations.push(Operation::new(
+                    "c",
+                    [first_control_point, second_control_point, end_point]
+                        .into_iter()
+                        .flat_map(point_to_operands)
+                        .collect(),
+                ));
+                current_point = end_point;
+            }
+            tiny_skia_path::PathSegment::Close => operations.push(Operation::new("h", vec![])),
+        }
+    }
+}
+
+/// Renders the given SVG image at the size it takes on the page and with the given resolution, and encodes it
+/// as a PNG image, whose alpha channel is kept.
+fn rasterize(tree: &usvg::Tree, size: [f32; 2], raster_dpi: f32) -> Result<Vec<u8>, ContextError> {
+    let [pixel_width, pixel_height] =
+        size.map(|millimeters| (millimeters / 25.4 * raster_dpi).round().max(1.0) as u32);
+    let mut pixmap = tiny_skia::Pixmap::new(pixel_width, pixel_height).ok_or(
+        ContextError::with_context(format!(
+            "Unable to rasterize the SVG image with a size of {}x{} pixels",
+            pixel_width, pixel_height
+        ))
+        .with_kind(ErrorKind::InvalidImage),
+    )?;
+    resvg::render(
+        tree,
+        tiny_skia::Transform::from_scale(
+            pixel_width as f32 / tree.size().width(),
+            pixel_height as f32 / tree.size().height(),
+        ),
+        &mut pixmap.as_mut(),
+    );
+
+    // The pixels are rendered with their colors premultiplied by their alpha, unlike the ones of the PNG images
+    let pixels = pixmap
+        .pixels()
+        .iter()
+        .flat_map(|pixel| {
+            let pixel = pixel.demultiply();
+            [pixel.red(), pixel.green(), pixel.blue(), pixel.alpha()]
+        })
+        .collect::<Vec<u8>>();
+    let image = image::RgbaImage::from_raw(pixel_width, pixel_height, pixels)
+        .expect("The pixels fill the whole image");
+    let mut png_bytes = Vec::new();
+    image
+        .write_to(
+            &mut std::io::Cursor::new(&mut png_bytes),
+            image::ImageFormat::Png,
+        )
+        .map_err(|error| {
+            ContextError::with_error("Failed to encode the rasterized SVG image", &error)
+                .with_kind(ErrorKind::InvalidImage)
+        })?;
+
+    Ok(png_bytes)
+}
@@ -0,0 +1,785 @@
+use rand::{distributions::Alphanumeric, rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    ops::Range,
+    path::Path,
+};
+
+use crate::{
+    document::{Document, Operation},
+    error::{ContextError, ErrorKind},
+};
+
+/// The parameters of the documents generated by a `DocumentGenerator`. The default configuration is the one with
+/// which the fuzz targets of this crate are generated.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct GeneratorConfiguration {
+    /// The range of the font indices of the text, which depends on the fonts loaded into the documents.
+    pub font_index_range: Range<usize>,
+    /// The maximum number of operations following the first page of each document.
+    pub maximum_operation_count: usize,
+    /// The maximum number of characters of each piece of text.
+    pub maximum_text_length: usize,
+    /// The range of the font sizes of the text.
+    pub font_size_range: Range<f32>,
+    /// The range of the widths of the pages in millimeters.
+    pub page_width_range: Range<f32>,
+    /// The range of the heights of the pages in millimeters.
+    pub page_height_range: Range<f32>,
+    /// The range of both the coordinates of the text in millimeters.
+    pub position_range: Range<f32>,
+    /// The probability that an operation following the first page writes some text rather than appending a page.
+    pub text_probability: f64,
+}
+
+impl Default for GeneratorConfiguration {
+    fn default() -> Self {
+        GeneratorConfiguration {
+            font_index_range: 0..30,
+            maximum_operation_count: 190,
+            maximum_text_length: 230,
+            font_size_range: 39.0..65.0,
+            page_width_range: 200.0..1300.0,
+            page_height_range: 200.0..800.0,
+            position_range: 0.0..600.0,
+            text_probability: 0.7,
+        }
+    }
+}
+
+/// Generates random documents, such as the fuzz targets of this crate, from an explicit seed, so that the same
+/// seed and configuration produce the same documents on every machine. Each document begins with a page, which
+/// is followed by pieces of text and other pages in a random order.
+///
+/// # Example
+///
+/// ```
+/// use textr::testing::{DocumentGenerator, GeneratorConfiguration};
+///
+/// let documents = DocumentGenerator::new(42, GeneratorConfiguration::default())
+///     .take(3)
+///     .collect::<Vec<_>>();
+/// assert_eq!(documents.len(), 3);
+/// ```
+#[derive(Debug, Clone)]
+pub struct DocumentGenerator {
+    /// The random number generator, seeded explicitly.
+    rng: StdRng,
+    /// The parameters of the generated documents.
+    configuration: GeneratorConfiguration,
+}
+
+impl DocumentGenerator {
+    /// Creates a generator of documents from the given seed and configuration.
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` - The seed of the random number generator.
+    /// * `configuration` - The parameters of the generated documents.
+    pub fn new(seed: u64, configuration: GeneratorConfiguration) -> Self {
+        DocumentGenerator {
+            rng: StdRng::seed_from_u64(seed),
+            configuration,
+        }
+    }
+
+    /// Returns the configuration of the generated documents.
+    pub fn configuration(&self) -> &GeneratorConfiguration {
+        &self.configuration
+    }
+
+    /// Generates the next document, whose document and instance IDs are random strings of 32 characters
+    /// as required by the PDF specification.
+    pub fn generate_document(&mut self) -> Document {
+        let document_id = self.generate_identifier();
+        let instance_id = self.generate_identifier();
+
+        // Each document needs to begin with a page, onto which the following text is written
+        let mut operations = vec![self.generate_page()];
+        let operation_count = self
+            .rng
+            .gen_range(1..self.configuration.maximum_operation_count.max(2));
+        for _ in 0..operation_count {
+            let operation = if self.rng.gen_bool(self.configuration.text_probability) {
+                self.generate_text()
+            } else {
+                self.generate_page()
+            };
+            operations.push(operation);
+        }
+
+        Document {
+            document_id,
+            instance_id,
+            operations,
+            page_background: None,
+            title: None,
+            author: None,
+            running_header: None,
+            timestamp: None,
+            // The generated documents serve as the references of the regression tests, so they must be reproducible
+            deterministic: true,
+            strict: false,
+        }
+    }
+
+    /// Generates a random alphanumeric string of 32 characters.
+    fn generate_identifier(&mut self) -> String {
+        (&mut self.rng)
+            .sample_iter(&Alphanumeric)
+            .map(char::from)
+            .take(32)
+            .collect()
+    }
+
+    /// Generates a page with a random size.
+    fn generate_page(&mut self) -> Operation {
+        Operation::AppendNewPage {
+            page_width: self
+                .rng
+                .gen_range(self.configuration.page_width_range.clone()),
+            page_height: self
+                .rng
+                .gen_range(self.configuration.page_height_range.clone()),
+        }
+    }
+
+    /// Generates a piece of random UTF-8 text with a random color, position, font and font size.
+    fn generate_text(&mut self) -> Operation {
+        let color = [
+            self.rng.gen_range(0.0..=1.0),
+            self.rng.gen_range(0.0..=1.0),
+            self.rng.gen_range(0.0..=1.0),
+        ];
+        let position = [
+            self.rng
+                .gen_range(self.configuration.position_range.clone()),
+            self.rng
+                .gen_range(self.configuration.position_range.clone()),
+        ];
+        let text_length = self
+            .rng
+            .gen_range(1..=self.configuration.maximum_text_length.max(1));
+        let text_string = rand_utf8::rand_utf8(&mut self.rng, text_length).to_string();
+
+        Operation::WriteUnicodeText {
+            color,
+            position,
+            text_string,
+            font_size: self
+                .rng
+                .gen_range(self.configuration.font_size_range.clone()),
+            font_index: self
+                .rng
+                .gen_range(self.configuration.font_index_range.clone()),
+            letter_spacing: 0.0,
+        }
+    }
+}
+
+impl Iterator for DocumentGenerator {
+    type Item = Document;
+
+    fn next(&mut self) -> Option<Document> {
+        Some(self.generate_document())
+    }
+}
+
+/// Shrinks the given failing document, such as a fuzz target, to a minimal reproducer by removing as many of its
+/// operations as possible while the given predicate still reports the failure, for instance the same conversion error
+/// or the same mismatch with a reference. The operations are removed in chunks which are halved whenever none of them
+/// can be removed, so that large documents are shrunk in few steps, until no single operation can be removed anymore.
+/// The document is returned as it is if it doesn't fail in the first place.
+///
+/// # Arguments
+///
+/// * `document` - The failing document.
+/// * `is_failing` - Whether a document still fails in the same way as the given one.
+pub fn minimize_document(
+    document: &Document,
+    mut is_failing: impl FnMut(&Document) -> bool,
+) -> Document {
+    let mut minimized_document = document.clone();
+    if !is_failing(&minimized_document) {
+        return minimized_document;
+    }
+
+    let mut chunk_count = 2;
+    while !minimized_document.operations.is_empty() {
+        let operation_count = minimized_document.operations.len();
+        let current_chunk_count = chunk_count.min(operation_count);
+        let chunk_length = operation_count.div_ceil(current_chunk_count);
+
+        // Try to remove each chunk of operations in turn, keeping the first removal which preserves the failure
+        let mut candidate_document = minimized_document.clone();
+        let has_shrunk = (0..operation_count)
+            .step_by(chunk_length)
+            .any(|chunk_start| {
+                candidate_document.operations = minimized_document.operations[..chunk_start]
+                    .iter()
+                    .chain(
+                        minimized_document.operations
+                            [(chunk_start + chunk_length).min(operation_count)..]
+                            .iter(),
+                    )
+                    .cloned()
+                    .collect();
+                is_failing(&candidate_document)
+            });
+
+        if has_shrunk {
+            minimized_document = candidate_document;
+            chunk_count = (current_chunk_count - 1).max(2);
+        } else if chunk_length > 1 {
+            chunk_count = (current_chunk_count * 2).min(operation_count);
+        } else {
+            break;
+        }
+    }
+
+    minimized_document
+}
+
+/// The entries of the information dictionary which change every time a PDF document is saved.
+const VOLATILE_INFO_ENTRIES: [&[u8]; 2] = [b"CreationDate", b"ModDate"];
+
+/// Rewrites the given PDF document in a canonical form, so that two documents with the same content can be
+/// compared byte by byte, as done by the regression tests of this crate. The creation and modification dates are
+/// removed, the streams are decompressed, the keys of the dictionaries are sorted and the objects are numbered in
+/// the order in which they are reached from the trailer, while the objects which can't be reached are dropped.
+///
+/// # Arguments
+///
+/// * `pdf_bytes` - The content of the PDF file.
+pub fn normalize_pdf(pdf_bytes: &[u8]) -> Result<Vec<u8>, ContextError> {
+    let pdf_document = load_without_volatile_fields(pdf_bytes)?;
+
+    // Number the objects in the order in which they are reached, visiting the entries of the dictionaries by key
+    let mut trailer = lopdf::Object::Dictionary(pdf_document.trailer.clone());
+    let mut new_object_ids = BTreeMap::new();
+    let mut objects_to_visit = VecDeque::new();
+    collect_references(&trailer, &mut objects_to_visit);
+    while let Some(object_id) = objects_to_visit.pop_front() {
+        if new_object_ids.contains_key(&object_id) {
+            continue;
+        }
+        let Ok(object) = pdf_document.get_object(object_id) else {
+            continue;
+        };
+        new_object_ids.insert(object_id, (new_object_ids.len() as u32 + 1, 0));
+        collect_references(object, &mut objects_to_visit);
+    }
+
+    let mut normalized_pdf_document = lopdf::Document::new();
+    normalized_pdf_document.version = pdf_document.version.clone();
+    for (object_id, new_object_id) in &new_object_ids {
+        let mut object = pdf_document.objects[object_id].clone();
+        canonicalize_object(&mut object, &new_object_ids);
+        normalized_pdf_document
+            .objects
+            .insert(*new_object_id, object);
+    }
+    canonicalize_object(&mut trailer, &new_object_ids);
+    if let lopdf::Object::Dictionary(mut trailer) = trailer {
+        // The cross-reference table is written again from scratch, so the entries which refer to the old one are dropped
+        for entry in [&b"Prev"[..], b"XRefStm", b"Size"] {
+            trailer.remove(entry);
+        }
+        normalized_pdf_document.trailer = trailer;
+    }
+    normalized_pdf_document.max_id = new_object_ids.len() as u32;
+
+    let mut normalized_pdf_bytes = Vec::new();
+    normalized_pdf_document
+        .save_to(&mut normalized_pdf_bytes)
+        .map_err(|error| {
+            ContextError::with_error("Failed to save the normalized PDF document", &error)
+                .with_kind(ErrorKind::PdfEncoding)
+        })?;
+
+    Ok(normalized_pdf_bytes)
+}
+
+/// Compares the given PDF documents once they have been normalized (see `normalize_pdf`), returning an error
+/// which tells the first line at which they differ if they aren't equivalent.
+///
+/// # Arguments
+///
+/// * `pdf_bytes` - The content of the PDF file under test.
+/// * `reference_pdf_bytes` - The content of the PDF file it is expected to be equivalent to.
+pub fn compare_pdfs(pdf_bytes: &[u8], reference_pdf_bytes: &[u8]) -> Result<(), ContextError> {
+    let normalized_pdf_bytes = normalize_pdf(pdf_bytes)?;
+    let normalized_reference_pdf_bytes = normalize_pdf(reference_pdf_bytes)?;
+    if normalized_pdf_bytes == normalized_reference_pdf_bytes {
+        return Ok(());
+    }
+
+    let lines = normalized_pdf_bytes
+        .split(|byte| *byte == b'\n')
+        .collect::<Vec<_>>();
+    let reference_lines = normalized_reference_pdf_bytes
+        .split(|byte| *byte == b'\n')
+        .collect::<Vec<_>>();
+    // If one of the documents is a prefix of the other, they differ from the end of the shorter one
+    let line_index = lines
+        .iter()
+        .zip(&reference_lines)
+        .position(|(line, reference_line)| line != reference_line)
+        .unwrap_or(lines.len().min(reference_lines.len()));
+
+    Err(ContextError::with_context(format!(
+        "The PDF documents differ from line {} of their normalized form",
+        line_index + 1
+    )))
+}
+
+/// A difference between the objects of two PDF documents, as reported by `diff_pdfs`. The objects are identified
+/// by the path through which they are first reached from the trailer, such as `/Root/Pages/Kids[0]/Contents`,
+/// rather than by their number, which changes whenever an object is added before them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "difference")]
+pub enum PdfDifference {
+    /// An object is only present in the other document.
+    #[serde(rename_all = "camelCase")]
+    Added {
+        /// The path of the object.
+        path: String,
+        /// The object in the syntax of the PDF documents, without the content of its stream.
+        object: String,
+    },
+    /// An object is only present in the original document.
+    #[serde(rename_all = "camelCase")]
+    Removed {
+        /// The path of the object.
+        path: String,
+        /// The object in the syntax of the PDF documents, without the content of its stream.
+        object: String,
+    },
+    /// An object is present in both documents, but some of its entries or the content of its stream differ.
+    #[serde(rename_all = "camelCase")]
+    Changed {
+        /// The path of the object.
+        path: String,
+        /// The entries of the dictionary which differ, or a single entry without key if the object is not a dictionary.
+        entries: Vec<PdfEntryChange>,
+        /// Whether the decompressed content of the stream differs.
+        content_changed: bool,
+    },
+}
+
+/// An entry of a dictionary which differs between two PDF documents, with both of its values described in the
+/// syntax of the PDF documents, where the references are replaced by the paths of the objects they refer to.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PdfEntryChange {
+    /// The key of the entry, such as `MediaBox`.
+    pub key: String,
+    /// The value of the entry in the original document, if it is present.
+    pub old: Option<String>,
+    /// The value of the entry in the other document, if it is present.
+    pub new: Option<String>,
+}
+
+/// Computes the differences between the objects of the two given PDF documents, ignoring their creation and
+/// modification dates and the numbering of their objects, so that a failing regression test tells which objects,
+/// and which of their entries, have changed. The differences are sorted by the path of the objects.
+///
+/// # Arguments
+///
+/// * `pdf_bytes` - The content of the original PDF file.
+/// * `other_pdf_bytes` - The content of the PDF file to compare the original one against.
+pub fn diff_pdfs(
+    pdf_bytes: &[u8],
+    other_pdf_bytes: &[u8],
+) -> Result<Vec<PdfDifference>, ContextError> {
+    let pdf_document = load_without_volatile_fields(pdf_bytes)?;
+    let other_pdf_document = load_without_volatile_fields(other_pdf_bytes)?;
+    let object_paths = object_paths_of(&pdf_document);
+    let other_object_paths = object_paths_of(&other_pdf_document);
+    let objects_by_path = invert(&object_paths);
+    let other_objects_by_path = invert(&other_object_paths);
+
+    let mut differences = Vec::new();
+    for (path, object_id) in &objects_by_path {
+        let object = &pdf_document.objects[object_id];
+        let Some(other_object_id) = other_objects_by_path.get(path) else {
+            differences.push(PdfDifference::Removed {
+                path: path.clone(),
+                object: describe_object(object, &object_paths),
+            });
+            continue;
+        };
+        let other_object = &other_pdf_document.objects[other_object_id];
+
+        let (dictionary, other_dictionary) = match (object, other_object) {
+            (
+                lopdf::Object::Dictionary(dictionary),
+                lopdf::Object::Dictionary(other_dictionary),
+            ) => (Some(dictionary), Some(other_dictionary)),
+            (lopdf::Object::Stream(stream), lopdf::Object::Stream(other_stream)) => {
+                (Some(&stream.dict), Some(&other_stream.dict))
+            }
+            _ => (None, None),
+        };
+        let entries = match (dictionary, other_dictionary) {
+            (Some(dictionary), Some(other_dictionary)) => {
+                let keys = dictionary
+                    .iter()
+                    .chain(other_dictionary.iter())
+                    .map(|(key, _)| key.clone())
+                    .collect::<BTreeSet<_>>();
+                keys.into_iter()
+                    .filter_map(|key| {
+                        let describe = |dictionary: &lopdf::Dictionary, object_paths| {
+                            dictionary
+                                .get(&key)
+                                .ok()
+                                .map(|object| describe_object(object, object_paths))
+                        };
+                        let old = describe(dictionary, &object_paths);
+                        let new = describe(other_dictionary, &other_object_paths);
+                        (old != new).then(|| PdfEntryChange {
+                            key: String::from_utf8_lossy(&key).into_owned(),
+                            old,
+                            new,
+                        })
+                    })
+                    .collect()
+            }
+            _ => {
+                let old = describe_object(object, &object_paths);
+                let new = describe_object(other_object, &other_object_paths);
+                if old == new {
+                    Vec::new()
+                } else {
+                    vec![PdfEntryChange {
+                        key: String::new(),
+                        old: Some(old),
+                        new: Some(new),
+                    }]
+                }
+            }
+        };
+        let content_changed = match (object, other_object) {
+            (lopdf::Object::Stream(stream), lopdf::Object::Stream(other_stream)) => {
+                stream.content != other_stream.content
+            }
+            _ => false,
+        };
+
+        if !entries.is_empty() || content_changed {
+            differences.push(PdfDifference::Changed {
+                path: path.clone(),
+                entries,
+                content_changed,
+            });
+        }
+    }
+    for (path, other_object_id) in &other_objects_by_path {
+        if !objects_by_path.contains_key(path) {
+            differences.push(PdfDifference::Added {
+                path: path.clone(),
+                object: describe_object(
+                    &other_pdf_document.objects[other_object_id],
+                    &other_object_paths,
+                ),
+            });
+        }
+    }
+    differences.sort_by_key(|difference| match difference {
+        PdfDifference::Added { path, .. }
+        | PdfDifference::Removed { path, .. }
+        | PdfDifference::Changed { path, .. } => path.clone(),
+    });
+
+    Ok(differences)
+}
+
+/// Describes the given object in the syntax of the PDF documents, with the entries of its dictionaries sorted by
+/// key, the references replaced by the paths of the objects they refer to, and the content of its stream left out.
+fn describe_object(
+    object: &lopdf::Object,
+    object_paths: &BTreeMap<lopdf::ObjectId, String>,
+) -> String {
+    match object {
+        lopdf::Object::Null => "null".to_string(),
+        lopdf::Object::Boolean(value) => value.to_string(),
+        lopdf::Object::Integer(value) => value.to_string(),
+        lopdf::Object::Real(value) => value.to_string(),
+        lopdf::Object::Name(name) => format!("/{}", String::from_utf8_lossy(name)),
+        lopdf::Object::String(bytes, _) => format!("({})", String::from_utf8_lossy(bytes)),
+        lopdf::Object::Array(objects) => format!(
+            "[{}]",
+            objects
+                .iter()
+                .map(|object| describe_object(object, object_paths))
+                .collect::<Vec<_>>()
+                .join(" ")
+        ),
+        lopdf::Object::Dictionary(dictionary) => describe_dictionary(dictionary, object_paths),
+        lopdf::Object::Stream(stream) => format!(
+            "{} stream of {} bytes",
+            describe_dictionary(&stream.dict, object_paths),
+            stream.content.len()
+        ),
+        lopdf::Object::Reference(object_id) => match object_paths.get(object_id) {
+            Some(path) => path.clone(),
+            None => format!("{} {} R", object_id.0, object_id.1),
+        },
+    }
+}
+
+/// Describes the given dictionary with its entries sorted by key, see `describe_object`.
+fn describe_dictionary(
+    dictionary: &lopdf::Dictionary,
+    object_paths: &BTreeMap<lopdf::ObjectId, String>,
+) -> String {
+    let entries = dictionary
+        .iter()
+        .collect::<BTreeMap<_, _>>()
+        .into_iter()
+        .map(|(key, object)| {
+            format!(
+                "/{} {}",
+                String::from_utf8_lossy(key),
+                describe_object(object, object_paths)
+            )
+        })
+        .collect::<Vec<_>>();
+
+    format!("<< {} >>", entries.join(" "))
+}
+
+/// Loads the given PDF document with its streams decompressed and without its creation and modification dates.
+fn load_without_volatile_fields(pdf_bytes: &[u8]) -> Result<lopdf::Document, ContextError> {
+    let mut pdf_document = lopdf::Document::load_mem(pdf_bytes).map_err(|error| {
+        ContextError::with_error("Failed to load the PDF document", &error)
+            .with_kind(ErrorKind::PdfEncoding)
+    })?;
+    pdf_document.decompress();
+    if let Ok(info_id) = pdf_document
+        .trailer
+        .get(b"Info")
+        .and_then(lopdf::Object::as_reference)
+    {
+        if let Ok(info) = pdf_document.get_dictionary_mut(info_id) {
+            for entry in VOLATILE_INFO_ENTRIES {
+                info.remove(entry);
+            }
+        }
+    }
+
+    Ok(pdf_document)
+}
+
+/// Finds the path through which each object of the given document is first reached from the trailer, visiting
+/// the objects breadth-first and the entries of the dictionaries by key.
+fn object_paths_of(pdf_document: &lopdf::Document) -> BTreeMap<lopdf::ObjectId, String> {
+    let mut object_paths = BTreeMap::new();
+    let mut objects_to_visit = VecDeque::new();
+    collect_reference_paths(
+        &lopdf::Object::Dictionary(pdf_document.trailer.clone()),
+        String::new(),
+        &mut objects_to_visit,
+    );
+    while let Some((object_id, path)) = objects_to_visit.pop_front() {
+        if object_paths.contains_key(&object_id) {
+            continue;
+        }
+        let Ok(object) = pdf_document.get_object(object_id) else {
+            continue;
+        };
+        collect_reference_paths(object, path.clone(), &mut objects_to_visit);
+        object_paths.insert(object_id, path);
+    }
+
+    object_paths
+}
+
+/// Appends the objects referred to by the given object to the queue, together with their paths.
+fn collect_reference_paths(
+    object: &lopdf::Object,
+    path: String,
+    objects_to_visit: &mut VecDeque<(lopdf::ObjectId, String)>,
+) {
+    let dictionary = match object {
+        lopdf::Object::Reference(object_id) => {
+            objects_to_visit.push_back((*object_id, path));
+            return;
+        }
+        lopdf::Object::Array(objects) => {
+            for (index, object) in objects.iter().enumerate() {
+                collect_reference_paths(object, format!("{path}[{index}]"), objects_to_visit);
+            }
+            return;
+        }
+        lopdf::Object::Dictionary(dictionary) => dictionary,
+        lopdf::Object::Stream(stream) => &stream.dict,
+        _ => return,
+    };
+    let entries = dictionary.iter().collect::<BTreeMap<_, _>>();
+    for (key, object) in entries {
+        collect_reference_paths(
+            object,
+            format!("{path}/{}", String::from_utf8_lossy(key)),
+            objects_to_visit,
+        );
+    }
+}
+
+/// Maps the paths of the objects to their IDs.
+fn invert(object_paths: &BTreeMap<lopdf::ObjectId, String>) -> BTreeMap<String, lopdf::ObjectId> {
+    object_paths
+        .iter()
+        .map(|(object_id, path)| (path.clone(), *object_id))
+        .collect()
+}
+
+/// Appends the objects referred to by the given object to the queue, in a deterministic order.
+fn collect_references(object: &lopdf::Object, object_ids: &mut VecDeque<lopdf::ObjectId>) {
+    match object {
+        lopdf::Object::Reference(object_id) => object_ids.push_back(*object_id),
+        lopdf::Object::Array(objects) => {
+            for object in objects {
+                collect_references(object, object_ids);
+            }
+        }
+        lopdf::Object::Dictionary(dictionary) => {
+            collect_dictionary_references(dictionary, object_ids)
+        }
+        lopdf::Object::Stream(stream) => collect_dictionary_references(&stream.dict, object_ids),
+        _ => {}
+    }
+}
+
+/// Appends the objects referred to by the entries of the given dictionary to the queue, sorted by their key.
+fn collect_dictionary_references(
+    dictionary: &lopdf::Dictionary,
+    object_ids: &mut VecDeque<lopdf::ObjectId>,
+) {
+    let entries = dictionary.iter().collect::<BTreeMap<_, _>>();
+    for object in entries.into_values() {
+        collect_references(object, object_ids);
+    }
+}
+
+/// Renumbers the references of the given object and sorts the keys of its dictionaries.
+fn canonicalize_object(
+    object: &mut lopdf::Object,
+    new_object_ids: &BTreeMap<lopdf::ObjectId, lopdf::ObjectId>,
+) {
+    match object {
+        lopdf::Object::Reference(object_id) => {
+            // The references to the objects which don't exist are left dangling, just like in the original
+            if let Some(new_object_id) = new_object_ids.get(object_id) {
+                *object_id = *new_object_id;
+            }
+        }
+        lopdf::Object::Array(objects) => {
+            for object in objects {
+                canonicalize_object(object, new_object_ids);
+            }
+        }
+        lopdf::Object::Dictionary(dictionary) => {
+            canonicalize_dictionary(dictionary, new_object_ids)
+        }
+        lopdf::Object::Stream(stream) => canonicalize_dictionary(&mut stream.dict, new_object_ids),
+        _ => {}
+    }
+}
+
+/// Renumbers the references of the entries of the given dictionary and sorts them by their key.
+fn canonicalize_dictionary(
+    dictionary: &mut lopdf::Dictionary,
+    new_object_ids: &BTreeMap<lopdf::ObjectId, lopdf::ObjectId>,
+) {
+    let entries = dictionary
+        .iter()
+        .map(|(key, object)| (key.clone(), object.clone()))
+        .collect::<BTreeMap<_, _>>();
+
+    let mut canonical_dictionary = lopdf::Dictionary::new();
+    for (key, mut object) in entries {
+        canonicalize_object(&mut object, new_object_ids);
+        canonical_dictionary.set(key, object);
+    }
+    *dictionary = canonical_dictionary;
+}
+
+/// The environment variable which, when set, makes `assert_snapshot` overwrite the snapshots with the current values
+/// instead of comparing them.
+pub const UPDATE_SNAPSHOTS_VARIABLE: &str = "TEXTR_UPDATE_SNAPSHOTS";
+
+/// Asserts that the given value, such as a `Document` or a `PdfDocumentSummary`, serializes to the same JSON as
+/// the snapshot committed at the given path, panicking with a line by line diff otherwise. The snapshot is written
+/// instead if it doesn't exist yet or if the `TEXTR_UPDATE_SNAPSHOTS` environment variable is set. This function is
+/// usually called through the `assert_document_snapshot!` macro.
+///
+/// # Arguments
+///
+/// * `snapshot_path` - The path of the JSON file of the snapshot.
+/// * `value` - The value which is compared against the snapshot.
+pub fn assert_snapshot<T: Serialize + ?Sized>(snapshot_path: impl AsRef<Path>, value: &T) {
+    let snapshot_path = snapshot_path.as_ref();
+    let snapshot =
+        serde_json::to_string_pretty(value).expect("The value should be serializable") + "\n";
+
+    if std::env::var_os(UPDATE_SNAPSHOTS_VARIABLE).is_some() || !snapshot_path.exists() {
+        if let Some(parent_path) = snapshot_path.parent() {
+            std::fs::create_dir_all(parent_path).unwrap_or_else(|error| {
+                panic!(
+                    "Failed to create the directory {:?}: {}",
+                    parent_path, error
+                )
+            });
+        }
+        std::fs::write(snapshot_path, snapshot).unwrap_or_else(|error| {
+            panic!(
+                "Failed to write the snapshot {:?}: {}",
+                snapshot_path, error
+            )
+        });
+        log::warn!("The snapshot {:?} has been written", snapshot_path);
+        return;
+    }
+
+    let committed_snapshot = std::fs::read_to_string(snapshot_path).unwrap_or_else(|error| {
+        panic!("Failed to read the snapshot {:?}: {}", snapshot_path, error)
+    });
+    similar_asserts::assert_eq!(
+        snapshot: committed_snapshot,
+        actual: snapshot,
+        "The value differs from the snapshot {:?}, set {} to update it",
+        snapshot_path,
+        UPDATE_SNAPSHOTS_VARIABLE
+    );
+}
+
+/// Asserts that a value, such as a `Document` or a `PdfDocumentSummary`, matches the snapshot with the given name,
+/// which is stored as a JSON file in the `tests/snapshots` directory of the crate calling the macro (see
+/// `textr::testing::assert_snapshot`).
+///
+/// ```no_run
+/// use textr::{assert_document_snapshot, builder::{a4, DocumentBuilder}};
+///
+/// let document = DocumentBuilder::new("5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9", "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj")
+///     .page(a4())
+///     .text("Hello, world!")
+///     .build()
+///     .unwrap();
+/// assert_document_snapshot!("hello_world", &document);
+/// ```
+#[macro_export]
+macro_rules! assert_document_snapshot {
+    ($name:expr, $value:expr $(,)?) => {
+        $crate::testing::assert_snapshot(
+            ::std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("tests")
+                .join("snapshots")
+                .join(format!("{}.json", $name)),
+            $value,
+        )
+    };
+}
@@ -0,0 +1,114 @@
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+/// A stage of the conversion of a JSON document into a PDF file, whose duration can be recorded into `Timings`.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Stage {
+    /// Reading the JSON document and parsing it into a `Document`.
+    Parsing,
+    /// Parsing the fonts and adding them to the PDF document.
+    FontLoading,
+    /// Drawing the operations of the document onto the pages of the PDF document.
+    Drawing,
+    /// Writing the PDF objects, among which the embedded fonts are by far the largest.
+    Writing,
+    /// Compressing the PDF document and serializing it into bytes, then into a file.
+    Saving,
+    /// Optimizing the PDF file with an external tool such as ghostscript.
+    Optimizing,
+}
+
+impl std::fmt::Display for Stage {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let stage_name = match self {
+            Stage::Parsing => "parsing",
+            Stage::FontLoading => "font loading",
+            Stage::Drawing => "drawing",
+            Stage::Writing => "writing",
+            Stage::Saving => "saving",
+            Stage::Optimizing => "optimizing",
+        };
+        write!(formatter, "{}", stage_name)
+    }
+}
+
+/// The durations of the stages of one or more conversions, in the order in which the stages have first been
+/// recorded. Stages recorded multiple times, for instance when converting many documents, are accumulated.
+///
+/// # Example
+///
+/// ```
+/// use textr::timings::{Stage, Timings};
+///
+/// let mut timings = Timings::default();
+/// let sum = timings.time(Stage::Drawing, || 1 + 1);
+/// assert_eq!(sum, 2);
+/// assert_eq!(timings.stages().count(), 1);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Timings {
+    /// The accumulated duration of each stage.
+    durations: Vec<(Stage, Duration)>,
+}
+
+impl Timings {
+    /// Runs the given function, recording the time it takes as part of the given stage, and returns its result.
+    /// The function also runs inside a `tracing` span named after the stage, so that the stages can be followed
+    /// by any `tracing` subscriber as well.
+    pub fn time<T, F: FnOnce() -> T>(&mut self, stage: Stage, function: F) -> T {
+        let started_at = Instant::now();
+        let result = tracing::info_span!("stage", %stage).in_scope(function);
+        self.record(stage, started_at.elapsed());
+
+        result
+    }
+
+    /// Adds the given duration to the given stage.
+    pub fn record(&mut self, stage: Stage, duration: Duration) {
+        match self
+            .durations
+            .iter_mut()
+            .find(|(recorded_stage, _)| *recorded_stage == stage)
+        {
+            Some((_, recorded_duration)) => *recorded_duration += duration,
+            None => self.durations.push((stage, duration)),
+        }
+    }
+
+    /// Adds the durations of the given timings to these ones.
+    pub fn merge(&mut self, timings: &Timings) {
+        for (stage, duration) in &timings.durations {
+            self.record(*stage, *duration);
+        }
+    }
+
+    /// Returns the accumulated duration of the given stage, which is zero if it has never been recorded.
+    pub fn duration(&self, stage: Stage) -> Duration {
+        self.durations
+            .iter()
+            .find(|(recorded_stage, _)| *recorded_stage == stage)
+            .map_or(Duration::ZERO, |(_, duration)| *duration)
+    }
+
+    /// Returns the recorded stages together with their accumulated durations.
+    pub fn stages(&self) -> impl Iterator<Item = (Stage, Duration)> + '_ {
+        self.durations.iter().copied()
+    }
+
+    /// Returns the sum of the durations of all the stages.
+    pub fn total(&self) -> Duration {
+        self.durations.iter().map(|(_, duration)| *duration).sum()
+    }
+}
+
+impl std::fmt::Display for Timings {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let stages = self
+            .durations
+            .iter()
+            .map(|(stage, duration)| format!("{} {:.2?}", stage, duration))
+            .collect::<Vec<_>>();
+        write!(formatter, "{}", stages.join(", "))
+    }
+}
@@ -0,0 +1,53 @@
+use serde::Serialize;
+
+/// A problem which doesn't prevent a `PdfDocument` from being produced but which the end user should know about,
+/// since part of the content won't be displayed as expected. The warnings are collected by the `PdfDocument`
+/// while it is built, see `PdfDocument::warnings`.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(tag = "warning", rename_all = "camelCase")]
+pub enum Warning {
+    /// A piece of text contains characters which are missing from its font, so they have been left out.
+    #[serde(rename_all = "camelCase")]
+    MissingCharacters {
+        /// The index of the page onto which the text has been written.
+        page_index: usize,
+        /// The font index of the text.
+        font_index: usize,
+        /// The missing characters, without repetitions.
+        characters: String,
+    },
+    /// Some glyphs of a font have no width, so they have been left out of the widths of the embedded font
+    /// and are displayed with the default width.
+    #[serde(rename_all = "camelCase")]
+    GlyphsWithoutWidth {
+        /// The name of the font in the PDF document, such as `F0`.
+        font_name: String,
+        /// The IDs of the glyphs without width.
+        glyph_ids: Vec<u16>,
+    },
+}
+
+impl std::fmt::Display for Warning {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Warning::MissingCharacters {
+                page_index,
+                font_index,
+                characters,
+            } => write!(
+                formatter,
+                "The characters {:?} written on page {} are missing from the font {}",
+                characters, page_index, font_index
+            ),
+            Warning::GlyphsWithoutWidth {
+                font_name,
+                glyph_ids,
+            } => write!(
+                formatter,
+                "{} glyph(s) of the font {} have no width",
+                glyph_ids.len(),
+                font_name
+            ),
+        }
+    }
+}
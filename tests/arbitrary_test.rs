@@ -0,0 +1,42 @@
+use std::sync::OnceLock;
+
+use proptest::prelude::*;
+use textr::{
+    document::{Document, Operation},
+    font::FontRegistry,
+};
+
+/// Returns the built-in fonts, which are read once for all the cases of the properties.
+fn font_registry() -> &'static FontRegistry {
+    static FONT_REGISTRY: OnceLock<FontRegistry> = OnceLock::new();
+    FONT_REGISTRY.get_or_init(|| FontRegistry::builtin().unwrap())
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    /// Verifies that the arbitrary documents are converted into PDF documents with one page for each of
+    /// their pages.
+    #[test]
+    fn arbitrary_documents_are_converted(document in any::<Document>()) {
+        let page_count = document
+            .operations
+            .iter()
+            .filter(|operation| matches!(operation, Operation::AppendNewPage { .. }))
+            .count();
+
+        let mut pdf_document = document.to_pdf_document_with_fonts(font_registry()).unwrap();
+        prop_assert_eq!(pdf_document.summary().pages.len(), page_count);
+        pdf_document.save_to_bytes().unwrap();
+    }
+
+    /// Verifies that the arbitrary documents start with a page, so that none of their content is out of range,
+    /// and that they survive a round trip through JSON.
+    #[test]
+    fn arbitrary_documents_start_with_a_page(document in any::<Document>()) {
+        let is_page = matches!(document.operations[0], Operation::AppendNewPage { .. });
+        prop_assert!(is_page);
+        let document_json = serde_json::to_string(&document).unwrap();
+        prop_assert_eq!(serde_json::from_str::<Document>(&document_json).unwrap(), document);
+    }
+}
@@ -0,0 +1,148 @@
+use textr::{
+    barcode::BarcodeKind,
+    document::{Document, Operation},
+    error::ErrorKind,
+    font::FontRegistry,
+    lint::LintIssue,
+};
+
+/// Converts a pattern of zeros and ones into the modules of a barcode.
+fn modules(pattern: &str) -> Vec<bool> {
+    pattern.bytes().map(|module| module == b'1').collect()
+}
+
+/// Verifies that an EAN-13 barcode is made of its guards and digits, and that its check digit is computed or checked.
+#[test]
+fn ean_13_barcode_is_encoded_with_its_check_digit() {
+    let rows = BarcodeKind::Ean13.encode("4006381333931").unwrap();
+    assert_eq!(rows.len(), 1);
+    let row = &rows[0];
+    assert_eq!(row.len(), 95);
+    assert_eq!(row[..3], modules("101"));
+    // The first left-hand digit is a 0 with odd parity, as the leading 4 requires
+    assert_eq!(row[3..10], modules("0001101"));
+    assert_eq!(row[45..50], modules("01010"));
+    // The last digit is the check digit 1, with its right-hand encoding
+    assert_eq!(row[85..92], modules("1100110"));
+    assert_eq!(row[92..], modules("101"));
+
+    assert_eq!(BarcodeKind::Ean13.encode("400638133393").unwrap(), rows);
+    let error = BarcodeKind::Ean13.encode("4006381333932").unwrap_err();
+    assert_eq!(error.kind, ErrorKind::InvalidDocument);
+    assert!(BarcodeKind::Ean13.encode("40063813339").is_err());
+    assert!(BarcodeKind::Ean13.encode("40063813339a").is_err());
+}
+
+/// Verifies that a Code 128 barcode begins with the start symbol of code set B, ends with the stop symbol and
+/// contains the check symbol, while the characters outside of printable ASCII are rejected.
+#[test]
+fn code_128_barcode_is_encoded_in_code_set_b() {
+    let rows = BarcodeKind::Code128.encode("Wikipedia").unwrap();
+    let row = &rows[0];
+    // The start symbol, the nine characters and the check symbol have 11 modules each, the stop symbol 13
+    assert_eq!(row.len(), 11 * 11 + 13);
+    assert_eq!(row[..11], modules("11010010000"));
+    assert_eq!(row[11..22], modules("11101000110"));
+    // The check symbol of "Wikipedia" has the value 88
+    assert_eq!(row[110..121], modules("11110010010"));
+    assert_eq!(row[121..], modules("1100011101011"));
+
+    let error = BarcodeKind::Code128.encode("Café").unwrap_err();
+    assert_eq!(error.kind, ErrorKind::InvalidDocument);
+}
+
+/// Verifies that a QR code is square and begins with its finder pattern.
+#[test]
+fn qr_code_is_encoded_with_its_finder_patterns() {
+    let rows = BarcodeKind::Qr
+        .encode("https://github.com/ghovax/textr")
+        .unwrap();
+    assert!(rows.len() >= 21);
+    assert!(rows.iter().all(|row| row.len() == rows.len()));
+    assert_eq!(rows[0][..8], modules("11111110"));
+    assert_eq!(rows[2][..8], modules("10111010"));
+}
+
+/// Verifies that a barcode operation is parsed from its own fields and drawn as vector rectangles.
+#[test]
+fn barcode_operation_is_drawn_as_rectangles() {
+    let document = Document::from_json_bytes(
+        br#"{
+            "documentId": "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+            "instanceId": "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+            "operations": [
+                { "pageWidth": 210.0, "pageHeight": 297.0 },
+                { "barcode": "ean13", "data": "400638133393", "position": [20.0, 20.0], "size": [38.0, 25.0] },
+                { "barcode": "qr", "data": "textr", "position": [100.0, 20.0], "size": [30.0, 30.0] }
+            ]
+        }"#,
+    )
+    .unwrap();
+    assert!(matches!(
+        document.operations[1],
+        Operation::DrawBarcode {
+            kind: BarcodeKind::Ean13,
+            ..
+        }
+    ));
+
+    let svg = document.to_svg().unwrap();
+    assert_eq!(svg.matches("<path ").count(), 2);
+    // The first bar of the EAN-13 barcode is one module wide and spans the whole height of the barcode
+    assert!(svg.contains("<path d=\"M 20 277 L 20.4 277 L 20.4 252 L 20 252 Z"));
+
+    let font_registry = FontRegistry::builtin().unwrap();
+    let mut pdf_document = document.to_pdf_document_with_fonts(&font_registry).unwrap();
+    let pdf_document = lopdf::Document::load_mem(&pdf_document.save_to_bytes().unwrap()).unwrap();
+    let first_page_id = pdf_document.get_pages()[&1];
+    let content = pdf_document
+        .get_and_decode_page_content(first_page_id)
+        .unwrap();
+    assert_eq!(
+        content
+            .operations
+            .iter()
+            .filter(|operation| operation.operator == "f")
+            .count(),
+        2
+    );
+}
+
+/// Verifies that a barcode whose data can't be encoded is reported by the validation and fails the conversion.
+#[test]
+fn barcode_operation_with_invalid_data_is_rejected() {
+    let document = Document {
+        document_id: "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9".to_string(),
+        instance_id: "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj".to_string(),
+        operations: vec![
+            Operation::AppendNewPage {
+                page_width: 210.0,
+                page_height: 297.0,
+            },
+            Operation::DrawBarcode {
+                kind: BarcodeKind::Ean13,
+                data: "ISBN".to_string(),
+                position: [20.0, 20.0],
+                size: [38.0, 25.0],
+            },
+        ],
+        page_background: None,
+        title: None,
+        author: None,
+        running_header: None,
+        timestamp: None,
+        deterministic: false,
+        strict: false,
+    };
+
+    let error = document.to_svg().unwrap_err();
+    assert_eq!(error.kind, ErrorKind::InvalidDocument);
+    assert_eq!(error.operation_index, Some(1));
+    assert!(matches!(
+        document.validate().as_slice(),
+        [LintIssue::InvalidBarcode {
+            operation_index: 1,
+            ..
+        }]
+    ));
+}
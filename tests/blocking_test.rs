@@ -0,0 +1,98 @@
+use std::{
+    collections::HashSet,
+    future::Future,
+    pin::pin,
+    sync::Arc,
+    task::{Context, Poll, Wake},
+    thread::{self, Thread},
+    time::Duration,
+};
+
+use textr::{
+    blocking::BlockingTask,
+    builder::{a4, DocumentBuilder},
+    document::Document,
+    font::FontRegistry,
+    pdf_info::PdfInfo,
+};
+
+/// Wakes the thread which is blocked on a future.
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Runs the given future to completion on the current thread, as the simplest possible asynchronous runtime.
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let waker = Arc::new(ThreadWaker(thread::current())).into();
+    let mut context = Context::from_waker(&waker);
+    loop {
+        match future.as_mut().poll(&mut context) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+/// Verifies that the asynchronous methods read the fonts, save a document and read it back just like their
+/// blocking counterparts.
+#[test]
+fn asynchronous_methods_convert_documents() {
+    let document = DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .page(a4())
+    .text("Hello, world!")
+    .at(20.0, 270.0)
+    .font(15, 12.0)
+    .build()
+    .unwrap();
+    let document_path = std::env::temp_dir().join("textr_blocking_test.json");
+    let pdf_path = std::env::temp_dir().join("textr_blocking_test.pdf");
+    std::fs::write(&document_path, serde_json::to_string(&document).unwrap()).unwrap();
+
+    let font_registry = block_on(FontRegistry::builtin_async()).unwrap();
+    assert_eq!(
+        font_registry.font_paths(),
+        FontRegistry::builtin().unwrap().font_paths()
+    );
+    let read_document = block_on(Document::from_path_async(document_path.clone())).unwrap();
+    assert_eq!(read_document, document);
+    let warnings =
+        block_on(read_document.save_to_pdf_file_async(pdf_path.clone(), &font_registry)).unwrap();
+    assert!(warnings.is_empty());
+    assert_eq!(PdfInfo::from_path(&pdf_path).unwrap().pages.len(), 1);
+
+    // The errors are returned through the future as well
+    let missing_path = std::env::temp_dir().join("textr_blocking_test_missing.json");
+    assert!(block_on(Document::from_path_async(missing_path)).is_err());
+
+    std::fs::remove_file(document_path).unwrap();
+    std::fs::remove_file(pdf_path).unwrap();
+}
+
+/// Verifies that the blocking functions are run on a bounded pool of threads, rather than on a new thread each,
+/// however many of them are spawned at once.
+#[test]
+fn blocking_tasks_share_a_bounded_pool_of_threads() {
+    let blocking_tasks = (0..4 * rayon::current_num_threads() + 8)
+        .map(|_| {
+            BlockingTask::spawn(|| {
+                thread::sleep(Duration::from_millis(5));
+                thread::current().id()
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let thread_ids = blocking_tasks
+        .into_iter()
+        .map(block_on)
+        .collect::<HashSet<_>>();
+    assert!(thread_ids.len() <= rayon::current_num_threads());
+    assert!(!thread_ids.contains(&thread::current().id()));
+}
@@ -0,0 +1,138 @@
+use textr::{
+    builder::{a4, DocumentBuilder, PageSize},
+    document::{builtin_font_paths, Operation},
+    error::ErrorKind,
+    font::FontRegistry,
+};
+
+/// Verifies that the builder produces the operations in the order in which they were chained,
+/// with the properties of the text being applied to the last piece of text only.
+#[test]
+fn builder_produces_operations_in_order() {
+    let document = DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .page(a4())
+    .text("Title")
+    .at(30.0, 270.0)
+    .font(12, 24.0)
+    .color([1.0, 0.0, 0.0])
+    .text("Body")
+    .page(PageSize::new(100.0, 200.0).landscape())
+    .build()
+    .unwrap();
+
+    assert_eq!(document.operations.len(), 4);
+    assert!(matches!(
+        document.operations[0],
+        Operation::AppendNewPage {
+            page_width,
+            page_height,
+        } if page_width == 210.0 && page_height == 297.0
+    ));
+    assert!(matches!(
+        &document.operations[1],
+        Operation::WriteUnicodeText {
+            color,
+            position,
+            text_string,
+            font_size,
+            font_index,
+            letter_spacing,
+        } if *color == [1.0, 0.0, 0.0]
+            && *position == [30.0, 270.0]
+            && text_string == "Title"
+            && *font_size == 24.0
+            && *font_index == 12
+            && *letter_spacing == 0.0
+    ));
+    assert!(matches!(
+        &document.operations[2],
+        Operation::WriteUnicodeText {
+            position,
+            font_index: 0,
+            ..
+        } if *position == [0.0, 0.0]
+    ));
+    assert!(matches!(
+        document.operations[3],
+        Operation::AppendNewPage {
+            page_width,
+            page_height,
+        } if page_width == 200.0 && page_height == 100.0
+    ));
+}
+
+/// Verifies that the fonts given by name are resolved into the index of the matching built-in font, with the
+/// family name alone referring to its regular style.
+#[test]
+fn builder_resolves_font_names() {
+    let document = DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .page(a4())
+    .text("Regular")
+    .font("cmu-serif", 12.0)
+    .text("Bold")
+    .font("cmu-serif-bold", 12.0)
+    .text("Bold italic")
+    .font("CMU Serif BoldItalic", 12.0)
+    .text("Replaced")
+    .font("cmu-serif", 12.0)
+    .font(3, 12.0)
+    .build()
+    .unwrap();
+
+    let font_indices = document
+        .operations
+        .iter()
+        .filter_map(|operation| match operation {
+            Operation::WriteUnicodeText { font_index, .. } => Some(*font_index),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(font_indices, vec![15, 8, 0, 3]);
+}
+
+/// Verifies that a font name which is not among the fonts fails the building of the document at the index of the
+/// operation which refers to it, and that the names are looked for among the fonts of the given registry.
+#[test]
+fn builder_rejects_unknown_font_names() {
+    let builder = DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .page(a4())
+    .text("Hello")
+    .font("cmu-serif", 12.0)
+    .text("world")
+    .font("comic-sans", 12.0);
+
+    let error = builder.clone().build().unwrap_err();
+    assert_eq!(error.kind, ErrorKind::FontNotFound);
+    assert_eq!(error.operation_index, Some(2));
+
+    // The only font of the registry is the regular CMU Serif, whose index within the registry is the first one
+    let font_registry =
+        FontRegistry::from_paths(vec![builtin_font_paths().unwrap()[15].clone()]).unwrap();
+    let builder = DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .page(a4())
+    .text("Hello")
+    .font("cmu-serif", 12.0);
+    let document = builder.clone().build_with_fonts(&font_registry).unwrap();
+    assert!(matches!(
+        document.operations[1],
+        Operation::WriteUnicodeText { font_index: 0, .. }
+    ));
+    let error = builder
+        .text("world")
+        .font("cmu-serif-bold", 12.0)
+        .build_with_fonts(&font_registry)
+        .unwrap_err();
+    assert_eq!(error.operation_index, Some(2));
+}
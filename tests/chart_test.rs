@@ -0,0 +1,169 @@
+use textr::{
+    chart::ChartKind,
+    document::{Document, Operation},
+    error::ErrorKind,
+    font::FontRegistry,
+    lint::LintIssue,
+};
+
+/// Creates a document with a single page onto which the given chart is drawn.
+fn document_with_chart(kind: ChartKind, values: Vec<f32>, labels: &[&str]) -> Document {
+    Document {
+        document_id: "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9".to_string(),
+        instance_id: "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj".to_string(),
+        operations: vec![
+            Operation::AppendNewPage {
+                page_width: 210.0,
+                page_height: 297.0,
+            },
+            Operation::DrawChart {
+                kind,
+                values,
+                labels: labels.iter().map(|label| label.to_string()).collect(),
+                position: [20.0, 20.0],
+                size: [120.0, 60.0],
+                font_index: 15,
+                font_size: 8.0,
+            },
+        ],
+        page_background: None,
+        title: None,
+        author: None,
+        running_header: None,
+        timestamp: None,
+        deterministic: false,
+        strict: false,
+    }
+}
+
+/// Verifies that a chart operation is parsed from its own fields, without being mistaken for a text.
+#[test]
+fn chart_operation_is_parsed() {
+    let document = Document::from_json_bytes(
+        br#"{
+            "documentId": "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+            "instanceId": "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+            "operations": [
+                { "pageWidth": 210.0, "pageHeight": 297.0 },
+                {
+                    "chart": "bar",
+                    "values": [3.0, -1.0, 2.0],
+                    "labels": ["Q1", "Q2", "Q3"],
+                    "position": [20.0, 20.0],
+                    "size": [120.0, 60.0],
+                    "fontIndex": 15,
+                    "fontSize": 8.0
+                }
+            ]
+        }"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        document,
+        document_with_chart(ChartKind::Bar, vec![3.0, -1.0, 2.0], &["Q1", "Q2", "Q3"])
+    );
+}
+
+/// Verifies that a bar chart draws a bar for each value, the zero line and the labels, with the negative
+/// values going below the zero line.
+#[test]
+fn bar_chart_draws_bars_axis_and_labels() {
+    let document = document_with_chart(ChartKind::Bar, vec![3.0, -1.0, 2.0], &["Q1", "Q2", "Q3"]);
+
+    let svg = document.to_svg().unwrap();
+    assert_eq!(svg.matches("<path ").count(), 4);
+    assert_eq!(svg.matches("<text ").count(), 3);
+    assert!(svg.contains(">Q2</text>"));
+    // The bars of the positive values rise from the zero line, while the bar of the negative value hangs from it,
+    // so its bottom is lower, that is further down in the SVG image
+    let bar_bottoms = svg
+        .match_indices("<path d=\"M ")
+        .take(3)
+        .map(|(index, prefix)| {
+            let coordinates = svg[index + prefix.len()..]
+                .split(' ')
+                .take(2)
+                .collect::<Vec<_>>();
+            coordinates[1].parse::<f32>().unwrap()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(bar_bottoms[0], bar_bottoms[2]);
+    assert!(bar_bottoms[1] > bar_bottoms[0]);
+
+    let plain_text = document.to_plain_text().unwrap();
+    assert!(plain_text.contains("Q1"));
+}
+
+/// Verifies that a line chart draws a single line through the values together with a marker for each of them.
+#[test]
+fn line_chart_draws_line_and_markers() {
+    let document = document_with_chart(ChartKind::Line, vec![1.0, 4.0, 2.0, 5.0], &[]);
+
+    let svg = document.to_svg().unwrap();
+    // The line, the four markers and the zero line
+    assert_eq!(svg.matches("<path ").count(), 6);
+    assert_eq!(svg.matches("<text ").count(), 0);
+    let line_path = svg.split("<path ").nth(1).unwrap();
+    assert_eq!(line_path.matches('Z').count(), 3);
+}
+
+/// Verifies that a pie chart draws a slice for each value, made of curves, and a legend of its labels, which
+/// is written with the font of the chart in the PDF document.
+#[test]
+fn pie_chart_draws_slices_and_legend() {
+    let document = document_with_chart(ChartKind::Pie, vec![1.0, 1.0, 2.0], &["A", "B", "C"]);
+
+    let svg = document.to_svg().unwrap();
+    // The three slices and the three swatches of the legend
+    assert_eq!(svg.matches("<path ").count(), 6);
+    assert_eq!(svg.matches("<text ").count(), 3);
+    // The largest slice covers half of the disc, so it's made of two quarter-turn curves
+    let largest_slice = svg.split("<path ").nth(3).unwrap();
+    assert_eq!(
+        largest_slice
+            .split('"')
+            .nth(1)
+            .unwrap()
+            .matches('C')
+            .count(),
+        2
+    );
+
+    let font_registry = FontRegistry::builtin().unwrap();
+    let mut pdf_document = document.to_pdf_document_with_fonts(&font_registry).unwrap();
+    let pdf_document = lopdf::Document::load_mem(&pdf_document.save_to_bytes().unwrap()).unwrap();
+    let first_page_id = pdf_document.get_pages()[&1];
+    assert_eq!(pdf_document.get_page_fonts(first_page_id).len(), 1);
+    let content = pdf_document
+        .get_and_decode_page_content(first_page_id)
+        .unwrap();
+    assert_eq!(
+        content
+            .operations
+            .iter()
+            .filter(|operation| operation.operator == "c")
+            .count(),
+        4
+    );
+}
+
+/// Verifies that a pie chart with a negative value is reported by the validation and fails the conversion.
+#[test]
+fn pie_chart_with_negative_value_is_rejected() {
+    let document = document_with_chart(ChartKind::Pie, vec![1.0, -1.0], &[]);
+
+    let error = document.to_svg().unwrap_err();
+    assert_eq!(error.kind, ErrorKind::InvalidDocument);
+    assert_eq!(error.operation_index, Some(1));
+    assert!(matches!(
+        document.validate().as_slice(),
+        [LintIssue::InvalidChart {
+            operation_index: 1,
+            ..
+        }]
+    ));
+    assert!(document_with_chart(ChartKind::Bar, vec![1.0, -1.0], &[])
+        .validate()
+        .is_empty());
+}
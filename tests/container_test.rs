@@ -0,0 +1,202 @@
+use textr::{
+    builder::{a4, DocumentBuilder},
+    container::ContainerLayout,
+    document::{builtin_font_paths, Document, Operation},
+    font::FontRegistry,
+};
+
+/// Returns a black piece of text with the given font size and no offset, to be placed inside a container.
+fn child_text(text_string: &str, font_size: f32) -> Operation {
+    Operation::WriteUnicodeText {
+        color: [0.0, 0.0, 0.0],
+        position: [0.0, 0.0],
+        text_string: text_string.to_string(),
+        font_size,
+        font_index: 0,
+        letter_spacing: 0.0,
+    }
+}
+
+/// Returns the positions of the text operations of the given operations.
+fn text_positions(operations: &[Operation]) -> Vec<[f32; 2]> {
+    operations
+        .iter()
+        .filter_map(|operation| match operation {
+            Operation::WriteUnicodeText { position, .. } => Some(*position),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Verifies that stacks, rows and grids position their children according to their padding and gap,
+/// and that nested containers are laid out recursively.
+#[test]
+fn containers_position_their_children() {
+    let mut document = DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .page(a4())
+    .build()
+    .unwrap();
+    document.operations.push(Operation::LayoutContainer {
+        layout: ContainerLayout::Stack,
+        position: [20.0, 280.0],
+        padding: 5.0,
+        gap: 2.0,
+        children: vec![
+            child_text("Invoice", 12.0),
+            Operation::LayoutContainer {
+                layout: ContainerLayout::Grid {
+                    columns: 2,
+                    cell_width: 40.0,
+                    cell_height: 8.0,
+                },
+                position: [0.0, 0.0],
+                padding: 0.0,
+                gap: 1.0,
+                children: vec![
+                    child_text("Item", 12.0),
+                    child_text("Price", 12.0),
+                    child_text("Paper", 12.0),
+                    child_text("10", 12.0),
+                ],
+            },
+        ],
+    });
+
+    let laid_out_document = document.lay_out_containers().unwrap();
+    let positions = text_positions(&laid_out_document.operations);
+    assert_eq!(positions.len(), 5);
+
+    // The title is inset by the padding and the grid follows it after the gap
+    assert_eq!(positions[0][0], 25.0);
+    assert!(positions[0][1] < 275.0);
+    assert!(positions[1][1] < positions[0][1] - 2.0);
+    // The cells of the grid are spaced by their size and the gap
+    assert_eq!(positions[1][0], 25.0);
+    assert_eq!(positions[2][0], 25.0 + 41.0);
+    assert_eq!(positions[2][1], positions[1][1]);
+    assert_eq!(positions[3][0], 25.0);
+    assert!((positions[1][1] - positions[3][1] - 9.0).abs() < 1e-4);
+
+    // Drawing the document lays the containers out in the same way
+    assert_eq!(
+        document.to_plain_text().unwrap(),
+        laid_out_document.to_plain_text().unwrap()
+    );
+    assert_eq!(
+        laid_out_document.to_plain_text().unwrap(),
+        "Invoice\nItem Price\nPaper 10\n\u{c}"
+    );
+}
+
+/// Verifies that the children of a row are placed from left to right and that the layout
+/// is read from the JSON representation of the operation.
+#[test]
+fn row_container_is_deserialized_and_laid_out() {
+    let operation: Operation = serde_json::from_str(
+        r#"{
+            "layout": { "kind": "row" },
+            "position": [10.0, 100.0],
+            "gap": 3.0,
+            "children": [
+                { "color": [0, 0, 0], "position": [0, 0], "textString": "A", "fontSize": 12, "fontIndex": 0 },
+                { "color": [0, 0, 0], "position": [0, 0], "textString": "B", "fontSize": 12, "fontIndex": 0 }
+            ]
+        }"#,
+    )
+    .unwrap();
+    assert!(matches!(
+        operation,
+        Operation::LayoutContainer {
+            layout: ContainerLayout::Row,
+            ..
+        }
+    ));
+
+    let mut document = DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .page(a4())
+    .build()
+    .unwrap();
+    document.operations.push(operation);
+    let positions = text_positions(&document.lay_out_containers().unwrap().operations);
+
+    assert_eq!(positions[0][0], 10.0);
+    assert!(positions[1][0] > 13.0);
+    assert_eq!(positions[0][1], positions[1][1]);
+}
+
+/// Creates a document with a row of two pieces of text written with the given font index.
+fn document_with_row(font_index: usize) -> Document {
+    let mut document = DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .page(a4())
+    .build()
+    .unwrap();
+    let child_text = |text_string: &str| Operation::WriteUnicodeText {
+        color: [0.0, 0.0, 0.0],
+        position: [0.0, 0.0],
+        text_string: text_string.to_string(),
+        font_size: 12.0,
+        font_index,
+        letter_spacing: 0.0,
+    };
+    document.operations.push(Operation::LayoutContainer {
+        layout: ContainerLayout::Row,
+        position: [20.0, 280.0],
+        padding: 0.0,
+        gap: 2.0,
+        children: vec![child_text("Wide words"), child_text("Next")],
+    });
+
+    document
+}
+
+/// Verifies that the children of a container are measured with the fonts of the registry with which the document
+/// is converted, rather than with the built-in fonts of the same index.
+#[test]
+fn containers_are_measured_with_the_fonts_of_the_registry() {
+    let font_registry =
+        FontRegistry::from_paths(vec![builtin_font_paths().unwrap()[15].clone()]).unwrap();
+    let document = document_with_row(0);
+
+    // The only font of the registry is the built-in font with index 15, so the row is as wide as with that font
+    let positions = text_positions(
+        &document
+            .lay_out_containers_with_fonts(&font_registry)
+            .unwrap()
+            .operations,
+    );
+    let expected_positions = text_positions(
+        &document_with_row(15)
+            .lay_out_containers()
+            .unwrap()
+            .operations,
+    );
+    assert_eq!(positions, expected_positions);
+    assert_ne!(
+        positions,
+        text_positions(&document.lay_out_containers().unwrap().operations)
+    );
+
+    // The same positions are used when the document is converted into a PDF document with the registry
+    let pdf_bytes = document
+        .to_pdf_document_with_fonts(&font_registry)
+        .unwrap()
+        .save_to_bytes()
+        .unwrap();
+    let imported_positions =
+        text_positions(&Document::from_pdf_bytes(&pdf_bytes).unwrap().operations);
+    assert_eq!(imported_positions.len(), 2);
+    for (imported_position, expected_position) in imported_positions.iter().zip(&expected_positions)
+    {
+        assert!((imported_position[0] - expected_position[0]).abs() < 1e-2);
+        assert!((imported_position[1] - expected_position[1]).abs() < 1e-2);
+    }
+}
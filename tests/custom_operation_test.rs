@@ -0,0 +1,86 @@
+use textr::{
+    canvas::FilledPath,
+    custom::{CustomOperation, CustomOperationContext, CustomOperations},
+    document::{Document, Operation},
+    error::{ContextError, ErrorKind},
+    font::FontRegistry,
+};
+
+/// A custom operation which draws a black bar of the given width, followed by a pair of raw PDF operations which
+/// save and restore the graphics state.
+struct DrawBar;
+
+impl CustomOperation for DrawBar {
+    fn name(&self) -> &str {
+        "DrawBar"
+    }
+
+    fn draw(
+        &self,
+        parameters: &serde_json::Value,
+        context: &mut CustomOperationContext<'_>,
+    ) -> Result<(), ContextError> {
+        let width = parameters["width"].as_f64().unwrap_or(10.0) as f32;
+        context.fill_path(&FilledPath::rectangle(
+            [20.0, 20.0 * context.page_number() as f32],
+            [width, 5.0],
+            [0.0, 0.0, 0.0],
+        ))?;
+        context.pdf_operations(vec![
+            lopdf::content::Operation::new("q", vec![]),
+            lopdf::content::Operation::new("Q", vec![]),
+        ])
+    }
+}
+
+/// Verifies that the custom operations of a JSON document are drawn by the ones registered with their names, and
+/// that the documents with custom operations which aren't registered can't be converted.
+#[test]
+fn custom_operations_are_drawn_by_their_handlers() {
+    let document: Document = serde_json::from_str(
+        r#"{
+            "documentId": "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+            "instanceId": "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+            "operations": [
+                { "pageWidth": 210.0, "pageHeight": 297.0 },
+                { "customOperation": "DrawBar", "parameters": { "width": 42.5 } }
+            ]
+        }"#,
+    )
+    .unwrap();
+    assert!(matches!(
+        &document.operations[1],
+        Operation::Custom { custom_operation, .. } if custom_operation == "DrawBar"
+    ));
+    let serialized_document = serde_json::to_string(&document).unwrap();
+    assert_eq!(
+        serde_json::from_str::<Document>(&serialized_document).unwrap(),
+        document
+    );
+
+    let font_registry = FontRegistry::builtin().unwrap();
+    let Err(error) = document.to_pdf_document_with_fonts(&font_registry) else {
+        panic!("Expected the unregistered custom operation to make the conversion fail");
+    };
+    assert_eq!(error.kind, ErrorKind::InvalidDocument);
+    assert_eq!(error.operation_index, Some(1));
+
+    let mut custom_operations = CustomOperations::new();
+    custom_operations.register(DrawBar);
+    let mut pdf_document = document
+        .to_pdf_document_with_custom_operations(&font_registry, &custom_operations)
+        .unwrap();
+    let saved_pdf_document =
+        lopdf::Document::load_mem(&pdf_document.save_to_bytes().unwrap()).unwrap();
+    let page_id = saved_pdf_document.page_iter().next().unwrap();
+    let content = saved_pdf_document
+        .get_and_decode_page_content(page_id)
+        .unwrap();
+    let operators = content
+        .operations
+        .iter()
+        .map(|operation| operation.operator.as_str())
+        .collect::<Vec<_>>();
+    assert!(operators.contains(&"f"));
+    assert!(operators.windows(2).any(|pair| pair == ["q", "Q"]));
+}
@@ -0,0 +1,43 @@
+use textr::{
+    document::Document,
+    font::FontRegistry,
+    testing::{DocumentGenerator, GeneratorConfiguration},
+};
+
+/// Converts the given document into the bytes of a PDF file, reading the fonts again so that no state is shared
+/// between two conversions, optionally optimizing it.
+fn pdf_bytes(document: &Document, optimize: bool) -> Vec<u8> {
+    let font_registry = FontRegistry::builtin().unwrap();
+    let mut pdf_document = document.to_pdf_document_with_fonts(&font_registry).unwrap();
+    if optimize {
+        pdf_document.optimize();
+    }
+    pdf_document.save_to_bytes().unwrap()
+}
+
+/// Verifies that converting the same documents twice, with or without the optimization, produces the same bytes,
+/// even when the conversions run on different threads.
+#[test]
+fn same_document_is_converted_into_the_same_bytes() {
+    let documents = DocumentGenerator::new(0xD373, GeneratorConfiguration::default())
+        .take(3)
+        .collect::<Vec<_>>();
+
+    for document in &documents {
+        for optimize in [false, true] {
+            let pdf_document_bytes = pdf_bytes(document, optimize);
+            let other_pdf_document_bytes = std::thread::scope(|scope| {
+                scope
+                    .spawn(|| pdf_bytes(document, optimize))
+                    .join()
+                    .unwrap()
+            });
+            assert!(
+                pdf_document_bytes == other_pdf_document_bytes,
+                "The document {} is converted into different bytes (optimized: {})",
+                document.document_id,
+                optimize
+            );
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use textr::{
+    builder::{a4, DocumentBuilder},
+    diff::Change,
+};
+
+/// Verifies that inserting an operation is reported as a single addition, while changing
+/// the text of an existing operation is reported as a modification of that field only.
+#[test]
+fn diff_reports_added_and_modified_operations() {
+    let document = DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .page(a4())
+    .text("First")
+    .text("Second")
+    .build()
+    .unwrap();
+    let other_document = DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .page(a4())
+    .text("Inserted")
+    .at(10.0, 10.0)
+    .text("First")
+    .text("Changed")
+    .build()
+    .unwrap();
+
+    let changes = document.diff(&other_document);
+    assert_eq!(changes.len(), 2);
+    assert!(matches!(changes[0], Change::Added { index: 1, .. }));
+    let Change::Modified {
+        old_index,
+        new_index,
+        fields,
+    } = &changes[1]
+    else {
+        panic!("Expected a modification, found {:?}", changes[1]);
+    };
+    assert_eq!((*old_index, *new_index), (2, 3));
+    assert_eq!(fields.len(), 1);
+    assert_eq!(fields[0].field, "textString");
+
+    assert!(document.diff(&document).is_empty());
+}
+
+/// Verifies that a field which is only present in the other operation, because it is left out when it is not set,
+/// is reported as modified.
+#[test]
+fn diff_reports_fields_only_present_in_the_other_operation() {
+    let document = DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .page(a4())
+    .text("Spaced")
+    .build()
+    .unwrap();
+    let other_document = DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .page(a4())
+    .text("Spaced")
+    .letter_spacing(2.0)
+    .build()
+    .unwrap();
+
+    let changes = document.diff(&other_document);
+    let [Change::Modified { fields, .. }] = changes.as_slice() else {
+        panic!("Expected a single modification, found {:?}", changes);
+    };
+    assert_eq!(fields.len(), 1);
+    assert_eq!(fields[0].field, "letterSpacing");
+    assert_eq!(fields[0].old, serde_json::Value::Null);
+    assert_eq!(fields[0].new, serde_json::json!(2.0));
+}
+
+/// Verifies that the properties of the whole document, such as its title, are compared even when the operations
+/// are the same.
+#[test]
+fn diff_reports_changed_document_properties() {
+    let document = DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .page(a4())
+    .build()
+    .unwrap();
+    let other_document = DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .title("Annual report")
+    .strict()
+    .page(a4())
+    .build()
+    .unwrap();
+
+    let changes = document.diff(&other_document);
+    let [Change::Properties { fields }] = changes.as_slice() else {
+        panic!("Expected a change of the properties, found {:?}", changes);
+    };
+    let field_names = fields
+        .iter()
+        .map(|field| field.field.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(field_names, ["strict", "title"]);
+    assert_eq!(fields[1].new, serde_json::json!("Annual report"));
+}
+
+/// Verifies that two large documents which differ at both ends are compared without aligning the whole region in
+/// between, which would need a table of billions of cells, while the operations that are equal in place are still
+/// left out of the changes.
+#[test]
+fn diff_of_a_large_rewrite_stays_bounded() {
+    let document_with_texts = |first_text: &str, last_text: &str| {
+        let mut document_builder = DocumentBuilder::new(
+            "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+            "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+        )
+        .page(a4())
+        .text(first_text);
+        for text_index in 0..50_000 {
+            document_builder = document_builder.text(format!("Line {}", text_index));
+        }
+        document_builder.text(last_text).build().unwrap()
+    };
+    let document = document_with_texts("First", "Last");
+    let other_document = document_with_texts("First, changed", "Last, changed");
+
+    let changes = document.diff(&other_document);
+    assert_eq!(changes.len(), 2);
+    assert!(matches!(
+        changes[0],
+        Change::Modified {
+            old_index: 1,
+            new_index: 1,
+            ..
+        }
+    ));
+    assert!(matches!(
+        changes[1],
+        Change::Modified {
+            old_index: 50_002,
+            new_index: 50_002,
+            ..
+        }
+    ));
+}
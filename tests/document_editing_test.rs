@@ -0,0 +1,101 @@
+use textr::{
+    builder::{a4, DocumentBuilder},
+    document::{Document, Operation},
+    edit::DocumentEditor,
+    error::ErrorKind,
+};
+
+/// Creates a document with a single page and two pieces of text.
+fn document() -> Document {
+    DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .page(a4())
+    .text("First")
+    .text("Second")
+    .build()
+    .unwrap()
+}
+
+/// Returns the text written by the given operation.
+fn text_of(operation: &Operation) -> &str {
+    match operation {
+        Operation::WriteUnicodeText { text_string, .. } => text_string,
+        _ => panic!("Expected a piece of text, found {:?}", operation),
+    }
+}
+
+/// Verifies that the operations can be inserted, replaced, moved and removed by their indices.
+#[test]
+fn operations_are_edited_by_index() {
+    let mut document = document();
+    let mut inserted_operation = document.operations[1].clone();
+    if let Operation::WriteUnicodeText { text_string, .. } = &mut inserted_operation {
+        *text_string = "Inserted".to_string();
+    }
+
+    document.insert_operation(3, inserted_operation).unwrap();
+    document.move_operation(3, 1).unwrap();
+    let removed_operation = document.remove_operation(2).unwrap();
+    assert_eq!(text_of(&removed_operation), "First");
+
+    let texts = document.operations[1..]
+        .iter()
+        .map(text_of)
+        .collect::<Vec<_>>();
+    assert_eq!(texts, ["Inserted", "Second"]);
+}
+
+/// Verifies that the edits which would make the document impossible to convert are rejected
+/// and leave the document unchanged.
+#[test]
+fn invalid_edits_are_rejected() {
+    let mut document = document();
+    let original_document = document.clone();
+
+    let error = document.remove_operation(0).unwrap_err();
+    assert_eq!(error.kind, ErrorKind::PageIndexOutOfRange);
+    let error = document.move_operation(0, 2).unwrap_err();
+    assert_eq!(error.kind, ErrorKind::PageIndexOutOfRange);
+    let error = document
+        .insert_operation(
+            1,
+            Operation::AppendNewPage {
+                page_width: 0.0,
+                page_height: 297.0,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(error.kind, ErrorKind::InvalidDocument);
+    assert_eq!(error.operation_index, Some(1));
+    let error = document.replace_operation(5, document.operations[1].clone());
+    assert_eq!(error.unwrap_err().kind, ErrorKind::InvalidDocument);
+
+    assert_eq!(document, original_document);
+}
+
+/// Verifies that the identifiers of the operations keep referring to them while the document is edited.
+#[test]
+fn operation_ids_survive_the_edits() {
+    let mut editor = DocumentEditor::new(document());
+    let second_operation_id = editor.operation_ids()[2];
+    let inserted_operation_id = editor
+        .insert_operation(1, editor.document().operations[1].clone())
+        .unwrap();
+    assert_eq!(editor.index_of(second_operation_id), Some(3));
+
+    editor.move_operation(second_operation_id, 1).unwrap();
+    assert_eq!(editor.index_of(second_operation_id), Some(1));
+    assert_eq!(editor.index_of(inserted_operation_id), Some(2));
+    assert_eq!(
+        text_of(editor.operation(second_operation_id).unwrap()),
+        "Second"
+    );
+
+    editor.remove_operation(inserted_operation_id).unwrap();
+    assert_eq!(editor.index_of(inserted_operation_id), None);
+    assert!(editor.remove_operation(inserted_operation_id).is_err());
+    assert_eq!(editor.operation_ids().len(), 3);
+    assert_eq!(editor.into_document().operations.len(), 3);
+}
@@ -0,0 +1,51 @@
+use textr::{document::Document, error::ErrorKind};
+
+/// Verifies that the error of a document which can't be parsed tells the position and the operation at fault.
+#[test]
+fn parse_error_locates_the_failing_operation() {
+    let document_path = std::env::temp_dir().join("textr_document_parse_test.json");
+    std::fs::write(
+        &document_path,
+        r#"{
+    "documentId": "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+    "instanceId": "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    "operations": [
+        { "pageWidth": 210.0, "pageHeight": 297.0 },
+        { "pageWidth": 210.0, "pageHeigth": 297.0 }
+    ]
+}"#,
+    )
+    .unwrap();
+
+    let error = Document::from_path(&document_path).unwrap_err();
+    assert_eq!(error.kind, ErrorKind::InvalidDocument);
+    assert_eq!(error.operation_index, Some(1));
+    assert!(error.context.ends_with("at line 7, column 5 (operation 1)"));
+    assert_eq!(
+        error.source_error.as_deref(),
+        Some("data did not match any variant of untagged enum Operation")
+    );
+
+    std::fs::remove_file(document_path).unwrap();
+}
+
+/// Verifies that a document is parsed from bytes just like from a file, and that the bytes which aren't even
+/// valid UTF-8 are rejected with an error rather than a panic.
+#[test]
+fn documents_are_parsed_from_bytes() {
+    let document = Document::from_json_bytes(
+        br#"{
+    "documentId": "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+    "instanceId": "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    "operations": [{ "pageWidth": 210.0, "pageHeight": 297.0 }]
+}"#,
+    )
+    .unwrap();
+    assert_eq!(document.operations.len(), 1);
+
+    let error = Document::from_json_bytes(b"{\"documentId\": \"\xff\"}").unwrap_err();
+    assert_eq!(error.kind, ErrorKind::InvalidDocument);
+    assert!(error
+        .context
+        .starts_with("Unable to parse the document at line 1"));
+}
@@ -0,0 +1,48 @@
+use textr::{
+    builder::{a4, DocumentBuilder},
+    error::ErrorKind,
+    font::FontRegistry,
+};
+
+/// Verifies that the errors carry the kind which tells them apart, independently of their context.
+#[test]
+fn errors_carry_their_kind() {
+    let font_error = FontRegistry::from_paths(vec!["fonts/missing-font.ttf".into()]).unwrap_err();
+    assert_eq!(font_error.kind, ErrorKind::FontNotFound);
+    assert_eq!(font_error.kind.code(), "E001");
+
+    let document = DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .page(a4())
+    .text("Hello, world!")
+    .at(20.0, 270.0)
+    .font(1000, 12.0)
+    .build()
+    .unwrap();
+    let Err(conversion_error) = document.to_pdf_document() else {
+        panic!("The document refers to a font which doesn't exist");
+    };
+    assert_eq!(conversion_error.kind, ErrorKind::FontIndexOutOfRange);
+}
+
+/// Verifies that failing to write the PDF file, such as onto a full disk, is reported as an I/O error rather than
+/// making the conversion panic.
+#[cfg(target_os = "linux")]
+#[test]
+fn failed_write_is_an_io_error() {
+    let document = DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .page(a4())
+    .build()
+    .unwrap();
+
+    // Every write onto this device fails as if the disk were full
+    let error = document
+        .save_to_pdf_file(std::path::Path::new("/dev/full"))
+        .unwrap_err();
+    assert_eq!(error.kind, ErrorKind::Io);
+}
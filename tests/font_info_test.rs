@@ -0,0 +1,26 @@
+use std::path::Path;
+use textr::font::FontInfo;
+
+/// Verifies that the summary of a built-in font reports its names and covers the printable ASCII characters.
+#[test]
+fn font_info_summarizes_builtin_font() {
+    let font_info = FontInfo::from_path(Path::new("fonts/computer-modern/cmunrm.ttf")).unwrap();
+    assert_eq!(font_info.family_name.as_deref(), Some("CMU Serif"));
+    assert_eq!(font_info.style_name.as_deref(), Some("Roman"));
+    assert_eq!(font_info.units_per_em, 2048);
+
+    let basic_latin = font_info
+        .unicode_blocks
+        .iter()
+        .find(|unicode_block| unicode_block.name == "Basic Latin")
+        .unwrap();
+    assert!(basic_latin.covered_characters_count >= 95);
+    assert_eq!(
+        font_info
+            .unicode_blocks
+            .iter()
+            .map(|unicode_block| unicode_block.covered_characters_count)
+            .sum::<usize>(),
+        font_info.character_count
+    );
+}
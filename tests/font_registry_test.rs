@@ -0,0 +1,57 @@
+use std::sync::Arc;
+
+use textr::{
+    builder::{a4, DocumentBuilder},
+    font::FontRegistry,
+    pdf::PdfDocument,
+};
+
+/// Verifies that converting a document with a shared registry of the built-in fonts produces
+/// the same PDF document as reading the fonts during the conversion.
+#[test]
+fn font_registry_produces_identical_pdf_documents() {
+    let document = DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .deterministic()
+    .page(a4())
+    .text("Hello, world!")
+    .at(20.0, 270.0)
+    .font(15, 12.0)
+    .build()
+    .unwrap();
+
+    let font_registry = FontRegistry::builtin().unwrap();
+    assert_eq!(
+        font_registry.font_paths(),
+        textr::document::builtin_font_paths().unwrap()
+    );
+    let pdf_document_bytes = document.to_pdf_document().unwrap().save_to_bytes().unwrap();
+    let shared_pdf_document_bytes = document
+        .to_pdf_document_with_fonts(&font_registry)
+        .unwrap()
+        .save_to_bytes()
+        .unwrap();
+    assert!(pdf_document_bytes == shared_pdf_document_bytes);
+}
+
+/// Verifies that the PDF documents built from a registry share the content of its fonts instead of copying it.
+#[test]
+fn font_registry_shares_font_bytes_with_pdf_documents() {
+    let font_registry = FontRegistry::builtin().unwrap();
+    let font_bytes = &font_registry.font_bytes()[15];
+
+    let mut pdf_documents = Vec::new();
+    for _ in 0..3 {
+        let mut pdf_document = PdfDocument::new("5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9".to_string());
+        pdf_document
+            .add_font_from_bytes(font_bytes.clone())
+            .unwrap();
+        pdf_documents.push(pdf_document);
+    }
+    assert_eq!(Arc::strong_count(font_bytes), 4);
+
+    drop(pdf_documents);
+    assert_eq!(Arc::strong_count(font_bytes), 1);
+}
@@ -0,0 +1,83 @@
+use lopdf::Object;
+use owned_ttf_parser::{AsFaceRef as _, OwnedFace};
+use std::collections::BTreeMap;
+use textr::{
+    builder::{a4, DocumentBuilder},
+    font::FontRegistry,
+};
+
+/// Verifies that the widths of the embedded font, which are compacted into ranges of glyphs with the same width,
+/// decode back to the widths of the glyphs of the font.
+#[test]
+fn embedded_font_widths_match_the_font() {
+    let document = DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .page(a4())
+    .text("Hello, world!")
+    .at(20.0, 270.0)
+    .font(15, 12.0)
+    .build()
+    .unwrap();
+
+    let font_registry = FontRegistry::builtin().unwrap();
+    let pdf_bytes = document
+        .to_pdf_document_with_fonts(&font_registry)
+        .unwrap()
+        .save_to_bytes()
+        .unwrap();
+    let inner_document = lopdf::Document::load_mem(&pdf_bytes).unwrap();
+    let width_objects = inner_document
+        .objects
+        .values()
+        .filter_map(|object| object.as_dict().ok())
+        .find_map(|dictionary| {
+            dictionary
+                .get(b"DescendantFonts")
+                .and_then(Object::as_array)
+                .ok()
+        })
+        .and_then(|descendant_fonts| descendant_fonts.first())
+        .unwrap()
+        .as_dict()
+        .and_then(|descendant_font| descendant_font.get(b"W"))
+        .and_then(Object::as_array)
+        .unwrap();
+
+    // Decode both the `first [widths]` and the `first last width` forms
+    let mut decoded_widths = BTreeMap::new();
+    let mut range_count = 0;
+    let mut index = 0;
+    while index < width_objects.len() {
+        let first_glyph_id = width_objects[index].as_i64().unwrap();
+        match &width_objects[index + 1] {
+            Object::Array(widths) => {
+                for (offset, width) in widths.iter().enumerate() {
+                    decoded_widths.insert(first_glyph_id + offset as i64, width.as_i64().unwrap());
+                }
+                index += 2;
+            }
+            last_glyph_id => {
+                let width = width_objects[index + 2].as_i64().unwrap();
+                for glyph_id in first_glyph_id..=last_glyph_id.as_i64().unwrap() {
+                    decoded_widths.insert(glyph_id, width);
+                }
+                range_count += 1;
+                index += 3;
+            }
+        }
+    }
+    assert!(range_count > 0);
+
+    let font_face = OwnedFace::from_vec(font_registry.font_bytes()[15].to_vec(), 0).unwrap();
+    let font_face = font_face.as_face_ref();
+    let scaling = 1000.0 / font_face.units_per_em() as f32;
+    let expected_widths = (0..font_face.number_of_glyphs())
+        .filter_map(|glyph_id| {
+            let width = font_face.glyph_hor_advance(owned_ttf_parser::GlyphId(glyph_id))?;
+            Some((glyph_id as i64, (width as f32 * scaling) as i64))
+        })
+        .collect::<BTreeMap<_, _>>();
+    assert_eq!(decoded_widths, expected_widths);
+}
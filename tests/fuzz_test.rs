@@ -1,87 +1,35 @@
 use image::{Rgba, RgbaImage};
-use rand::{distributions::Alphanumeric, Rng};
+use rand::{distributions::Alphanumeric, rngs::StdRng, Rng, SeedableRng};
 use serde::Serialize as _;
-use std::{io::Write as _, ops::Range, str::FromStr as _};
-use textr::error::ContextError;
+use std::{io::Write as _, str::FromStr as _};
+use textr::{
+    document::Operation,
+    error::ContextError,
+    testing::{compare_pdfs, diff_pdfs, normalize_pdf, DocumentGenerator, GeneratorConfiguration},
+};
+
+/// The seed from which the fuzz targets and the random images are generated, unless another one is given via the
+/// `TEXTR_FUZZ_SEED` environment variable, so that the same corpus is obtained on every machine.
+const DEFAULT_SEED: u64 = 0x7E87;
+
+/// Returns the seed of the generation, see `DEFAULT_SEED`.
+fn seed() -> u64 {
+    std::env::var("TEXTR_FUZZ_SEED")
+        .ok()
+        .and_then(|seed| seed.parse().ok())
+        .unwrap_or(DEFAULT_SEED)
+}
 
 /// The function which generates the fuzz targets (the JSON files to be fed to the
-/// `generate_target_references_from_fuzz_targets` function). Because this function is only exposed
-/// to the developer, for simplicity I have made it so that the main way to configure the generation of the fuzz targets
-/// is to alter the parameters inside the function definition it in order to obtain the desired range.
+/// `generate_target_references_from_fuzz_targets` function). The documents are generated by a `DocumentGenerator`
+/// with the default configuration, which can be altered in order to obtain different documents.
 #[test]
 fn generate_fuzz_targets() {
     // The number of documents to generate
     let documents_to_generate = 7;
-    // The range of font indices to pick from (this is chosen based on the fonts loaded)
-    let font_indices_range = 0..30;
-    // The maximum number of elements
-    let maximum_number_of_elements = 190;
-    // The maximum string length for the unicode text
-    let maximum_string_length = 230;
-    // The range of font sizes to use in the document
-    let font_size_range = 39.0..65.0;
-    // The range of page widths to use when creating a new page
-    let page_width_range = 200.0..1300.0;
-    // The range of page heights to use when creating a new page
-    let page_height_range = 200.0..800.0;
-    // The range of elements positions to choose from when positioning any element
-    let elements_position_range = 0.0..600.0;
-
-    // Generate the number of documents specified one by one and collect them into a vector of documents
-    let documents: Vec<_> = (0..documents_to_generate)
-        .map(|_| {
-            let mut rng = rand::thread_rng();
-            // Randomly generate both the document ID and the instance ID as 32 character long strings
-            // accordingly to the PDF specification
-            let document_id = rng
-                .clone()
-                .sample_iter(&Alphanumeric)
-                .map(char::from)
-                .take(32)
-                .collect::<String>();
-            let instance_id = rng
-                .clone()
-                .sample_iter(&Alphanumeric)
-                .map(char::from)
-                .take(32)
-                .collect::<String>();
-
-            let mut operations = Vec::new();
-
-            // The requirement for each document is to initially create a page, so we generate the first page
-            // by setting it with random width and height in the given range and appending its onto the operations vector
-            let page_width = rng.gen_range(page_width_range.clone());
-            let page_height = rng.gen_range(page_height_range.clone());
-            let first_page = textr::document::Operation::AppendNewPage {
-                page_width,
-                page_height,
-            };
-            operations.push(first_page);
-
-            let number_of_elements = rng.gen_range(1..maximum_number_of_elements);
-            for _ in 0..number_of_elements {
-                // Generate a random operation for each of the randomly selected number of elements
-                // within the specified range and append it onto the operations vector
-                let randomly_generated_operation = random_operation(
-                    &mut rng,
-                    elements_position_range.clone(),
-                    maximum_string_length,
-                    font_size_range.clone(),
-                    font_indices_range.clone(),
-                    page_width_range.clone(),
-                    page_height_range.clone(),
-                );
-                operations.push(randomly_generated_operation);
-            }
-
-            // Then return to document with the constructed operations
-            textr::document::Document {
-                document_id,
-                instance_id,
-                operations,
-            }
-        })
-        .collect();
+    let documents = DocumentGenerator::new(seed(), GeneratorConfiguration::default())
+        .take(documents_to_generate)
+        .collect::<Vec<_>>();
 
     // Save all the documents to JSON files to the predefined path for fuzz targets
     documents.into_iter().for_each(|document| {
@@ -97,60 +45,29 @@ fn generate_fuzz_targets() {
     });
 }
 
-/// Returns a randomly generated operation with a predefined chance (can be pre-configured by altering
-/// the function definition) with the given parameters for the different properties of the operations.
-fn random_operation(
-    rng: &mut rand::rngs::ThreadRng,
-    elements_position_range: Range<f32>,
-    maximum_string_length: usize,
-    font_size_range: Range<f32>,
-    font_indices_range: Range<usize>,
-    page_width_range: Range<f32>,
-    page_height_range: Range<f32>,
-) -> textr::document::Operation {
-    // This variable represents the chance of selecting an operation over the other
-    let operation_chance = rng.gen_range(0..=100);
-    match operation_chance {
-        // With a predefined 70% chance the `WriteUnicodeText` operation is chosen
-        0..=69 => {
-            let color = [
-                rng.gen_range(0.0..=1.0),
-                rng.gen_range(0.0..=1.0),
-                rng.gen_range(0.0..=1.0),
-            ];
-            let position = [
-                rng.gen_range(elements_position_range.clone()),
-                rng.gen_range(elements_position_range),
-            ];
-            let text_string = random_utf8_characters(rng, maximum_string_length);
-            let font_size = rng.gen_range(font_size_range.clone());
-            let font_index = rng.gen_range(font_indices_range.clone());
-            textr::document::Operation::WriteUnicodeText {
-                color,
-                position,
-                text_string,
-                font_size,
-                font_index,
-            }
-        }
-        // With a predefined 30% chance the `WriteImage` operation is chosen
-        70..=100 => {
-            let page_width = rng.gen_range(page_width_range.clone());
-            let page_height = rng.gen_range(page_height_range.clone());
-            textr::document::Operation::AppendNewPage {
-                page_width,
-                page_height,
-            }
-        }
-        // No other possible range should be left out, so this branch is technically unreachable
-        _ => unreachable!(),
-    }
-}
-
-/// Returns a randomly generated string with a length within the given range of the maximum string length.
-fn random_utf8_characters(rng: &mut rand::rngs::ThreadRng, maximum_string_length: usize) -> String {
-    let length = rng.gen_range(1..=maximum_string_length);
-    rand_utf8::rand_utf8(rng, length).to_string()
+/// Verifies that the same seed and configuration always generate the same documents.
+#[test]
+fn generation_is_reproducible() {
+    let configuration = GeneratorConfiguration {
+        maximum_operation_count: 20,
+        ..Default::default()
+    };
+    let documents = DocumentGenerator::new(seed(), configuration.clone())
+        .take(3)
+        .collect::<Vec<_>>();
+    let other_documents = DocumentGenerator::new(seed(), configuration.clone())
+        .take(3)
+        .collect::<Vec<_>>();
+    assert_eq!(documents, other_documents);
+    assert_ne!(
+        documents,
+        DocumentGenerator::new(seed() + 1, configuration)
+            .take(3)
+            .collect::<Vec<_>>()
+    );
+    assert!(documents
+        .iter()
+        .all(|document| matches!(document.operations[0], Operation::AppendNewPage { .. })));
 }
 
 /// Generates a random image within the given range of the parameters defined in its body.
@@ -160,7 +77,7 @@ fn generate_random_image() {
     let image_width_range = 1..150;
     let image_height_range = 1..150;
 
-    let mut rng = rand::thread_rng();
+    let mut rng = StdRng::seed_from_u64(seed());
     // Create a new image with random width and height within the given ranges
     let mut image = RgbaImage::new(
         rng.gen_range(image_width_range),
@@ -187,16 +104,10 @@ fn generate_random_image() {
     image.save(format!("images/{}.png", image_name)).unwrap();
 }
 
-/// This function generates the target references (the PDF documents which get then converted to postscript)
-/// starting from the fuzz targets (the JSON files representing the documents). It reads the fuzz targets
-/// documents from the predefined directory in the `fuzz` folder, outputting the postscript files in the
-/// target references folder present in the same directory. This function also temporarily generates a PDF file
-/// which gets replaced it with the associated file in postscript format, with the creation date removed.
-///
-/// # Disclaimer
-///
-/// In order to run this function it is needed to have on the computer a shell which has available
-/// in the PATH environment the commands `sed` and `pdf2ps`.
+/// This function generates the target references (the normalized PDF documents, see `normalize_pdf`) starting
+/// from the fuzz targets (the JSON files representing the documents). It reads the fuzz targets documents from the
+/// predefined directory in the `fuzz` folder, outputting the normalized PDF files in the target references folder
+/// present in the same directory.
 #[test]
 fn generate_target_references_from_fuzz_targets() {
     // Get a list of all the fuzz targets in the predefined folder
@@ -258,86 +169,21 @@ fn generate_target_references_from_fuzz_targets() {
         .unwrap();
         document.save_to_pdf_file(&pdf_document_path).unwrap();
 
-        // Convert the PDF document to postscript
-        let ps_document_path = std::path::PathBuf::from_str(&format!(
-            "fuzz/target_references/{}.ps",
-            fuzz_target_file_stem
-        ))
-        .unwrap();
-        convert_pdf_file_to_ps(
-            pdf_document_path.to_str().unwrap(),
-            ps_document_path.to_str().unwrap(),
+        // Replace the PDF document with its normalized form, which doesn't depend on the date of the conversion
+        let pdf_document_content = std::fs::read(&pdf_document_path).unwrap();
+        std::fs::write(
+            &pdf_document_path,
+            normalize_pdf(&pdf_document_content).unwrap(),
         )
         .unwrap();
-
-        // Remove the creation date from the postscript file by using the `sed -i -e '7d' file.ps` command
-        let command = std::process::Command::new("sed")
-            .arg("-i")
-            .arg("-e")
-            .arg("7d")
-            .arg(ps_document_path.clone())
-            .spawn();
-        command
-            .unwrap()
-            .wait()
-            .map_err(|error| {
-                ContextError::with_error(
-                    format!(
-                        "Failed to remove creation date from PS document {:?}",
-                        ps_document_path
-                    ),
-                    &error,
-                )
-            })
-            .unwrap();
-
-        // Remove the leftover PDF file
-        let command = std::process::Command::new("rm")
-            .arg(pdf_document_path.clone())
-            .spawn();
-        command
-            .unwrap()
-            .wait()
-            .map_err(|error| {
-                ContextError::with_error(
-                    format!("Failed to remove PDF document {:?}", pdf_document_path),
-                    &error,
-                )
-            })
-            .unwrap();
-
-        // Remove the `ps-e` leftover file from running the command `pdf2ps`
-        let ps_e_file_path = std::path::PathBuf::from_str(&format!(
-            "fuzz/target_references/{}.ps-e",
-            fuzz_target_file_stem
-        ))
-        .unwrap();
-        let command = std::process::Command::new("rm")
-            .arg(ps_e_file_path.clone())
-            .spawn();
-        command
-            .unwrap()
-            .wait()
-            .map_err(|error| {
-                ContextError::with_error(
-                    format!("Failed to remove PS-e document {:?}", ps_e_file_path),
-                    &error,
-                )
-            })
-            .unwrap();
     }
 }
 
 /// This function is responsible for verifying that the PDF documents dynamically-generated by the latest version
 /// of the library actually match the expected reference targets which were previously created.
 /// The testing is done by loading the JSON documents from the predefined path, parsing them and then generating the
-/// associated PDF document, which is converted to postscript in order for it to be tested against the target references
-/// (which are the postscript files generated by the `generate_target_references_from_fuzz_targets` function).
-///
-/// # Disclaimer
-///
-/// Just as the function `generate_target_references_from_fuzz_targets`, this function needs to be run in a shell
-/// that has available in the PATH environment the commands `pdf2ps`, `bash`, `rm` and `sed`.
+/// associated PDF document, which is normalized in order for it to be tested against the target references
+/// (which are the normalized PDF files generated by the `generate_target_references_from_fuzz_targets` function).
 #[test]
 fn compare_fuzz_targets_with_target_references() {
     // Get a list of all the fuzz targets in the predefined folder
@@ -367,113 +213,23 @@ fn compare_fuzz_targets_with_target_references() {
         ))
         .unwrap();
         document.save_to_pdf_file(&pdf_document_path).unwrap();
-        // Convert the PDF document to postscript
-        let ps_document_path = std::path::PathBuf::from_str(&format!(
-            "fuzz/fuzz_targets/{}.ps",
-            fuzz_target_file_stem
-        ))
-        .unwrap();
-        convert_pdf_file_to_ps(
-            pdf_document_path.to_str().unwrap(),
-            ps_document_path.to_str().unwrap(),
-        )
-        .unwrap();
-
-        // Remove the creation date from the postscript file by using the `sed -i -e '7d' file.ps` command
-        let command = std::process::Command::new("sed")
-            .arg("-i")
-            .arg("-e")
-            .arg("7d")
-            .arg(ps_document_path.clone())
-            .spawn();
-        command
-            .unwrap()
-            .wait()
-            .map_err(|error| {
-                ContextError::with_error(
-                    format!(
-                        "Failed to remove creation date from PS document {:?}",
-                        ps_document_path
-                    ),
-                    &error,
-                )
-            })
-            .unwrap();
-        // Load the document as a string, but this time from the postscript file
-        let ps_document_path = format!("fuzz/fuzz_targets/{}.ps", fuzz_target_file_stem);
-        let ps_document_content = std::fs::read_to_string(ps_document_path).unwrap();
-
-        // And then load the reference document saved in the postscript format from the target references path
-        let reference_ps_document_path =
-            format!("fuzz/target_references/{}.ps", fuzz_target_file_stem);
-        let reference_ps_document_content =
-            std::fs::read_to_string(reference_ps_document_path.clone()).unwrap();
-
-        // Run a comparison test between the contents of the two documents, reporting
-        // any differences in the console by using a diffing algorithm
-        similar_asserts::assert_eq!(ps_document_content, reference_ps_document_content);
-
-        // If the comparison is deemed successful, then remove all the leftover files from the dynamical
-        // generation of the PDF and postscript documents from the fuzz targets
-        // This is done by invoking the shell command `bash -c rm`
-        let all_files_path = std::path::PathBuf::from_str(&format!(
-            "fuzz/fuzz_targets/{}.pdf fuzz/fuzz_targets/{}.ps fuzz/fuzz_targets/{}.ps-e",
-            fuzz_target_file_stem, fuzz_target_file_stem, fuzz_target_file_stem
-        ))
-        .unwrap();
-        let command = std::process::Command::new("bash")
-            .arg("-c")
-            .arg(format!("rm {}", all_files_path.to_str().unwrap()))
-            .spawn();
-        command
-            .unwrap()
-            .wait()
-            .map_err(|error| {
-                ContextError::with_error(
-                    format!(
-                        "Failed to remove all documents for comparison {:?}",
-                        all_files_path
-                    ),
-                    &error,
-                )
-            })
-            .unwrap();
+        let pdf_document_content = std::fs::read(&pdf_document_path).unwrap();
+        std::fs::remove_file(&pdf_document_path).unwrap();
+
+        // And then load the reference document from the target references path
+        let reference_pdf_document_path =
+            format!("fuzz/target_references/{}.pdf", fuzz_target_file_stem);
+        let reference_pdf_document_content = std::fs::read(reference_pdf_document_path).unwrap();
+
+        // Run a comparison test between the normalized contents of the two documents, reporting
+        // the objects which differ and how their entries have changed
+        if let Err(error) = compare_pdfs(&pdf_document_content, &reference_pdf_document_content) {
+            let differences =
+                diff_pdfs(&reference_pdf_document_content, &pdf_document_content).unwrap();
+            panic!(
+                "The fuzz target {} differs from its reference, {}: {:#?}",
+                fuzz_target_file_stem, error, differences
+            );
+        }
     }
 }
-
-/// This function is a convenience function responsible for converting a PDF file to a postscript file.
-/// It does so by invoking the `pdf2ps` command, which needs to be available in the PATH environment of the shell.
-///
-/// # Arguments
-///
-/// * `pdf_file_path` - The path to the PDF file that needs to be converted to a postscript file.
-/// * `ps_file_path` - The path to the postscript file that will be created from the PDF file.
-fn convert_pdf_file_to_ps(pdf_file_path: &str, ps_file_path: &str) -> Result<(), ContextError> {
-    // Create the paths to the PDF and postscript files
-    let pdf_document_path = std::path::PathBuf::from_str(pdf_file_path).map_err(|error| {
-        ContextError::with_error(
-            format!("Failed to create the PDF document path {:?}", pdf_file_path),
-            &error,
-        )
-    })?;
-    let ps_document_path = std::path::PathBuf::from_str(ps_file_path).map_err(|error| {
-        ContextError::with_error(
-            format!("Failed to create the PS document path {:?}", pdf_file_path),
-            &error,
-        )
-    })?;
-
-    // Convert the saved PDF file to a postscript file via the command `pdf2ps`
-    let command = std::process::Command::new("pdf2ps")
-        .arg(pdf_document_path.clone())
-        .arg(ps_document_path.clone())
-        .spawn();
-    command.unwrap().wait().map_err(|error| {
-        ContextError::with_error(
-            format!("Failed to convert PDF to PS document {:?}", pdf_file_path),
-            &error,
-        )
-    })?;
-
-    Ok(())
-}
@@ -0,0 +1,56 @@
+use textr::builder::{a4, DocumentBuilder};
+
+/// Verifies that the HTML export contains one block per page and escapes the text of the spans.
+#[test]
+fn html_export_mirrors_pages_and_escapes_text() {
+    let document = DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .page(a4())
+    .text("Fish & <chips>")
+    .at(30.0, 270.0)
+    .font(15, 12.0)
+    .page(a4())
+    .build()
+    .unwrap();
+
+    let html = document.to_html().unwrap();
+    assert_eq!(html.matches("<div class=\"page\"").count(), 2);
+    assert!(html.contains("font-family: F15;"));
+    assert!(html.contains(">Fish &amp; &lt;chips&gt;</span>"));
+}
+
+/// Verifies that the page background of the document is mirrored by the HTML export.
+#[test]
+fn html_export_mirrors_page_background() {
+    let document = DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .page_background([1.0, 0.0, 0.0])
+    .page(a4())
+    .build()
+    .unwrap();
+
+    let html = document.to_html().unwrap();
+    assert!(html.contains("fill=\"rgb(255, 0, 0)\""));
+}
+
+/// Verifies that the letter spacing of the text is mirrored by the HTML export only when it has been set.
+#[test]
+fn html_export_mirrors_letter_spacing() {
+    let document = DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .page(a4())
+    .text("HEADING")
+    .letter_spacing(2.5)
+    .text("Body")
+    .build()
+    .unwrap();
+
+    let html = document.to_html().unwrap();
+    assert_eq!(html.matches("letter-spacing: 2.5pt;").count(), 1);
+}
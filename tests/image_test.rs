@@ -0,0 +1,163 @@
+use lopdf::{content::Content, Object};
+use textr::{error::ErrorKind, pdf::PdfDocument};
+
+/// The header of a JPEG image of 3 by 2 pixels with three color components, which is enough to be embedded
+/// since the JPEG images aren't decoded.
+const JPEG_BYTES: &[u8] = &[
+    0xFF, 0xD8, // Start of the image
+    0xFF, 0xE0, 0x00, 0x04, 0x00, 0x00, // An application segment, which is skipped
+    0xFF, 0xC0, 0x00, 0x11, 0x08, 0x00, 0x02, 0x00, 0x03, 0x03, 0x01, 0x11, 0x00, 0x02, 0x11, 0x01,
+    0x03, 0x11, 0x01, // The start of the frame
+    0xFF, 0xD9, // End of the image
+];
+
+/// Draws the given image onto a new page and returns the saved document together with the images of the page.
+fn save_with_image(image_bytes: &[u8]) -> (lopdf::Document, Vec<lopdf::Stream>) {
+    let mut pdf_document = PdfDocument::new("5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9".to_string());
+    let (page_index, layer_index) = pdf_document.add_page_with_layer(210.0, 297.0);
+    pdf_document
+        .add_image_to_layer_in_page(
+            page_index,
+            layer_index,
+            image_bytes,
+            [20.0, 20.0],
+            [30.0, 20.0],
+        )
+        .unwrap();
+    pdf_document
+        .write_all("hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj".to_string())
+        .unwrap();
+
+    let saved_pdf_document =
+        lopdf::Document::load_mem(&pdf_document.save_to_bytes().unwrap()).unwrap();
+    let page_id = saved_pdf_document.page_iter().next().unwrap();
+    let content = saved_pdf_document.get_page_content(page_id).unwrap();
+    assert!(String::from_utf8_lossy(&content).contains("/X0 Do"));
+    let images = saved_pdf_document
+        .objects
+        .values()
+        .filter_map(|object| object.as_stream().ok())
+        .filter(|stream| {
+            stream
+                .dict
+                .get(b"Subtype")
+                .and_then(Object::as_name_str)
+                .ok()
+                == Some("Image")
+        })
+        .cloned()
+        .collect();
+
+    (saved_pdf_document, images)
+}
+
+/// Verifies that a JPEG image is embedded as it is, with the size and the color space found in its header.
+#[test]
+fn jpeg_images_are_passed_through() {
+    let (_, images) = save_with_image(JPEG_BYTES);
+    assert_eq!(images.len(), 1);
+    let image = &images[0];
+    assert_eq!(
+        image.dict.get(b"Filter").unwrap().as_name_str().unwrap(),
+        "DCTDecode"
+    );
+    assert_eq!(image.dict.get(b"Width").unwrap().as_i64().unwrap(), 3);
+    assert_eq!(image.dict.get(b"Height").unwrap().as_i64().unwrap(), 2);
+    assert_eq!(
+        image
+            .dict
+            .get(b"ColorSpace")
+            .unwrap()
+            .as_name_str()
+            .unwrap(),
+        "DeviceRGB"
+    );
+    assert_eq!(image.content, JPEG_BYTES);
+}
+
+/// Verifies that the alpha channel of a PNG image is embedded as the soft mask of the image.
+#[test]
+fn png_alpha_channel_becomes_a_soft_mask() {
+    let mut png_bytes = Vec::new();
+    image::RgbaImage::from_fn(4, 4, |x, _| image::Rgba([255, 0, 0, (x * 64) as u8]))
+        .write_to(
+            &mut std::io::Cursor::new(&mut png_bytes),
+            image::ImageFormat::Png,
+        )
+        .unwrap();
+
+    let (saved_pdf_document, images) = save_with_image(&png_bytes);
+    assert_eq!(images.len(), 2);
+    let image = images
+        .iter()
+        .find(|image| image.dict.has(b"SMask"))
+        .unwrap();
+    assert_eq!(
+        image
+            .dict
+            .get(b"ColorSpace")
+            .unwrap()
+            .as_name_str()
+            .unwrap(),
+        "DeviceRGB"
+    );
+    let soft_mask_id = image.dict.get(b"SMask").unwrap().as_reference().unwrap();
+    let soft_mask = saved_pdf_document
+        .get_object(soft_mask_id)
+        .and_then(Object::as_stream)
+        .unwrap();
+    assert_eq!(
+        soft_mask
+            .dict
+            .get(b"ColorSpace")
+            .unwrap()
+            .as_name_str()
+            .unwrap(),
+        "DeviceGray"
+    );
+    assert_eq!(soft_mask.dict.get(b"Width").unwrap().as_i64().unwrap(), 4);
+}
+
+/// Verifies that the images which are neither JPEG nor PNG are rejected.
+#[test]
+fn unsupported_images_are_rejected() {
+    let mut pdf_document = PdfDocument::new("5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9".to_string());
+    let (page_index, layer_index) = pdf_document.add_page_with_layer(210.0, 297.0);
+    let error = pdf_document
+        .add_image_to_layer_in_page(page_index, layer_index, b"GIF89a", [0.0, 0.0], [1.0, 1.0])
+        .unwrap_err();
+    assert_eq!(error.kind, ErrorKind::InvalidImage);
+}
+
+/// Verifies that a JPEG image whose EXIF orientation tells that it is to be rotated clockwise by 90 degrees is
+/// drawn rotated into the given rectangle.
+#[test]
+fn jpeg_images_are_oriented() {
+    let mut jpeg_bytes = JPEG_BYTES[..2].to_vec();
+    jpeg_bytes.extend_from_slice(&[
+        0xFF, 0xE1, 0x00, 0x22, b'E', b'x', b'i', b'f', 0x00, 0x00, // The EXIF segment
+        b'M', b'M', 0x00, 0x2A, 0x00, 0x00, 0x00, 0x08, // The big-endian TIFF header
+        0x00, 0x01, 0x01, 0x12, 0x00, 0x03, 0x00, 0x00, 0x00, 0x01, 0x00, 0x06, 0x00,
+        0x00, // The orientation
+        0x00, 0x00, 0x00, 0x00, // The end of the directories
+    ]);
+    jpeg_bytes.extend_from_slice(&JPEG_BYTES[2..]);
+
+    let (saved_pdf_document, _) = save_with_image(&jpeg_bytes);
+    let page_id = saved_pdf_document.page_iter().next().unwrap();
+    let content = Content::decode(&saved_pdf_document.get_page_content(page_id).unwrap()).unwrap();
+    let matrix = content
+        .operations
+        .iter()
+        .find(|operation| operation.operator == "cm")
+        .unwrap()
+        .operands
+        .iter()
+        .map(|operand| operand.as_float().unwrap())
+        .collect::<Vec<_>>();
+    let [width, height, x, y] = [30.0, 20.0, 20.0, 20.0].map(|millimeters| millimeters * 2.834646);
+    let expected_matrix = [0.0, -height, width, 0.0, x, y + height];
+    for (value, expected_value) in matrix.iter().zip(expected_matrix) {
+        assert!((value - expected_value).abs() < 1e-3, "{:?}", matrix);
+    }
+}
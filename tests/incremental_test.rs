@@ -0,0 +1,165 @@
+use textr::{
+    builder::{a4, DocumentBuilder},
+    canvas::FilledPath,
+    custom::{CustomOperation, CustomOperationContext, CustomOperations},
+    document::{Document, Operation},
+    error::ContextError,
+    font::FontRegistry,
+    incremental::IncrementalConverter,
+};
+
+/// A custom operation which draws a black bar at the bottom of the current page.
+struct DrawBar;
+
+impl CustomOperation for DrawBar {
+    fn name(&self) -> &str {
+        "DrawBar"
+    }
+
+    fn draw(
+        &self,
+        _parameters: &serde_json::Value,
+        context: &mut CustomOperationContext<'_>,
+    ) -> Result<(), ContextError> {
+        context.fill_path(&FilledPath::rectangle(
+            [20.0, 20.0],
+            [50.0, 5.0],
+            [0.0, 0.0, 0.0],
+        ))
+    }
+}
+
+/// Creates a document with three pages, the second of which contains the given text.
+fn document_with_text(text: &str) -> Document {
+    DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .deterministic()
+    .page(a4())
+    .text("First page")
+    .at(20.0, 270.0)
+    .font(15, 12.0)
+    .page(a4())
+    .text(text)
+    .at(20.0, 270.0)
+    .font(15, 12.0)
+    .page(a4())
+    .text("Third page, 世界")
+    .at(20.0, 270.0)
+    .font(3, 12.0)
+    .build()
+    .unwrap()
+}
+
+/// Verifies that changing the text of a page draws only that page again, and that the resulting PDF document
+/// is the same as the one obtained by converting the changed document from scratch.
+#[test]
+fn update_draws_only_the_changed_pages() {
+    let font_registry = FontRegistry::builtin().unwrap();
+    let document = document_with_text("Second page");
+    let mut incremental_converter =
+        IncrementalConverter::new(document.clone(), &font_registry).unwrap();
+
+    let changed_document = document_with_text("Second page, changed");
+    let changes = document.diff(&changed_document);
+    let page_indices = incremental_converter
+        .update(changed_document.clone(), &changes)
+        .unwrap();
+    assert_eq!(page_indices.into_iter().collect::<Vec<_>>(), vec![1]);
+    assert_eq!(incremental_converter.document(), &changed_document);
+
+    let mut pdf_document = incremental_converter.to_pdf_document().unwrap();
+    let mut expected_pdf_document = changed_document
+        .to_pdf_document_with_fonts(&font_registry)
+        .unwrap();
+    assert_eq!(pdf_document.stats(), expected_pdf_document.stats());
+    assert_eq!(
+        pdf_document.save_to_bytes().unwrap(),
+        expected_pdf_document.save_to_bytes().unwrap()
+    );
+}
+
+/// Verifies that moving a page break draws the whole document again, since the operations around it move
+/// onto another page.
+#[test]
+fn update_draws_all_pages_when_a_page_break_moves() {
+    let font_registry = FontRegistry::builtin().unwrap();
+    let document = DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .deterministic()
+    .page(a4())
+    .text("First")
+    .text("Second")
+    .page(a4())
+    .text("Third")
+    .build()
+    .unwrap();
+    let mut changed_document = document.clone();
+    changed_document.operations.swap(2, 3);
+    let mut incremental_converter =
+        IncrementalConverter::new(document.clone(), &font_registry).unwrap();
+
+    let changes = document.diff(&changed_document);
+    let page_indices = incremental_converter
+        .update(changed_document.clone(), &changes)
+        .unwrap();
+    assert_eq!(page_indices.into_iter().collect::<Vec<_>>(), vec![0, 1]);
+
+    let mut pdf_document = incremental_converter.to_pdf_document().unwrap();
+    let mut expected_pdf_document = changed_document
+        .to_pdf_document_with_fonts(&font_registry)
+        .unwrap();
+    assert_eq!(
+        pdf_document.save_to_bytes().unwrap(),
+        expected_pdf_document.save_to_bytes().unwrap()
+    );
+}
+
+/// Verifies that the custom operations given to the converter are drawn both when the document is first drawn and
+/// when its pages are drawn again, just like they are when converting the document from scratch.
+#[test]
+fn update_draws_the_custom_operations() {
+    let font_registry = FontRegistry::builtin().unwrap();
+    let custom_operations = || {
+        let mut custom_operations = CustomOperations::new();
+        custom_operations.register(DrawBar);
+        custom_operations
+    };
+    let with_bar = |mut document: Document| {
+        document.operations.insert(
+            4,
+            Operation::Custom {
+                custom_operation: "DrawBar".to_string(),
+                parameters: serde_json::Value::Null,
+            },
+        );
+        document
+    };
+    let document = with_bar(document_with_text("Second page"));
+    assert!(IncrementalConverter::new(document.clone(), &font_registry).is_err());
+    let mut incremental_converter = IncrementalConverter::with_custom_operations(
+        document.clone(),
+        &font_registry,
+        custom_operations(),
+    )
+    .unwrap();
+
+    let changed_document = with_bar(document_with_text("Second page, changed"));
+    let changes = document.diff(&changed_document);
+    let page_indices = incremental_converter
+        .update(changed_document.clone(), &changes)
+        .unwrap();
+    assert_eq!(page_indices.into_iter().collect::<Vec<_>>(), vec![1]);
+
+    let mut pdf_document = incremental_converter.to_pdf_document().unwrap();
+    let mut expected_pdf_document = changed_document
+        .to_pdf_document_with_custom_operations(&font_registry, &custom_operations())
+        .unwrap();
+    assert_eq!(
+        pdf_document.save_to_bytes().unwrap(),
+        expected_pdf_document.save_to_bytes().unwrap()
+    );
+}
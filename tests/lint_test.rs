@@ -0,0 +1,131 @@
+use textr::{
+    builder::{a4, DocumentBuilder},
+    document::Operation,
+    lint::LintIssue,
+};
+
+/// Verifies that the lint reports each kind of problem at the index of the operation where it is found,
+/// while a well-formed document has no issues.
+#[test]
+fn lint_reports_document_issues() {
+    let well_formed_document = DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .page(a4())
+    .text("Hello, world!")
+    .at(20.0, 270.0)
+    .build()
+    .unwrap();
+    assert_eq!(well_formed_document.lint().unwrap(), vec![]);
+
+    let mut document = DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .page(a4())
+    .build()
+    .unwrap();
+    let text =
+        |text_string: &str, position: [f32; 2], font_index: usize| Operation::WriteUnicodeText {
+            color: [0.0, 0.0, 0.0],
+            position,
+            text_string: text_string.to_string(),
+            font_size: 12.0,
+            font_index,
+            letter_spacing: 0.0,
+        };
+    document.operations = vec![
+        text("Too early", [10.0, 10.0], 0),
+        Operation::AppendNewPage {
+            page_width: 0.0,
+            page_height: 297.0,
+        },
+        Operation::AppendNewPage {
+            page_width: 210.0,
+            page_height: 297.0,
+        },
+        text("Off the page", [250.0, 10.0], 0),
+        text("Unknown font", [10.0, 10.0], 1000),
+        text("Snowman \u{2603}", [10.0, 10.0], 0),
+    ];
+
+    let issues = document.lint().unwrap();
+    assert_eq!(issues.len(), 5);
+    assert_eq!(
+        issues[0],
+        LintIssue::TextBeforeFirstPage { operation_index: 0 }
+    );
+    assert!(matches!(
+        issues[1],
+        LintIssue::ZeroSizePage {
+            operation_index: 1,
+            ..
+        }
+    ));
+    assert!(matches!(
+        issues[2],
+        LintIssue::OffPageText {
+            operation_index: 3,
+            ..
+        }
+    ));
+    assert!(matches!(
+        issues[3],
+        LintIssue::FontIndexOutOfRange {
+            operation_index: 4,
+            font_index: 1000,
+            ..
+        }
+    ));
+    assert_eq!(
+        issues[4],
+        LintIssue::MissingCharacters {
+            operation_index: 5,
+            font_index: 0,
+            characters: "\u{2603}".to_string(),
+        }
+    );
+}
+
+/// Verifies that the validation reports all the problems of a document at once, including the ones
+/// which are not found by the conversion.
+#[test]
+fn validate_reports_all_issues() {
+    let mut document = DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .page(a4())
+    .build()
+    .unwrap();
+    let text = |text_string: &str, color: [f32; 3]| Operation::WriteUnicodeText {
+        color,
+        position: [10.0, 10.0],
+        text_string: text_string.to_string(),
+        font_size: 12.0,
+        font_index: 0,
+        letter_spacing: 0.0,
+    };
+    document.operations = vec![
+        text("Too early", [0.0, 0.0, 0.0]),
+        Operation::AppendNewPage {
+            page_width: 210.0,
+            page_height: 297.0,
+        },
+        text("Too bright", [0.0, 255.0, 0.0]),
+        text("", [0.0, 0.0, 0.0]),
+    ];
+
+    assert_eq!(
+        document.validate(),
+        vec![
+            LintIssue::TextBeforeFirstPage { operation_index: 0 },
+            LintIssue::InvalidColor {
+                operation_index: 2,
+                color: [0.0, 255.0, 0.0],
+            },
+            LintIssue::EmptyText { operation_index: 3 },
+        ]
+    );
+}
@@ -0,0 +1,75 @@
+use textr::{
+    builder::{a4, DocumentBuilder, PageSize},
+    document::{Document, Operation},
+    testing::minimize_document,
+};
+
+/// Creates a document with ten pages of ten pieces of text each, where the fifth page is smaller than the others
+/// and one of the pieces of text of the last page is the culprit of the failure.
+fn document() -> Document {
+    let mut builder = DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .page(a4())
+    .text("Text 0")
+    .at(20.0, 280.0);
+    for text_index in 1..100 {
+        let text = if text_index == 95 {
+            "Culprit".to_string()
+        } else {
+            format!("Text {}", text_index)
+        };
+        builder = if text_index % 10 == 0 {
+            let page_size = if text_index == 50 {
+                PageSize::new(100.0, 100.0)
+            } else {
+                a4()
+            };
+            builder.page(page_size).text(text).at(20.0, 280.0)
+        } else {
+            builder.text(text).at(20.0, 280.0)
+        };
+    }
+
+    builder.build().unwrap()
+}
+
+/// Returns whether the document has both the small page and the culprit text, in this order.
+fn is_failing(document: &Document) -> bool {
+    let small_page_index = document.operations.iter().position(|operation| {
+        matches!(operation, Operation::AppendNewPage { page_width, .. } if *page_width == 100.0)
+    });
+    let culprit_index = document.operations.iter().position(|operation| {
+        matches!(operation, Operation::WriteUnicodeText { text_string, .. } if text_string == "Culprit")
+    });
+
+    matches!((small_page_index, culprit_index), (Some(small_page_index), Some(culprit_index)) if small_page_index < culprit_index)
+}
+
+/// Verifies that a failing document is shrunk to the operations which cause the failure, in their order,
+/// while keeping the identifiers of the document.
+#[test]
+fn failing_document_is_minimized() {
+    let document = document();
+    assert_eq!(document.operations.len(), 110);
+    let mut predicate_call_count = 0;
+    let minimized_document = minimize_document(&document, |document| {
+        predicate_call_count += 1;
+        is_failing(document)
+    });
+
+    assert_eq!(minimized_document.operations.len(), 2);
+    assert!(is_failing(&minimized_document));
+    assert_eq!(minimized_document.document_id, document.document_id);
+    assert!(predicate_call_count < 200, "{}", predicate_call_count);
+}
+
+/// Verifies that a document which doesn't fail is returned as it is.
+#[test]
+fn passing_document_is_not_minimized() {
+    let document = document();
+    let minimized_document = minimize_document(&document, |_| false);
+
+    assert_eq!(minimized_document, document);
+}
@@ -0,0 +1,124 @@
+use textr::builder::{a4, DocumentBuilder};
+
+/// Verifies that optimizing a PDF file in pure Rust makes it smaller while keeping all of its pages.
+#[test]
+fn optimize_pdf_file_reduces_size() {
+    let document = DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .page(a4())
+    .text("Hello, world!")
+    .at(20.0, 270.0)
+    .font(15, 12.0)
+    .page(a4())
+    .text("Hello, again!")
+    .at(20.0, 270.0)
+    .font(15, 12.0)
+    .build()
+    .unwrap();
+
+    let input_path = std::env::temp_dir().join("textr_optimize_test_input.pdf");
+    let output_path = std::env::temp_dir().join("textr_optimize_test_output.pdf");
+    let pdf_document_bytes = document.to_pdf_document().unwrap().save_to_bytes().unwrap();
+    std::fs::write(&input_path, &pdf_document_bytes).unwrap();
+
+    textr::pdf::optimize_pdf_file(&input_path, &output_path).unwrap();
+    let optimized_pdf_document_bytes = std::fs::read(&output_path).unwrap();
+    assert!(optimized_pdf_document_bytes.len() < pdf_document_bytes.len());
+
+    let optimized_pdf_document = lopdf::Document::load_mem(&optimized_pdf_document_bytes).unwrap();
+    assert_eq!(optimized_pdf_document.get_pages().len(), 2);
+
+    std::fs::remove_file(input_path).unwrap();
+    std::fs::remove_file(output_path).unwrap();
+}
+
+/// Verifies that optimizing a PDF document merges the identical content streams of its pages into a single
+/// object referenced by all of them.
+#[test]
+fn optimize_merges_identical_streams() {
+    let document = DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .page(a4())
+    .text("The same header")
+    .at(20.0, 270.0)
+    .font(15, 12.0)
+    .page(a4())
+    .text("The same header")
+    .at(20.0, 270.0)
+    .font(15, 12.0)
+    .page(a4())
+    .text("The same header")
+    .at(20.0, 270.0)
+    .font(15, 12.0)
+    .build()
+    .unwrap();
+
+    let mut pdf_document = document.to_pdf_document().unwrap();
+    pdf_document.optimize();
+    let optimized_pdf_document =
+        lopdf::Document::load_mem(&pdf_document.save_to_bytes().unwrap()).unwrap();
+
+    let content_ids = optimized_pdf_document
+        .get_pages()
+        .into_values()
+        .map(|page_id| {
+            optimized_pdf_document
+                .get_dictionary(page_id)
+                .unwrap()
+                .get(b"Contents")
+                .unwrap()
+                .as_reference()
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(content_ids.len(), 3);
+    assert!(content_ids
+        .iter()
+        .all(|content_id| *content_id == content_ids[0]));
+}
+
+/// Verifies that optimizing a PDF document keeps the layer of each page apart, even though their dictionaries are
+/// identical, so that the optional content properties of the catalog list each of them once.
+#[test]
+fn optimize_keeps_the_layers_apart() {
+    let document = DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .page(a4())
+    .page(a4())
+    .page(a4())
+    .build()
+    .unwrap();
+
+    let layer_counts = |pdf_document: &mut textr::pdf::PdfDocument| {
+        let inner_document =
+            lopdf::Document::load_mem(&pdf_document.save_to_bytes().unwrap()).unwrap();
+        let layer_references = inner_document
+            .catalog()
+            .unwrap()
+            .get(b"OCProperties")
+            .and_then(lopdf::Object::as_dict)
+            .unwrap()
+            .get(b"OCGs")
+            .and_then(lopdf::Object::as_array)
+            .unwrap()
+            .iter()
+            .map(|layer| layer.as_reference().unwrap())
+            .collect::<Vec<_>>();
+        let distinct_layer_count = layer_references
+            .iter()
+            .collect::<std::collections::BTreeSet<_>>()
+            .len();
+        (layer_references.len(), distinct_layer_count)
+    };
+
+    let mut pdf_document = document.to_pdf_document().unwrap();
+    assert_eq!(layer_counts(&mut pdf_document.clone()), (3, 3));
+    pdf_document.optimize();
+    assert_eq!(layer_counts(&mut pdf_document), (3, 3));
+}
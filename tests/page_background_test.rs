@@ -0,0 +1,115 @@
+use textr::{
+    builder::{a4, a5, DocumentBuilder},
+    document::{Document, Operation},
+    error::ErrorKind,
+    font::FontRegistry,
+    lint::LintIssue,
+};
+
+/// Verifies that the page background operation is parsed from its own field, without being mistaken for a text.
+#[test]
+fn page_background_operation_is_parsed() {
+    let document = Document::from_json_bytes(
+        br#"{
+            "documentId": "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+            "instanceId": "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+            "operations": [
+                { "pageWidth": 210.0, "pageHeight": 297.0 },
+                { "pageBackground": [0.1, 0.2, 0.3] }
+            ]
+        }"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        document.operations[1],
+        Operation::SetPageBackground {
+            color: [0.1, 0.2, 0.3]
+        }
+    );
+    let document_json = serde_json::to_string(&document).unwrap();
+    assert!(document_json.contains(r#"{"pageBackground":[0.1,0.2,0.3]}"#));
+}
+
+/// Verifies that the page background operation only paints its own page, in place of the page background
+/// of the document, and that it is painted beneath the text written before it.
+#[test]
+fn page_background_operation_paints_its_page_beneath_the_text() {
+    let document = DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .page_background([0.0, 0.0, 1.0])
+    .page(a4())
+    .text("Cover")
+    .at(20.0, 270.0)
+    .font(15, 12.0)
+    .background([1.0, 0.0, 0.0])
+    .page(a5())
+    .build()
+    .unwrap();
+
+    let svg = document.to_svg().unwrap();
+    let cover_background = svg.find("fill=\"rgb(255, 0, 0)\"").unwrap();
+    let second_page_background = svg.find("fill=\"rgb(0, 0, 255)\"").unwrap();
+    let cover_text = svg.find(">Cover</text>").unwrap();
+    assert_eq!(svg.matches("fill=\"rgb(255, 0, 0)\"").count(), 1);
+    assert_eq!(svg.matches("fill=\"rgb(0, 0, 255)\"").count(), 1);
+    assert!(cover_background < cover_text && cover_text < second_page_background);
+
+    // The PDF document paints the background before the text is written
+    let font_registry = FontRegistry::builtin().unwrap();
+    let mut pdf_document = document.to_pdf_document_with_fonts(&font_registry).unwrap();
+    let pdf_document = lopdf::Document::load_mem(&pdf_document.save_to_bytes().unwrap()).unwrap();
+    let first_page_id = pdf_document.get_pages()[&1];
+    let content = pdf_document
+        .get_and_decode_page_content(first_page_id)
+        .unwrap();
+    let operators = content
+        .operations
+        .iter()
+        .map(|operation| operation.operator.as_str())
+        .collect::<Vec<_>>();
+    let fill_index = operators.iter().position(|operator| *operator == "f");
+    let text_index = operators.iter().position(|operator| *operator == "BT");
+    assert!(fill_index.unwrap() < text_index.unwrap());
+}
+
+/// Verifies that a page background can't be set before the first page, and that its color is checked.
+#[test]
+fn page_background_operation_before_first_page_is_rejected() {
+    let document = Document {
+        document_id: "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9".to_string(),
+        instance_id: "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj".to_string(),
+        operations: vec![
+            Operation::SetPageBackground {
+                color: [2.0, 0.0, 0.0],
+            },
+            Operation::AppendNewPage {
+                page_width: 210.0,
+                page_height: 297.0,
+            },
+        ],
+        page_background: None,
+        title: None,
+        author: None,
+        running_header: None,
+        timestamp: None,
+        deterministic: false,
+        strict: false,
+    };
+
+    let error = document.to_svg().unwrap_err();
+    assert_eq!(error.kind, ErrorKind::PageIndexOutOfRange);
+    assert_eq!(error.operation_index, Some(0));
+    assert_eq!(
+        document.validate(),
+        vec![
+            LintIssue::TextBeforeFirstPage { operation_index: 0 },
+            LintIssue::InvalidColor {
+                operation_index: 0,
+                color: [2.0, 0.0, 0.0]
+            },
+        ]
+    );
+}
@@ -0,0 +1,25 @@
+use textr::builder::{a4, DocumentBuilder};
+
+/// Verifies that the page numbering tokens are resolved against the page on which the text is written
+/// and the total number of pages of the document, wherever they appear in the text.
+#[test]
+fn page_numbering_tokens_are_resolved() {
+    let document = DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .page(a4())
+    .text("Page {page} of {pages}")
+    .at(20.0, 20.0)
+    .page(a4())
+    .text("Continued on {page}/{pages}, {unknown} is kept")
+    .at(20.0, 20.0)
+    .page(a4())
+    .build()
+    .unwrap();
+
+    assert_eq!(
+        document.to_plain_text().unwrap(),
+        "Page 1 of 3\n\u{c}Continued on 2/3, {unknown} is kept\n\u{c}\u{c}"
+    );
+}
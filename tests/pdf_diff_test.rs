@@ -0,0 +1,108 @@
+use textr::{
+    builder::{a4, DocumentBuilder},
+    document::Document,
+    font::FontRegistry,
+    testing::{diff_pdfs, PdfDifference},
+};
+
+/// Converts the given document into the bytes of a PDF file.
+fn pdf_bytes(document: &Document) -> Vec<u8> {
+    let font_registry = FontRegistry::builtin().unwrap();
+    let mut pdf_document = document.to_pdf_document_with_fonts(&font_registry).unwrap();
+    pdf_document.save_to_bytes().unwrap()
+}
+
+/// Creates a document with the given pages, each with a single piece of text.
+fn document_with_texts(texts: &[&str]) -> Document {
+    let mut builder = DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .page(a4())
+    .text(texts[0])
+    .at(20.0, 280.0);
+    for text in &texts[1..] {
+        builder = builder.page(a4()).text(*text).at(20.0, 280.0);
+    }
+
+    builder.build().unwrap()
+}
+
+/// Returns the path of the object a difference refers to.
+fn path(difference: &PdfDifference) -> &str {
+    match difference {
+        PdfDifference::Added { path, .. }
+        | PdfDifference::Removed { path, .. }
+        | PdfDifference::Changed { path, .. } => path,
+    }
+}
+
+/// Verifies that a document has no differences with itself, even once its objects have been renumbered.
+#[test]
+fn identical_documents_have_no_differences() {
+    let pdf_document_bytes = pdf_bytes(&document_with_texts(&["Hello"]));
+    let mut renumbered_pdf_document = lopdf::Document::load_mem(&pdf_document_bytes).unwrap();
+    renumbered_pdf_document.renumber_objects_with(100);
+    let mut renumbered_pdf_document_bytes = Vec::new();
+    renumbered_pdf_document
+        .save_to(&mut renumbered_pdf_document_bytes)
+        .unwrap();
+
+    assert!(diff_pdfs(&pdf_document_bytes, &pdf_document_bytes)
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        diff_pdfs(&pdf_document_bytes, &renumbered_pdf_document_bytes).unwrap(),
+        vec![]
+    );
+}
+
+/// Verifies that a different text is reported as a change of the content stream of the page.
+#[test]
+fn changed_text_is_reported_as_a_changed_content_stream() {
+    let differences = diff_pdfs(
+        &pdf_bytes(&document_with_texts(&["Hello"])),
+        &pdf_bytes(&document_with_texts(&["World"])),
+    )
+    .unwrap();
+
+    assert!(differences.iter().any(|difference| matches!(
+        difference,
+        PdfDifference::Changed { path, content_changed: true, .. } if path.ends_with("/Contents")
+    )));
+}
+
+/// Verifies that the objects of an additional page are reported as added, and that the page tree reports
+/// its changed entries with their old and new values.
+#[test]
+fn additional_page_is_reported_as_added_objects() {
+    let differences = diff_pdfs(
+        &pdf_bytes(&document_with_texts(&["Hello"])),
+        &pdf_bytes(&document_with_texts(&["Hello", "World"])),
+    )
+    .unwrap();
+
+    assert!(differences.iter().any(|difference| matches!(
+        difference,
+        PdfDifference::Added { path, .. } if path == "/Root/Pages/Kids[1]"
+    )));
+    let Some(PdfDifference::Changed { entries, .. }) = differences
+        .iter()
+        .find(|difference| path(difference) == "/Root/Pages")
+    else {
+        panic!("The page tree should have changed: {:#?}", differences);
+    };
+    let count_change = entries.iter().find(|entry| entry.key == "Count").unwrap();
+    assert_eq!(count_change.old.as_deref(), Some("1"));
+    assert_eq!(count_change.new.as_deref(), Some("2"));
+
+    let removed_differences = diff_pdfs(
+        &pdf_bytes(&document_with_texts(&["Hello", "World"])),
+        &pdf_bytes(&document_with_texts(&["Hello"])),
+    )
+    .unwrap();
+    assert!(removed_differences.iter().any(|difference| matches!(
+        difference,
+        PdfDifference::Removed { path, .. } if path == "/Root/Pages/Kids[1]"
+    )));
+}
@@ -0,0 +1,214 @@
+use lopdf::dictionary;
+use textr::{
+    builder::{a4, DocumentBuilder, PageSize},
+    document::{Document, Operation},
+    font::FontRegistry,
+};
+
+/// Converts the given document into the bytes of a PDF file.
+fn pdf_bytes(document: &Document) -> Vec<u8> {
+    let font_registry = FontRegistry::builtin().unwrap();
+    let mut pdf_document = document.to_pdf_document_with_fonts(&font_registry).unwrap();
+    pdf_document.save_to_bytes().unwrap()
+}
+
+/// Asserts that the two given numbers are equal, up to the rounding of the conversions between units.
+fn assert_close(value: f32, expected_value: f32) {
+    assert!(
+        (value - expected_value).abs() < 1e-2,
+        "{} is not close to {}",
+        value,
+        expected_value
+    );
+}
+
+/// Verifies that the page sizes and the runs of text of a converted document, with their positions, font sizes,
+/// fonts, colors and letter spacing, are recovered from its PDF file.
+#[test]
+fn converted_document_is_recovered() {
+    let document = DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .page(a4())
+    .text("Hello, world!")
+    .at(20.0, 270.0)
+    .font(15, 12.0)
+    .text("Café à la crème")
+    .at(20.0, 260.0)
+    .color([0.5, 0.25, 0.0])
+    .page(PageSize::new(100.0, 50.0))
+    .text("Page {page} of {pages}")
+    .at(10.0, 10.0)
+    .font(3, 8.0)
+    .build()
+    .unwrap();
+
+    let recovered_document = Document::from_pdf_bytes(&pdf_bytes(&document)).unwrap();
+    assert_eq!(recovered_document.document_id, document.document_id);
+    assert_eq!(recovered_document.instance_id, document.instance_id);
+    assert_eq!(recovered_document.operations.len(), 5);
+
+    let expected_texts = ["Hello, world!", "Café à la crème", "Page 2 of 2"];
+    let mut texts = expected_texts.iter();
+    for (operation, recovered_operation) in document
+        .operations
+        .iter()
+        .zip(&recovered_document.operations)
+    {
+        match (operation, recovered_operation) {
+            (
+                Operation::AppendNewPage {
+                    page_width,
+                    page_height,
+                },
+                Operation::AppendNewPage {
+                    page_width: recovered_page_width,
+                    page_height: recovered_page_height,
+                },
+            ) => {
+                assert_close(*recovered_page_width, *page_width);
+                assert_close(*recovered_page_height, *page_height);
+            }
+            (
+                Operation::WriteUnicodeText {
+                    color,
+                    position,
+                    font_size,
+                    font_index,
+                    ..
+                },
+                Operation::WriteUnicodeText {
+                    color: recovered_color,
+                    position: recovered_position,
+                    text_string: recovered_text_string,
+                    font_size: recovered_font_size,
+                    font_index: recovered_font_index,
+                    letter_spacing: recovered_letter_spacing,
+                },
+            ) => {
+                assert_eq!(recovered_text_string, texts.next().unwrap());
+                assert_eq!(recovered_font_index, font_index);
+                assert_close(*recovered_font_size, *font_size);
+                for (recovered_coordinate, coordinate) in recovered_position.iter().zip(position) {
+                    assert_close(*recovered_coordinate, *coordinate);
+                }
+                for (recovered_component, component) in recovered_color.iter().zip(color) {
+                    assert_close(*recovered_component, *component);
+                }
+                assert_eq!(*recovered_letter_spacing, 0.0);
+            }
+            _ => panic!(
+                "The operation {:?} has been recovered as {:?}",
+                operation, recovered_operation
+            ),
+        }
+    }
+}
+
+/// Verifies that the text written with letter spacing is recovered with it, and that the text of a PDF file
+/// which isn't produced by this crate, written with a simple font and positioned by a text matrix, is recovered.
+#[test]
+fn letter_spacing_and_simple_fonts_are_recovered() {
+    let document = DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .page(a4())
+    .text("Spaced")
+    .at(20.0, 280.0)
+    .letter_spacing(1.5)
+    .build()
+    .unwrap();
+    let recovered_document = Document::from_pdf_bytes(&pdf_bytes(&document)).unwrap();
+    let Operation::WriteUnicodeText { letter_spacing, .. } = recovered_document.operations[1]
+    else {
+        panic!("The text should have been recovered");
+    };
+    assert_close(letter_spacing, 1.5);
+
+    let mut inner_document = lopdf::Document::with_version("1.5");
+    let pages_id = inner_document.new_object_id();
+    let font_id = inner_document.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => "Helvetica",
+    });
+    let content = lopdf::content::Content {
+        operations: vec![
+            lopdf::content::Operation::new("BT", vec![]),
+            lopdf::content::Operation::new("Tf", vec!["F1".into(), 1.into()]),
+            lopdf::content::Operation::new(
+                "Tm",
+                vec![
+                    12.into(),
+                    0.into(),
+                    0.into(),
+                    12.into(),
+                    72.into(),
+                    144.into(),
+                ],
+            ),
+            lopdf::content::Operation::new(
+                "TJ",
+                vec![lopdf::Object::Array(vec![
+                    lopdf::Object::string_literal("Hello,"),
+                    (-300).into(),
+                    lopdf::Object::string_literal("w"),
+                    (-20).into(),
+                    lopdf::Object::string_literal("orld"),
+                ])],
+            ),
+            lopdf::content::Operation::new("ET", vec![]),
+        ],
+    };
+    let content_id = inner_document.add_object(lopdf::Stream::new(
+        dictionary! {},
+        content.encode().unwrap(),
+    ));
+    let page_id = inner_document.add_object(dictionary! {
+        "Type" => "Page",
+        "Parent" => pages_id,
+        "Contents" => content_id,
+        "Resources" => dictionary! { "Font" => dictionary! { "F1" => font_id } },
+    });
+    inner_document.objects.insert(
+        pages_id,
+        lopdf::Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Kids" => vec![page_id.into()],
+            "Count" => 1,
+            "MediaBox" => vec![0.into(), 0.into(), 612.into(), 792.into()],
+        }),
+    );
+    let catalog_id = inner_document.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+    });
+    inner_document.trailer.set("Root", catalog_id);
+    let mut simple_pdf_bytes = Vec::new();
+    inner_document.save_to(&mut simple_pdf_bytes).unwrap();
+
+    let recovered_document = Document::from_pdf_bytes(&simple_pdf_bytes).unwrap();
+    assert_eq!(recovered_document.document_id, "");
+    let [Operation::AppendNewPage {
+        page_width,
+        page_height,
+    }, Operation::WriteUnicodeText {
+        position,
+        text_string,
+        font_size,
+        font_index,
+        ..
+    }] = &recovered_document.operations[..]
+    else {
+        panic!("Unexpected operations {:?}", recovered_document.operations);
+    };
+    assert_close(*page_width, 215.9);
+    assert_close(*page_height, 279.4);
+    assert_eq!(text_string, "Hello, world");
+    assert_close(*font_size, 12.0);
+    assert_eq!(*font_index, 1);
+    assert_close(position[0], 25.4);
+    assert_close(position[1], 50.8);
+}
@@ -0,0 +1,49 @@
+use textr::{
+    builder::{a4, DocumentBuilder, PageSize},
+    pdf_info::PdfInfo,
+};
+
+/// Verifies that reading back a PDF document reports its identifiers, pages, layers and embedded fonts.
+#[test]
+fn pdf_info_reads_back_pdf_document() {
+    let document = DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .page(a4())
+    .text("Hello, world!")
+    .at(20.0, 270.0)
+    .font(15, 12.0)
+    .page(PageSize::new(100.0, 50.0))
+    .build()
+    .unwrap();
+
+    let mut pdf_document = document.to_pdf_document().unwrap();
+    pdf_document.optimize();
+    let pdf_info = PdfInfo::from_bytes(&pdf_document.save_to_bytes().unwrap()).unwrap();
+
+    assert_eq!(
+        pdf_info.identifiers,
+        [
+            "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+            "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj"
+        ]
+    );
+    assert!(pdf_info.metadata.contains(&(
+        "Identifier".to_string(),
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9".to_string()
+    )));
+    assert_eq!(pdf_info.pages.len(), 2);
+    assert!((pdf_info.pages[0].width - 210.0).abs() < 0.1);
+    assert!((pdf_info.pages[0].height - 297.0).abs() < 0.1);
+    assert!((pdf_info.pages[1].width - 100.0).abs() < 0.1);
+    assert_eq!(pdf_info.layers.len(), 2);
+    assert_eq!(pdf_info.pages[0].layers.len(), 1);
+
+    // Only the font with which the text has been written is embedded
+    assert_eq!(pdf_info.fonts.len(), 1);
+    assert!(pdf_info
+        .fonts
+        .iter()
+        .all(|font| font.subtype == "Type0" && font.embedded_size.unwrap_or(0) > 0));
+}
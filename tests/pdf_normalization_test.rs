@@ -0,0 +1,62 @@
+use lopdf::Object;
+use textr::{
+    builder::{a4, DocumentBuilder},
+    document::Document,
+    font::FontRegistry,
+    testing::{compare_pdfs, normalize_pdf},
+};
+
+/// Converts the given document into the bytes of a PDF file.
+fn pdf_bytes(document: &Document) -> Vec<u8> {
+    let font_registry = FontRegistry::builtin().unwrap();
+    let mut pdf_document = document.to_pdf_document_with_fonts(&font_registry).unwrap();
+    pdf_document.save_to_bytes().unwrap()
+}
+
+/// Creates a document with a single piece of text.
+fn document_with_text(text: &str) -> Document {
+    DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .page(a4())
+    .text(text)
+    .at(20.0, 280.0)
+    .build()
+    .unwrap()
+}
+
+/// Verifies that the documents which only differ by their dates and by the numbering of their objects
+/// are equivalent, while the ones with a different content are not.
+#[test]
+fn normalized_documents_ignore_dates_and_object_numbers() {
+    let pdf_document_bytes = pdf_bytes(&document_with_text("Hello"));
+
+    let mut other_pdf_document = lopdf::Document::load_mem(&pdf_document_bytes).unwrap();
+    let info_id = other_pdf_document
+        .trailer
+        .get(b"Info")
+        .and_then(Object::as_reference)
+        .unwrap();
+    other_pdf_document
+        .get_dictionary_mut(info_id)
+        .unwrap()
+        .set("CreationDate", Object::string_literal("D:20240101000000Z"));
+    other_pdf_document.renumber_objects_with(100);
+    let mut other_pdf_document_bytes = Vec::new();
+    other_pdf_document
+        .save_to(&mut other_pdf_document_bytes)
+        .unwrap();
+    assert_ne!(pdf_document_bytes, other_pdf_document_bytes);
+
+    compare_pdfs(&pdf_document_bytes, &other_pdf_document_bytes).unwrap();
+    assert_eq!(
+        normalize_pdf(&pdf_document_bytes).unwrap(),
+        normalize_pdf(&other_pdf_document_bytes).unwrap()
+    );
+    assert!(compare_pdfs(
+        &pdf_document_bytes,
+        &pdf_bytes(&document_with_text("World"))
+    )
+    .is_err());
+}
@@ -0,0 +1,65 @@
+use textr::{
+    builder::{a4, DocumentBuilder, PageSize},
+    font::FontRegistry,
+    pdf::{PdfCanvas, PdfDocument},
+};
+
+/// Verifies that the summary of a PDF document reports its pages, the operations of their layers and the fonts
+/// used by them before the document is written, and that it can be serialized.
+#[test]
+fn summary_reports_pages_layers_and_fonts() {
+    let document = DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .page(a4())
+    .text("Hello, world!")
+    .at(20.0, 270.0)
+    .font(15, 12.0)
+    .text("Hello, again!")
+    .at(20.0, 260.0)
+    .font(15, 12.0)
+    .page(PageSize::new(100.0, 50.0))
+    .build()
+    .unwrap();
+
+    let font_registry = FontRegistry::builtin().unwrap();
+    let mut pdf_document = PdfDocument::new(document.document_id.clone());
+    for font_bytes in font_registry.font_bytes() {
+        pdf_document
+            .add_font_from_bytes(font_bytes.clone())
+            .unwrap();
+    }
+    document
+        .draw(&mut PdfCanvas::new(&mut pdf_document))
+        .unwrap();
+
+    let summary = pdf_document.summary();
+    assert_eq!(summary.identifier, document.document_id);
+    assert_eq!(summary.pages.len(), 2);
+    assert!((summary.pages[1].width - 100.0).abs() < 1e-3);
+    assert!((summary.pages[1].height - 50.0).abs() < 1e-3);
+    assert_eq!(summary.pages[0].used_fonts, vec!["F15".to_string()]);
+    assert_eq!(summary.pages[0].stats.text_run_count, 2);
+    assert!(summary.pages[1].used_fonts.is_empty());
+    let layer = &summary.pages[0].layers[0];
+    assert_eq!(layer.operator_counts["BT"], 2);
+    assert_eq!(
+        layer.operation_count,
+        layer.operator_counts.values().sum::<usize>()
+    );
+    assert_eq!(summary.fonts.len(), font_registry.font_bytes().len());
+    for (font_index, font) in summary.fonts.iter().enumerate() {
+        assert_eq!(font.identifier, format!("F{font_index}"));
+        assert_eq!(font.data_size, font_registry.font_bytes()[font_index].len());
+        assert_eq!(font.is_used, font_index == 15);
+    }
+    assert_eq!(summary.stats, pdf_document.stats());
+
+    let summary_json = serde_json::to_value(&summary).unwrap();
+    assert_eq!(summary_json["pages"][0]["usedFonts"][0], "F15");
+    assert_eq!(
+        summary_json["pages"][0]["layers"][0]["operatorCounts"]["BT"],
+        2
+    );
+}
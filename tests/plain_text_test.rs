@@ -0,0 +1,28 @@
+use textr::builder::{a4, DocumentBuilder};
+
+/// Verifies that the plain-text export orders the text from the top to the bottom of each page
+/// and from left to right, joining the pieces of text which share the same line.
+#[test]
+fn plain_text_export_follows_reading_order() {
+    let document = DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .page(a4())
+    .text("world")
+    .at(60.0, 250.0)
+    .text("Second line")
+    .at(20.0, 240.0)
+    .text("Hello")
+    .at(20.0, 250.5)
+    .page(a4())
+    .text("Next page")
+    .at(20.0, 250.0)
+    .build()
+    .unwrap();
+
+    assert_eq!(
+        document.to_plain_text().unwrap(),
+        "Hello world\nSecond line\n\u{c}Next page\n\u{c}"
+    );
+}
@@ -0,0 +1,113 @@
+#![cfg(feature = "interop-printpdf")]
+
+use printpdf::{Mm, PdfDocumentReference};
+use textr::{
+    builder::{a4, a5, DocumentBuilder},
+    canvas::{Canvas as _, FilledPath},
+    document::Document,
+    error::ErrorKind,
+    font::FontRegistry,
+    pdf_info::PdfInfo,
+    printpdf_interop::PrintpdfCanvas,
+};
+
+/// Creates a document with two pages of different sizes and a piece of text on each of them.
+fn document_with_two_pages() -> Document {
+    DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .title("Annual report")
+    .page_background([0.9, 0.9, 0.8])
+    .deterministic()
+    .page(a4())
+    .text("Hello, world!")
+    .at(20.0, 270.0)
+    .font(15, 12.0)
+    .page(a5())
+    .text("Second page")
+    .at(20.0, 180.0)
+    .font(15, 12.0)
+    .color([1.0, 0.0, 0.0])
+    .build()
+    .unwrap()
+}
+
+/// Verifies that a document drawn onto a printpdf document keeps its pages, its text and its title, and that it
+/// is recovered when the printpdf document is converted back.
+#[test]
+fn document_is_converted_into_printpdf_and_back() {
+    let document = document_with_two_pages();
+    let font_registry = FontRegistry::builtin().unwrap();
+
+    let printpdf_document = document.to_printpdf_document(&font_registry).unwrap();
+    let pdf_info = PdfInfo::from_bytes(&printpdf_document.save_to_bytes().unwrap()).unwrap();
+    let page_sizes = pdf_info
+        .pages
+        .iter()
+        .map(|page| [page.width.round(), page.height.round()])
+        .collect::<Vec<_>>();
+    assert_eq!(page_sizes, vec![[210.0, 297.0], [148.0, 210.0]]);
+    assert!(pdf_info
+        .metadata
+        .contains(&("Title".to_string(), "Annual report".to_string())));
+
+    let printpdf_document = PdfDocumentReference::try_from(&document).unwrap();
+    let recovered_document = Document::try_from(printpdf_document).unwrap();
+    assert_eq!(recovered_document.operations.len(), 4);
+    let plain_text = recovered_document.to_plain_text().unwrap();
+    assert!(plain_text.contains("Hello, world!"));
+    assert!(plain_text.contains("Second page"));
+}
+
+/// Verifies that a document can be drawn onto the pages of an existing printpdf document, whose code can then keep
+/// writing onto them with the fonts of the document.
+#[test]
+fn document_is_drawn_onto_an_existing_printpdf_document() {
+    let (printpdf_document, _, _) =
+        printpdf::PdfDocument::new("Mixed report", Mm(210.0), Mm(297.0), "Cover");
+    let font_registry = FontRegistry::builtin().unwrap();
+
+    let mut printpdf_canvas = PrintpdfCanvas::new(&printpdf_document, &font_registry);
+    document_with_two_pages()
+        .draw(&mut printpdf_canvas)
+        .unwrap();
+    let &[_, (page_index, layer_index)] = printpdf_canvas.pages() else {
+        panic!("Expected two pages to be begun");
+    };
+    let font = printpdf_canvas.font(15).unwrap();
+    printpdf_document
+        .get_page(page_index)
+        .get_layer(layer_index)
+        .use_text("Written by printpdf", 12.0, Mm(20.0), Mm(20.0), &font);
+
+    let pdf_bytes = printpdf_document.save_to_bytes().unwrap();
+    assert_eq!(PdfInfo::from_bytes(&pdf_bytes).unwrap().pages.len(), 3);
+    let plain_text = Document::from_pdf_bytes(&pdf_bytes)
+        .unwrap()
+        .to_plain_text()
+        .unwrap();
+    assert!(plain_text.contains("Written by printpdf"));
+}
+
+/// Verifies that the fonts missing from the registry and the paths drawn before any page are reported with the
+/// same kinds of errors as in the conversion into the PDF documents of this crate.
+#[test]
+fn printpdf_canvas_reports_its_errors() {
+    let printpdf_document = printpdf::PdfDocument::empty("Empty");
+    let font_registry = FontRegistry::builtin().unwrap();
+    let mut printpdf_canvas = PrintpdfCanvas::new(&printpdf_document, &font_registry);
+
+    assert_eq!(
+        printpdf_canvas.font(1000).unwrap_err().kind,
+        ErrorKind::FontIndexOutOfRange
+    );
+    let error = printpdf_canvas
+        .fill_path(&FilledPath::rectangle(
+            [0.0, 0.0],
+            [10.0, 10.0],
+            [0.0, 0.0, 0.0],
+        ))
+        .unwrap_err();
+    assert_eq!(error.kind, ErrorKind::PageIndexOutOfRange);
+}
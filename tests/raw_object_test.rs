@@ -0,0 +1,61 @@
+use lopdf::{content::Operation, Dictionary, Object};
+use textr::{error::ErrorKind, pdf::PdfDocument};
+
+/// Verifies that a raw object registered as a resource of a page is referred to by the resource dictionary of
+/// the page once it is saved, and that the categories managed by the document can't be registered.
+#[test]
+fn registered_resources_are_saved_with_the_page() {
+    let mut pdf_document = PdfDocument::new("5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9".to_string());
+    let (page_index, layer_index) = pdf_document.add_page_with_layer(210.0, 297.0);
+
+    let graphics_state_id = pdf_document.add_raw_object(Dictionary::from_iter(vec![
+        ("Type", Object::Name(b"ExtGState".to_vec())),
+        ("CA", Object::Real(0.5)),
+    ]));
+    pdf_document
+        .register_page_resource(page_index, "ExtGState", "GS0", graphics_state_id)
+        .unwrap();
+    pdf_document
+        .add_raw_operations_to_layer_in_page(
+            page_index,
+            layer_index,
+            vec![Operation::new("gs", vec![Object::Name(b"GS0".to_vec())])],
+        )
+        .unwrap();
+    assert_eq!(
+        pdf_document
+            .register_page_resource(page_index, "Font", "F0", graphics_state_id)
+            .unwrap_err()
+            .kind,
+        ErrorKind::InvalidDocument
+    );
+    assert_eq!(
+        pdf_document
+            .register_page_resource(1, "ExtGState", "GS1", graphics_state_id)
+            .unwrap_err()
+            .kind,
+        ErrorKind::PageIndexOutOfRange
+    );
+
+    pdf_document
+        .write_all("hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj".to_string())
+        .unwrap();
+    let saved_pdf_document =
+        lopdf::Document::load_mem(&pdf_document.save_to_bytes().unwrap()).unwrap();
+    let page_id = saved_pdf_document.page_iter().next().unwrap();
+    let graphics_state = saved_pdf_document
+        .get_dictionary(page_id)
+        .and_then(|page| page.get(b"Resources"))
+        .and_then(Object::as_reference)
+        .and_then(|resources_id| saved_pdf_document.get_dictionary(resources_id))
+        .and_then(|resources| resources.get(b"ExtGState"))
+        .and_then(Object::as_dict)
+        .and_then(|graphics_states| graphics_states.get(b"GS0"))
+        .and_then(Object::as_reference)
+        .and_then(|graphics_state_id| saved_pdf_document.get_dictionary(graphics_state_id))
+        .unwrap();
+    assert_eq!(graphics_state.get(b"CA").unwrap().as_float().unwrap(), 0.5);
+
+    let content = saved_pdf_document.get_page_content(page_id).unwrap();
+    assert!(String::from_utf8_lossy(&content).contains("/GS0 gs"));
+}
@@ -0,0 +1,164 @@
+use textr::{
+    builder::{a4, a5, DocumentBuilder},
+    document::{builtin_font_paths, Document, Operation},
+    font::FontRegistry,
+    header::RunningHeader,
+    pdf_info::PdfInfo,
+};
+
+/// Creates a document with two pages, the given title and a running header with a date.
+fn document_with_running_header(title: &str) -> Document {
+    DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .title(title)
+    .author("Jane Doe")
+    .running_header(RunningHeader {
+        date: Some("16 October 2026".to_string()),
+        ..RunningHeader::new(15, 9.0)
+    })
+    .page(a4())
+    .text("Hello, world!")
+    .at(20.0, 250.0)
+    .font(15, 12.0)
+    .page(a5())
+    .build()
+    .unwrap()
+}
+
+/// Extracts the horizontal position of the SVG text element with the given content.
+fn text_x(svg: &str, text: &str) -> f32 {
+    let end = svg.find(&format!(">{}</text>", text)).unwrap();
+    let start = svg[..end].rfind(" x=\"").unwrap() + 4;
+    svg[start..].split('"').next().unwrap().parse().unwrap()
+}
+
+/// Verifies that the title and the date are written at the top of every page, with the date aligned to the right.
+#[test]
+fn running_header_is_written_on_every_page() {
+    let document = document_with_running_header("Annual report");
+
+    let svg = document.to_svg().unwrap();
+    assert_eq!(svg.matches(">Annual report</text>").count(), 2);
+    assert_eq!(svg.matches(">16 October 2026</text>").count(), 2);
+    assert_eq!(text_x(&svg, "Annual report"), 10.0);
+    // The date ends at the right margin, so it begins well after the middle of the page
+    assert!(text_x(&svg, "16 October 2026") > 150.0);
+
+    let plain_text = document.to_plain_text().unwrap();
+    assert!(plain_text.contains("Annual report"));
+}
+
+/// Verifies that no header is written when the document has no title, even though a running header is set.
+#[test]
+fn running_header_needs_a_title() {
+    let mut document = document_with_running_header("Annual report");
+    document.title = None;
+
+    let svg = document.to_svg().unwrap();
+    assert!(!svg.contains("16 October 2026"));
+}
+
+/// Verifies that the title and the author are written into the metadata of the PDF document, also when they
+/// are not ASCII, and that they are recovered when the PDF document is imported.
+#[test]
+fn title_and_author_are_written_into_the_metadata() {
+    let document = document_with_running_header("Rapport annuel — été");
+
+    let mut pdf_document = document.to_pdf_document().unwrap();
+    let pdf_bytes = pdf_document.save_to_bytes().unwrap();
+    let pdf_info = PdfInfo::from_bytes(&pdf_bytes).unwrap();
+    assert!(pdf_info
+        .metadata
+        .contains(&("Title".to_string(), "Rapport annuel — été".to_string())));
+    assert!(pdf_info
+        .metadata
+        .contains(&("Author".to_string(), "Jane Doe".to_string())));
+
+    let imported_document = Document::from_pdf_bytes(&pdf_bytes).unwrap();
+    assert_eq!(
+        imported_document.title.as_deref(),
+        Some("Rapport annuel — été")
+    );
+    assert_eq!(imported_document.author.as_deref(), Some("Jane Doe"));
+}
+
+/// Verifies that the running header is parsed with its default margin, and that a document without a title
+/// keeps "Unknown" as its metadata.
+#[test]
+fn running_header_is_parsed_with_its_default_margin() {
+    let document = Document::from_json_bytes(
+        br#"{
+            "documentId": "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+            "instanceId": "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+            "title": "Annual report",
+            "runningHeader": { "fontIndex": 15, "fontSize": 9.0 },
+            "operations": [
+                { "pageWidth": 210.0, "pageHeight": 297.0 }
+            ]
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(document.running_header, Some(RunningHeader::new(15, 9.0)));
+    assert_eq!(document.author, None);
+
+    let mut pdf_document = document.to_pdf_document().unwrap();
+    let pdf_info = PdfInfo::from_bytes(&pdf_document.save_to_bytes().unwrap()).unwrap();
+    assert!(pdf_info
+        .metadata
+        .contains(&("Author".to_string(), "Unknown".to_string())));
+}
+
+/// Verifies that the date of the timestamp of the document is written when the running header has no date.
+#[test]
+fn running_header_defaults_to_the_date_of_the_timestamp() {
+    let mut document = document_with_running_header("Annual report");
+    document.running_header = Some(RunningHeader::new(15, 9.0));
+    document.timestamp = Some(1_760_617_800);
+
+    let svg = document.to_svg().unwrap();
+    assert_eq!(svg.matches(">16 October 2025</text>").count(), 2);
+
+    document.timestamp = None;
+    let svg = document.to_svg().unwrap();
+    assert!(!svg.contains("2025"));
+}
+
+/// Verifies that the date is aligned to the right with the fonts of the registry with which the document is
+/// converted, rather than with the built-in fonts of the same index.
+#[test]
+fn running_header_is_aligned_with_the_fonts_of_the_registry() {
+    let date_x = |document: &Document, font_registry: &FontRegistry| {
+        let pdf_bytes = document
+            .to_pdf_document_with_fonts(font_registry)
+            .unwrap()
+            .save_to_bytes()
+            .unwrap();
+        Document::from_pdf_bytes(&pdf_bytes)
+            .unwrap()
+            .operations
+            .iter()
+            .find_map(|operation| match operation {
+                Operation::WriteUnicodeText {
+                    text_string,
+                    position,
+                    ..
+                } if text_string == "16 October 2026" => Some(position[0]),
+                _ => None,
+            })
+            .unwrap()
+    };
+
+    // The only font of the registry is the built-in font with index 15, which the header refers to by index 0
+    let font_registry =
+        FontRegistry::from_paths(vec![builtin_font_paths().unwrap()[15].clone()]).unwrap();
+    let mut document = document_with_running_header("Annual report");
+    document.operations.truncate(1);
+    let expected_date_x = date_x(&document, &FontRegistry::builtin().unwrap());
+    if let Some(running_header) = &mut document.running_header {
+        running_header.font_index = 0;
+    }
+
+    assert!((date_x(&document, &font_registry) - expected_date_x).abs() < 1e-2);
+}
@@ -0,0 +1,36 @@
+use textr::builder::{a4, DocumentBuilder};
+
+/// Verifies that saving PDF documents into a buffer which is reused produces the same bytes as saving them anew.
+#[test]
+fn save_to_buffer_reuses_the_buffer() {
+    let document = DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .deterministic()
+    .page(a4())
+    .text("Hello, world!")
+    .at(20.0, 270.0)
+    .font(15, 12.0)
+    .page(a4())
+    .build()
+    .unwrap();
+
+    let pdf_document_bytes = document.to_pdf_document().unwrap().save_to_bytes().unwrap();
+
+    // The previous content of the buffer is discarded, while the same buffer serves many documents
+    let mut buffer = b"leftover content".to_vec();
+    for _ in 0..2 {
+        let mut pdf_document = document.to_pdf_document().unwrap();
+        pdf_document.save_to_buffer(&mut buffer).unwrap();
+        assert_eq!(buffer, pdf_document_bytes);
+    }
+
+    // Both layers are enclosed in their own marked-content sequence
+    let pdf_document = lopdf::Document::load_mem(&buffer).unwrap();
+    for page_id in pdf_document.get_pages().into_values() {
+        let content = pdf_document.get_page_content(page_id).unwrap();
+        assert!(content.starts_with(b"/OC /"));
+        assert!(content.ends_with(b"Q\nEMC"));
+    }
+}
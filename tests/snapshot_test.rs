@@ -0,0 +1,70 @@
+use textr::{
+    assert_document_snapshot,
+    builder::{a4, DocumentBuilder},
+    document::Document,
+    font::FontRegistry,
+    pdf::{PdfCanvas, PdfDocument},
+    testing::assert_snapshot,
+};
+
+/// Creates a document with two pieces of text on a single page.
+fn document() -> Document {
+    DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .page(a4())
+    .text("Hello, world!")
+    .at(20.0, 270.0)
+    .font(15, 12.0)
+    .text("Hello, again!")
+    .at(20.0, 260.0)
+    .color([0.5, 0.0, 0.0])
+    .build()
+    .unwrap()
+}
+
+/// Verifies that a document and the summary of the PDF document it is converted into match their snapshots.
+#[test]
+fn document_and_summary_match_their_snapshots() {
+    let document = document();
+    assert_document_snapshot!("two_texts_document", &document);
+
+    let font_registry = FontRegistry::builtin().unwrap();
+    let mut pdf_document = PdfDocument::new(document.document_id.clone());
+    for font_bytes in font_registry.font_bytes() {
+        pdf_document
+            .add_font_from_bytes(font_bytes.clone())
+            .unwrap();
+    }
+    document
+        .draw(&mut PdfCanvas::new(&mut pdf_document))
+        .unwrap();
+    assert_document_snapshot!("two_texts_summary", &pdf_document.summary());
+}
+
+/// Verifies that a missing snapshot is written, and that a value which differs from it makes the assertion fail.
+#[test]
+fn snapshot_is_written_and_then_compared() {
+    let snapshot_path = std::env::temp_dir().join(format!(
+        "textr_snapshot_test_{}/document.json",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&snapshot_path);
+
+    let document = document();
+    assert_snapshot(&snapshot_path, &document);
+    assert_eq!(
+        serde_json::from_str::<Document>(&std::fs::read_to_string(&snapshot_path).unwrap())
+            .unwrap(),
+        document
+    );
+    assert_snapshot(&snapshot_path, &document);
+
+    let mut changed_document = document.clone();
+    changed_document.operations.pop();
+    let result = std::panic::catch_unwind(|| assert_snapshot(&snapshot_path, &changed_document));
+    assert!(result.is_err());
+
+    std::fs::remove_dir_all(snapshot_path.parent().unwrap()).unwrap();
+}
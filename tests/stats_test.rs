@@ -0,0 +1,36 @@
+use textr::{
+    builder::{a4, DocumentBuilder},
+    font::FontRegistry,
+};
+
+/// Verifies that the statistics of a conversion count the pages, the text and the embedded fonts.
+#[test]
+fn conversion_stats_describe_the_output() {
+    let document = DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .page(a4())
+    .text("Hello, world!")
+    .at(20.0, 270.0)
+    .font(15, 12.0)
+    .page(a4())
+    .text("Hello, 世界!")
+    .at(20.0, 270.0)
+    .font(15, 12.0)
+    .build()
+    .unwrap();
+
+    let font_registry = FontRegistry::builtin().unwrap();
+    let stats = document
+        .to_pdf_document_with_fonts(&font_registry)
+        .unwrap()
+        .stats();
+    assert_eq!(stats.page_count, 2);
+    assert_eq!(stats.text_run_count, 2);
+    assert_eq!(stats.emitted_glyph_count, 13 + 8);
+    assert_eq!(stats.dropped_glyph_count, 2);
+    // Only the font with which the text has been written is embedded
+    assert_eq!(stats.embedded_font_count, 1);
+    assert_eq!(stats.font_data_size, font_registry.font_bytes()[15].len());
+}
@@ -0,0 +1,66 @@
+use textr::{
+    builder::{a4, DocumentBuilder, PageSize},
+    font::FontRegistry,
+    pdf_info::PdfInfo,
+};
+
+/// Verifies that a document written page by page has the same pages, layers and fonts as the same document
+/// converted in memory, and that its cross-reference table points at its objects.
+#[test]
+fn streamed_pdf_document_matches_in_memory_one() {
+    let document = DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .page(a4())
+    .page_background([0.9, 0.9, 1.0])
+    .text("Page {page} of {pages} (with parentheses)")
+    .at(20.0, 270.0)
+    .font(15, 12.0)
+    .page(PageSize::new(100.0, 50.0))
+    .text("Hello, 世界!")
+    .at(10.0, 40.0)
+    .font(3, 10.0)
+    .page(a4())
+    .build()
+    .unwrap();
+
+    let font_registry = FontRegistry::builtin().unwrap();
+    let (pdf_document_bytes, warnings) =
+        document.stream_to_pdf(&font_registry, Vec::new()).unwrap();
+    assert_eq!(warnings.len(), 1);
+
+    let streamed_pdf_info = PdfInfo::from_bytes(&pdf_document_bytes).unwrap();
+    let mut pdf_document = document.to_pdf_document_with_fonts(&font_registry).unwrap();
+    let pdf_info = PdfInfo::from_bytes(&pdf_document.save_to_bytes().unwrap()).unwrap();
+    assert_eq!(streamed_pdf_info.identifiers, pdf_info.identifiers);
+    assert_eq!(streamed_pdf_info.metadata, pdf_info.metadata);
+    assert_eq!(streamed_pdf_info.pages, pdf_info.pages);
+    assert_eq!(streamed_pdf_info.layers, pdf_info.layers);
+    assert_eq!(streamed_pdf_info.fonts.len(), 2);
+
+    // Each entry of the cross-reference table in use points at the beginning of its object
+    let pdf_document_text = String::from_utf8_lossy(&pdf_document_bytes);
+    let cross_reference_offset = pdf_document_text
+        .rsplit("startxref\n")
+        .next()
+        .and_then(|tail| tail.lines().next())
+        .unwrap()
+        .parse::<usize>()
+        .unwrap();
+    let cross_reference_table = &pdf_document_bytes[cross_reference_offset..];
+    assert!(cross_reference_table.starts_with(b"xref\n0 "));
+    let entries = String::from_utf8_lossy(cross_reference_table)
+        .lines()
+        .skip(2)
+        .take_while(|line| *line != "trailer")
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    for (object_number, entry) in entries.iter().enumerate().skip(1) {
+        if let Some(offset) = entry.strip_suffix(" 00000 n ") {
+            let offset = offset.parse::<usize>().unwrap();
+            let object_header = format!("{} 0 obj\n", object_number);
+            assert!(pdf_document_bytes[offset..].starts_with(object_header.as_bytes()));
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use lopdf::{content::Content, Object};
+use textr::{error::ErrorKind, font::FontRegistry, pdf::PdfDocument};
+
+/// Draws the given SVG image onto a new page of a document with the built-in fonts, and returns the operators
+/// of the content of the page together with the number of images of the saved document.
+fn save_with_svg(svg: &str) -> (Vec<String>, usize) {
+    let font_registry = FontRegistry::builtin().unwrap();
+    let mut pdf_document = PdfDocument::new("5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9".to_string());
+    for font_bytes in font_registry.font_bytes() {
+        pdf_document
+            .add_font_from_bytes(font_bytes.clone())
+            .unwrap();
+    }
+    let (page_index, layer_index) = pdf_document.add_page_with_layer(210.0, 297.0);
+    pdf_document
+        .add_svg_to_layer_in_page(
+            page_index,
+            layer_index,
+            svg.as_bytes(),
+            [20.0, 20.0],
+            [40.0, 20.0],
+            72.0,
+        )
+        .unwrap();
+    pdf_document
+        .write_all("hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj".to_string())
+        .unwrap();
+
+    let saved_pdf_document =
+        lopdf::Document::load_mem(&pdf_document.save_to_bytes().unwrap()).unwrap();
+    let page_id = saved_pdf_document.page_iter().next().unwrap();
+    let content = Content::decode(&saved_pdf_document.get_page_content(page_id).unwrap()).unwrap();
+    let image_count = saved_pdf_document
+        .objects
+        .values()
+        .filter_map(|object| object.as_stream().ok())
+        .filter(|stream| {
+            stream
+                .dict
+                .get(b"Subtype")
+                .and_then(Object::as_name_str)
+                .ok()
+                == Some("Image")
+        })
+        .count();
+
+    (
+        content
+            .operations
+            .into_iter()
+            .map(|operation| operation.operator)
+            .collect(),
+        image_count,
+    )
+}
+
+/// Verifies that the shapes painted with plain colors are converted into PDF paths.
+#[test]
+fn plain_shapes_are_converted_into_paths() {
+    let (operators, image_count) = save_with_svg(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="40" height="20">
+            <rect x="1" y="1" width="18" height="18" fill="#ff0000" stroke="#000000" stroke-width="2"/>
+            <circle cx="30" cy="10" r="8" fill="#0000ff"/>
+        </svg>"##,
+    );
+    assert_eq!(image_count, 0);
+    assert!(operators.contains(&"B".to_string()));
+    assert!(operators.contains(&"f".to_string()));
+    assert!(operators.contains(&"c".to_string()));
+}
+
+/// Verifies that the text is converted into paths with the fonts of the document.
+#[test]
+fn text_is_converted_into_paths() {
+    let (operators, image_count) = save_with_svg(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="40" height="20">
+            <text x="2" y="15" font-size="12" fill="#000000">Logo</text>
+        </svg>"##,
+    );
+    assert_eq!(image_count, 0);
+    assert!(operators.contains(&"f".to_string()));
+}
+
+/// Verifies that the images which use gradients are rasterized instead.
+#[test]
+fn gradients_are_rasterized() {
+    let (operators, image_count) = save_with_svg(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="40" height="20">
+            <linearGradient id="gradient"><stop offset="0" stop-color="#ff0000"/><stop offset="1" stop-color="#0000ff"/></linearGradient>
+            <rect width="40" height="20" fill="url(#gradient)"/>
+        </svg>"##,
+    );
+    // The rasterized image is transparent around the shape, so it comes with a soft mask
+    assert_eq!(image_count, 2);
+    assert!(operators.contains(&"Do".to_string()));
+}
+
+/// Verifies that the files which are not SVG images are rejected.
+#[test]
+fn invalid_svg_images_are_rejected() {
+    let mut pdf_document = PdfDocument::new("5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9".to_string());
+    let (page_index, layer_index) = pdf_document.add_page_with_layer(210.0, 297.0);
+    let error = pdf_document
+        .add_svg_to_layer_in_page(
+            page_index,
+            layer_index,
+            b"not an image",
+            [0.0, 0.0],
+            [10.0, 10.0],
+            72.0,
+        )
+        .unwrap_err();
+    assert_eq!(error.kind, ErrorKind::InvalidImage);
+}
+
+/// Draws the given SVG image onto a new page of the given document, and returns the number of filled paths of the
+/// content of the page.
+fn fill_count_with_svg(pdf_document: &mut PdfDocument, svg: &str) -> usize {
+    let (page_index, layer_index) = pdf_document.add_page_with_layer(210.0, 297.0);
+    pdf_document
+        .add_svg_to_layer_in_page(
+            page_index,
+            layer_index,
+            svg.as_bytes(),
+            [20.0, 20.0],
+            [40.0, 20.0],
+            72.0,
+        )
+        .unwrap();
+
+    pdf_document.summary().pages[page_index].layers[layer_index]
+        .operator_counts
+        .get("f")
+        .copied()
+        .unwrap_or_default()
+}
+
+/// Verifies that the fonts added after an SVG image has been drawn are used for the text of the following ones.
+#[test]
+fn fonts_added_after_an_svg_image_are_used() {
+    let svg = r##"<svg xmlns="http://www.w3.org/2000/svg" width="40" height="20">
+            <text x="2" y="15" font-size="12" fill="#000000">Logo</text>
+        </svg>"##;
+    let mut pdf_document = PdfDocument::new("5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9".to_string());
+    assert_eq!(fill_count_with_svg(&mut pdf_document, svg), 0);
+
+    for font_bytes in FontRegistry::builtin().unwrap().font_bytes() {
+        pdf_document
+            .add_font_from_bytes(font_bytes.clone())
+            .unwrap();
+    }
+    assert!(fill_count_with_svg(&mut pdf_document, svg) > 0);
+}
@@ -0,0 +1,25 @@
+use textr::builder::{a4, a5, DocumentBuilder};
+
+/// Verifies that the SVG export places the pages one below the other and flips the vertical
+/// coordinate of the text, whose font size is converted into millimeters.
+#[test]
+fn svg_export_stacks_pages_and_flips_coordinates() {
+    let document = DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .page(a4())
+    .text("Fish & <chips>")
+    .at(30.0, 270.0)
+    .font(15, 12.0)
+    .page(a5())
+    .build()
+    .unwrap();
+
+    let svg = document.to_svg().unwrap();
+    assert!(svg
+        .starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"210mm\" height=\"517mm\""));
+    assert!(svg.contains("<svg x=\"0\" y=\"307\" width=\"148\" height=\"210\""));
+    assert!(svg.contains("<text x=\"30\" y=\"27\" font-family=\"F15\""));
+    assert!(svg.contains(">Fish &amp; &lt;chips&gt;</text>"));
+}
@@ -0,0 +1,112 @@
+use textr::{
+    builder::{a4, DocumentBuilder},
+    document::Document,
+    error::ErrorKind,
+    pdf_info::PdfInfo,
+};
+use time::{Date, Month, OffsetDateTime};
+
+/// Creates a document with a single page, dated with the given timestamp if any.
+fn document_with_timestamp(timestamp: Option<i64>) -> Document {
+    let document_builder = DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    );
+    match timestamp {
+        Some(timestamp) => document_builder.timestamp(timestamp),
+        None => document_builder,
+    }
+    .page(a4())
+    .build()
+    .unwrap()
+}
+
+/// Converts the given document into a PDF document and reads back the entry of its metadata with the given key.
+fn metadata_entry(document: &Document, key: &str) -> String {
+    let mut pdf_document = document.to_pdf_document().unwrap();
+    let pdf_info = PdfInfo::from_bytes(&pdf_document.save_to_bytes().unwrap()).unwrap();
+    pdf_info
+        .metadata
+        .into_iter()
+        .find(|(entry_key, _)| entry_key == key)
+        .map(|(_, value)| value)
+        .unwrap()
+}
+
+/// Verifies that the timestamp of the document is written as both its creation and modification date.
+#[test]
+fn timestamp_is_written_into_the_metadata() {
+    let document = document_with_timestamp(Some(1_760_617_800));
+
+    assert_eq!(
+        metadata_entry(&document, "CreationDate"),
+        "D:20251016123000+00'00'"
+    );
+    assert_eq!(
+        metadata_entry(&document, "ModDate"),
+        "D:20251016123000+00'00'"
+    );
+}
+
+/// Verifies that the documents without a timestamp are dated at the Unix epoch in deterministic mode.
+#[test]
+fn missing_timestamp_is_the_unix_epoch_in_deterministic_mode() {
+    let mut document = document_with_timestamp(None);
+    document.deterministic = true;
+
+    assert_eq!(
+        metadata_entry(&document, "CreationDate"),
+        "D:19700101000000+00'00'"
+    );
+}
+
+/// Verifies that the documents without a timestamp are otherwise dated at the time at which they are written.
+#[test]
+fn missing_timestamp_is_the_current_time() {
+    let document = document_with_timestamp(None);
+
+    let written_before = OffsetDateTime::now_utc().unix_timestamp();
+    let creation_date = metadata_entry(&document, "CreationDate");
+    let written_after = OffsetDateTime::now_utc().unix_timestamp();
+    // The date is formatted as D:YYYYMMDDHHmmSS+00'00', in UTC
+    let component = |range: std::ops::Range<usize>| creation_date[range].parse::<u16>().unwrap();
+    let creation_timestamp = Date::from_calendar_date(
+        component(2..6) as i32,
+        Month::try_from(component(6..8) as u8).unwrap(),
+        component(8..10) as u8,
+    )
+    .and_then(|date| {
+        date.with_hms(
+            component(10..12) as u8,
+            component(12..14) as u8,
+            component(14..16) as u8,
+        )
+    })
+    .unwrap()
+    .assume_utc()
+    .unix_timestamp();
+    assert!((written_before..=written_after).contains(&creation_timestamp));
+}
+
+/// Verifies that the timestamp is parsed from the JSON format, and that one which can't be represented as a date
+/// fails the conversion.
+#[test]
+fn timestamp_is_parsed_and_checked() {
+    let document = Document::from_json_bytes(
+        br#"{
+            "documentId": "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+            "instanceId": "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+            "timestamp": 1760617800,
+            "operations": [
+                { "pageWidth": 210.0, "pageHeight": 297.0 }
+            ]
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(document, document_with_timestamp(Some(1_760_617_800)));
+
+    let Err(error) = document_with_timestamp(Some(i64::MAX)).to_pdf_document() else {
+        panic!("The document with an invalid timestamp has been converted");
+    };
+    assert_eq!(error.kind, ErrorKind::InvalidDocument);
+}
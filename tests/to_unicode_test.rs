@@ -0,0 +1,102 @@
+use lopdf::Object;
+use textr::{
+    builder::{a4, DocumentBuilder},
+    document::Document,
+    font::FontRegistry,
+};
+
+/// Converts the given document into a PDF document and extracts the decoded `ToUnicode` CMap of its only font.
+fn to_unicode_map(document: &Document) -> String {
+    let font_registry = FontRegistry::builtin().unwrap();
+    let pdf_bytes = document
+        .to_pdf_document_with_fonts(&font_registry)
+        .unwrap()
+        .save_to_bytes()
+        .unwrap();
+    let inner_document = lopdf::Document::load_mem(&pdf_bytes).unwrap();
+    let to_unicode_id = inner_document
+        .objects
+        .values()
+        .filter_map(|object| object.as_dict().ok())
+        .find_map(|dictionary| {
+            dictionary
+                .get(b"ToUnicode")
+                .and_then(Object::as_reference)
+                .ok()
+        })
+        .unwrap();
+    let mut to_unicode_stream = inner_document
+        .get_object(to_unicode_id)
+        .and_then(Object::as_stream)
+        .unwrap()
+        .clone();
+    to_unicode_stream.decompress();
+
+    String::from_utf8(to_unicode_stream.content).unwrap()
+}
+
+/// Verifies that the `ToUnicode` CMap only maps the glyphs written by the text of the document back to their
+/// characters, rather than every glyph of the font.
+#[test]
+fn to_unicode_map_only_covers_the_used_glyphs() {
+    let document = DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .page(a4())
+    .text("Hello")
+    .at(20.0, 270.0)
+    .font(15, 12.0)
+    .text("Hé")
+    .at(20.0, 260.0)
+    .font(15, 12.0)
+    .build()
+    .unwrap();
+
+    let to_unicode_map = to_unicode_map(&document);
+    // The characters H, e, l, o and é, together with the glyph 0
+    let mappings = to_unicode_map
+        .split("endcodespacerange")
+        .nth(1)
+        .unwrap()
+        .lines()
+        .filter(|line| line.starts_with('<') && line.matches('<').count() == 2)
+        .collect::<Vec<_>>();
+    assert_eq!(mappings.len(), 6);
+    for character in ['H', 'e', 'l', 'o', 'é'] {
+        let unicode = format!("<{:04x}>", character as u32);
+        assert!(
+            mappings.iter().any(|mapping| mapping.ends_with(&unicode)),
+            "The character {:?} is not mapped",
+            character
+        );
+    }
+    assert!(!mappings.iter().any(|mapping| mapping.ends_with("<0057>")));
+}
+
+/// Verifies that the text of a converted document can still be recovered from its PDF file.
+#[test]
+fn text_is_recovered_through_the_reduced_map() {
+    let document = DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .page(a4())
+    .text("Café à la crème")
+    .at(20.0, 270.0)
+    .font(15, 12.0)
+    .build()
+    .unwrap();
+
+    let font_registry = FontRegistry::builtin().unwrap();
+    let pdf_bytes = document
+        .to_pdf_document_with_fonts(&font_registry)
+        .unwrap()
+        .save_to_bytes()
+        .unwrap();
+    let imported_document = Document::from_pdf_bytes(&pdf_bytes).unwrap();
+    assert_eq!(
+        imported_document.to_plain_text().unwrap().trim(),
+        "Café à la crème"
+    );
+}
@@ -0,0 +1,62 @@
+use textr::{
+    builder::{a4, DocumentBuilder},
+    error::ErrorKind,
+    warning::Warning,
+};
+
+/// Verifies that the characters missing from a font are reported as a warning of the PDF document
+/// rather than only being logged.
+#[test]
+fn missing_characters_are_collected_as_warnings() {
+    let document = DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .page(a4())
+    .text("Hello, world!")
+    .at(20.0, 270.0)
+    .font(15, 12.0)
+    .page(a4())
+    .text("Hello, 世界世界!")
+    .at(20.0, 270.0)
+    .font(15, 12.0)
+    .build()
+    .unwrap();
+
+    let pdf_document = document.to_pdf_document().unwrap();
+    assert_eq!(
+        pdf_document.warnings(),
+        [Warning::MissingCharacters {
+            page_index: 1,
+            font_index: 15,
+            characters: "世界".to_string(),
+        }]
+    );
+}
+
+/// Verifies that in strict mode the characters missing from a font make the conversion fail,
+/// with the index of the operation at fault.
+#[test]
+fn missing_characters_are_errors_in_strict_mode() {
+    let document = DocumentBuilder::new(
+        "5jKs8aPqRvWo0DmFb2GhNzXcUy3VlQw9",
+        "hTjW7nEfLmRk5sOpAqZcVbYxNwMuGdFj",
+    )
+    .strict()
+    .page(a4())
+    .text("Hello, world!")
+    .at(20.0, 270.0)
+    .font(15, 12.0)
+    .text("Hello, 世界!")
+    .at(20.0, 250.0)
+    .font(15, 12.0)
+    .build()
+    .unwrap();
+
+    let Err(error) = document.to_pdf_document() else {
+        panic!("The document contains characters which are missing from the font");
+    };
+    assert_eq!(error.kind, ErrorKind::MissingGlyph);
+    assert_eq!(error.context, "Unable to write the text of operation 2");
+    assert_eq!(error.operation_index, Some(2));
+}